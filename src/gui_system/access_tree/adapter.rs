@@ -0,0 +1,133 @@
+//! Converts the tree to the shape `accesskit` expects. Every type here has the same fields as its
+//! `accesskit` counterpart, so the platform adapter only has to copy them over.
+
+use super::{AccessNode, AccessNodeId, AccessRole, AccessTree};
+
+/// Id of the node that holds every root of the tree, it stands for the OS window
+pub const ROOT_ID: AccessNodeId = AccessNodeId(0);
+
+pub const TOOLKIT_NAME: &str = "rwge gui";
+
+/// `accesskit::Rect`, in physical pixels with y going down
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AccessKitRect {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessKitNode {
+    pub role: AccessRole,
+    pub bounds: Option<AccessKitRect>,
+    pub label: Option<String>,
+    pub value: Option<String>,
+    pub numeric_value: Option<f64>,
+    pub min_numeric_value: Option<f64>,
+    pub max_numeric_value: Option<f64>,
+    pub scroll_y: Option<f64>,
+    pub scroll_y_min: Option<f64>,
+    pub scroll_y_max: Option<f64>,
+    pub selected: Option<bool>,
+    pub toggled: Option<bool>,
+    pub expanded: Option<bool>,
+    pub disabled: bool,
+    pub children: Vec<AccessNodeId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessKitTree {
+    pub root: AccessNodeId,
+    pub app_name: Option<String>,
+    pub toolkit_name: Option<String>,
+}
+
+/// `accesskit::TreeUpdate`, always a full update since the whole tree is built every frame
+#[derive(Clone, Debug)]
+pub struct AccessKitTreeUpdate {
+    pub nodes: Vec<(AccessNodeId, AccessKitNode)>,
+    pub tree: Option<AccessKitTree>,
+    pub focus: AccessNodeId,
+}
+
+/// `screen_height` flips the y axis, `app_name` is the label of the root node
+pub fn tree_update(tree: &AccessTree, screen_height: f32, app_name: &str) -> AccessKitTreeUpdate {
+    let mut nodes = Vec::with_capacity(tree.nodes().len() + 1);
+    nodes.push((
+        ROOT_ID,
+        AccessKitNode {
+            role: AccessRole::Window,
+            bounds: None,
+            label: Some(String::from(app_name)),
+            value: None,
+            numeric_value: None,
+            min_numeric_value: None,
+            max_numeric_value: None,
+            scroll_y: None,
+            scroll_y_min: None,
+            scroll_y_max: None,
+            selected: None,
+            toggled: None,
+            expanded: None,
+            disabled: false,
+            children: tree.roots().map(|node| node.id).collect(),
+        },
+    ));
+    nodes.extend(
+        tree.nodes()
+            .iter()
+            .map(|node| (node.id, convert_node(node, screen_height))),
+    );
+
+    AccessKitTreeUpdate {
+        nodes,
+        tree: Some(AccessKitTree {
+            root: ROOT_ID,
+            app_name: Some(String::from(app_name)),
+            toolkit_name: Some(String::from(TOOLKIT_NAME)),
+        }),
+        focus: tree.focused().map_or(ROOT_ID, |node| node.id),
+    }
+}
+
+fn convert_node(node: &AccessNode, screen_height: f32) -> AccessKitNode {
+    let half_size = node.bounds.size * 0.5;
+    let bounds = AccessKitRect {
+        x0: (node.bounds.position.x - half_size.x) as f64,
+        y0: (screen_height - node.bounds.position.y - half_size.y) as f64,
+        x1: (node.bounds.position.x + half_size.x) as f64,
+        y1: (screen_height - node.bounds.position.y + half_size.y) as f64,
+    };
+
+    // The range of a scroll view is how far it is scrolled
+    let (numeric_range, scroll_range) = match node.role {
+        AccessRole::ScrollView => (None, node.range),
+        _ => (node.range, None),
+    };
+
+    AccessKitNode {
+        role: node.role,
+        bounds: Some(bounds),
+        label: (!node.label.is_empty()).then(|| node.label.clone()),
+        value: node.value.clone(),
+        numeric_value: numeric_range.map(|range| range.value as f64),
+        min_numeric_value: numeric_range.map(|range| range.min as f64),
+        max_numeric_value: numeric_range.map(|range| range.max as f64),
+        scroll_y: scroll_range.map(|range| range.value as f64),
+        scroll_y_min: scroll_range.map(|range| range.min as f64),
+        scroll_y_max: scroll_range.map(|range| range.max as f64),
+        selected: match node.role {
+            AccessRole::Tab
+            | AccessRole::MenuItem
+            | AccessRole::ListBoxOption
+            | AccessRole::TreeItem
+            | AccessRole::Row => Some(node.states.selected),
+            _ => None,
+        },
+        toggled: node.states.checked,
+        expanded: node.states.expanded,
+        disabled: node.states.disabled,
+        children: node.children.clone(),
+    }
+}
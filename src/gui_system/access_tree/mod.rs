@@ -0,0 +1,347 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use rwge::gui::rect_ui::Rect;
+
+use super::control::Uiid;
+
+pub mod adapter;
+#[cfg(test)]
+mod test;
+
+/// Id of a node, it is kept between frames as long as the node stays in the same place of the tree
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AccessNodeId(pub u64);
+
+/// The variants have the same name as their counterpart in `accesskit::Role`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AccessRole {
+    Window,
+    TabList,
+    Tab,
+    TabPanel,
+    Button,
+    Slider,
+    SpinButton,
+    ColorWell,
+    CheckBox,
+    Switch,
+    RadioGroup,
+    RadioButton,
+    ComboBox,
+    ListBox,
+    ListBoxOption,
+    Tree,
+    TreeItem,
+    Table,
+    Row,
+    Cell,
+    ColumnHeader,
+    TextInput,
+    Label,
+    ScrollView,
+    Menu,
+    MenuItem,
+    Dialog,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AccessRange {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct AccessStates {
+    pub selected: bool,
+    /// Only set on the controls that can be checked
+    pub checked: Option<bool>,
+    /// Only set on the controls that open a list or a popup
+    pub expanded: Option<bool>,
+    pub focused: bool,
+    pub hovered: bool,
+    pub disabled: bool,
+}
+
+#[derive(Clone)]
+pub struct AccessNode {
+    pub id: AccessNodeId,
+    pub role: AccessRole,
+    /// Same coordinates as the rest of the GUI, y goes up and the position is the center
+    pub bounds: Rect,
+    pub label: String,
+    pub range: Option<AccessRange>,
+    /// Text of the controls that edit it
+    pub value: Option<String>,
+    pub states: AccessStates,
+    pub parent: Option<AccessNodeId>,
+    pub children: Vec<AccessNodeId>,
+    /// Control the hovered and focused states are read from
+    control: Option<Uiid>,
+}
+
+impl AccessNode {
+    pub fn new(role: AccessRole, bounds: Rect, label: &str) -> Self {
+        Self {
+            id: AccessNodeId(0),
+            role,
+            bounds,
+            label: String::from(label),
+            range: None,
+            value: None,
+            states: AccessStates::default(),
+            parent: None,
+            children: Vec::new(),
+            control: None,
+        }
+    }
+
+    pub fn set_control(mut self, id: Uiid) -> Self {
+        self.control = Some(id);
+        self
+    }
+
+    pub fn set_range(mut self, value: f32, min: f32, max: f32) -> Self {
+        self.range = Some(AccessRange { value, min, max });
+        self
+    }
+
+    pub fn set_value(mut self, value: &str) -> Self {
+        self.value = Some(String::from(value));
+        self
+    }
+
+    pub fn set_selected(mut self, selected: bool) -> Self {
+        self.states.selected = selected;
+        self
+    }
+
+    pub fn set_checked(mut self, checked: bool) -> Self {
+        self.states.checked = Some(checked);
+        self
+    }
+
+    pub fn set_expanded(mut self, expanded: bool) -> Self {
+        self.states.expanded = Some(expanded);
+        self
+    }
+
+    pub fn set_disabled(mut self, disabled: bool) -> Self {
+        self.states.disabled = disabled;
+        self
+    }
+
+    /// For controls that track the focus on their own, the focus of the control is added to it
+    pub fn set_focused(mut self, focused: bool) -> Self {
+        self.states.focused = focused;
+        self
+    }
+
+    pub fn control(&self) -> Option<Uiid> {
+        self.control
+    }
+}
+
+/// Semantic description of the GUI built during the last update, the nodes are stored in the
+/// order they were handled so parents always come before their children
+#[derive(Clone)]
+pub struct AccessTree {
+    nodes: Vec<AccessNode>,
+}
+
+impl AccessTree {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    pub fn nodes(&self) -> &[AccessNode] {
+        &self.nodes
+    }
+
+    pub fn get(&self, id: AccessNodeId) -> Option<&AccessNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Nodes without a parent, in the order they were handled
+    pub fn roots(&self) -> impl Iterator<Item = &AccessNode> {
+        self.nodes.iter().filter(|node| node.parent.is_none())
+    }
+
+    pub fn children(&self, id: AccessNodeId) -> impl Iterator<Item = &AccessNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.parent == Some(id))
+    }
+
+    pub fn parent(&self, id: AccessNodeId) -> Option<&AccessNode> {
+        self.get(id)
+            .and_then(|node| node.parent)
+            .and_then(|parent| self.get(parent))
+    }
+
+    pub fn find_all(&self, role: AccessRole) -> impl Iterator<Item = &AccessNode> {
+        self.nodes.iter().filter(move |node| node.role == role)
+    }
+
+    pub fn find(&self, role: AccessRole, label: &str) -> Option<&AccessNode> {
+        self.find_all(role).find(|node| node.label == label)
+    }
+
+    pub fn find_by_label(&self, label: &str) -> Option<&AccessNode> {
+        self.nodes.iter().find(|node| node.label == label)
+    }
+
+    /// First node of `role` with `label` that is inside of `ancestor`
+    pub fn find_in(
+        &self,
+        ancestor: AccessNodeId,
+        role: AccessRole,
+        label: &str,
+    ) -> Option<&AccessNode> {
+        self.find_all(role)
+            .filter(|node| node.label == label)
+            .find(|node| self.is_ancestor(ancestor, node.id))
+    }
+
+    pub fn is_ancestor(&self, ancestor: AccessNodeId, id: AccessNodeId) -> bool {
+        let mut current = self.get(id).and_then(|node| node.parent);
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.get(parent).and_then(|node| node.parent);
+        }
+        false
+    }
+
+    pub fn focused(&self) -> Option<&AccessNode> {
+        self.nodes.iter().find(|node| node.states.focused)
+    }
+
+    pub fn hovered(&self) -> Option<&AccessNode> {
+        self.nodes.iter().find(|node| node.states.hovered)
+    }
+}
+
+/// Collects the nodes while the update event goes through the GUI. Nodes pushed outside of it are
+/// ignored, so the controls can push them on any event.
+pub struct AccessTreeBuilder {
+    building: bool,
+    nodes: Vec<AccessNode>,
+    /// Index of the nodes the next ones are pushed into
+    parent_stack: Vec<usize>,
+    tree: AccessTree,
+}
+
+impl AccessTreeBuilder {
+    pub fn new() -> Self {
+        Self {
+            building: false,
+            nodes: Vec::with_capacity(100),
+            parent_stack: Vec::with_capacity(10),
+            tree: AccessTree::new(),
+        }
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.building = true;
+        self.nodes.clear();
+        self.parent_stack.clear();
+    }
+
+    /// The nodes pushed since `begin_frame` replace the last tree
+    pub fn end_frame(&mut self) {
+        if !self.building {
+            return;
+        }
+        debug_assert!(
+            self.parent_stack.is_empty(),
+            "Every push_parent needs a pop_parent before the end of the frame"
+        );
+        // A missing pop_parent would otherwise nest the next frames into a stale node
+        self.parent_stack.clear();
+        self.building = false;
+        self.tree.nodes = std::mem::take(&mut self.nodes);
+    }
+
+    pub fn is_building(&self) -> bool {
+        self.building
+    }
+
+    /// Last tree that was built
+    pub fn tree(&self) -> &AccessTree {
+        &self.tree
+    }
+
+    /// Prefer `ControlState::push_access_node`, which fills the hovered and focused states
+    pub fn push(&mut self, mut node: AccessNode) -> AccessNodeId {
+        let parent = self.parent_stack.last().map(|index| self.nodes[*index].id);
+        // Siblings are told apart by their role and how many of them came before
+        let sibling_index = match parent {
+            Some(_) => self.nodes[*self.parent_stack.last().unwrap()]
+                .children
+                .iter()
+                .filter(|child| self.node_role(**child) == Some(node.role))
+                .count(),
+            None => self
+                .nodes
+                .iter()
+                .filter(|other| other.parent.is_none() && other.role == node.role)
+                .count(),
+        };
+        let mut hasher = DefaultHasher::new();
+        parent.map(|parent| parent.0).hash(&mut hasher);
+        node.role.hash(&mut hasher);
+        sibling_index.hash(&mut hasher);
+        node.id = AccessNodeId(hasher.finish());
+
+        if self.building {
+            node.parent = parent;
+            if let Some(parent_index) = self.parent_stack.last() {
+                self.nodes[*parent_index].children.push(node.id);
+            }
+            self.nodes.push(node);
+        }
+        node.id
+    }
+
+    /// The nodes pushed until `pop_parent` become children of `id`
+    pub fn push_parent(&mut self, id: AccessNodeId) {
+        if !self.building {
+            return;
+        }
+        let index = self
+            .nodes
+            .iter()
+            .rposition(|node| node.id == id)
+            .expect("The parent has to be pushed during the same frame");
+        self.parent_stack.push(index);
+    }
+
+    /// For controls that do not take a label, like the slider
+    pub fn set_last_label(&mut self, label: &str) {
+        if !self.building {
+            return;
+        }
+        if let Some(node) = self.nodes.last_mut() {
+            node.label = String::from(label);
+        }
+    }
+
+    pub fn pop_parent(&mut self) {
+        if self.building {
+            self.parent_stack.pop();
+        }
+    }
+
+    fn node_role(&self, id: AccessNodeId) -> Option<AccessRole> {
+        self.nodes
+            .iter()
+            .rev()
+            .find(|node| node.id == id)
+            .map(|node| node.role)
+    }
+}
@@ -0,0 +1,166 @@
+use rwge::{glam::vec2, gui::rect_ui::Rect};
+
+use super::{
+    adapter::{self, AccessKitRect, ROOT_ID, TOOLKIT_NAME},
+    AccessNode, AccessRole, AccessTreeBuilder,
+};
+
+fn rect() -> Rect {
+    Rect {
+        position: vec2(50.0, 50.0),
+        size: vec2(100.0, 20.0),
+    }
+}
+
+/// A window with a tab list and a panel holding two buttons
+fn build_frame(builder: &mut AccessTreeBuilder) {
+    builder.begin_frame();
+    let window = builder.push(AccessNode::new(AccessRole::Window, rect(), "Main"));
+    builder.push_parent(window);
+    builder.push(AccessNode::new(AccessRole::TabList, rect(), "Tabs"));
+    let panel = builder.push(AccessNode::new(AccessRole::TabPanel, rect(), "Panel"));
+    builder.push_parent(panel);
+    builder.push(AccessNode::new(AccessRole::Button, rect(), "Ok"));
+    builder.push(AccessNode::new(AccessRole::Button, rect(), "Cancel").set_disabled(true));
+    builder.pop_parent();
+    builder.pop_parent();
+    builder.end_frame();
+}
+
+#[test]
+fn tree_is_queried_after_the_frame() {
+    let mut builder = AccessTreeBuilder::new();
+    build_frame(&mut builder);
+    let tree = builder.tree();
+
+    assert_eq!(tree.nodes().len(), 5);
+    let window = tree.roots().next().unwrap();
+    assert_eq!(window.role, AccessRole::Window);
+    assert_eq!(tree.roots().count(), 1);
+
+    let panel = tree.find(AccessRole::TabPanel, "Panel").unwrap();
+    assert_eq!(tree.parent(panel.id).unwrap().id, window.id);
+    let buttons: Vec<&str> = tree
+        .children(panel.id)
+        .map(|node| node.label.as_str())
+        .collect();
+    assert_eq!(buttons, ["Ok", "Cancel"]);
+
+    let cancel = tree
+        .find_in(window.id, AccessRole::Button, "Cancel")
+        .unwrap();
+    assert!(cancel.states.disabled);
+    assert!(tree.is_ancestor(window.id, cancel.id));
+    assert!(!tree.is_ancestor(cancel.id, window.id));
+    assert!(tree.find(AccessRole::Button, "Apply").is_none());
+}
+
+#[test]
+fn ids_are_kept_between_frames() {
+    let mut builder = AccessTreeBuilder::new();
+    build_frame(&mut builder);
+    let first: Vec<_> = builder.tree().nodes().iter().map(|node| node.id).collect();
+    build_frame(&mut builder);
+    let second: Vec<_> = builder.tree().nodes().iter().map(|node| node.id).collect();
+    assert_eq!(first, second);
+
+    let tree = builder.tree();
+    let ok = tree.find_by_label("Ok").unwrap();
+    let cancel = tree.find_by_label("Cancel").unwrap();
+    assert_ne!(ok.id, cancel.id);
+}
+
+#[test]
+fn nodes_outside_of_a_frame_are_ignored() {
+    let mut builder = AccessTreeBuilder::new();
+    build_frame(&mut builder);
+    builder.push(AccessNode::new(AccessRole::Button, rect(), "Late"));
+    assert!(!builder.is_building());
+    assert!(builder.tree().find_by_label("Late").is_none());
+    assert_eq!(builder.tree().nodes().len(), 5);
+}
+
+#[test]
+fn missing_pop_parent_does_not_leak_into_the_next_frame() {
+    let mut builder = AccessTreeBuilder::new();
+    builder.begin_frame();
+    let window = builder.push(AccessNode::new(AccessRole::Window, rect(), "Main"));
+    builder.push_parent(window);
+    // Only checked in debug builds
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| builder.end_frame()));
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+
+    build_frame(&mut builder);
+    let tree = builder.tree();
+    assert_eq!(tree.roots().count(), 1);
+    assert!(tree
+        .find(AccessRole::Window, "Main")
+        .unwrap()
+        .parent
+        .is_none());
+}
+
+#[test]
+fn tree_update_has_every_node_under_the_root() {
+    let mut builder = AccessTreeBuilder::new();
+    build_frame(&mut builder);
+    let tree = builder.tree();
+    let update = adapter::tree_update(tree, 100.0, "Game");
+
+    assert_eq!(update.nodes.len(), 6);
+    let (root_id, root) = &update.nodes[0];
+    assert_eq!(*root_id, ROOT_ID);
+    assert_eq!(root.label.as_deref(), Some("Game"));
+    let window = tree.roots().next().unwrap();
+    assert_eq!(root.children, [window.id]);
+    assert_eq!(update.focus, ROOT_ID);
+
+    let update_tree = update.tree.unwrap();
+    assert_eq!(update_tree.root, ROOT_ID);
+    assert_eq!(update_tree.toolkit_name.as_deref(), Some(TOOLKIT_NAME));
+
+    let cancel = tree.find_by_label("Cancel").unwrap();
+    let (_, cancel_node) = update
+        .nodes
+        .iter()
+        .find(|(id, _)| *id == cancel.id)
+        .unwrap();
+    assert_eq!(cancel_node.role, AccessRole::Button);
+    assert!(cancel_node.disabled);
+    assert_eq!(cancel_node.selected, None);
+    // The y axis goes down and the bounds start at the top left corner
+    assert_eq!(
+        cancel_node.bounds,
+        Some(AccessKitRect {
+            x0: 0.0,
+            y0: 40.0,
+            x1: 100.0,
+            y1: 60.0,
+        })
+    );
+
+    let panel = tree.find(AccessRole::TabPanel, "Panel").unwrap();
+    let (_, panel_node) = update.nodes.iter().find(|(id, _)| *id == panel.id).unwrap();
+    assert_eq!(panel_node.children, panel.children);
+}
+
+#[test]
+fn tree_update_splits_scroll_and_numeric_ranges() {
+    let mut builder = AccessTreeBuilder::new();
+    builder.begin_frame();
+    builder.push(AccessNode::new(AccessRole::Slider, rect(), "Volume").set_range(0.5, 0.0, 1.0));
+    builder
+        .push(AccessNode::new(AccessRole::ScrollView, rect(), "List").set_range(20.0, 0.0, 80.0));
+    builder.end_frame();
+    let update = adapter::tree_update(builder.tree(), 100.0, "Game");
+
+    let slider = &update.nodes[1].1;
+    assert_eq!(slider.numeric_value, Some(0.5));
+    assert_eq!(slider.max_numeric_value, Some(1.0));
+    assert_eq!(slider.scroll_y, None);
+
+    let scroll_view = &update.nodes[2].1;
+    assert_eq!(scroll_view.numeric_value, None);
+    assert_eq!(scroll_view.scroll_y, Some(20.0));
+    assert_eq!(scroll_view.scroll_y_max, Some(80.0));
+}
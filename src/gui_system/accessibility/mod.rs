@@ -0,0 +1,96 @@
+use crate::runtime_data::PublicData;
+
+use super::theme::Theme;
+
+/// Font sizes offered on the context menu, 0 keeps the sizes of the theme
+pub const MIN_FONT_SIZES: [f32; 3] = [0.0, 18.0, 22.0];
+
+/// Preferences shared by every widget and container, stored on the `PublicData`
+#[derive(Clone)]
+pub struct AccessibilitySettings {
+    /// Continuous animations stop and transitions are instant
+    pub reduced_motion: bool,
+    /// Set through `set_high_contrast`, which also switches the theme
+    high_contrast: bool,
+    /// Font sizes below this are raised to it, in the same units as the rest of the GUI sizes
+    pub min_font_size: f32,
+    /// Theme restored once high contrast is turned off
+    theme_before_high_contrast: Option<Theme>,
+}
+
+impl AccessibilitySettings {
+    pub fn new() -> Self {
+        Self {
+            reduced_motion: false,
+            high_contrast: false,
+            min_font_size: 0.0,
+            theme_before_high_contrast: None,
+        }
+    }
+
+    pub fn high_contrast(&self) -> bool {
+        self.high_contrast
+    }
+
+    /// `value` changes continuously over time, with reduced motion `rest` is used instead
+    pub fn animated(&self, value: f32, rest: f32) -> f32 {
+        if self.reduced_motion {
+            rest
+        } else {
+            value
+        }
+    }
+
+    /// Interpolation factor of a transition, with reduced motion the target is reached at once
+    pub fn transition(&self, factor: f32) -> f32 {
+        if self.reduced_motion {
+            1.0
+        } else {
+            factor
+        }
+    }
+}
+
+pub fn get_accessibility(public_data: &PublicData) -> &AccessibilitySettings {
+    public_data.get().expect("AccessibilitySettings not found")
+}
+
+pub fn set_reduced_motion(public_data: &PublicData, enabled: bool) {
+    public_data.push_mut(Box::new(move |public_data| {
+        public_data
+            .get_mut::<AccessibilitySettings>()
+            .unwrap()
+            .reduced_motion = enabled;
+    }));
+}
+
+pub fn set_min_font_size(public_data: &PublicData, size: f32) {
+    public_data.push_mut(Box::new(move |public_data| {
+        public_data
+            .get_mut::<AccessibilitySettings>()
+            .unwrap()
+            .min_font_size = size;
+    }));
+}
+
+/// Switches to the high contrast theme, the current theme comes back once it is turned off
+pub fn set_high_contrast(public_data: &PublicData, enabled: bool) {
+    public_data.push_mut(Box::new(move |public_data| {
+        let current_theme = public_data.get::<Theme>().unwrap().clone();
+        let settings = public_data.get_mut::<AccessibilitySettings>().unwrap();
+        if settings.high_contrast == enabled {
+            return;
+        }
+        settings.high_contrast = enabled;
+        let new_theme = if enabled {
+            settings.theme_before_high_contrast = Some(current_theme);
+            Theme::high_contrast()
+        } else {
+            settings
+                .theme_before_high_contrast
+                .take()
+                .unwrap_or_else(Theme::dark)
+        };
+        *public_data.get_mut::<Theme>().unwrap() = new_theme;
+    }));
+}
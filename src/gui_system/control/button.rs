@@ -9,6 +9,8 @@ use rwge::{
     },
 };
 
+use crate::gui_system::access_tree::{AccessNode, AccessRole};
+
 use super::ControlState;
 
 pub fn button(
@@ -34,6 +36,9 @@ pub fn button(
 		if let Some(interact_rect) = interact_rect {
 			control_state.set_hot_with_rect(control_id, &interact_rect);
 		}
+		control_state.push_access_node(
+			AccessNode::new(AccessRole::Button, rect, label).set_control(control_id),
+		);
 	}
 
 	if let UIEvent::MouseButton(mouse_input) = event{
@@ -45,7 +50,7 @@ pub fn button(
     if let UIEvent::Render { gui_rects, .. } = event {
         let rect_mask_index = push_rect_mask(mask, gui_rects) as u16;
 
-        ElementBuilder::new_with_rect(rect)
+        let background = ElementBuilder::new_with_rect(rect)
             .set_linear_gradient(
                 LinearGradient {
                     colors: if control_state.is_hovered(control_id) {
@@ -59,8 +64,8 @@ pub fn button(
                 .into(),
             )
             .set_rect_mask(rect_mask_index.into())
-            .set_round_rect(border_radius.into())
-            .build(gui_rects);
+            .set_round_rect(border_radius.into());
+        control_state.layers.build(background, gui_rects);
 
         let (font_elems, font_rect) = create_single_line(
             label,
@@ -72,10 +77,10 @@ pub fn button(
         let label_offset = rect.position - font_rect.size * 0.5;
         for elements in font_elems {
             let font_rect = elements.rect.offset_position(label_offset);
-            ElementBuilder::new_with_rect(font_rect)
+            let glyph = ElementBuilder::new_with_rect(font_rect)
                 .set_sdffont(elements.tx_slice.into())
-                .set_rect_mask(rect_mask_index.into())
-                .build(gui_rects);
+                .set_rect_mask(rect_mask_index.into());
+            control_state.layers.build(glyph, gui_rects);
         }
     }
     return false;
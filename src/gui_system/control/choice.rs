@@ -0,0 +1,560 @@
+use rwge::{
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, GUIRects, Rect,
+    },
+    math_utils::lerp_f32,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        accessibility::get_accessibility,
+        layers::LayerManager,
+        theme::{get_theme, Theme},
+    },
+    runtime_data::{
+        utils::{get_font_collections, get_time},
+        PublicData,
+    },
+};
+
+use super::{keyboard, scale::GUIScale, tooltip, ControlState, Uiid};
+
+const CHECK_SIZE: f32 = 16.0;
+const SWITCH_SIZE: (f32, f32) = (34.0, 18.0);
+const KNOB_MARGIN: f32 = 3.0;
+/// Part of the distance left the knob moves on every update, at 90 updates per second
+const KNOB_SPEED: f32 = 0.25;
+const SEGMENT_GAP: f32 = 2.0;
+
+/// Theme values read by every widget of this module
+struct ChoiceStyle<'a> {
+    theme: &'a Theme,
+    scale: GUIScale,
+    font_size: f32,
+    font_collection: &'a FontCollection,
+}
+
+impl<'a> ChoiceStyle<'a> {
+    fn new(control_state: &ControlState, public_data: &'a PublicData) -> Self {
+        let theme = get_theme(public_data);
+        let scale = control_state.scale;
+        Self {
+            theme,
+            scale,
+            font_size: scale.font(theme.fonts.size),
+            font_collection: &get_font_collections(public_data)[theme.fonts.collection],
+        }
+    }
+
+    /// Thicker and highlighted while the control has the focus
+    fn border(&self, focused: bool) -> Border {
+        let (width, color) = if focused {
+            (
+                self.theme.metrics.focus_border_width,
+                self.theme.colors.focus,
+            )
+        } else {
+            (self.theme.metrics.border_width, self.theme.colors.border)
+        };
+        Border {
+            size: self.scale.border(width),
+            color: color.into(),
+        }
+    }
+
+    /// `label` is vertically centered on `anchor`, it starts at it unless it is `centered`
+    fn draw_label(
+        &self,
+        layers: &mut LayerManager,
+        gui_rects: &mut GUIRects,
+        label: &str,
+        anchor: Vec2,
+        centered: bool,
+        mask: Rect,
+    ) {
+        let (font_elements, label_rect) =
+            create_single_line(label, self.font_size, self.font_collection, 0, 0.0);
+        let origin = if centered {
+            anchor - label_rect.size * 0.5
+        } else {
+            anchor - vec2(0.0, label_rect.height() * 0.5)
+        };
+        for element in font_elements {
+            let glyph = ElementBuilder::new_with_rect(element.rect.offset_position(origin.round()))
+                .set_sdffont(element.tx_slice.into())
+                .set_color(self.theme.colors.text.into())
+                .set_rect_mask(mask.into());
+            layers.build(glyph, gui_rects);
+        }
+    }
+}
+
+/// Hover and click handling shared by the widgets, a click gives the focus to `focus_id`. Returns
+/// true if `control_id` was clicked.
+fn handle_click(
+    control_id: Uiid,
+    focus_id: Uiid,
+    rect: Rect,
+    mask: Rect,
+    event: &UIEvent,
+    control_state: &mut ControlState,
+) -> bool {
+    match event {
+        UIEvent::Update => {
+            if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(control_id, &interact_rect);
+            }
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() && control_state.is_hovered(control_id) {
+                control_state.set_focus(focus_id);
+                return true;
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Space and Enter toggle the focused control
+fn activation_pressed(event: &UIEvent, focus_id: Uiid, control_state: &ControlState) -> bool {
+    control_state.is_focused(focus_id)
+        && matches!(
+            keyboard::get_pressed_key(event),
+            Some(VirtualKeyCode::Space | VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter)
+        )
+}
+
+/// The arrow keys move the selection of the focused group by one option
+fn arrow_step(event: &UIEvent, focus_id: Uiid, control_state: &ControlState) -> isize {
+    if !control_state.is_focused(focus_id) {
+        return 0;
+    }
+    match keyboard::get_pressed_key(event) {
+        Some(VirtualKeyCode::Left | VirtualKeyCode::Up) => -1,
+        Some(VirtualKeyCode::Right | VirtualKeyCode::Down) => 1,
+        _ => 0,
+    }
+}
+
+/// Moves the selection by `step` options, wrapping around at both ends
+fn step_selection(selected: usize, step: isize, option_count: usize) -> usize {
+    if option_count == 0 {
+        return selected;
+    }
+    (selected as isize + step).rem_euclid(option_count as isize) as usize
+}
+
+/// Box followed by `label`, clicking anywhere on `rect` flips the value. Returns the new value.
+pub fn checkbox(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    checked: bool,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> bool {
+    let control_id = control_state.get_id();
+    let style = ChoiceStyle::new(control_state, public_data);
+
+    let mut checked = checked;
+    if handle_click(control_id, control_id, rect, mask, event, control_state)
+        || activation_pressed(event, control_id, control_state)
+    {
+        checked = !checked;
+    }
+
+    match event {
+        UIEvent::Update => {
+            control_state.push_access_node(
+                AccessNode::new(AccessRole::CheckBox, rect, label)
+                    .set_control(control_id)
+                    .set_checked(checked),
+            );
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let colors = style.theme.colors;
+            let box_size = style.scale.px(CHECK_SIZE);
+            let box_rect = Rect {
+                position: vec2(rect.left_position().x + box_size * 0.5, rect.position.y).round(),
+                size: Vec2::splat(box_size),
+            };
+            let fill = if checked {
+                colors.accent
+            } else if control_state.is_hovered(control_id) {
+                colors.control_hover
+            } else {
+                colors.control
+            };
+            let check_box = ElementBuilder::new_with_rect(box_rect)
+                .set_color(fill.into())
+                .set_round_rect(
+                    BorderRadius::ForAll(style.scale.px(style.theme.metrics.radius_small)).into(),
+                )
+                .set_border(Some(style.border(control_state.is_focused(control_id))))
+                .set_rect_mask(mask.into());
+            control_state.layers.build(check_box, gui_rects);
+
+            if checked {
+                let check_mark = ElementBuilder::new(box_rect.position, box_rect.size * 0.4)
+                    .set_color(colors.text.into())
+                    .set_round_rect(BorderRadius::ForAll(box_size * 0.1).into())
+                    .set_rect_mask(mask.into());
+                control_state.layers.build(check_mark, gui_rects);
+            }
+
+            let spacing = style.scale.px(style.theme.metrics.spacing);
+            style.draw_label(
+                &mut control_state.layers,
+                gui_rects,
+                label,
+                vec2(
+                    box_rect.position.x + box_size * 0.5 + spacing,
+                    rect.position.y,
+                ),
+                false,
+                mask,
+            );
+        }
+        _ => {}
+    }
+    checked
+}
+
+/// State of a toggle switch kept by the container, the knob slides over when the value changes
+pub struct ToggleSwitch {
+    pub on: bool,
+    /// 0 on the left side of the track, 1 on the right side
+    knob_position: f32,
+}
+
+impl ToggleSwitch {
+    pub fn new(on: bool) -> Self {
+        Self {
+            on,
+            knob_position: if on { 1.0 } else { 0.0 },
+        }
+    }
+}
+
+/// Switch followed by `label`, clicking anywhere on `rect` flips it. Returns true if it was
+/// toggled during the event.
+pub fn toggle_switch(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    switch: &mut ToggleSwitch,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> bool {
+    let control_id = control_state.get_id();
+    let style = ChoiceStyle::new(control_state, public_data);
+
+    let toggled = handle_click(control_id, control_id, rect, mask, event, control_state)
+        || activation_pressed(event, control_id, control_state);
+    if toggled {
+        switch.on = !switch.on;
+    }
+
+    match event {
+        UIEvent::Update => {
+            let target = if switch.on { 1.0 } else { 0.0 };
+            let anim_scaler = get_time(public_data).delta_time_millis.0 / 11.0;
+            let factor = get_accessibility(public_data)
+                .transition(KNOB_SPEED * anim_scaler)
+                .min(1.0);
+            switch.knob_position = lerp_f32(switch.knob_position, target, factor);
+            if (switch.knob_position - target).abs() < 0.001 {
+                switch.knob_position = target;
+            }
+
+            control_state.push_access_node(
+                AccessNode::new(AccessRole::Switch, rect, label)
+                    .set_control(control_id)
+                    .set_checked(switch.on),
+            );
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let colors = style.theme.colors;
+            let track_size = style.scale.vec(vec2(SWITCH_SIZE.0, SWITCH_SIZE.1));
+            let track_rect = Rect {
+                position: vec2(rect.left_position().x + track_size.x * 0.5, rect.position.y)
+                    .round(),
+                size: track_size,
+            };
+            let fill = if switch.on {
+                colors.accent
+            } else if control_state.is_hovered(control_id) {
+                colors.control_hover
+            } else {
+                colors.control
+            };
+            let track = ElementBuilder::new_with_rect(track_rect)
+                .set_color(fill.into())
+                .set_round_rect(BorderRadius::ForAll(track_size.y * 0.5).into())
+                .set_border(Some(style.border(control_state.is_focused(control_id))))
+                .set_rect_mask(mask.into());
+            control_state.layers.build(track, gui_rects);
+
+            let knob_margin = style.scale.px(KNOB_MARGIN);
+            let knob_size = track_size.y - knob_margin * 2.0;
+            let knob_travel = track_size.x - knob_margin * 2.0 - knob_size;
+            let knob_position = vec2(
+                track_rect.left_position().x
+                    + knob_margin
+                    + knob_size * 0.5
+                    + knob_travel * switch.knob_position,
+                track_rect.position.y,
+            );
+            let knob = ElementBuilder::new(knob_position.round(), Vec2::splat(knob_size))
+                .set_circle()
+                .set_color(colors.text.into())
+                .set_rect_mask(mask.into());
+            control_state.layers.build(knob, gui_rects);
+
+            let spacing = style.scale.px(style.theme.metrics.spacing);
+            style.draw_label(
+                &mut control_state.layers,
+                gui_rects,
+                label,
+                vec2(
+                    track_rect.position.x + track_size.x * 0.5 + spacing,
+                    rect.position.y,
+                ),
+                false,
+                mask,
+            );
+        }
+        _ => {}
+    }
+    toggled
+}
+
+/// `options` stacked from the top of `rect` in rows of the same height, only one of them is
+/// selected. The arrow keys move the selection while the group has the focus. Returns the new
+/// selected index.
+pub fn radio_group(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    options: &[&str],
+    selected: usize,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> usize {
+    let group_id = control_state.get_id();
+    let option_ids: Vec<Uiid> = options.iter().map(|_| control_state.get_id()).collect();
+    let style = ChoiceStyle::new(control_state, public_data);
+
+    let row_height = rect.height() / options.len().max(1) as f32;
+    let row_rect = |index: usize| Rect {
+        position: vec2(
+            rect.position.x,
+            rect.top_left_position().y - row_height * (index as f32 + 0.5),
+        ),
+        size: vec2(rect.width(), row_height),
+    };
+
+    let mut new_selected = selected;
+    for (index, option_id) in option_ids.iter().enumerate() {
+        if handle_click(
+            *option_id,
+            group_id,
+            row_rect(index),
+            mask,
+            event,
+            control_state,
+        ) {
+            new_selected = index;
+        }
+    }
+    let step = arrow_step(event, group_id, control_state);
+    if step != 0 {
+        new_selected = step_selection(new_selected, step, options.len());
+    }
+
+    match event {
+        UIEvent::Update => {
+            let group_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::RadioGroup, rect, label)
+                    .set_focused(control_state.is_focused(group_id)),
+            );
+            control_state.access.push_parent(group_access_id);
+            for (index, option_id) in option_ids.iter().enumerate() {
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::RadioButton, row_rect(index), options[index])
+                        .set_control(*option_id)
+                        .set_checked(index == new_selected),
+                );
+            }
+            control_state.access.pop_parent();
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let colors = style.theme.colors;
+            let circle_size = style.scale.px(CHECK_SIZE);
+            let spacing = style.scale.px(style.theme.metrics.spacing);
+            let group_focused = control_state.is_focused(group_id);
+            for (index, option_id) in option_ids.iter().enumerate() {
+                let row = row_rect(index);
+                let circle_position =
+                    vec2(row.left_position().x + circle_size * 0.5, row.position.y).round();
+                let fill = if control_state.is_hovered(*option_id) {
+                    colors.control_hover
+                } else {
+                    colors.control
+                };
+                let circle = ElementBuilder::new(circle_position, Vec2::splat(circle_size))
+                    .set_circle()
+                    .set_color(fill.into())
+                    .set_border(Some(style.border(group_focused && index == new_selected)))
+                    .set_rect_mask(mask.into());
+                control_state.layers.build(circle, gui_rects);
+
+                if index == new_selected {
+                    let dot = ElementBuilder::new(circle_position, Vec2::splat(circle_size * 0.5))
+                        .set_circle()
+                        .set_color(colors.accent.into())
+                        .set_rect_mask(mask.into());
+                    control_state.layers.build(dot, gui_rects);
+                }
+
+                style.draw_label(
+                    &mut control_state.layers,
+                    gui_rects,
+                    options[index],
+                    vec2(
+                        circle_position.x + circle_size * 0.5 + spacing,
+                        row.position.y,
+                    ),
+                    false,
+                    mask,
+                );
+            }
+        }
+        _ => {}
+    }
+    new_selected
+}
+
+/// Row of `options` splitting `rect` in equal segments, the selected one is highlighted. The
+/// arrow keys move the selection while the control has the focus. `tooltips` are shown for the
+/// option of the same index, it can be shorter than `options`. Returns the new selected index.
+pub fn segmented_control(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    options: &[&str],
+    tooltips: &[&str],
+    selected: usize,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> usize {
+    let group_id = control_state.get_id();
+    let option_ids: Vec<Uiid> = options.iter().map(|_| control_state.get_id()).collect();
+    let style = ChoiceStyle::new(control_state, public_data);
+
+    let gap = style.scale.px(SEGMENT_GAP);
+    let option_count = options.len().max(1) as f32;
+    let segment_width = (rect.width() - gap * (option_count - 1.0)) / option_count;
+    let segment_rect = |index: usize| Rect {
+        position: vec2(
+            rect.left_position().x + (segment_width + gap) * index as f32 + segment_width * 0.5,
+            rect.position.y,
+        ),
+        size: vec2(segment_width, rect.height()),
+    };
+
+    let mut new_selected = selected;
+    for (index, option_id) in option_ids.iter().enumerate() {
+        if handle_click(
+            *option_id,
+            group_id,
+            segment_rect(index),
+            mask,
+            event,
+            control_state,
+        ) {
+            new_selected = index;
+        }
+    }
+    let step = arrow_step(event, group_id, control_state);
+    if step != 0 {
+        new_selected = step_selection(new_selected, step, options.len());
+    }
+    for (option_id, text) in option_ids.iter().zip(tooltips) {
+        tooltip::tooltip(control_state, *option_id, text);
+    }
+
+    match event {
+        UIEvent::Update => {
+            let group_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::RadioGroup, rect, label)
+                    .set_focused(control_state.is_focused(group_id)),
+            );
+            control_state.access.push_parent(group_access_id);
+            for (index, option_id) in option_ids.iter().enumerate() {
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::RadioButton, segment_rect(index), options[index])
+                        .set_control(*option_id)
+                        .set_checked(index == new_selected),
+                );
+            }
+            control_state.access.pop_parent();
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let colors = style.theme.colors;
+            let radius = rect.height() * 0.5;
+            let group_focused = control_state.is_focused(group_id);
+            for (index, option_id) in option_ids.iter().enumerate() {
+                let segment = segment_rect(index);
+                // Only the outer corners of the row are round
+                let border_radius = BorderRadius::ForLeftRight {
+                    left: if index == 0 { radius } else { 0.0 },
+                    right: if index + 1 == options.len() {
+                        radius
+                    } else {
+                        0.0
+                    },
+                };
+                let fill = if index == new_selected {
+                    colors.accent
+                } else if control_state.is_hovered(*option_id) {
+                    colors.control_hover
+                } else {
+                    colors.control
+                };
+                let element = ElementBuilder::new_with_rect(segment)
+                    .set_color(fill.into())
+                    .set_round_rect(border_radius.into())
+                    .set_rect_mask(mask.into());
+                let element = if group_focused && index == new_selected {
+                    element.set_border(Some(style.border(true)))
+                } else {
+                    element
+                };
+                control_state.layers.build(element, gui_rects);
+
+                style.draw_label(
+                    &mut control_state.layers,
+                    gui_rects,
+                    options[index],
+                    segment.position,
+                    true,
+                    mask,
+                );
+            }
+        }
+        _ => {}
+    }
+    new_selected
+}
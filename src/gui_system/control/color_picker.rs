@@ -0,0 +1,1112 @@
+use rwge::{
+    color::RGBA,
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border, LinearGradient},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        layers::Layer,
+        theme::{get_theme, parse_color, Theme},
+    },
+    runtime_data::{
+        utils::{get_engine_data, get_font_collections},
+        PublicData,
+    },
+};
+
+use super::{
+    cursor::{CursorIcon, CursorPriority},
+    drag_value::{self, DragValue},
+    keyboard,
+    text_field::{self, TextField},
+    ControlState, Uiid,
+};
+
+/// Colors kept in the recent palette, the newest comes first
+pub const MAX_RECENT_COLORS: usize = 10;
+
+const SQUARE_SIZE: f32 = 160.0;
+const STRIP_WIDTH: f32 = 16.0;
+const ROW_HEIGHT: f32 = 22.0;
+const ROW_LABEL_WIDTH: f32 = 40.0;
+const PREVIEW_WIDTH: f32 = 48.0;
+const SWATCH_GAP: f32 = 3.0;
+const HANDLE_SIZE: f32 = 10.0;
+const HANDLE_THICKNESS: f32 = 4.0;
+const SAMPLE_PREVIEW_SIZE: f32 = 24.0;
+/// Two colors closer than this in every channel are the same
+const COLOR_EPSILON: f32 = 0.002;
+
+const CHECKER_LIGHT: f32 = 0.8;
+const CHECKER_DARK: f32 = 0.55;
+
+/// The first four channels edit the color as RGBA and the next four as HSLA
+const CHANNEL_LABELS: [&str; 8] = [
+    "Red",
+    "Green",
+    "Blue",
+    "Alpha",
+    "Hue",
+    "Saturation",
+    "Lightness",
+    "Alpha",
+];
+const ROW_LABELS: [&str; 2] = ["RGBA", "HSLA"];
+/// Typed as `RRGGBB` or `RRGGBBAA`, the `#` is optional
+const HEX_FIELD: usize = 8;
+const FIELD_COUNT: usize = 9;
+
+/// Bytes for RGBA, degrees for the hue and percentages for the rest of HSLA
+fn channel_field(index: usize) -> DragValue {
+    let (max, step) = match index {
+        0..=3 => (255.0, 1.0),
+        4 => (360.0, 1.0),
+        _ => (100.0, 0.5),
+    };
+    DragValue::new()
+        .set_step(step)
+        .set_precision(0)
+        .set_range(0.0, max)
+}
+
+fn same_color(a: RGBA, b: RGBA) -> bool {
+    (a.r - b.r).abs() < COLOR_EPSILON
+        && (a.g - b.g).abs() < COLOR_EPSILON
+        && (a.b - b.b).abs() < COLOR_EPSILON
+        && (a.a - b.a).abs() < COLOR_EPSILON
+}
+
+/// Hue in degrees, saturation and value between 0 and 1
+fn hsv_to_rgb([hue, saturation, value]: [f32; 3]) -> [f32; 3] {
+    let channel = |n: f32| {
+        let k = (n + hue / 60.0) % 6.0;
+        value - value * saturation * k.min(4.0 - k).min(1.0).max(0.0)
+    };
+    [channel(5.0), channel(3.0), channel(1.0)]
+}
+
+fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    let hue = if delta <= 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max > 0.0 { delta / max } else { 0.0 };
+    [hue, saturation, max]
+}
+
+fn hsv_to_hsl([hue, saturation, value]: [f32; 3]) -> [f32; 3] {
+    let lightness = value * (1.0 - saturation * 0.5);
+    let saturation = if lightness <= 0.0 || lightness >= 1.0 {
+        0.0
+    } else {
+        (value - lightness) / lightness.min(1.0 - lightness)
+    };
+    [hue, saturation, lightness]
+}
+
+fn hsl_to_hsv([hue, saturation, lightness]: [f32; 3]) -> [f32; 3] {
+    let value = lightness + saturation * lightness.min(1.0 - lightness);
+    let saturation = if value <= 0.0 {
+        0.0
+    } else {
+        2.0 * (1.0 - lightness / value)
+    };
+    [hue, saturation, value]
+}
+
+fn to_byte(channel: f32) -> u8 {
+    (channel.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+/// `#RRGGBB`, the alpha is only added when the color is not opaque
+pub fn to_hex(color: RGBA) -> String {
+    let rgb = format!(
+        "#{:02X}{:02X}{:02X}",
+        to_byte(color.r),
+        to_byte(color.g),
+        to_byte(color.b)
+    );
+    match to_byte(color.a) {
+        255 => rgb,
+        alpha => format!("{}{:02X}", rgb, alpha),
+    }
+}
+
+/// Pixel of the rendered frame under the cursor, read for the eyedropper, and the recent colors
+/// shared by every picker. The GUI system copies the requested pixel after rendering, so the
+/// sample is a frame behind the cursor.
+pub struct ColorSampler {
+    requested: Option<Vec2>,
+    sample: Option<RGBA>,
+    recent: Vec<RGBA>,
+}
+
+impl ColorSampler {
+    pub fn new() -> Self {
+        Self {
+            requested: None,
+            sample: None,
+            recent: Vec::with_capacity(MAX_RECENT_COLORS),
+        }
+    }
+
+    /// The color of a previous pick is not shown for the new one
+    fn start(&mut self) {
+        self.requested = None;
+        self.sample = None;
+    }
+
+    /// Asks for the pixel at `position` of the frame being rendered
+    fn request(&mut self, position: Vec2) {
+        self.requested = Some(position);
+    }
+
+    /// Position in the GUI of the pixel to read from the rendered frame
+    pub fn take_request(&mut self) -> Option<Vec2> {
+        self.requested.take()
+    }
+
+    pub fn set_sample(&mut self, color: RGBA) {
+        self.sample = Some(color);
+    }
+
+    pub fn sample(&self) -> Option<RGBA> {
+        self.sample
+    }
+
+    pub fn recent(&self) -> &[RGBA] {
+        &self.recent
+    }
+
+    /// A color already in the palette is moved to the front
+    pub fn add_recent(&mut self, color: RGBA) {
+        self.recent.retain(|recent| !same_color(*recent, color));
+        self.recent.insert(0, color);
+        self.recent.truncate(MAX_RECENT_COLORS);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DragTarget {
+    Square,
+    Hue,
+    Alpha,
+}
+
+/// State of a color button and its popup, kept by the container between frames
+pub struct ColorPicker {
+    open: bool,
+    /// Hue in degrees, saturation and value between 0 and 1. Kept apart from the color so the
+    /// hue and the saturation are not lost on grays and black.
+    hsv: [f32; 3],
+    alpha: f32,
+    /// Color when the popup was opened, shown next to the new one
+    initial: RGBA,
+    drag: Option<(DragTarget, Uuid)>,
+    channels: Vec<DragValue>,
+    /// Typed as `RRGGBB` or `RRGGBBAA`, the `#` is optional
+    hex: TextField,
+    /// Set on every update, the click that takes the focus away commits the typed color
+    hex_focused: bool,
+    /// The next click picks a color from the GUI instead of going to the controls
+    sampling: bool,
+}
+
+impl ColorPicker {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            hsv: [0.0, 0.0, 1.0],
+            alpha: 1.0,
+            initial: RGBA::WHITE,
+            drag: None,
+            channels: (0..HEX_FIELD).map(channel_field).collect(),
+            hex: TextField::new("").set_placeholder("Hex"),
+            hex_focused: false,
+            sampling: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn color(&self) -> RGBA {
+        let [r, g, b] = hsv_to_rgb(self.hsv);
+        RGBA::new(r, g, b, self.alpha)
+    }
+
+    fn set_color(&mut self, color: RGBA) {
+        self.set_rgb([color.r, color.g, color.b]);
+        self.alpha = color.a;
+    }
+
+    fn set_rgb(&mut self, rgb: [f32; 3]) {
+        let [hue, saturation, value] = rgb_to_hsv(rgb);
+        // Grays have no hue and black has no saturation either, the previous ones are kept
+        if value > 0.0 {
+            if saturation > 0.0 {
+                self.hsv[0] = hue;
+            }
+            self.hsv[1] = saturation;
+        }
+        self.hsv[2] = value;
+    }
+
+    fn open(&mut self, color: RGBA) {
+        self.open = true;
+        self.set_color(color);
+        self.initial = color;
+        self.hex_focused = false;
+        self.sampling = false;
+    }
+
+    /// A changed color goes to the recent palette
+    fn close(&mut self, sampler: &mut ColorSampler) {
+        if !same_color(self.color(), self.initial) {
+            sampler.add_recent(self.color());
+        }
+        self.open = false;
+        self.sampling = false;
+        self.drag = None;
+    }
+
+    fn field_value(&self, index: usize) -> f32 {
+        let [r, g, b] = hsv_to_rgb(self.hsv);
+        let [hue, saturation, lightness] = hsv_to_hsl(self.hsv);
+        match index {
+            0 => r * 255.0,
+            1 => g * 255.0,
+            2 => b * 255.0,
+            3 => self.alpha * 255.0,
+            4 => hue,
+            5 => saturation * 100.0,
+            6 => lightness * 100.0,
+            _ => self.alpha * 100.0,
+        }
+    }
+
+    /// The value is already clamped by the channel field
+    fn set_field_value(&mut self, index: usize, value: f32) {
+        match index {
+            0..=2 => {
+                let mut rgb = hsv_to_rgb(self.hsv);
+                rgb[index] = value / 255.0;
+                self.set_rgb(rgb);
+            }
+            3 => self.alpha = value / 255.0,
+            4..=6 => {
+                let mut hsl = hsv_to_hsl(self.hsv);
+                hsl[index - 4] = if index == 4 { value } else { value / 100.0 };
+                self.hsv = hsl_to_hsv(hsl);
+            }
+            _ => self.alpha = value / 100.0,
+        }
+    }
+
+    /// The channel fields and the hex field handle Escape while they are typed in
+    fn is_typing(&self) -> bool {
+        self.hex_focused || self.channels.iter().any(DragValue::is_editing)
+    }
+
+    fn drag_to(&mut self, target: DragTarget, cursor_pos: Vec2, layout: &PickerLayout) {
+        match target {
+            DragTarget::Square => {
+                self.hsv[1] = progress(
+                    cursor_pos.x,
+                    layout.square.left_position().x,
+                    layout.square.width(),
+                );
+                self.hsv[2] = progress(cursor_pos.y, bottom(layout.square), layout.square.height());
+            }
+            // Red is at the top of the hue strip, and opaque at the top of the alpha strip
+            DragTarget::Hue => {
+                self.hsv[0] =
+                    (1.0 - progress(cursor_pos.y, bottom(layout.hue), layout.hue.height())) * 360.0;
+            }
+            DragTarget::Alpha => {
+                self.alpha = progress(cursor_pos.y, bottom(layout.alpha), layout.alpha.height());
+            }
+        }
+    }
+}
+
+/// Where `value` is between `start` and `start + length`, from 0 to 1
+fn progress(value: f32, start: f32, length: f32) -> f32 {
+    ((value - start) / length).max(0.0).min(1.0)
+}
+
+fn bottom(rect: Rect) -> f32 {
+    rect.position.y - rect.height() * 0.5
+}
+
+fn rect_from_top_left(top_left: Vec2, size: Vec2) -> Rect {
+    Rect {
+        position: top_left + vec2(size.x, -size.y) * 0.5,
+        size,
+    }
+}
+
+/// Where every part of the popup goes
+struct PickerLayout {
+    rect: Rect,
+    /// Saturation from left to right and value from bottom to top
+    square: Rect,
+    hue: Rect,
+    alpha: Rect,
+    /// The color when the popup was opened on the left half, the new one on the right half
+    preview: Rect,
+    eyedropper: Rect,
+    fields: Vec<Rect>,
+    row_labels: [Rect; 2],
+    recent: Vec<Rect>,
+}
+
+impl PickerLayout {
+    /// The popup opens below the button, or above it if there is no room
+    fn new(button: Rect, screen_size: Vec2, control_state: &ControlState, theme: &Theme) -> Self {
+        let scale = control_state.scale;
+        let padding = scale.px(theme.metrics.spacing);
+        let gap = scale.px(theme.metrics.spacing_small);
+        let square_size = scale.px(SQUARE_SIZE);
+        let strip_width = scale.px(STRIP_WIDTH);
+        let row_height = scale.px(ROW_HEIGHT);
+        let swatch_gap = scale.px(SWATCH_GAP);
+
+        let content_width = square_size + (gap + strip_width) * 2.0;
+        let swatch_size = (content_width - swatch_gap * (MAX_RECENT_COLORS - 1) as f32)
+            / MAX_RECENT_COLORS as f32;
+        let content_height = square_size + row_height * 3.0 + gap * 4.0 + swatch_size;
+        let size = vec2(content_width, content_height) + Vec2::splat(padding * 2.0);
+
+        let mut top = bottom(button) - gap;
+        if top - size.y < 0.0 {
+            top = button.position.y + button.height() * 0.5 + gap + size.y;
+        }
+        let top = top.min(screen_size.y);
+        let left = button
+            .left_position()
+            .x
+            .min(screen_size.x - size.x)
+            .max(0.0);
+        let rect = rect_from_top_left(vec2(left, top), size);
+
+        let origin = vec2(left + padding, top - padding);
+        let square = rect_from_top_left(origin, Vec2::splat(square_size));
+        let hue = rect_from_top_left(
+            origin + vec2(square_size + gap, 0.0),
+            vec2(strip_width, square_size),
+        );
+        let alpha = rect_from_top_left(
+            origin + vec2(square_size + gap * 2.0 + strip_width, 0.0),
+            vec2(strip_width, square_size),
+        );
+
+        let mut y = origin.y - square_size - gap;
+        let preview =
+            rect_from_top_left(vec2(origin.x, y), vec2(scale.px(PREVIEW_WIDTH), row_height));
+        let eyedropper = rect_from_top_left(
+            vec2(origin.x + preview.width() + gap, y),
+            Vec2::splat(row_height),
+        );
+        let hex_left = origin.x + preview.width() + row_height + gap * 2.0;
+        let hex = rect_from_top_left(
+            vec2(hex_left, y),
+            vec2(origin.x + content_width - hex_left, row_height),
+        );
+        y -= row_height + gap;
+
+        let label_width = scale.px(ROW_LABEL_WIDTH);
+        let field_width = (content_width - label_width - gap * 4.0) / 4.0;
+        let mut fields = Vec::with_capacity(FIELD_COUNT);
+        let mut row_labels = [rect; 2];
+        for row_label in row_labels.iter_mut() {
+            *row_label = rect_from_top_left(vec2(origin.x, y), vec2(label_width, row_height));
+            for column in 0..4 {
+                fields.push(rect_from_top_left(
+                    vec2(
+                        origin.x + label_width + gap + (field_width + gap) * column as f32,
+                        y,
+                    ),
+                    vec2(field_width, row_height),
+                ));
+            }
+            y -= row_height + gap;
+        }
+        fields.push(hex);
+
+        let recent = (0..MAX_RECENT_COLORS)
+            .map(|index| {
+                rect_from_top_left(
+                    vec2(origin.x + (swatch_size + swatch_gap) * index as f32, y),
+                    Vec2::splat(swatch_size),
+                )
+            })
+            .collect();
+
+        Self {
+            rect,
+            square,
+            hue,
+            alpha,
+            preview,
+            eyedropper,
+            fields,
+            row_labels,
+            recent,
+        }
+    }
+}
+
+/// Ids of the parts of the popup, they are taken even while it is closed
+struct PopupIds {
+    panel: Uiid,
+    square: Uiid,
+    hue: Uiid,
+    alpha: Uiid,
+    eyedropper: Uiid,
+    recent: Vec<Uiid>,
+}
+
+impl PopupIds {
+    fn new(control_state: &mut ControlState) -> Self {
+        Self {
+            panel: control_state.get_id(),
+            square: control_state.get_id(),
+            hue: control_state.get_id(),
+            alpha: control_state.get_id(),
+            eyedropper: control_state.get_id(),
+            recent: (0..MAX_RECENT_COLORS)
+                .map(|_| control_state.get_id())
+                .collect(),
+        }
+    }
+}
+
+/// Light and dark squares behind the colors that are not opaque
+fn checker_elements(rect: Rect, cell_size: f32, elements: &mut Vec<ElementBuilder>) {
+    elements.push(ElementBuilder::new_with_rect(rect).set_color(RGBA::rrr1(CHECKER_LIGHT).into()));
+    let columns = (rect.width() / cell_size).ceil() as usize;
+    let rows = (rect.height() / cell_size).ceil() as usize;
+    let top_left = rect.top_left_position();
+    for row in 0..rows {
+        for column in (row % 2..columns).step_by(2) {
+            let cell = rect_from_top_left(
+                top_left + vec2(column as f32, -(row as f32)) * cell_size,
+                Vec2::splat(cell_size),
+            );
+            elements.push(
+                ElementBuilder::new_with_rect(cell)
+                    .set_color(RGBA::rrr1(CHECKER_DARK).into())
+                    .set_rect_mask(rect.into()),
+            );
+        }
+    }
+}
+
+/// Text vertically centered in `rect`, horizontally centered too unless `align_left` is set
+fn text_elements(
+    text: &str,
+    rect: Rect,
+    align_left: bool,
+    font_size: f32,
+    font_collection: &FontCollection,
+    color: RGBA,
+) -> Vec<ElementBuilder> {
+    let (font_elements, text_rect) = create_single_line(text, font_size, font_collection, 0, 0.0);
+    let origin = if align_left {
+        vec2(
+            rect.left_position().x,
+            rect.position.y - text_rect.height() * 0.5,
+        )
+    } else {
+        rect.position - text_rect.size * 0.5
+    }
+    .round();
+    font_elements
+        .into_iter()
+        .map(|element| {
+            ElementBuilder::new_with_rect(element.rect.offset_position(origin))
+                .set_sdffont(element.tx_slice.into())
+                .set_color(color.into())
+                .set_rect_mask(rect.into())
+        })
+        .collect()
+}
+
+/// Thin bar across a strip that shows the position of its value
+fn strip_handle(strip: Rect, y: f32, thickness: f32, color: RGBA) -> ElementBuilder {
+    ElementBuilder::new(
+        vec2(strip.position.x, y).round(),
+        vec2(strip.width() + thickness * 2.0, thickness),
+    )
+    .set_color(RGBA::TRANSPARENT.into())
+    .set_border(Some(Border {
+        size: thickness * 0.5,
+        color: color.into(),
+    }))
+}
+
+/// Swatch of `color` that opens a popup to edit it, with a saturation/value square, hue and
+/// alpha strips, RGBA, HSLA and hex fields, the recent colors and an eyedropper that picks a
+/// color from the GUI. Returns the new color when it was edited.
+pub fn color_button(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    color: RGBA,
+    state: &mut ColorPicker,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Option<RGBA> {
+    let button_id = control_state.get_id();
+    // The popup goes over everything else, the blocker catches the clicks outside of it
+    control_state.set_depth_and_save(control_state.layers.top_layer_depth(Layer::Popup, 0));
+    let blocker_id = control_state.get_id();
+    control_state.restore_depth();
+    let popup_depth = control_state.layers.top_layer_depth(Layer::Popup, 1);
+    control_state.set_depth_and_save(popup_depth);
+    let ids = PopupIds::new(control_state);
+    control_state.restore_depth();
+    // While sampling, every click goes to the eyedropper
+    control_state.set_depth_and_save(control_state.layers.top_layer_depth(Layer::Popup, 2));
+    let sampling_id = control_state.get_id();
+    control_state.restore_depth();
+
+    if !state.open || !same_color(state.color(), color) {
+        state.set_color(color);
+    }
+
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+    let screen_size = get_engine_data(public_data).screen_size.as_vec2();
+    let screen_rect = Rect {
+        position: screen_size * 0.5,
+        size: screen_size,
+    };
+    let layout = PickerLayout::new(rect, screen_size, control_state, theme);
+
+    match keyboard::get_pressed_key(event) {
+        Some(VirtualKeyCode::Escape) if state.open && !state.is_typing() => {
+            if state.sampling {
+                state.sampling = false;
+            } else {
+                state.close(&mut control_state.color_sampler);
+            }
+        }
+        Some(VirtualKeyCode::Space | VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter)
+            if !state.open && control_state.is_focused(button_id) =>
+        {
+            state.open(color)
+        }
+        _ => {}
+    }
+
+    // A click that closes the popup still reaches the fields, so the typed value is kept
+    let was_open = state.open;
+    match event {
+        UIEvent::Update => {
+            if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(button_id, &interact_rect);
+            }
+            if state.open {
+                control_state.set_hot_with_rect(blocker_id, &screen_rect);
+                control_state.set_hot_with_rect(ids.panel, &layout.rect);
+                if let Some((_, active_id)) = state.drag {
+                    control_state.hold_active_state(active_id);
+                } else {
+                    let parts = [
+                        (ids.square, layout.square),
+                        (ids.hue, layout.hue),
+                        (ids.alpha, layout.alpha),
+                        (ids.eyedropper, layout.eyedropper),
+                    ];
+                    let recent = ids
+                        .recent
+                        .iter()
+                        .copied()
+                        .zip(layout.recent.iter().copied())
+                        .take(control_state.color_sampler.recent().len());
+                    for (id, part_rect) in parts.into_iter().chain(recent) {
+                        control_state.set_hot_with_rect(id, &part_rect);
+                    }
+                }
+                if state.sampling {
+                    control_state.set_hot_with_rect(sampling_id, &screen_rect);
+                }
+            }
+
+            let button_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::ColorWell, rect, label)
+                    .set_control(button_id)
+                    .set_value(&to_hex(state.color()))
+                    .set_expanded(state.open),
+            );
+            if state.open {
+                control_state.access.push_parent(button_access_id);
+                let dialog_access_id = control_state.push_access_node(
+                    AccessNode::new(AccessRole::Dialog, layout.rect, label).set_control(ids.panel),
+                );
+                control_state.access.push_parent(dialog_access_id);
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Slider, layout.hue, "Hue")
+                        .set_control(ids.hue)
+                        .set_range(state.hsv[0], 0.0, 360.0),
+                );
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Slider, layout.alpha, "Alpha")
+                        .set_control(ids.alpha)
+                        .set_range(state.alpha, 0.0, 1.0),
+                );
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Button, layout.eyedropper, "Eyedropper")
+                        .set_control(ids.eyedropper)
+                        .set_selected(state.sampling),
+                );
+                let recent: Vec<RGBA> = control_state.color_sampler.recent().to_vec();
+                for ((id, swatch), recent_color) in
+                    ids.recent.iter().zip(&layout.recent).zip(recent)
+                {
+                    control_state.push_access_node(
+                        AccessNode::new(AccessRole::Button, *swatch, &to_hex(recent_color))
+                            .set_control(*id),
+                    );
+                }
+                // The fields are added to the dialog by `popup_fields`, which pops both parents
+            }
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+            if state.sampling && keyboard::is_right_pressed(mouse_input) {
+                state.sampling = false;
+            }
+            if mouse_input.is_left_pressed() {
+                if state.sampling {
+                    if control_state.is_hovered(sampling_id) {
+                        if let Some(sample) = control_state.color_sampler.sample() {
+                            state.set_color(sample);
+                            control_state.color_sampler.add_recent(sample);
+                        }
+                        state.sampling = false;
+                    }
+                } else if !state.open {
+                    if control_state.is_hovered(button_id) {
+                        control_state.set_focus(button_id);
+                        state.open(color);
+                    }
+                } else if control_state.is_hovered(blocker_id) {
+                    // Clicking the button again closes the popup but keeps the focus
+                    if rect.inside_rect(cursor_pos) {
+                        control_state.set_focus(button_id);
+                    }
+                    state.close(&mut control_state.color_sampler);
+                } else {
+                    let drag_target = if control_state.is_hovered(ids.square) {
+                        Some((DragTarget::Square, ids.square))
+                    } else if control_state.is_hovered(ids.hue) {
+                        Some((DragTarget::Hue, ids.hue))
+                    } else if control_state.is_hovered(ids.alpha) {
+                        Some((DragTarget::Alpha, ids.alpha))
+                    } else {
+                        None
+                    };
+
+                    if let Some((target, id)) = drag_target {
+                        if let Some(active_id) = control_state.set_active(id) {
+                            state.drag = Some((target, active_id));
+                            state.drag_to(target, cursor_pos, &layout);
+                        }
+                    } else if control_state.is_hovered(ids.eyedropper) {
+                        state.sampling = true;
+                        control_state.color_sampler.start();
+                    } else if let Some(index) = ids
+                        .recent
+                        .iter()
+                        .position(|id| control_state.is_hovered(*id))
+                    {
+                        if let Some(recent) = control_state.color_sampler.recent().get(index) {
+                            state.set_color(*recent);
+                        }
+                    }
+                }
+            }
+            if mouse_input.is_left_released() {
+                state.drag = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if let Some((target, active_id)) = state.drag {
+                if control_state.is_active(Some(active_id)) {
+                    state.drag_to(target, *corrected, &layout);
+                }
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let new_color = state.color();
+            let (border_width, border_color) = if control_state.is_focused(button_id) {
+                (theme.metrics.focus_border_width, colors.focus)
+            } else if state.open || control_state.is_hovered(button_id) {
+                (theme.metrics.border_width, colors.text_muted)
+            } else {
+                (theme.metrics.border_width, colors.border)
+            };
+            if let Some(button_mask) = rect.combine_rects(&mask) {
+                let mut elements = Vec::new();
+                checker_elements(rect, rect.height() * 0.5, &mut elements);
+                elements.push(
+                    ElementBuilder::new_with_rect(rect)
+                        .set_color(new_color.into())
+                        .set_border(Some(Border {
+                            size: scale.border(border_width),
+                            color: border_color.into(),
+                        })),
+                );
+                for element in elements {
+                    let element = element.set_rect_mask(button_mask.into());
+                    control_state.layers.build(element, gui_rects);
+                }
+            }
+
+            if state.sampling {
+                control_state.request_cursor(CursorIcon::Crosshair, CursorPriority::Active);
+                let cursor_pos = control_state.last_cursor_position;
+                if let Some(cursor_pos) = cursor_pos {
+                    control_state.color_sampler.request(cursor_pos);
+                }
+                let sample = control_state.color_sampler.sample();
+                if let (Some(cursor_pos), Some(sample)) = (cursor_pos, sample) {
+                    // Shown next to the cursor, so it does not hide what is being picked
+                    let size = scale.px(SAMPLE_PREVIEW_SIZE);
+                    let sample_rect = Rect {
+                        position: (cursor_pos + vec2(size, size)).round(),
+                        size: Vec2::splat(size),
+                    };
+                    let border = Border {
+                        size: scale.border(theme.metrics.border_width),
+                        color: colors.text.into(),
+                    };
+                    control_state.layers.push_top_render_step(
+                        Layer::Popup,
+                        2,
+                        Box::new(move |gui_rects| {
+                            ElementBuilder::new_with_rect(sample_rect)
+                                .set_color(sample.into())
+                                .set_round_rect(BorderRadius::ForAll(size * 0.25).into())
+                                .set_border(Some(border))
+                                .build(gui_rects);
+                        }),
+                    );
+                }
+            }
+
+            if state.open {
+                let elements = popup_elements(state, &layout, &ids, control_state, public_data);
+                control_state.layers.push_top_render_step(
+                    Layer::Popup,
+                    1,
+                    Box::new(move |gui_rects| {
+                        for element in elements {
+                            element.build(gui_rects);
+                        }
+                    }),
+                );
+            }
+        }
+        _ => {}
+    }
+
+    control_state.set_depth_and_save(popup_depth);
+    popup_fields(state, was_open, &layout, event, control_state, public_data);
+    control_state.restore_depth();
+
+    edited_color(color, state.color())
+}
+
+/// Drag values for the channels and a text field for the hex code. They take their ids even
+/// while the popup is closed, and are drawn in a render step over the rest of the popup.
+fn popup_fields(
+    state: &mut ColorPicker,
+    open: bool,
+    layout: &PickerLayout,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) {
+    if !open {
+        for _ in 0..FIELD_COUNT {
+            control_state.get_id();
+        }
+        return;
+    }
+
+    let render = matches!(event, UIEvent::Render { .. });
+    if render {
+        control_state.layers.begin_capture();
+    }
+
+    for index in 0..HEX_FIELD {
+        let value = state.field_value(index);
+        let new_value = drag_value::drag_value(
+            layout.fields[index],
+            layout.rect,
+            CHANNEL_LABELS[index],
+            value,
+            &mut state.channels[index],
+            event,
+            control_state,
+            public_data,
+        );
+        if new_value != value {
+            state.set_field_value(index, new_value);
+        }
+    }
+
+    let response = text_field::text_field(
+        layout.fields[HEX_FIELD],
+        layout.rect,
+        &mut state.hex,
+        event,
+        control_state,
+        public_data,
+    );
+    let hex_id = control_state.get_last_id();
+    // Like the channels, Enter and a click outside keep the typed color and Escape drops it
+    let clicked_away = match event {
+        UIEvent::MouseButton(mouse_input) => {
+            state.hex_focused && mouse_input.is_left_pressed() && !control_state.is_hovered(hex_id)
+        }
+        _ => false,
+    };
+    if response.submitted || clicked_away {
+        let hex = format!("#{}", state.hex.text().trim().trim_start_matches('#'));
+        if let Ok(color) = parse_color(&hex) {
+            state.set_color(color);
+        }
+        state.hex_focused = false;
+    }
+    if let UIEvent::Update = event {
+        state.hex_focused = control_state.is_focused(hex_id);
+        let hex = to_hex(state.color());
+        if !state.hex_focused && state.hex.text() != hex {
+            state.hex.editor.set_text(&hex);
+        }
+        control_state.access.pop_parent();
+        control_state.access.pop_parent();
+    }
+
+    if render {
+        let elements = control_state.layers.end_capture();
+        control_state.layers.push_top_render_step(
+            Layer::Popup,
+            1,
+            Box::new(move |gui_rects| {
+                for element in elements {
+                    element.build(gui_rects);
+                }
+            }),
+        );
+    }
+}
+
+/// Nothing is returned for the small differences left by the conversions, so the color does not
+/// drift while the popup is open
+fn edited_color(color: RGBA, new_color: RGBA) -> Option<RGBA> {
+    (!same_color(color, new_color)).then(|| new_color)
+}
+
+/// Everything in the popup, drawn over the rest of the GUI
+fn popup_elements(
+    state: &ColorPicker,
+    layout: &PickerLayout,
+    ids: &PopupIds,
+    control_state: &ControlState,
+    public_data: &PublicData,
+) -> Vec<ElementBuilder> {
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+    let new_color = state.color();
+    let radius = scale.px(theme.metrics.radius_small);
+    let mut elements = vec![ElementBuilder::new_with_rect(layout.rect)
+        .set_color(colors.panel.into())
+        .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+        .set_border(Some(Border {
+            size: scale.border(theme.metrics.border_width),
+            color: colors.border.into(),
+        }))];
+
+    // White to the pure hue from left to right, darkened to black from top to bottom
+    let square = layout.square;
+    let [r, g, b] = hsv_to_rgb([state.hsv[0], 1.0, 1.0]);
+    elements.push(
+        ElementBuilder::new_with_rect(square).set_linear_gradient(
+            LinearGradient {
+                colors: [RGBA::WHITE, RGBA::rgb(r, g, b)],
+                start_position: vec2(-square.width() * 0.5, 0.0),
+                end_position: vec2(square.width() * 0.5, 0.0),
+            }
+            .into(),
+        ),
+    );
+    elements.push(
+        ElementBuilder::new_with_rect(square).set_linear_gradient(
+            LinearGradient {
+                colors: [RGBA::BLACK.set_alpha(0.0), RGBA::BLACK],
+                start_position: vec2(0.0, square.height() * 0.5),
+                end_position: vec2(0.0, -square.height() * 0.5),
+            }
+            .into(),
+        ),
+    );
+    let handle_size = scale.px(HANDLE_SIZE);
+    let handle_position = vec2(
+        square.left_position().x + state.hsv[1] * square.width(),
+        bottom(square) + state.hsv[2] * square.height(),
+    );
+    elements.push(
+        ElementBuilder::new(handle_position.round(), Vec2::splat(handle_size))
+            .set_circle()
+            .set_color(new_color.set_alpha(1.0).into())
+            .set_border(Some(Border {
+                size: scale.border(theme.metrics.focus_border_width),
+                color: if state.hsv[2] > 0.5 && state.hsv[1] < 0.5 {
+                    RGBA::BLACK.into()
+                } else {
+                    RGBA::WHITE.into()
+                },
+            }))
+            .set_rect_mask(layout.rect.into()),
+    );
+
+    // One gradient for every sixth of the hue circle, red at the top
+    let hue = layout.hue;
+    let segment_height = hue.height() / 6.0;
+    for segment in 0..6 {
+        let [r0, g0, b0] = hsv_to_rgb([segment as f32 * 60.0, 1.0, 1.0]);
+        let [r1, g1, b1] = hsv_to_rgb([(segment + 1) as f32 * 60.0, 1.0, 1.0]);
+        elements.push(
+            ElementBuilder::new_with_rect(rect_from_top_left(
+                hue.top_left_position() - vec2(0.0, segment_height * segment as f32),
+                vec2(hue.width(), segment_height),
+            ))
+            .set_linear_gradient(
+                LinearGradient {
+                    colors: [RGBA::rgb(r0, g0, b0), RGBA::rgb(r1, g1, b1)],
+                    start_position: vec2(0.0, segment_height * 0.5),
+                    end_position: vec2(0.0, -segment_height * 0.5),
+                }
+                .into(),
+            ),
+        );
+    }
+    let handle_thickness = scale.px(HANDLE_THICKNESS);
+    let hue_y = hue.top_left_position().y - state.hsv[0] / 360.0 * hue.height();
+    elements.push(strip_handle(hue, hue_y, handle_thickness, colors.text));
+
+    // The opaque color fades out from top to bottom over a checkerboard
+    let alpha = layout.alpha;
+    checker_elements(alpha, alpha.width() * 0.5, &mut elements);
+    elements.push(
+        ElementBuilder::new_with_rect(alpha).set_linear_gradient(
+            LinearGradient {
+                colors: [new_color.set_alpha(1.0), new_color.set_alpha(0.0)],
+                start_position: vec2(0.0, alpha.height() * 0.5),
+                end_position: vec2(0.0, -alpha.height() * 0.5),
+            }
+            .into(),
+        ),
+    );
+    let alpha_y = bottom(alpha) + state.alpha * alpha.height();
+    elements.push(strip_handle(alpha, alpha_y, handle_thickness, colors.text));
+
+    let preview = layout.preview;
+    checker_elements(preview, preview.height() * 0.5, &mut elements);
+    let half_size = vec2(preview.width() * 0.5, preview.height());
+    elements.push(
+        ElementBuilder::new(preview.position - vec2(half_size.x * 0.5, 0.0), half_size)
+            .set_color(state.initial.into()),
+    );
+    elements.push(
+        ElementBuilder::new(preview.position + vec2(half_size.x * 0.5, 0.0), half_size)
+            .set_color(new_color.into()),
+    );
+
+    let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+    let font_size = scale.font(theme.fonts.size);
+    let small_font_size = scale.font(theme.fonts.size_small);
+
+    let eyedropper_fill = if state.sampling {
+        colors.accent
+    } else if control_state.is_hovered(ids.eyedropper) {
+        colors.control_hover
+    } else {
+        colors.control
+    };
+    elements.push(
+        ElementBuilder::new_with_rect(layout.eyedropper)
+            .set_color(eyedropper_fill.into())
+            .set_round_rect(BorderRadius::ForAll(radius).into()),
+    );
+    elements.extend(text_elements(
+        "+",
+        layout.eyedropper,
+        false,
+        font_size,
+        font_collection,
+        colors.text,
+    ));
+
+    for (row_label, text) in layout.row_labels.iter().zip(ROW_LABELS) {
+        elements.extend(text_elements(
+            text,
+            *row_label,
+            true,
+            small_font_size,
+            font_collection,
+            colors.text_muted,
+        ));
+    }
+
+    let recent = control_state.color_sampler.recent();
+    for (index, (id, swatch)) in ids.recent.iter().zip(&layout.recent).enumerate() {
+        match recent.get(index) {
+            Some(recent_color) => {
+                checker_elements(*swatch, swatch.height() * 0.5, &mut elements);
+                let mut swatch_element =
+                    ElementBuilder::new_with_rect(*swatch).set_color((*recent_color).into());
+                if control_state.is_hovered(*id) {
+                    swatch_element = swatch_element.set_border(Some(Border {
+                        size: scale.border(theme.metrics.focus_border_width),
+                        color: colors.focus.into(),
+                    }));
+                }
+                elements.push(swatch_element);
+            }
+            None => elements.push(
+                ElementBuilder::new_with_rect(*swatch)
+                    .set_color(colors.control.into())
+                    .set_round_rect(BorderRadius::ForAll(radius).into()),
+            ),
+        }
+    }
+
+    elements
+}
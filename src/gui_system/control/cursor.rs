@@ -0,0 +1,46 @@
+pub use rwge::winit::window::CursorIcon;
+
+use crate::runtime_data::{utils::get_window, PublicData};
+
+/// A control that is being used wins over one that is only hovered
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub enum CursorPriority {
+    Hover,
+    Active,
+}
+
+pub struct CursorState {
+    request: Option<(CursorIcon, CursorPriority)>,
+    applied: CursorIcon,
+}
+
+impl CursorState {
+    pub fn new() -> Self {
+        Self {
+            request: None,
+            applied: CursorIcon::Default,
+        }
+    }
+
+    /// With the same priority the last request wins, like the hot control
+    pub fn request(&mut self, icon: CursorIcon, priority: CursorPriority) {
+        match self.request {
+            Some((_, current_priority)) if current_priority > priority => {}
+            _ => self.request = Some((icon, priority)),
+        }
+    }
+
+    /// Called once per frame after the GUI was rendered. The window is only touched when the
+    /// icon changes.
+    pub fn apply(&mut self, public_data: &PublicData) {
+        let icon = match self.request.take() {
+            Some((icon, _)) => icon,
+            None => CursorIcon::Default,
+        };
+        if icon == self.applied {
+            return;
+        }
+        self.applied = icon;
+        get_window(public_data).set_cursor_icon(icon);
+    }
+}
@@ -0,0 +1,491 @@
+use rwge::{
+    color::RGBA,
+    font::font_layout::create_single_line,
+    glam::{vec2, Vec2, Vec3, Vec4},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        theme::get_theme,
+    },
+    runtime_data::{utils::get_font_collections, PublicData},
+};
+
+use super::{
+    cursor::{CursorIcon, CursorPriority},
+    keyboard,
+    text_field::{self, TextField},
+    ControlState,
+};
+
+/// Step multiplier while Shift is held
+const FINE_FACTOR: f32 = 0.1;
+/// Step multiplier while Ctrl is held
+const COARSE_FACTOR: f32 = 10.0;
+const COLOR_STRIP_WIDTH: f32 = 3.0;
+const COMPONENT_GAP: f32 = 4.0;
+const COMPONENT_LABELS: [&str; 4] = ["X", "Y", "Z", "W"];
+
+/// Red, green and blue like the axes of a transform, the fourth component is yellow
+fn component_color(index: usize) -> RGBA {
+    match index {
+        0 => RGBA::rgb(0.85, 0.3, 0.3),
+        1 => RGBA::rgb(0.4, 0.75, 0.3),
+        2 => RGBA::rgb(0.3, 0.5, 0.9),
+        _ => RGBA::rgb(0.85, 0.7, 0.25),
+    }
+}
+
+/// Settings and state of a drag value field, kept by the container between frames
+pub struct DragValue {
+    /// Change of the value for every pixel the cursor is dragged, and for every arrow key press
+    pub step: f32,
+    /// Decimals shown, the value is rounded to them
+    pub precision: usize,
+    /// Drawn after the value, like a unit
+    pub suffix: String,
+    pub range: Option<(f32, f32)>,
+    /// Drawn as a strip on the left side
+    pub color: Option<RGBA>,
+    active_id: Option<Uuid>,
+    last_cursor_x: f32,
+    /// Value being dragged, before it is rounded
+    unrounded: f32,
+    /// Present while the exact value is typed
+    editing: Option<TextField>,
+    start_editing: bool,
+}
+
+impl DragValue {
+    pub fn new() -> Self {
+        Self {
+            step: 0.1,
+            precision: 2,
+            suffix: String::new(),
+            range: None,
+            color: None,
+            active_id: None,
+            last_cursor_x: 0.0,
+            unrounded: 0.0,
+            editing: None,
+            start_editing: false,
+        }
+    }
+
+    pub fn set_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn set_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn set_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = String::from(suffix);
+        self
+    }
+
+    /// The value is clamped between `min` and `max`
+    pub fn set_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn set_color(mut self, color: RGBA) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn clamp(&self, value: f32) -> f32 {
+        match self.range {
+            Some((min, max)) => value.max(min).min(max),
+            None => value,
+        }
+    }
+
+    fn round(&self, value: f32) -> f32 {
+        let factor = 10f32.powi(self.precision as i32);
+        self.clamp((value * factor).round() / factor)
+    }
+
+    fn format(&self, value: f32) -> String {
+        format!("{:.*}{}", self.precision, value, self.suffix)
+    }
+
+    /// The suffix can be typed too
+    fn parse(&self, text: &str) -> Option<f32> {
+        let text = text.trim();
+        let text = text.strip_suffix(self.suffix.trim()).unwrap_or(text);
+        text.trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+    }
+
+    /// Shift gives fine steps and Ctrl coarse ones
+    fn step_factor(control_state: &ControlState) -> f32 {
+        if control_state.text_input.shift() {
+            FINE_FACTOR
+        } else if control_state.text_input.ctrl() {
+            COARSE_FACTOR
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Compact numeric field. Dragging it horizontally changes the value, double clicking it or
+/// pressing Enter while it has the focus turns it into a text field to type the exact value.
+/// Returns the new value.
+pub fn drag_value(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    value: f32,
+    field: &mut DragValue,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> f32 {
+    let mut new_value = value;
+
+    // The text field only shows up on an update, so it does not handle the click that opened it
+    let mut just_started = false;
+    if let UIEvent::Update = event {
+        if field.start_editing {
+            field.start_editing = false;
+            let mut text_field =
+                TextField::new(&format!("{:.*}", field.precision, value)).set_placeholder(label);
+            text_field.editor.select_all();
+            field.editing = Some(text_field);
+            just_started = true;
+        }
+    }
+
+    // Both branches take a single id, so the controls after this one keep theirs
+    if let Some(text_field) = field.editing.as_mut() {
+        let response =
+            text_field::text_field(rect, mask, text_field, event, control_state, public_data);
+        let text_field_id = control_state.get_last_id();
+        // Enter and a click outside of the field keep the typed value, Escape restores the old one
+        let clicked_away = match event {
+            UIEvent::MouseButton(mouse_input) => {
+                mouse_input.is_left_pressed() && !control_state.is_hovered(text_field_id)
+            }
+            _ => false,
+        };
+        if response.submitted || clicked_away {
+            if let Some(typed) = field.parse(text_field.text()) {
+                new_value = field.round(typed);
+            }
+            field.editing = None;
+        } else if response.cancelled {
+            field.editing = None;
+        }
+        if let UIEvent::Update = event {
+            if just_started {
+                control_state.set_focus(text_field_id);
+            }
+        }
+        // Enter and Escape keep the focus on the field, so it can be stepped or edited again
+        if response.submitted || response.cancelled {
+            control_state.set_focus(text_field_id);
+        }
+        return new_value;
+    }
+
+    let control_id = control_state.get_id();
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+
+    if control_state.is_focused(control_id) {
+        match keyboard::get_pressed_key(event) {
+            Some(VirtualKeyCode::Up | VirtualKeyCode::Right) => {
+                new_value = field.round(value + field.step * DragValue::step_factor(control_state))
+            }
+            Some(VirtualKeyCode::Down | VirtualKeyCode::Left) => {
+                new_value = field.round(value - field.step * DragValue::step_factor(control_state))
+            }
+            Some(VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter) => {
+                field.start_editing = true
+            }
+            _ => {}
+        }
+    }
+
+    match event {
+        UIEvent::Update => {
+            if let Some(active_id) = field.active_id {
+                control_state.hold_active_state(active_id);
+            } else if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(control_id, &interact_rect);
+            }
+
+            let mut node = AccessNode::new(AccessRole::SpinButton, rect, label)
+                .set_control(control_id)
+                .set_value(&field.format(value));
+            if let Some((min, max)) = field.range {
+                node = node.set_range(value, min, max);
+            }
+            control_state.push_access_node(node);
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() && control_state.is_hovered(control_id) {
+                control_state.set_focus(control_id);
+                if control_state.register_click(control_id) {
+                    field.start_editing = true;
+                } else if let Some(cursor_pos) = control_state.last_cursor_position {
+                    field.active_id = control_state.set_active(control_id);
+                    field.last_cursor_x = cursor_pos.x;
+                    field.unrounded = value;
+                }
+            }
+            if mouse_input.is_left_released() {
+                field.active_id = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if control_state.is_active(field.active_id) {
+                let delta = corrected.x - field.last_cursor_x;
+                field.last_cursor_x = corrected.x;
+                field.unrounded = field.clamp(
+                    field.unrounded + delta * field.step * DragValue::step_factor(control_state),
+                );
+                new_value = field.round(field.unrounded);
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let hovered = control_state.is_hovered(control_id);
+            let active = field.active_id.is_some();
+            if active {
+                control_state.request_cursor(CursorIcon::EwResize, CursorPriority::Active);
+            } else if hovered {
+                control_state.request_cursor(CursorIcon::EwResize, CursorPriority::Hover);
+            }
+
+            let (border_width, border_color) = if control_state.is_focused(control_id) {
+                (theme.metrics.focus_border_width, colors.focus)
+            } else {
+                (theme.metrics.border_width, colors.border)
+            };
+            let radius = scale.px(theme.metrics.radius_small);
+            let background = ElementBuilder::new_with_rect(rect)
+                .set_color(if hovered || active {
+                    colors.control_hover.into()
+                } else {
+                    colors.control.into()
+                })
+                .set_round_rect(BorderRadius::ForAll(radius).into())
+                .set_border(Some(Border {
+                    size: scale.border(border_width),
+                    color: border_color.into(),
+                }))
+                .set_rect_mask(mask.into());
+            control_state.layers.build(background, gui_rects);
+
+            if let Some(color) = field.color {
+                let strip_width = scale.px(COLOR_STRIP_WIDTH);
+                let strip = ElementBuilder::new(
+                    vec2(rect.left_position().x + strip_width * 0.5, rect.position.y),
+                    vec2(strip_width, rect.height()),
+                )
+                .set_color(color.into())
+                .set_round_rect(
+                    BorderRadius::ForLeftRight {
+                        left: radius,
+                        right: 0.0,
+                    }
+                    .into(),
+                )
+                .set_rect_mask(mask.into());
+                control_state.layers.build(strip, gui_rects);
+            }
+
+            let text_mask = match rect.combine_rects(&mask) {
+                Some(text_mask) => text_mask,
+                None => return new_value,
+            };
+            let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+            let (font_elements, text_rect) = create_single_line(
+                &field.format(value),
+                scale.font(theme.fonts.size),
+                font_collection,
+                0,
+                0.0,
+            );
+            let text_origin = rect.position - text_rect.size * 0.5;
+            for element in font_elements {
+                let glyph = ElementBuilder::new_with_rect(
+                    element.rect.offset_position(text_origin.round()),
+                )
+                .set_sdffont(element.tx_slice.into())
+                .set_color(colors.text.into())
+                .set_rect_mask(text_mask.into());
+                control_state.layers.build(glyph, gui_rects);
+            }
+        }
+        _ => {}
+    }
+
+    new_value
+}
+
+/// One color coded drag value for every component of a vector, kept by the container between
+/// frames. The settings are the same for every component.
+pub struct VectorEditor {
+    pub fields: Vec<DragValue>,
+}
+
+impl VectorEditor {
+    pub fn new(components: usize) -> Self {
+        Self {
+            fields: (0..components)
+                .map(|index| DragValue::new().set_color(component_color(index)))
+                .collect(),
+        }
+    }
+
+    pub fn set_step(self, step: f32) -> Self {
+        self.map_fields(|field| field.set_step(step))
+    }
+
+    pub fn set_precision(self, precision: usize) -> Self {
+        self.map_fields(|field| field.set_precision(precision))
+    }
+
+    pub fn set_suffix(self, suffix: &str) -> Self {
+        self.map_fields(|field| field.set_suffix(suffix))
+    }
+
+    pub fn set_range(self, min: f32, max: f32) -> Self {
+        self.map_fields(|field| field.set_range(min, max))
+    }
+
+    fn map_fields(self, map: impl Fn(DragValue) -> DragValue) -> Self {
+        Self {
+            fields: self.fields.into_iter().map(map).collect(),
+        }
+    }
+}
+
+/// The fields split `rect` evenly, the editor needs at least as many fields as `values`
+fn vector_editor(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    values: &mut [f32],
+    editor: &mut VectorEditor,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) {
+    let gap = control_state.scale.px(COMPONENT_GAP);
+    let count = values.len() as f32;
+    let width = (rect.width() - gap * (count - 1.0)) / count;
+    for (index, (value, field)) in values.iter_mut().zip(editor.fields.iter_mut()).enumerate() {
+        let field_rect = Rect {
+            position: vec2(
+                rect.left_position().x + width * (index as f32 + 0.5) + gap * index as f32,
+                rect.position.y,
+            ),
+            size: vec2(width, rect.height()),
+        };
+        *value = drag_value(
+            field_rect,
+            mask,
+            &format!("{} {}", label, COMPONENT_LABELS[index]),
+            *value,
+            field,
+            event,
+            control_state,
+            public_data,
+        );
+    }
+}
+
+pub fn vec2_editor(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    value: Vec2,
+    editor: &mut VectorEditor,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Vec2 {
+    let mut values = value.to_array();
+    vector_editor(
+        rect,
+        mask,
+        label,
+        &mut values,
+        editor,
+        event,
+        control_state,
+        public_data,
+    );
+    Vec2::from(values)
+}
+
+pub fn vec3_editor(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    value: Vec3,
+    editor: &mut VectorEditor,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Vec3 {
+    let mut values = value.to_array();
+    vector_editor(
+        rect,
+        mask,
+        label,
+        &mut values,
+        editor,
+        event,
+        control_state,
+        public_data,
+    );
+    Vec3::from(values)
+}
+
+pub fn vec4_editor(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    value: Vec4,
+    editor: &mut VectorEditor,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Vec4 {
+    let mut values = value.to_array();
+    vector_editor(
+        rect,
+        mask,
+        label,
+        &mut values,
+        editor,
+        event,
+        control_state,
+        public_data,
+    );
+    Vec4::from(values)
+}
@@ -0,0 +1,710 @@
+use rwge::{
+    color::RGBA,
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        layers::Layer,
+        theme::{get_theme, Theme},
+    },
+    runtime_data::{
+        utils::{get_engine_data, get_font_collections},
+        PublicData,
+    },
+};
+
+use super::{text_field::TextInputEvent, ControlState};
+
+/// Rows shown at once, longer lists scroll
+pub const MAX_VISIBLE_ITEMS: usize = 8;
+/// Rows scrolled by every line of the mouse wheel
+const WHEEL_ROWS: f32 = 3.0;
+
+const ITEM_HEIGHT: f32 = 24.0;
+const LABEL_MARGIN: f32 = 8.0;
+const ARROW_WIDTH: f32 = 20.0;
+const LIST_GAP: f32 = 2.0;
+const SCROLLBAR_WIDTH: f32 = 6.0;
+const NO_MATCHES: &str = "No matches";
+
+/// Draws the option with the given index inside of the rect, it replaces the label of the option
+pub type ItemBuilder<'a> = &'a dyn Fn(usize, Rect) -> Vec<ElementBuilder>;
+
+/// State of a combo box, kept by the container between frames
+pub struct Dropdown {
+    open: bool,
+    /// Typed while the list is open, only the options that contain it are listed
+    filter: String,
+    /// Index into the filtered options
+    highlighted: usize,
+    /// First visible row, in rows
+    scroll: f32,
+    scroll_active_id: Option<Uuid>,
+    /// Distance from the center of the thumb to the cursor when the drag started
+    drag_offset: f32,
+}
+
+impl Dropdown {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            filter: String::new(),
+            highlighted: 0,
+            scroll: 0.0,
+            scroll_active_id: None,
+            drag_offset: 0.0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.filter.clear();
+        self.scroll_active_id = None;
+    }
+
+    fn open(&mut self, selected: usize) {
+        self.open = true;
+        self.filter.clear();
+        self.highlighted = selected;
+        self.scroll = 0.0;
+        self.scroll_to_highlighted();
+    }
+
+    /// Keeps the highlighted row inside of the visible rows
+    fn scroll_to_highlighted(&mut self) {
+        let highlighted = self.highlighted as f32;
+        if highlighted < self.scroll {
+            self.scroll = highlighted;
+        } else if highlighted + 1.0 > self.scroll + MAX_VISIBLE_ITEMS as f32 {
+            self.scroll = highlighted + 1.0 - MAX_VISIBLE_ITEMS as f32;
+        }
+    }
+
+    fn move_highlight(&mut self, step: isize, filtered_count: usize) {
+        if filtered_count == 0 {
+            return;
+        }
+        let highlighted = self.highlighted as isize + step;
+        self.highlighted = highlighted.max(0).min(filtered_count as isize - 1) as usize;
+        self.scroll_to_highlighted();
+    }
+
+    /// The selected option stays highlighted while it matches the filter
+    fn apply_filter(&mut self, options: &[&str], selected: usize) -> Vec<usize> {
+        let filtered = filter_options(options, &self.filter);
+        self.highlighted = filtered
+            .iter()
+            .position(|index| *index == selected)
+            .unwrap_or(0);
+        self.scroll = 0.0;
+        self.scroll_to_highlighted();
+        filtered
+    }
+}
+
+/// Indices of the options that contain `filter`, ignoring the case
+fn filter_options(options: &[&str], filter: &str) -> Vec<usize> {
+    let filter = filter.to_lowercase();
+    options
+        .iter()
+        .enumerate()
+        .filter(|(_, option)| option.to_lowercase().contains(&filter))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Where the list is placed and how it is split in rows
+struct ListLayout {
+    rect: Rect,
+    /// The rows are drawn inside of it
+    inner_rect: Rect,
+    item_height: f32,
+    /// Only present when the options do not fit
+    scrollbar: Option<Rect>,
+}
+
+impl ListLayout {
+    /// The list opens below the combo box, or above it if there is no room
+    fn new(
+        rect: Rect,
+        row_count: usize,
+        screen_size: Vec2,
+        control_state: &ControlState,
+        theme: &Theme,
+    ) -> Self {
+        let scale = control_state.scale;
+        let item_height = scale.px(ITEM_HEIGHT);
+        let padding = scale.px(theme.metrics.spacing_small);
+        let gap = scale.px(LIST_GAP);
+        let visible_rows = row_count.min(MAX_VISIBLE_ITEMS).max(1);
+        let height = visible_rows as f32 * item_height + padding * 2.0;
+
+        let mut top = rect.position.y - rect.height() * 0.5 - gap;
+        if top - height < 0.0 {
+            top = rect.position.y + rect.height() * 0.5 + gap + height;
+        }
+        top = top.min(screen_size.y);
+
+        let list_rect = Rect {
+            position: vec2(rect.position.x, top - height * 0.5),
+            size: vec2(rect.width(), height),
+        };
+        let scrollbar = (row_count > MAX_VISIBLE_ITEMS).then(|| {
+            let width = scale.px(SCROLLBAR_WIDTH);
+            Rect {
+                position: vec2(
+                    list_rect.position.x + list_rect.width() * 0.5 - padding - width * 0.5,
+                    list_rect.position.y,
+                ),
+                size: vec2(width, height - padding * 2.0),
+            }
+        });
+        let scrollbar_width = scrollbar.map_or(0.0, |scrollbar| scrollbar.width() + padding);
+        Self {
+            rect: list_rect,
+            inner_rect: Rect {
+                position: list_rect.position - vec2(scrollbar_width * 0.5, 0.0),
+                size: list_rect.size - vec2(padding * 2.0 + scrollbar_width, padding * 2.0),
+            },
+            item_height,
+            scrollbar,
+        }
+    }
+
+    fn row_rect(&self, row: usize, scroll: f32) -> Rect {
+        Rect {
+            position: vec2(
+                self.inner_rect.position.x,
+                self.inner_rect.top_left_position().y
+                    - (row as f32 - scroll + 0.5) * self.item_height,
+            ),
+            size: vec2(self.inner_rect.width(), self.item_height),
+        }
+    }
+
+    fn row_at(&self, y: f32, scroll: f32, row_count: usize) -> Option<usize> {
+        let row = (self.inner_rect.top_left_position().y - y) / self.item_height + scroll;
+        if row >= 0.0 && (row as usize) < row_count {
+            Some(row as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Rows that are at least partly visible
+    fn visible_rows(&self, scroll: f32, row_count: usize) -> std::ops::Range<usize> {
+        let start = scroll.floor() as usize;
+        let end = ((scroll + MAX_VISIBLE_ITEMS as f32).ceil() as usize).min(row_count);
+        start..end.max(start)
+    }
+
+    fn thumb_rect(&self, track: Rect, scroll: f32, row_count: usize) -> Rect {
+        let max_scroll = max_scroll(row_count);
+        let height = track.height() * MAX_VISIBLE_ITEMS as f32 / row_count as f32;
+        let progress = if max_scroll > 0.0 {
+            scroll / max_scroll
+        } else {
+            0.0
+        };
+        Rect {
+            position: vec2(
+                track.position.x,
+                track.top_left_position().y - height * 0.5 - progress * (track.height() - height),
+            ),
+            size: vec2(track.width(), height),
+        }
+    }
+
+    /// Scroll that puts the center of the thumb at `y`
+    fn scroll_at(&self, track: Rect, y: f32, row_count: usize) -> f32 {
+        let thumb_height = track.height() * MAX_VISIBLE_ITEMS as f32 / row_count as f32;
+        let travel = track.height() - thumb_height;
+        if travel <= 0.0 {
+            return 0.0;
+        }
+        let progress = (track.top_left_position().y - thumb_height * 0.5 - y) / travel;
+        progress.max(0.0).min(1.0) * max_scroll(row_count)
+    }
+}
+
+fn max_scroll(row_count: usize) -> f32 {
+    row_count.saturating_sub(MAX_VISIBLE_ITEMS) as f32
+}
+
+/// Text vertically centered in `rect`, starting at its left
+fn label_elements(
+    text: &str,
+    rect: Rect,
+    font_size: f32,
+    font_collection: &FontCollection,
+    color: RGBA,
+    mask: Rect,
+) -> Vec<ElementBuilder> {
+    let (font_elements, text_rect) = create_single_line(text, font_size, font_collection, 0, 0.0);
+    let origin = vec2(
+        rect.left_position().x,
+        rect.position.y - text_rect.height() * 0.5,
+    )
+    .round();
+    font_elements
+        .into_iter()
+        .map(|element| {
+            ElementBuilder::new_with_rect(element.rect.offset_position(origin))
+                .set_sdffont(element.tx_slice.into())
+                .set_color(color.into())
+                .set_rect_mask(mask.into())
+        })
+        .collect()
+}
+
+/// Combo box showing the selected option, clicking it opens the list of `options` over the rest
+/// of the GUI. While the list is open typing filters it. Returns the new selected index.
+pub fn dropdown(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    options: &[&str],
+    selected: usize,
+    state: &mut Dropdown,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> usize {
+    dropdown_impl(
+        rect,
+        mask,
+        label,
+        options,
+        selected,
+        state,
+        None,
+        event,
+        control_state,
+        public_data,
+    )
+}
+
+/// Same as `dropdown`, but the options are drawn by `item_builder`. The labels are still used
+/// to filter the options and by the accessibility tree.
+pub fn dropdown_custom(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    options: &[&str],
+    selected: usize,
+    state: &mut Dropdown,
+    item_builder: ItemBuilder,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> usize {
+    dropdown_impl(
+        rect,
+        mask,
+        label,
+        options,
+        selected,
+        state,
+        Some(item_builder),
+        event,
+        control_state,
+        public_data,
+    )
+}
+
+fn dropdown_impl(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    options: &[&str],
+    selected: usize,
+    state: &mut Dropdown,
+    item_builder: Option<ItemBuilder>,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> usize {
+    let mut new_selected = selected.min(options.len().saturating_sub(1));
+
+    let control_id = control_state.get_id();
+    // The list goes over everything else, the blocker catches the clicks outside of it
+    control_state.set_depth_and_save(control_state.layers.top_layer_depth(Layer::Popup, 0));
+    let blocker_id = control_state.get_id();
+    control_state.restore_depth();
+    control_state.set_depth_and_save(control_state.layers.top_layer_depth(Layer::Popup, 1));
+    let list_id = control_state.get_id();
+    let scrollbar_id = control_state.get_id();
+    control_state.restore_depth();
+
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+    let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+    let font_size = scale.font(theme.fonts.size);
+    let screen_size = get_engine_data(public_data).screen_size.as_vec2();
+
+    let mut filtered = filter_options(options, &state.filter);
+    if let UIEvent::Update = event {
+        // The focus moved somewhere else, every click outside of the list takes it away
+        if state.open && !control_state.is_focused(control_id) {
+            state.close();
+            filtered = filter_options(options, &state.filter);
+        }
+        if control_state.is_focused(control_id) {
+            // Chars typed in the same update as the key that opened the list are not filtered
+            let was_open = state.open;
+            for input in control_state.text_input.take_events() {
+                match input {
+                    TextInputEvent::Char(c) if was_open && state.open && !c.is_control() => {
+                        state.filter.push(c);
+                        filtered = state.apply_filter(options, new_selected);
+                    }
+                    TextInputEvent::Commit(text) if was_open && state.open => {
+                        state.filter.push_str(&text);
+                        filtered = state.apply_filter(options, new_selected);
+                    }
+                    TextInputEvent::Key { key, .. } if state.open => match key {
+                        VirtualKeyCode::Up => state.move_highlight(-1, filtered.len()),
+                        VirtualKeyCode::Down => state.move_highlight(1, filtered.len()),
+                        VirtualKeyCode::PageUp => {
+                            state.move_highlight(-(MAX_VISIBLE_ITEMS as isize), filtered.len())
+                        }
+                        VirtualKeyCode::PageDown => {
+                            state.move_highlight(MAX_VISIBLE_ITEMS as isize, filtered.len())
+                        }
+                        VirtualKeyCode::Back => {
+                            if state.filter.pop().is_some() {
+                                filtered = state.apply_filter(options, new_selected);
+                            }
+                        }
+                        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                            if let Some(index) = filtered.get(state.highlighted) {
+                                new_selected = *index;
+                            }
+                            state.close();
+                            filtered = filter_options(options, &state.filter);
+                        }
+                        VirtualKeyCode::Escape => {
+                            state.close();
+                            filtered = filter_options(options, &state.filter);
+                        }
+                        _ => {}
+                    },
+                    TextInputEvent::Key { key, .. } => match key {
+                        VirtualKeyCode::Space
+                        | VirtualKeyCode::Return
+                        | VirtualKeyCode::NumpadEnter
+                        | VirtualKeyCode::F4 => state.open(new_selected),
+                        VirtualKeyCode::Up => new_selected = new_selected.saturating_sub(1),
+                        VirtualKeyCode::Down => {
+                            new_selected = (new_selected + 1).min(options.len().saturating_sub(1))
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let list = ListLayout::new(rect, filtered.len(), screen_size, control_state, theme);
+    state.scroll = state.scroll.max(0.0).min(max_scroll(filtered.len()));
+
+    match event {
+        UIEvent::Update => {
+            if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(control_id, &interact_rect);
+            }
+            if state.open {
+                control_state.set_hot_with_rect(
+                    blocker_id,
+                    &Rect {
+                        position: screen_size * 0.5,
+                        size: screen_size,
+                    },
+                );
+                control_state.set_hot_with_rect(list_id, &list.rect);
+                if let Some(active_id) = state.scroll_active_id {
+                    control_state.hold_active_state(active_id);
+                } else if let Some(track) = list.scrollbar {
+                    control_state.set_hot_with_rect(scrollbar_id, &track);
+                }
+                // Taken here so the controls under the list do not scroll
+                if control_state.is_hovered(list_id) || control_state.is_hovered(scrollbar_id) {
+                    let lines = control_state.take_wheel_delta();
+                    state.scroll = (state.scroll - lines * WHEEL_ROWS)
+                        .max(0.0)
+                        .min(max_scroll(filtered.len()));
+                }
+            }
+
+            let combo_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::ComboBox, rect, label)
+                    .set_control(control_id)
+                    .set_value(options.get(new_selected).copied().unwrap_or(""))
+                    .set_expanded(state.open),
+            );
+            if state.open {
+                control_state.access.push_parent(combo_access_id);
+                let list_access_id = control_state.push_access_node(
+                    AccessNode::new(AccessRole::ListBox, list.rect, label).set_control(list_id),
+                );
+                control_state.access.push_parent(list_access_id);
+                for row in list.visible_rows(state.scroll, filtered.len()) {
+                    let index = filtered[row];
+                    control_state.push_access_node(
+                        AccessNode::new(
+                            AccessRole::ListBoxOption,
+                            list.row_rect(row, state.scroll),
+                            options[index],
+                        )
+                        .set_selected(index == new_selected)
+                        .set_focused(row == state.highlighted),
+                    );
+                }
+                control_state.access.pop_parent();
+                control_state.access.pop_parent();
+            }
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() {
+                let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+                if !state.open && control_state.is_hovered(control_id) {
+                    control_state.set_focus(control_id);
+                    state.open(new_selected);
+                } else if state.open {
+                    control_state.set_focus(control_id);
+                    if control_state.is_hovered(scrollbar_id) {
+                        state.scroll_active_id = control_state.set_active(scrollbar_id);
+                        let track = list.scrollbar.unwrap();
+                        let thumb = list.thumb_rect(track, state.scroll, filtered.len());
+                        state.drag_offset = if thumb.inside_rect(cursor_pos) {
+                            cursor_pos.y - thumb.position.y
+                        } else {
+                            0.0
+                        };
+                        state.scroll =
+                            list.scroll_at(track, cursor_pos.y - state.drag_offset, filtered.len());
+                    } else if control_state.is_hovered(list_id) {
+                        if let Some(row) = list.row_at(cursor_pos.y, state.scroll, filtered.len()) {
+                            new_selected = filtered[row];
+                            state.close();
+                        }
+                    } else if control_state.is_hovered(blocker_id) {
+                        // Clicking the combo box again closes the list but keeps the focus
+                        if !rect.inside_rect(cursor_pos) {
+                            control_state.clear_focus();
+                        }
+                        state.close();
+                    }
+                }
+            }
+            if mouse_input.is_left_released() {
+                state.scroll_active_id = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if control_state.is_active(state.scroll_active_id) {
+                if let Some(track) = list.scrollbar {
+                    state.scroll =
+                        list.scroll_at(track, corrected.y - state.drag_offset, filtered.len());
+                }
+            } else if state.open && control_state.is_hovered(list_id) {
+                if let Some(row) = list.row_at(corrected.y, state.scroll, filtered.len()) {
+                    state.highlighted = row;
+                }
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let focused = control_state.is_focused(control_id);
+            let (border_width, border_color) = if focused {
+                (theme.metrics.focus_border_width, colors.focus)
+            } else {
+                (theme.metrics.border_width, colors.border)
+            };
+            let fill = if state.open || control_state.is_hovered(control_id) {
+                colors.control_hover
+            } else {
+                colors.control
+            };
+            let field = ElementBuilder::new_with_rect(rect)
+                .set_color(fill.into())
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+                .set_border(Some(Border {
+                    size: scale.border(border_width),
+                    color: border_color.into(),
+                }))
+                .set_rect_mask(mask.into());
+            control_state.layers.build(field, gui_rects);
+
+            let label_margin = scale.px(LABEL_MARGIN);
+            let arrow_width = scale.px(ARROW_WIDTH);
+            let content_rect = Rect {
+                position: rect.position - vec2(arrow_width * 0.5, 0.0),
+                size: rect.size - vec2(label_margin * 2.0 + arrow_width, 0.0),
+            };
+            if let Some(content_mask) = content_rect.combine_rects(&mask) {
+                // The filter replaces the selected option while it is being typed
+                let elements = if !state.filter.is_empty() {
+                    label_elements(
+                        &state.filter,
+                        content_rect,
+                        font_size,
+                        font_collection,
+                        colors.text,
+                        content_mask,
+                    )
+                } else {
+                    match (item_builder, options.get(new_selected)) {
+                        (Some(item_builder), Some(_)) => item_builder(new_selected, content_rect)
+                            .into_iter()
+                            .map(|element| element.set_rect_mask(content_mask.into()))
+                            .collect(),
+                        (None, Some(option)) => label_elements(
+                            option,
+                            content_rect,
+                            font_size,
+                            font_collection,
+                            colors.text,
+                            content_mask,
+                        ),
+                        (_, None) => Vec::new(),
+                    }
+                };
+                for element in elements {
+                    control_state.layers.build(element, gui_rects);
+                }
+            }
+
+            let (arrow_elements, arrow_rect) =
+                create_single_line("v", font_size, font_collection, 0, 0.0);
+            let arrow_origin = vec2(
+                rect.left_position().x + rect.width() - label_margin * 0.5 - arrow_width * 0.5,
+                rect.position.y,
+            ) - arrow_rect.size * 0.5;
+            for element in arrow_elements {
+                let glyph = ElementBuilder::new_with_rect(
+                    element.rect.offset_position(arrow_origin.round()),
+                )
+                .set_sdffont(element.tx_slice.into())
+                .set_color(colors.text_muted.into())
+                .set_rect_mask(mask.into());
+                control_state.layers.build(glyph, gui_rects);
+            }
+
+            if !state.open {
+                return new_selected;
+            }
+
+            let mut elements = vec![ElementBuilder::new_with_rect(list.rect)
+                .set_color(colors.panel.into())
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+                .set_border(Some(Border {
+                    size: scale.border(theme.metrics.border_width),
+                    color: colors.border.into(),
+                }))];
+
+            if filtered.is_empty() {
+                let row_rect = list.row_rect(0, 0.0);
+                elements.extend(label_elements(
+                    NO_MATCHES,
+                    row_rect.offset_size(-vec2(label_margin * 2.0, 0.0)),
+                    font_size,
+                    font_collection,
+                    colors.text_muted,
+                    list.inner_rect,
+                ));
+            }
+
+            for row in list.visible_rows(state.scroll, filtered.len()) {
+                let index = filtered[row];
+                let row_rect = list.row_rect(row, state.scroll);
+                let row_mask = match row_rect.combine_rects(&list.inner_rect) {
+                    Some(row_mask) => row_mask,
+                    None => continue,
+                };
+                let row_fill = if row == state.highlighted {
+                    Some(colors.accent)
+                } else if index == new_selected {
+                    Some(colors.control)
+                } else {
+                    None
+                };
+                if let Some(row_fill) = row_fill {
+                    elements.push(
+                        ElementBuilder::new_with_rect(row_rect)
+                            .set_color(row_fill.into())
+                            .set_round_rect(
+                                BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into(),
+                            )
+                            .set_rect_mask(list.inner_rect.into()),
+                    );
+                }
+
+                let item_rect = row_rect.offset_size(-vec2(label_margin * 2.0, 0.0));
+                match item_builder {
+                    Some(item_builder) => elements.extend(
+                        item_builder(index, item_rect)
+                            .into_iter()
+                            .map(|element| element.set_rect_mask(row_mask.into())),
+                    ),
+                    None => elements.extend(label_elements(
+                        options[index],
+                        item_rect,
+                        font_size,
+                        font_collection,
+                        colors.text,
+                        row_mask,
+                    )),
+                }
+            }
+
+            if let Some(track) = list.scrollbar {
+                let thumb = list.thumb_rect(track, state.scroll, filtered.len());
+                let thumb_color =
+                    if state.scroll_active_id.is_some() || control_state.is_hovered(scrollbar_id) {
+                        colors.handle_active
+                    } else {
+                        colors.handle_hover
+                    };
+                elements.push(
+                    ElementBuilder::new_with_rect(track)
+                        .set_color(colors.control.into())
+                        .set_round_rect(BorderRadius::ForAll(track.width() * 0.5).into()),
+                );
+                elements.push(
+                    ElementBuilder::new_with_rect(thumb)
+                        .set_color(thumb_color.into())
+                        .set_round_rect(BorderRadius::ForAll(thumb.width() * 0.5).into()),
+                );
+            }
+
+            control_state.layers.push_top_render_step(
+                Layer::Popup,
+                1,
+                Box::new(move |gui_rects| {
+                    for element in elements {
+                        element.build(gui_rects);
+                    }
+                }),
+            );
+        }
+        _ => {}
+    }
+
+    new_selected
+}
@@ -0,0 +1,607 @@
+use rwge::{
+    color::RGBA,
+    font::font_layout::create_single_line,
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border, LinearGradient, RadialGradient},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    uuid::Uuid,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        theme::{get_theme, parse_color},
+    },
+    runtime_data::{utils::get_font_collections, PublicData},
+};
+
+use super::{
+    button, choice,
+    color_picker::{self, to_hex, ColorPicker},
+    cursor::{CursorIcon, CursorPriority},
+    ControlState, Uiid,
+};
+
+#[cfg(test)]
+mod test;
+
+const TOOLBAR_HEIGHT: f32 = 28.0;
+const STATUS_HEIGHT: f32 = 20.0;
+const KIND_WIDTH: f32 = 140.0;
+const COLOR_BUTTON_WIDTH: f32 = 36.0;
+const ACTION_BUTTON_WIDTH: f32 = 84.0;
+const HANDLE_SIZE: f32 = 12.0;
+/// The handles are easier to grab than they look
+const HANDLE_HIT_SIZE: f32 = 18.0;
+const KIND_LABELS: [&str; 2] = ["Linear", "Radial"];
+const HANDLE_LABELS: [&str; 5] = [
+    "Start point",
+    "End point",
+    "Center",
+    "Start radius",
+    "End radius",
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GradientKind {
+    Linear,
+    Radial,
+}
+
+/// A linear or a radial gradient. The positions are relative to the center of the element, like
+/// in `LinearGradient` and `RadialGradient`.
+#[derive(Clone, Copy)]
+pub struct GradientValue {
+    pub kind: GradientKind,
+    pub colors: [RGBA; 2],
+    /// Only used by the linear gradient
+    pub start_position: Vec2,
+    pub end_position: Vec2,
+    /// Only used by the radial gradient
+    pub center_position: Vec2,
+    pub start_radius: f32,
+    pub end_radius: f32,
+}
+
+impl GradientValue {
+    pub fn linear(colors: [RGBA; 2], start_position: Vec2, end_position: Vec2) -> Self {
+        Self {
+            kind: GradientKind::Linear,
+            colors,
+            start_position,
+            end_position,
+            center_position: Vec2::ZERO,
+            start_radius: 0.0,
+            end_radius: start_position.distance(end_position) * 0.5,
+        }
+    }
+
+    pub fn radial(
+        colors: [RGBA; 2],
+        center_position: Vec2,
+        start_radius: f32,
+        end_radius: f32,
+    ) -> Self {
+        Self {
+            kind: GradientKind::Radial,
+            colors,
+            start_position: center_position + vec2(0.0, end_radius),
+            end_position: center_position - vec2(0.0, end_radius),
+            center_position,
+            start_radius,
+            end_radius,
+        }
+    }
+
+    pub fn linear_gradient(&self) -> LinearGradient {
+        LinearGradient {
+            colors: self.colors,
+            start_position: self.start_position,
+            end_position: self.end_position,
+        }
+    }
+
+    pub fn radial_gradient(&self) -> RadialGradient {
+        RadialGradient {
+            colors: self.colors,
+            center_position: self.center_position,
+            start_radius: self.start_radius,
+            end_radius: self.end_radius,
+        }
+    }
+
+    /// Fills the element with the gradient of the current kind
+    pub fn apply(&self, builder: ElementBuilder) -> ElementBuilder {
+        match self.kind {
+            GradientKind::Linear => builder.set_linear_gradient(self.linear_gradient().into()),
+            GradientKind::Radial => builder.set_radial_gradient(self.radial_gradient().into()),
+        }
+    }
+
+    /// Struct literal that can be pasted in the code
+    pub fn to_rust(&self) -> String {
+        let colors = format!(
+            "[{}, {}]",
+            rust_color(self.colors[0]),
+            rust_color(self.colors[1])
+        );
+        match self.kind {
+            GradientKind::Linear => format!(
+                "LinearGradient {{\n    colors: {},\n    start_position: {},\n    \
+                 end_position: {},\n}}",
+                colors,
+                rust_vec2(self.start_position),
+                rust_vec2(self.end_position)
+            ),
+            GradientKind::Radial => format!(
+                "RadialGradient {{\n    colors: {},\n    center_position: {},\n    \
+                 start_radius: {:?},\n    end_radius: {:?},\n}}",
+                colors,
+                rust_vec2(self.center_position),
+                round(self.start_radius),
+                round(self.end_radius)
+            ),
+        }
+    }
+
+    /// Single line that `parse` reads back, like `linear #FF0000 #0000FF80 0 10 0 -10`. The
+    /// radial gradient stores the center and the two radii instead of the two points.
+    pub fn serialize(&self) -> String {
+        let numbers = match self.kind {
+            GradientKind::Linear => [
+                self.start_position.x,
+                self.start_position.y,
+                self.end_position.x,
+                self.end_position.y,
+            ],
+            GradientKind::Radial => [
+                self.center_position.x,
+                self.center_position.y,
+                self.start_radius,
+                self.end_radius,
+            ],
+        };
+        let kind = match self.kind {
+            GradientKind::Linear => "linear",
+            GradientKind::Radial => "radial",
+        };
+        format!(
+            "{} {} {} {} {} {} {}",
+            kind,
+            to_hex(self.colors[0]),
+            to_hex(self.colors[1]),
+            round(numbers[0]),
+            round(numbers[1]),
+            round(numbers[2]),
+            round(numbers[3])
+        )
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        if parts.len() != 7 {
+            return Err(String::from(
+                "expected `linear|radial #color #color` followed by four numbers",
+            ));
+        }
+        let colors = [parse_color(parts[1])?, parse_color(parts[2])?];
+        let mut numbers = [0.0; 4];
+        for (number, part) in numbers.iter_mut().zip(&parts[3..]) {
+            *number = part
+                .parse::<f32>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| format!("`{part}` is not a valid number"))?;
+        }
+        let [a, b, c, d] = numbers;
+        match parts[0] {
+            "linear" => Ok(Self::linear(colors, vec2(a, b), vec2(c, d))),
+            "radial" => Ok(Self::radial(colors, vec2(a, b), c.max(0.0), d.max(0.0))),
+            kind => Err(format!("unknown gradient `{kind}`")),
+        }
+    }
+}
+
+/// Two decimals are enough for pixels
+fn round(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+fn rust_vec2(value: Vec2) -> String {
+    format!("vec2({:?}, {:?})", round(value.x), round(value.y))
+}
+
+fn rust_color(color: RGBA) -> String {
+    let channel = |value: f32| (value * 1000.0).round() / 1000.0;
+    format!(
+        "RGBA::new({:?}, {:?}, {:?}, {:?})",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a)
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Handle {
+    Start,
+    End,
+    Center,
+    StartRadius,
+    EndRadius,
+}
+
+impl Handle {
+    const ALL: [Handle; 5] = [
+        Handle::Start,
+        Handle::End,
+        Handle::Center,
+        Handle::StartRadius,
+        Handle::EndRadius,
+    ];
+
+    fn is_visible(&self, kind: GradientKind) -> bool {
+        match self {
+            Handle::Start | Handle::End => kind == GradientKind::Linear,
+            _ => kind == GradientKind::Radial,
+        }
+    }
+
+    /// Relative to the center of the preview, the radii are dragged along the x axis
+    fn position(&self, value: &GradientValue) -> Vec2 {
+        match self {
+            Handle::Start => value.start_position,
+            Handle::End => value.end_position,
+            Handle::Center => value.center_position,
+            Handle::StartRadius => value.center_position + vec2(value.start_radius, 0.0),
+            Handle::EndRadius => value.center_position + vec2(value.end_radius, 0.0),
+        }
+    }
+
+    fn move_to(&self, value: &mut GradientValue, position: Vec2) {
+        match self {
+            Handle::Start => value.start_position = position,
+            Handle::End => value.end_position = position,
+            Handle::Center => value.center_position = position,
+            Handle::StartRadius => {
+                value.start_radius = (position.x - value.center_position.x).max(0.0)
+            }
+            Handle::EndRadius => value.end_radius = (position.x - value.center_position.x).max(0.0),
+        }
+    }
+}
+
+/// Gradient being edited and the state of the editor, kept by the container between frames
+pub struct GradientEditor {
+    pub value: GradientValue,
+    color_pickers: [ColorPicker; 2],
+    drag: Option<(Handle, Uuid)>,
+    /// Distance from the handle to the cursor when the drag started
+    drag_offset: Vec2,
+    /// Result of the last copy or paste, shown under the toolbar instead of the value
+    status: Option<String>,
+}
+
+impl GradientEditor {
+    pub fn new(value: GradientValue) -> Self {
+        Self {
+            value,
+            color_pickers: [ColorPicker::new(), ColorPicker::new()],
+            drag: None,
+            drag_offset: Vec2::ZERO,
+            status: None,
+        }
+    }
+}
+
+/// Toolbar with the kind of gradient, its two colors and the copy and paste buttons, above a
+/// live preview where the points and radii are dragged. The preview is the element the
+/// positions are relative to. Returns true if the gradient changed.
+pub fn gradient_editor(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    editor: &mut GradientEditor,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> bool {
+    let handle_ids: Vec<Uiid> = Handle::ALL.iter().map(|_| control_state.get_id()).collect();
+
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+    let gap = scale.px(theme.metrics.spacing_small);
+    let toolbar_height = scale.px(TOOLBAR_HEIGHT);
+    let status_height = scale.px(STATUS_HEIGHT);
+    let top_left = rect.top_left_position();
+    let toolbar_rect = |left: f32, width: f32| Rect {
+        position: vec2(
+            top_left.x + left + width * 0.5,
+            top_left.y - toolbar_height * 0.5,
+        ),
+        size: vec2(width, toolbar_height),
+    };
+    let preview_height = rect.height() - toolbar_height - status_height - gap * 2.0;
+    let preview = Rect {
+        position: vec2(
+            rect.position.x,
+            rect.position.y - rect.height() * 0.5 + preview_height * 0.5,
+        ),
+        size: vec2(rect.width(), preview_height.max(0.0)),
+    };
+    let mut changed = false;
+
+    let kind_width = scale.px(KIND_WIDTH);
+    let kind_index = match editor.value.kind {
+        GradientKind::Linear => 0,
+        GradientKind::Radial => 1,
+    };
+    let new_kind_index = choice::segmented_control(
+        toolbar_rect(0.0, kind_width),
+        mask,
+        &format!("{} kind", label),
+        &KIND_LABELS,
+        &[],
+        kind_index,
+        event,
+        control_state,
+        public_data,
+    );
+    if new_kind_index != kind_index {
+        editor.value.kind = match new_kind_index {
+            0 => GradientKind::Linear,
+            _ => GradientKind::Radial,
+        };
+        changed = true;
+    }
+
+    let color_width = scale.px(COLOR_BUTTON_WIDTH);
+    for (index, picker) in editor.color_pickers.iter_mut().enumerate() {
+        let left = kind_width + gap * (index + 1) as f32 + color_width * index as f32;
+        if let Some(color) = color_picker::color_button(
+            toolbar_rect(left, color_width),
+            mask,
+            &format!("{} color {}", label, index + 1),
+            editor.value.colors[index],
+            picker,
+            event,
+            control_state,
+            public_data,
+        ) {
+            editor.value.colors[index] = color;
+            changed = true;
+        }
+    }
+
+    // Right aligned, the last one is at the right edge
+    let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+    let action_width = scale.px(ACTION_BUTTON_WIDTH);
+    let actions = ["Copy Rust", "Copy value", "Paste"];
+    for (index, action) in actions.iter().enumerate() {
+        let left = rect.width() - (action_width + gap) * (actions.len() - index) as f32 + gap;
+        let pressed = button::button(
+            toolbar_rect(left, action_width),
+            mask,
+            action,
+            event,
+            control_state,
+            BorderRadius::ForAll(scale.px(theme.metrics.radius)),
+            scale.font(theme.fonts.size_small),
+            font_collection,
+            0,
+            0.0,
+            [colors.button, colors.button],
+            [colors.button_hover, colors.button_hover],
+        );
+        if !pressed {
+            continue;
+        }
+        let clipboard = control_state.text_input.clipboard();
+        editor.status = Some(match index {
+            0 => {
+                clipboard.set_text(&editor.value.to_rust());
+                String::from("Copied as Rust code")
+            }
+            1 => {
+                clipboard.set_text(&editor.value.serialize());
+                String::from("Copied as a value")
+            }
+            _ => match clipboard.get_text().map(|text| GradientValue::parse(&text)) {
+                Some(Ok(value)) => {
+                    editor.value = value;
+                    changed = true;
+                    String::from("Pasted")
+                }
+                Some(Err(message)) => format!("Could not paste: {}", message),
+                None => String::from("The clipboard is empty"),
+            },
+        });
+    }
+
+    let handle_size = scale.px(HANDLE_SIZE);
+    let hit_size = scale.px(HANDLE_HIT_SIZE);
+    let handle_rect = |handle: &Handle, value: &GradientValue| Rect {
+        position: preview.position + handle.position(value),
+        size: Vec2::splat(hit_size),
+    };
+    let half_size = preview.size * 0.5;
+
+    match event {
+        UIEvent::Update => {
+            if let Some((_, active_id)) = editor.drag {
+                control_state.hold_active_state(active_id);
+            } else {
+                for (handle, id) in Handle::ALL.iter().zip(&handle_ids) {
+                    if !handle.is_visible(editor.value.kind) {
+                        continue;
+                    }
+                    if let Some(hit_rect) = handle_rect(handle, &editor.value)
+                        .combine_rects(&preview)
+                        .and_then(|hit_rect| hit_rect.combine_rects(&mask))
+                    {
+                        control_state.set_hot_with_rect(*id, &hit_rect);
+                    }
+                }
+            }
+
+            for ((handle, id), handle_label) in
+                Handle::ALL.iter().zip(&handle_ids).zip(HANDLE_LABELS)
+            {
+                if !handle.is_visible(editor.value.kind) {
+                    continue;
+                }
+                let position = handle.position(&editor.value);
+                control_state.push_access_node(
+                    AccessNode::new(
+                        AccessRole::Button,
+                        handle_rect(handle, &editor.value),
+                        handle_label,
+                    )
+                    .set_control(*id)
+                    .set_value(&format!(
+                        "{}, {}",
+                        round(position.x),
+                        round(position.y)
+                    )),
+                );
+            }
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() {
+                let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+                for (handle, id) in Handle::ALL.iter().zip(&handle_ids) {
+                    if control_state.is_hovered(*id) {
+                        if let Some(active_id) = control_state.set_active(*id) {
+                            editor.drag = Some((*handle, active_id));
+                            editor.drag_offset =
+                                preview.position + handle.position(&editor.value) - cursor_pos;
+                        }
+                    }
+                }
+            }
+            if mouse_input.is_left_released() {
+                editor.drag = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if let Some((handle, active_id)) = editor.drag {
+                if control_state.is_active(Some(active_id)) {
+                    let position = (*corrected + editor.drag_offset - preview.position)
+                        .max(-half_size)
+                        .min(half_size);
+                    handle.move_to(&mut editor.value, position.round());
+                    changed = true;
+                }
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let dragging = editor.drag.is_some();
+            let hovering = handle_ids.iter().any(|id| control_state.is_hovered(*id));
+            if dragging {
+                control_state.request_cursor(CursorIcon::Grabbing, CursorPriority::Active);
+            } else if hovering {
+                control_state.request_cursor(CursorIcon::Grab, CursorPriority::Hover);
+            }
+
+            let status = editor
+                .status
+                .clone()
+                .unwrap_or_else(|| editor.value.serialize());
+            let (font_elements, status_rect) = create_single_line(
+                &status,
+                scale.font(theme.fonts.size_small),
+                font_collection,
+                0,
+                0.0,
+            );
+            let status_origin = vec2(
+                rect.left_position().x,
+                top_left.y
+                    - toolbar_height
+                    - gap
+                    - status_height * 0.5
+                    - status_rect.height() * 0.5,
+            )
+            .round();
+            for element in font_elements {
+                let glyph =
+                    ElementBuilder::new_with_rect(element.rect.offset_position(status_origin))
+                        .set_sdffont(element.tx_slice.into())
+                        .set_color(colors.text_muted.into())
+                        .set_rect_mask(mask.into());
+                control_state.layers.build(glyph, gui_rects);
+            }
+
+            let preview_mask = match preview.combine_rects(&mask) {
+                Some(preview_mask) => preview_mask,
+                None => return changed,
+            };
+            let gradient = editor
+                .value
+                .apply(ElementBuilder::new_with_rect(preview))
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+                .set_border(Some(Border {
+                    size: scale.border(theme.metrics.border_width),
+                    color: colors.border.into(),
+                }))
+                .set_rect_mask(preview_mask.into());
+            control_state.layers.build(gradient, gui_rects);
+
+            if editor.value.kind == GradientKind::Radial {
+                let center = preview.position + editor.value.center_position;
+                for radius in [editor.value.start_radius, editor.value.end_radius] {
+                    let ring = ElementBuilder::new(center.round(), Vec2::splat(radius * 2.0))
+                        .set_circle()
+                        .set_color(RGBA::TRANSPARENT.into())
+                        .set_border(Some(Border {
+                            size: scale.border(theme.metrics.border_width),
+                            color: colors.text.set_alpha(0.6).into(),
+                        }))
+                        .set_rect_mask(preview_mask.into());
+                    control_state.layers.build(ring, gui_rects);
+                }
+            }
+
+            for (handle, id) in Handle::ALL.iter().zip(&handle_ids) {
+                if !handle.is_visible(editor.value.kind) {
+                    continue;
+                }
+                let active = matches!(editor.drag, Some((dragged, _)) if dragged == *handle);
+                let fill = match handle {
+                    Handle::Start => editor.value.colors[0],
+                    Handle::End => editor.value.colors[1],
+                    _ => colors.control,
+                };
+                let border_color = if active || control_state.is_hovered(*id) {
+                    colors.focus
+                } else {
+                    colors.text
+                };
+                let position = (preview.position + handle.position(&editor.value)).round();
+                let element = ElementBuilder::new(position, Vec2::splat(handle_size))
+                    .set_color(fill.into())
+                    .set_border(Some(Border {
+                        size: scale.border(theme.metrics.focus_border_width),
+                        color: border_color.into(),
+                    }))
+                    .set_rect_mask(preview_mask.into());
+                // The radii are squares so they are not confused with the points
+                let element = match handle {
+                    Handle::StartRadius | Handle::EndRadius => element,
+                    _ => element.set_circle(),
+                };
+                control_state.layers.build(element, gui_rects);
+            }
+        }
+        _ => {}
+    }
+
+    if changed {
+        editor.status = None;
+    }
+    changed
+}
@@ -0,0 +1,80 @@
+use rwge::{color::RGBA, glam::vec2};
+
+use super::{GradientKind, GradientValue};
+
+fn same_color(a: RGBA, b: RGBA) -> bool {
+    let close = |a: f32, b: f32| (a - b).abs() <= 1.0 / 255.0;
+    close(a.r, b.r) && close(a.g, b.g) && close(a.b, b.b) && close(a.a, b.a)
+}
+
+fn error(text: &str) -> String {
+    match GradientValue::parse(text) {
+        Ok(_) => panic!("`{text}` was accepted"),
+        Err(message) => message,
+    }
+}
+
+#[test]
+fn linear_gradient_is_read_back() {
+    let colors = [RGBA::new(1.0, 0.0, 0.0, 1.0), RGBA::new(0.0, 0.0, 1.0, 0.5)];
+    let value = GradientValue::linear(colors, vec2(0.0, 10.5), vec2(-3.25, -10.0));
+    let parsed = GradientValue::parse(&value.serialize()).unwrap();
+    assert_eq!(parsed.kind, GradientKind::Linear);
+    assert!(same_color(parsed.colors[0], colors[0]));
+    assert!(same_color(parsed.colors[1], colors[1]));
+    assert_eq!(parsed.start_position, vec2(0.0, 10.5));
+    assert_eq!(parsed.end_position, vec2(-3.25, -10.0));
+}
+
+#[test]
+fn radial_gradient_is_read_back() {
+    let colors = [RGBA::new(0.0, 1.0, 0.0, 1.0), RGBA::new(0.0, 0.0, 0.0, 0.0)];
+    let value = GradientValue::radial(colors, vec2(4.0, -2.0), 5.0, 30.0);
+    let parsed = GradientValue::parse(&value.serialize()).unwrap();
+    assert_eq!(parsed.kind, GradientKind::Radial);
+    assert!(same_color(parsed.colors[1], colors[1]));
+    assert_eq!(parsed.center_position, vec2(4.0, -2.0));
+    assert_eq!(parsed.start_radius, 5.0);
+    assert_eq!(parsed.end_radius, 30.0);
+}
+
+#[test]
+fn extra_whitespace_is_ignored() {
+    let parsed = GradientValue::parse("  linear\t#000000   #FFFFFF 0 1\n2 3 ").unwrap();
+    assert_eq!(parsed.start_position, vec2(0.0, 1.0));
+    assert_eq!(parsed.end_position, vec2(2.0, 3.0));
+}
+
+#[test]
+fn negative_radii_are_clamped() {
+    let parsed = GradientValue::parse("radial #000000 #FFFFFF 0 0 -5 -1").unwrap();
+    assert_eq!(parsed.start_radius, 0.0);
+    assert_eq!(parsed.end_radius, 0.0);
+}
+
+#[test]
+fn wrong_part_count_is_rejected() {
+    assert!(error("").contains("expected"));
+    assert!(error("linear #000000 #FFFFFF 0 0 0").contains("expected"));
+    assert!(error("linear #000000 #FFFFFF 0 0 0 0 0").contains("expected"));
+}
+
+#[test]
+fn unknown_kind_is_rejected() {
+    let message = error("conic #000000 #FFFFFF 0 0 0 0");
+    assert!(message.contains("conic"), "{message}");
+}
+
+#[test]
+fn invalid_numbers_are_rejected() {
+    for number in ["ten", "NaN", "inf", "1,5"] {
+        let message = error(&format!("linear #000000 #FFFFFF 0 0 0 {number}"));
+        assert!(message.contains(number), "{message}");
+    }
+}
+
+#[test]
+fn invalid_colors_are_rejected() {
+    error("linear red #FFFFFF 0 0 0 0");
+    error("linear #000000 #FFF 0 0 0 0");
+}
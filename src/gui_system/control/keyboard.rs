@@ -0,0 +1,34 @@
+use rwge::{
+    gui::rect_ui::event::{MouseInput, UIEvent},
+    winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode},
+};
+
+/// Returns the key if the event is a key press
+pub fn get_pressed_key(event: &UIEvent) -> Option<VirtualKeyCode> {
+    if let UIEvent::KeyboardInput(KeyboardInput {
+        virtual_keycode: Some(key),
+        state: ElementState::Pressed,
+        ..
+    }) = event
+    {
+        Some(*key)
+    } else {
+        None
+    }
+}
+
+pub fn key_pressed(event: &UIEvent, key: VirtualKeyCode) -> bool {
+    get_pressed_key(event) == Some(key)
+}
+
+pub fn is_right_pressed(mouse_input: &MouseInput) -> bool {
+    if let MouseInput {
+        button: MouseButton::Right,
+        state: ElementState::Pressed,
+    } = mouse_input
+    {
+        true
+    } else {
+        false
+    }
+}
@@ -6,9 +6,16 @@ use rwge::{
     winit::dpi::PhysicalPosition,
 };
 
-use crate::runtime_data::{utils::get_window, PublicData, RuntimeData};
+use crate::{
+    gui_system::theme::get_theme,
+    runtime_data::{utils::get_window, PublicData, RuntimeData},
+};
 
-use super::{drag_element::DragElement, ControlId, ControlState, State};
+use super::{
+    cursor::{CursorIcon, CursorPriority},
+    drag_element::DragElement,
+    ControlId, ControlState, State,
+};
 
 pub fn main_window_top_bar(
     position: Vec2,
@@ -33,17 +40,19 @@ pub fn main_window_top_bar(
         UIEvent::Render { gui_rects, .. } => {
             let current_control_id = get_control_id();
             let state = control_state.get_control_state(current_control_id);
+            let colors = &get_theme(public_data).colors;
             let color: RGBA = if let State::Active = state {
-                RGBA::GREEN
+                control_state.request_cursor(CursorIcon::Grabbing, CursorPriority::Active);
+                colors.top_bar_active
             } else if let State::Hovered = state {
-                RGBA::rrr1(0.45)
+                control_state.request_cursor(CursorIcon::Grab, CursorPriority::Hover);
+                colors.top_bar_hover
             } else {
-                RGBA::rrr1(0.25)
+                colors.top_bar
             };
 
-            ElementBuilder::new(position, size)
-                .set_color(color.into())
-                .build(gui_rects);
+            let bar = ElementBuilder::new(position, size).set_color(color.into());
+            control_state.layers.build(bar, gui_rects);
         }
         UIEvent::Update => {
             control_state.set_hot_with_rect(control_id, &Rect { position, size });
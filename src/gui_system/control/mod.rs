@@ -33,24 +33,70 @@ use rwge::glam::Vec2;
 use rwge::gui::rect_ui::Rect;
 use rwge::uuid::Uuid;
 
+use super::{
+    access_tree::{AccessNode, AccessNodeId, AccessTreeBuilder},
+    layers::LayerManager,
+    modal::ModalSystem,
+};
+
+pub mod cursor;
 pub mod drag_element;
+pub mod keyboard;
 pub mod main_window_top_bar;
+pub mod scale;
 pub mod slider;
+pub mod tooltip;
+pub mod snapping;
+pub mod text_field;
 pub mod button;
+pub mod choice;
+pub mod dropdown;
+pub mod drag_value;
+pub mod color_picker;
+pub mod gradient_editor;
+pub mod tree_view;
+pub mod scroll_area;
+pub mod table;
+pub mod selection;
 pub use button::button;
 
+use color_picker::ColorSampler;
+use cursor::{CursorIcon, CursorPriority, CursorState};
+use scale::GUIScale;
+use text_field::TextInputState;
+use tooltip::TooltipState;
+
+pub const DOUBLE_CLICK_TIME: f32 = 0.35;
+
 pub struct ControlState {
     current_ui_id: Option<Uiid>,
 
     hot: Option<Uiid>,
     hold_hover: bool,
     pub hovered: Option<Uiid>,
+    hover_start_time: f32,
+    current_time: f32,
 
     hold_active: bool,
     active: Uuid,
 
+    /// Control that receives the keyboard input
+    focused: Option<Uiid>,
+    last_click: Option<(Uiid, f32)>,
+    /// Lines scrolled with the mouse wheel since the last update
+    wheel_delta: f32,
+
     pub last_cursor_position: Option<Vec2>,
     pub depth_stack: Vec<u32>,
+
+    pub layers: LayerManager,
+    pub tooltip: TooltipState,
+    pub modals: ModalSystem,
+    pub scale: GUIScale,
+    pub cursor: CursorState,
+    pub access: AccessTreeBuilder,
+    pub text_input: TextInputState,
+    pub color_sampler: ColorSampler,
 }
 
 impl ControlState {
@@ -58,18 +104,106 @@ impl ControlState {
         Self {
             last_cursor_position: None,
 
+            focused: None,
+            last_click: None,
+            wheel_delta: 0.0,
+
             current_ui_id: None,
 
             hot: None,
             hold_hover: false,
             hovered: None,
+            hover_start_time: 0.0,
+            current_time: 0.0,
 
             active: Uuid::nil(),
             hold_active: false,
             depth_stack: Vec::with_capacity(25),
+
+            layers: LayerManager::new(),
+            tooltip: TooltipState::new(),
+            modals: ModalSystem::new(),
+            scale: GUIScale::new(),
+            cursor: CursorState::new(),
+            access: AccessTreeBuilder::new(),
+            text_input: TextInputState::new(),
+            color_sampler: ColorSampler::new(),
         }
     }
 
+    /// Time in seconds, used to know for how long a control has been hovered
+    pub fn update_time(&mut self, time: f32) {
+        self.current_time = time;
+    }
+
+    pub fn hover_duration(&self) -> f32 {
+        if self.hovered.is_some() {
+            self.current_time - self.hover_start_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Should be called when `id` is clicked, returns true if it is the second click in a row on
+    /// it inside of `DOUBLE_CLICK_TIME`
+    pub fn register_click(&mut self, id: Uiid) -> bool {
+        match self.last_click {
+            Some((last_id, last_time))
+                if last_id == id && self.current_time - last_time <= DOUBLE_CLICK_TIME =>
+            {
+                self.last_click = None;
+                true
+            }
+            _ => {
+                self.last_click = Some((id, self.current_time));
+                false
+            }
+        }
+    }
+
+    pub fn set_focus(&mut self, id: Uiid) {
+        self.focused = Some(id);
+    }
+
+    pub fn is_focused(&self, id: Uiid) -> bool {
+        self.focused == Some(id)
+    }
+
+    /// Called when the left button is pressed, the control under the cursor takes the focus again
+    /// while handling the event
+    pub fn clear_focus(&mut self) {
+        self.focused = None;
+    }
+
+    pub fn push_wheel_delta(&mut self, lines: f32) {
+        self.wheel_delta += lines;
+    }
+
+    /// Read by the hovered control on the update, whatever is left after it is dropped
+    pub fn take_wheel_delta(&mut self) -> f32 {
+        std::mem::take(&mut self.wheel_delta)
+    }
+
+    /// The icon is applied to the OS cursor once the frame was rendered
+    pub fn request_cursor(&mut self, icon: CursorIcon, priority: CursorPriority) {
+        self.cursor.request(icon, priority);
+    }
+
+    /// Adds a node to the accessibility tree, the states of its control are filled in here
+    pub fn push_access_node(&mut self, mut node: AccessNode) -> AccessNodeId {
+        if let Some(id) = node.control() {
+            node.states.hovered = self.is_hovered(id);
+            node.states.focused |= self.is_focused(id);
+        }
+        self.access.push(node)
+    }
+
+    /// Id returned by the last call to `get_id`
+    pub fn get_last_id(&self) -> Uiid {
+        self.current_ui_id
+            .expect("GUI Control state was not initialized properly")
+    }
+
     pub fn get_id(&mut self) -> Uiid {
         self.current_ui_id
             .as_mut()
@@ -173,6 +307,13 @@ impl ControlState {
         }
     }
 
+    /// Returns true if the hovered control took its id between `first` and `last`, both
+    /// included. Tells if the cursor is over a group of controls and not over something above it.
+    pub fn is_hovered_between(&self, first: Uiid, last: Uiid) -> bool {
+        self.hovered
+            .map_or(false, |hovered| hovered.id >= first.id && hovered.id <= last.id)
+    }
+
     pub fn set_active(&mut self, id: Uiid) -> Option<Uuid> {
         if let Some(hovered) = self.hovered {
             if self.active.is_nil() && hovered == id {
@@ -214,6 +355,9 @@ impl ControlState {
         assert_eq!(self.depth_stack.len(), 0, "The depth stack should be empty. If it is not empty it might inadvertently change the state of other controls.");
         if self.active.is_nil() {
             if self.hot.is_some() {
+                if self.hovered != self.hot {
+                    self.hover_start_time = self.current_time;
+                }
                 self.hovered = self.hot;
             }
 
@@ -239,6 +383,8 @@ impl ControlState {
     }
 
     pub fn on_after_update(&mut self) {
+        self.text_input.on_after_update();
+        self.wheel_delta = 0.0;
         if !self.hold_active {
             self.active = Uuid::nil();
         }
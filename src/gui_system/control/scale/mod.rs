@@ -0,0 +1,85 @@
+use rwge::glam::Vec2;
+
+#[cfg(test)]
+mod test;
+
+pub const MIN_ZOOM: f32 = 0.5;
+pub const MAX_ZOOM: f32 = 3.0;
+pub const ZOOM_STEP: f32 = 0.1;
+
+/// Sizes of the GUI are written for a 100% display, this turns them into physical pixels.
+/// The factor is the scale factor of the monitor multiplied by the zoom chosen by the user.
+#[derive(Copy, Clone)]
+pub struct GUIScale {
+    dpi: f32,
+    zoom: f32,
+    /// Set from the accessibility settings, smaller fonts are raised to it before scaling
+    min_font_size: f32,
+}
+
+impl GUIScale {
+    pub fn new() -> Self {
+        Self {
+            dpi: 1.0,
+            zoom: 1.0,
+            min_font_size: 0.0,
+        }
+    }
+
+    pub fn factor(&self) -> f32 {
+        self.dpi * self.zoom
+    }
+
+    /// Returns true if the scale factor of the monitor changed
+    pub fn set_dpi(&mut self, dpi: f32) -> bool {
+        let changed = self.dpi != dpi;
+        self.dpi = dpi;
+        changed
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        // Rounded to the step so zooming in and out lands on the same values
+        let zoom = (zoom / ZOOM_STEP).round() * ZOOM_STEP;
+        self.zoom = zoom.max(MIN_ZOOM).min(MAX_ZOOM);
+    }
+
+    pub fn zoom_by(&mut self, steps: f32) {
+        self.set_zoom(self.zoom + steps * ZOOM_STEP);
+    }
+
+    pub fn reset_zoom(&mut self) {
+        self.zoom = 1.0;
+    }
+
+    pub fn set_min_font_size(&mut self, min_font_size: f32) {
+        self.min_font_size = min_font_size;
+    }
+
+    /// Snapped to whole pixels, so edges stay crisp
+    pub fn px(&self, size: f32) -> f32 {
+        (size * self.factor()).round()
+    }
+
+    pub fn vec(&self, size: Vec2) -> Vec2 {
+        (size * self.factor()).round()
+    }
+
+    /// Font sizes are not snapped, the glyphs are positioned on whole pixels instead
+    pub fn font(&self, size: f32) -> f32 {
+        size.max(self.min_font_size) * self.factor()
+    }
+
+    /// Borders never disappear, even when zoomed out
+    pub fn border(&self, size: u32) -> u32 {
+        ((size as f32 * self.factor()).round() as u32).max(1)
+    }
+
+    /// Converts a size in physical pixels back to the units the GUI is written in
+    pub fn to_logical(&self, px_size: f32) -> f32 {
+        px_size / self.factor()
+    }
+}
@@ -0,0 +1,32 @@
+use super::{GUIScale, MAX_ZOOM, MIN_ZOOM};
+
+#[test]
+fn min_font_size_is_applied_before_the_factor() {
+    let mut scale = GUIScale::new();
+    scale.set_dpi(2.0);
+    scale.set_min_font_size(16.0);
+    assert_eq!(scale.font(12.0), 32.0);
+    assert_eq!(scale.font(20.0), 40.0);
+}
+
+#[test]
+fn zoom_applies_to_fonts() {
+    let mut scale = GUIScale::new();
+    scale.set_zoom(1.5);
+    assert_eq!(scale.font(10.0), 15.0);
+    scale.set_min_font_size(14.0);
+    assert_eq!(scale.font(10.0), 21.0);
+    scale.set_min_font_size(0.0);
+    assert_eq!(scale.font(10.0), 15.0);
+}
+
+#[test]
+fn zoom_is_clamped_and_borders_stay_visible() {
+    let mut scale = GUIScale::new();
+    scale.zoom_by(-100.0);
+    assert_eq!(scale.zoom(), MIN_ZOOM);
+    assert_eq!(scale.border(1), 1);
+    scale.set_zoom(10.0);
+    assert_eq!(scale.zoom(), MAX_ZOOM);
+    assert_eq!(scale.to_logical(scale.px(10.0)), 10.0);
+}
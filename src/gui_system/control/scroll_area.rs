@@ -0,0 +1,373 @@
+use rwge::{
+    glam::{vec2, Vec2},
+    gui::rect_ui::{element::builder::ElementBuilder, event::UIEvent, BorderRadius, Rect},
+    uuid::Uuid,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessNodeId, AccessRole},
+        theme::get_theme,
+    },
+    runtime_data::PublicData,
+};
+
+use super::{ControlState, Uiid};
+
+const SCROLLBAR_WIDTH: f32 = 8.0;
+const MIN_THUMB_LENGTH: f32 = 20.0;
+/// Pixels scrolled by a line of the mouse wheel
+const WHEEL_STEP: f32 = 40.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Axis {
+    Vertical,
+    Horizontal,
+}
+
+impl Axis {
+    fn get(&self, value: Vec2) -> f32 {
+        match self {
+            Axis::Vertical => value.y,
+            Axis::Horizontal => value.x,
+        }
+    }
+}
+
+/// Scroll offset of a scroll area, kept by the container between frames
+pub struct ScrollArea {
+    /// How far the content is scrolled right and down, in pixels
+    offset: Vec2,
+    drag: Option<(Axis, Uuid)>,
+    /// Distance from the start of the thumb to the cursor when the drag started
+    drag_offset: f32,
+}
+
+impl ScrollArea {
+    pub fn new() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            drag: None,
+            drag_offset: 0.0,
+        }
+    }
+
+    pub fn offset(&self) -> Vec2 {
+        self.offset
+    }
+
+    /// Clamped to the content on the next event
+    pub fn set_offset(&mut self, offset: Vec2) {
+        self.offset = offset;
+    }
+
+    /// Scrolls as little as possible so the content from `top` to `bottom` is visible. Both are
+    /// measured down from the top of the content.
+    pub fn scroll_to_y(&mut self, top: f32, bottom: f32, view_height: f32) {
+        if top < self.offset.y {
+            self.offset.y = top;
+        } else if bottom > self.offset.y + view_height {
+            self.offset.y = bottom - view_height;
+        }
+    }
+}
+
+/// Visible part of the content, returned by `scroll_area`
+#[derive(Clone, Copy)]
+pub struct ScrollView {
+    /// Control that is hovered when the cursor is over the content, the content can use it for
+    /// its own clicks
+    pub id: Uiid,
+    /// Where the content is drawn, without the scroll bars
+    pub viewport: Rect,
+    pub offset: Vec2,
+    /// The content nodes go inside of it, only set on the update
+    pub access_id: Option<AccessNodeId>,
+}
+
+impl ScrollView {
+    /// Screen position of a point of the content, `content_position` is measured right and down
+    /// from its top left corner
+    pub fn to_screen(&self, content_position: Vec2) -> Vec2 {
+        let top_left = self.viewport.top_left_position();
+        vec2(
+            top_left.x + content_position.x - self.offset.x,
+            top_left.y - content_position.y + self.offset.y,
+        )
+    }
+
+    /// Inverse of `to_screen`
+    pub fn to_content(&self, screen_position: Vec2) -> Vec2 {
+        let top_left = self.viewport.top_left_position();
+        vec2(
+            screen_position.x - top_left.x + self.offset.x,
+            top_left.y - screen_position.y + self.offset.y,
+        )
+    }
+}
+
+/// Track and thumb of one scroll bar
+struct Scrollbar {
+    axis: Axis,
+    track: Rect,
+    thumb: Rect,
+    /// Pixels of content for every pixel the thumb moves
+    ratio: f32,
+}
+
+impl Scrollbar {
+    fn new(axis: Axis, track: Rect, view: f32, content: f32, offset: f32, min_thumb: f32) -> Self {
+        let track_length = axis.get(track.size);
+        let thumb_length = (track_length * view / content)
+            .max(min_thumb)
+            .min(track_length);
+        let travel = track_length - thumb_length;
+        let max_offset = content - view;
+        let ratio = if travel > 0.0 {
+            max_offset / travel
+        } else {
+            0.0
+        };
+        let start = if max_offset > 0.0 {
+            offset / max_offset * travel
+        } else {
+            0.0
+        };
+        let thumb = match axis {
+            Axis::Vertical => Rect {
+                position: vec2(
+                    track.position.x,
+                    track.top_left_position().y - start - thumb_length * 0.5,
+                ),
+                size: vec2(track.width(), thumb_length),
+            },
+            Axis::Horizontal => Rect {
+                position: vec2(
+                    track.left_position().x + start + thumb_length * 0.5,
+                    track.position.y,
+                ),
+                size: vec2(thumb_length, track.height()),
+            },
+        };
+        Self {
+            axis,
+            track,
+            thumb,
+            ratio,
+        }
+    }
+
+    /// Distance from the start of the track to `position`, down or right
+    fn distance(&self, position: Vec2) -> f32 {
+        match self.axis {
+            Axis::Vertical => self.track.top_left_position().y - position.y,
+            Axis::Horizontal => position.x - self.track.left_position().x,
+        }
+    }
+
+    fn thumb_start(&self) -> f32 {
+        match self.axis {
+            Axis::Vertical => self.track.top_left_position().y - self.thumb.top_left_position().y,
+            Axis::Horizontal => self.thumb.left_position().x - self.track.left_position().x,
+        }
+    }
+}
+
+/// Viewport of `rect` over content of `content_size`. The wheel scrolls it while the cursor is
+/// over it, with Shift held it scrolls horizontally. The scroll bars only show up when the
+/// content does not fit. The caller draws the content with the returned view.
+pub fn scroll_area(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    content_size: Vec2,
+    state: &mut ScrollArea,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> ScrollView {
+    let area_id = control_state.get_id();
+    let thumb_ids = [control_state.get_id(), control_state.get_id()];
+
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+    let bar_width = scale.px(SCROLLBAR_WIDTH);
+
+    // A bar takes room from the other axis, which can make that one needed too
+    let mut vertical = content_size.y > rect.height();
+    let mut horizontal = content_size.x > rect.width() - if vertical { bar_width } else { 0.0 };
+    vertical = content_size.y > rect.height() - if horizontal { bar_width } else { 0.0 };
+    horizontal |= vertical && content_size.x > rect.width() - bar_width;
+
+    let top_left = rect.top_left_position();
+    let viewport_size = vec2(
+        rect.width() - if vertical { bar_width } else { 0.0 },
+        rect.height() - if horizontal { bar_width } else { 0.0 },
+    )
+    .max(Vec2::ZERO);
+    let viewport = Rect {
+        position: vec2(
+            top_left.x + viewport_size.x * 0.5,
+            top_left.y - viewport_size.y * 0.5,
+        ),
+        size: viewport_size,
+    };
+    let max_offset = (content_size - viewport_size).max(Vec2::ZERO);
+    state.offset = state.offset.max(Vec2::ZERO).min(max_offset);
+
+    let min_thumb = scale.px(MIN_THUMB_LENGTH);
+    let scrollbar = |axis: Axis, offset: Vec2| match axis {
+        Axis::Vertical => Scrollbar::new(
+            axis,
+            Rect {
+                position: vec2(
+                    top_left.x + rect.width() - bar_width * 0.5,
+                    viewport.position.y,
+                ),
+                size: vec2(bar_width, viewport_size.y),
+            },
+            viewport_size.y,
+            content_size.y,
+            offset.y,
+            min_thumb,
+        ),
+        Axis::Horizontal => Scrollbar::new(
+            axis,
+            Rect {
+                position: vec2(
+                    viewport.position.x,
+                    top_left.y - rect.height() + bar_width * 0.5,
+                ),
+                size: vec2(viewport_size.x, bar_width),
+            },
+            viewport_size.x,
+            content_size.x,
+            offset.x,
+            min_thumb,
+        ),
+    };
+    let bars: Vec<(Uiid, Axis)> = [
+        (thumb_ids[0], Axis::Vertical),
+        (thumb_ids[1], Axis::Horizontal),
+    ]
+    .into_iter()
+    .filter(|(_, axis)| match axis {
+        Axis::Vertical => vertical,
+        Axis::Horizontal => horizontal,
+    })
+    .collect();
+
+    let mut access_id = None;
+    match event {
+        UIEvent::Update => {
+            if let Some((_, active_id)) = state.drag {
+                control_state.hold_active_state(active_id);
+            } else {
+                if let Some(interact_rect) = viewport.combine_rects(&mask) {
+                    control_state.set_hot_with_rect(area_id, &interact_rect);
+                }
+                for (id, axis) in &bars {
+                    if let Some(interact_rect) =
+                        scrollbar(*axis, state.offset).track.combine_rects(&mask)
+                    {
+                        control_state.set_hot_with_rect(*id, &interact_rect);
+                    }
+                }
+            }
+
+            let hovered = control_state.is_hovered(area_id)
+                || bars.iter().any(|(id, _)| control_state.is_hovered(*id));
+            if hovered {
+                let step = control_state.take_wheel_delta() * scale.px(WHEEL_STEP);
+                if control_state.text_input.shift() {
+                    state.offset.x -= step;
+                } else {
+                    state.offset.y -= step;
+                }
+                state.offset = state.offset.max(Vec2::ZERO).min(max_offset);
+            }
+
+            access_id = Some(
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::ScrollView, viewport, label)
+                        .set_control(area_id)
+                        .set_range(state.offset.y, 0.0, max_offset.y),
+                ),
+            );
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() {
+                let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+                for (id, axis) in &bars {
+                    if !control_state.is_hovered(*id) {
+                        continue;
+                    }
+                    if let Some(active_id) = control_state.set_active(*id) {
+                        let bar = scrollbar(*axis, state.offset);
+                        // A press outside of the thumb grabs it by the middle
+                        state.drag_offset = if bar.thumb.inside_rect(cursor_pos) {
+                            bar.distance(cursor_pos) - bar.thumb_start()
+                        } else {
+                            bar.axis.get(bar.thumb.size) * 0.5
+                        };
+                        state.drag = Some((*axis, active_id));
+                        move_thumb(state, &bar, cursor_pos, max_offset);
+                    }
+                }
+            }
+            if mouse_input.is_left_released() {
+                state.drag = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if let Some((axis, active_id)) = state.drag {
+                if control_state.is_active(Some(active_id)) {
+                    let bar = scrollbar(axis, state.offset);
+                    move_thumb(state, &bar, *corrected, max_offset);
+                }
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            for (id, axis) in &bars {
+                let bar = scrollbar(*axis, state.offset);
+                let radius = BorderRadius::ForAll(bar_width * 0.5);
+                let track = ElementBuilder::new_with_rect(bar.track)
+                    .set_color(colors.control.into())
+                    .set_round_rect(radius.into())
+                    .set_rect_mask(mask.into());
+                control_state.layers.build(track, gui_rects);
+
+                let dragged = matches!(state.drag, Some((dragged, _)) if dragged == *axis);
+                let thumb_color = if dragged {
+                    colors.handle_active
+                } else if control_state.is_hovered(*id) {
+                    colors.handle_hover
+                } else {
+                    colors.border
+                };
+                let thumb = ElementBuilder::new_with_rect(bar.thumb)
+                    .set_color(thumb_color.into())
+                    .set_round_rect(radius.into())
+                    .set_rect_mask(mask.into());
+                control_state.layers.build(thumb, gui_rects);
+            }
+        }
+        _ => {}
+    }
+
+    ScrollView {
+        id: area_id,
+        viewport,
+        offset: state.offset,
+        access_id,
+    }
+}
+
+fn move_thumb(state: &mut ScrollArea, bar: &Scrollbar, cursor_pos: Vec2, max_offset: Vec2) {
+    let offset = (bar.distance(cursor_pos) - state.drag_offset) * bar.ratio;
+    match bar.axis {
+        Axis::Vertical => state.offset.y = offset.max(0.0).min(max_offset.y),
+        Axis::Horizontal => state.offset.x = offset.max(0.0).min(max_offset.x),
+    }
+}
@@ -0,0 +1,78 @@
+use std::{collections::HashSet, hash::Hash};
+
+/// Multiple selection of the items of a list, shared by the table and the tree view.
+/// The items are kept by id, so the selection stays on the same items when they move.
+pub struct Selection<T> {
+    pub(super) ids: HashSet<T>,
+    /// Item of the keyboard focus, it is not always selected
+    pub(super) cursor: Option<T>,
+    /// The Shift ranges go from it to the cursor
+    pub(super) anchor: Option<T>,
+}
+
+impl<T: Copy + Eq + Hash> Selection<T> {
+    pub fn new() -> Self {
+        Self {
+            ids: HashSet::new(),
+            cursor: None,
+            anchor: None,
+        }
+    }
+
+    pub fn ids(&self) -> &HashSet<T> {
+        &self.ids
+    }
+
+    pub fn contains(&self, id: T) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn set(&mut self, ids: impl IntoIterator<Item = T>) {
+        self.ids = ids.into_iter().collect();
+        self.cursor = self.ids.iter().next().copied();
+        self.anchor = self.cursor;
+    }
+
+    /// Returns true if the selection changed
+    pub fn select_only(&mut self, id: T) -> bool {
+        let changed = self.ids.len() != 1 || !self.ids.contains(&id);
+        self.ids.clear();
+        self.ids.insert(id);
+        self.cursor = Some(id);
+        self.anchor = Some(id);
+        changed
+    }
+
+    pub fn toggle(&mut self, id: T) {
+        if !self.ids.remove(&id) {
+            self.ids.insert(id);
+        }
+        self.cursor = Some(id);
+        self.anchor = Some(id);
+    }
+
+    /// Selects from the anchor to `position` of `ids`, which are in display order.
+    /// The rest of the selection is kept with `add`.
+    pub fn select_range(
+        &mut self,
+        mut ids: impl Iterator<Item = T> + Clone,
+        position: usize,
+        add: bool,
+    ) {
+        let anchor_position = self
+            .anchor
+            .and_then(|anchor| ids.clone().position(|id| id == anchor))
+            .unwrap_or(position);
+        if !add {
+            self.ids.clear();
+        }
+        let start = anchor_position.min(position);
+        let end = anchor_position.max(position);
+        self.ids
+            .extend(ids.clone().skip(start).take(end - start + 1));
+        self.cursor = ids.nth(position);
+        if self.anchor.is_none() {
+            self.anchor = self.cursor;
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use rwge::{
+    color::RGBA,
+    font::font_layout::create_single_line,
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border, LinearGradient},
+        event::UIEvent,
+        BorderRadius, GUIRects, Rect,
+    },
+    math_utils::lerp_f32,
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        theme::{get_theme, Theme},
+    },
+    runtime_data::{utils::get_font_collections, PublicData},
+};
+
+use super::{get_current_control_id, keyboard, ControlState, State, Uiid};
+
+#[cfg(test)]
+mod test;
+
+/// Part of the track an arrow key or a line of the mouse wheel moves the thumb, when the slider
+/// has no steps
+const KEY_STEP: f32 = 0.01;
+const PAGE_STEP: f32 = 0.1;
+const TICK_LENGTH: f32 = 4.0;
+const TICK_WIDTH: f32 = 1.0;
+const TICK_GAP: f32 = 3.0;
+/// Ticks closer than this are not drawn
+const MIN_TICK_SPACING: f32 = 4.0;
+const VALUE_LABEL_GAP: f32 = 4.0;
+
+pub fn inv_lerp(a: f32, b: f32, t: f32) -> f32 {
+    (t - a) / (b - a)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SliderOrientation {
+    Horizontal,
+    /// The minimum is at the bottom
+    Vertical,
+}
+
+/// How the position of the thumb maps to the value
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SliderCurve {
+    Linear,
+    /// Moving the thumb multiplies the value, the range has to be positive or it stays linear
+    Logarithmic,
+    /// The position along the track is raised to the exponent, above 1 the low values get more
+    /// room
+    Power(f32),
+}
+
+impl SliderCurve {
+    /// `position` goes from 0 at the start of the track to 1 at its end
+    pub fn value_at(&self, position: f32, min: f32, max: f32) -> f32 {
+        match self {
+            SliderCurve::Logarithmic if min > 0.0 && max > 0.0 => min * (max / min).powf(position),
+            SliderCurve::Power(exponent) => lerp_f32(min, max, position.powf(*exponent)),
+            _ => lerp_f32(min, max, position),
+        }
+    }
+
+    pub fn position_of(&self, value: f32, min: f32, max: f32) -> f32 {
+        let position = match self {
+            SliderCurve::Logarithmic if min > 0.0 && max > 0.0 => {
+                (value / min).ln() / (max / min).ln()
+            }
+            SliderCurve::Power(exponent) => {
+                inv_lerp(min, max, value).max(0.0).powf(1.0 / *exponent)
+            }
+            _ => inv_lerp(min, max, value),
+        };
+        if position.is_finite() {
+            position.max(0.0).min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Everything about a slider besides its value and range
+pub struct SliderOptions {
+    pub orientation: SliderOrientation,
+    pub curve: SliderCurve,
+    /// The track is split in this many equal steps, the thumb snaps to them and they get ticks
+    pub steps: Option<u32>,
+    /// Decimals of the value shown next to the thumb while the slider is hovered, used or focused
+    pub value_label: Option<usize>,
+    /// Replaces the fill gradient of the theme
+    pub fill_colors: Option<[RGBA; 2]>,
+}
+
+impl SliderOptions {
+    pub fn new() -> Self {
+        Self {
+            orientation: SliderOrientation::Horizontal,
+            curve: SliderCurve::Linear,
+            steps: None,
+            value_label: None,
+            fill_colors: None,
+        }
+    }
+
+    pub fn set_orientation(mut self, orientation: SliderOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn set_curve(mut self, curve: SliderCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn set_steps(mut self, steps: u32) -> Self {
+        self.steps = Some(steps.max(1));
+        self
+    }
+
+    pub fn set_value_label(mut self, precision: usize) -> Self {
+        self.value_label = Some(precision);
+        self
+    }
+
+    pub fn set_fill_colors(mut self, start: RGBA, end: RGBA) -> Self {
+        self.fill_colors = Some([start, end]);
+        self
+    }
+
+    fn snap(&self, position: f32) -> f32 {
+        let position = position.max(0.0).min(1.0);
+        match self.steps {
+            Some(steps) => (position * steps as f32).round() / steps as f32,
+            None => position,
+        }
+    }
+
+    fn key_step(&self) -> f32 {
+        self.steps.map_or(KEY_STEP, |steps| 1.0 / steps as f32)
+    }
+
+    fn page_step(&self) -> f32 {
+        self.key_step().max(PAGE_STEP)
+    }
+}
+
+/// State of a range slider, kept by the container between frames
+pub struct RangeSliderState {
+    active_id: Option<Uuid>,
+    /// Thumb that was dragged last, the keyboard and the mouse wheel move it
+    thumb: usize,
+}
+
+impl RangeSliderState {
+    pub fn new() -> Self {
+        Self {
+            active_id: None,
+            thumb: 0,
+        }
+    }
+}
+
+/// Positions along the main axis of the slider go from 0 at the start of the track to 1 at its
+/// end
+struct Track {
+    rect: Rect,
+    vertical: bool,
+}
+
+impl Track {
+    fn length(&self) -> f32 {
+        if self.vertical {
+            self.rect.height()
+        } else {
+            self.rect.width()
+        }
+    }
+
+    fn cross_size(&self) -> f32 {
+        if self.vertical {
+            self.rect.width()
+        } else {
+            self.rect.height()
+        }
+    }
+
+    /// Unit vector along the track
+    fn axis(&self) -> Vec2 {
+        if self.vertical {
+            vec2(0.0, 1.0)
+        } else {
+            vec2(1.0, 0.0)
+        }
+    }
+
+    /// Size with `along` on the main axis and `across` on the other one
+    fn oriented(&self, along: f32, across: f32) -> Vec2 {
+        if self.vertical {
+            vec2(across, along)
+        } else {
+            vec2(along, across)
+        }
+    }
+
+    fn point(&self, position: f32) -> Vec2 {
+        self.rect.position + self.axis() * (position - 0.5) * self.length()
+    }
+
+    fn position_at(&self, cursor_pos: Vec2) -> f32 {
+        let position = (cursor_pos - self.rect.position).dot(self.axis()) / self.length() + 0.5;
+        position.max(0.0).min(1.0)
+    }
+
+    /// Part of the track between two positions, `across` wide
+    fn section(&self, start: f32, end: f32, across: f32) -> Rect {
+        Rect {
+            position: self.point((start + end) * 0.5),
+            size: self.oriented((end - start) * self.length(), across),
+        }
+    }
+}
+
+/// Moves a thumb to `position`, the thumbs of a range never cross
+fn move_thumb(
+    values: &mut [f32],
+    thumb: usize,
+    position: f32,
+    min: f32,
+    max: f32,
+    options: &SliderOptions,
+) {
+    let mut position = options.snap(position);
+    if values.len() > 1 {
+        let curve = options.curve;
+        if thumb == 0 {
+            position = position.min(curve.position_of(values[1], min, max));
+        } else {
+            position = position.max(curve.position_of(values[0], min, max));
+        }
+    }
+    values[thumb] = options.curve.value_at(position, min, max);
+}
+
+/// Thumb closest to the cursor, the upper one wins when they are on top of each other and the
+/// cursor is above them
+fn closest_thumb(positions: &[f32], position: f32) -> usize {
+    if positions.len() < 2 {
+        return 0;
+    }
+    let low_distance = (position - positions[0]).abs();
+    let high_distance = (position - positions[1]).abs();
+    if low_distance < high_distance || (low_distance == high_distance && position < positions[0]) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Shared by `slider` and `range_slider`, `values` holds one value or the two ends of a range
+fn slider_impl(
+    rect: Rect,
+    mask: Rect,
+    values: &mut [f32],
+    min: f32,
+    max: f32,
+    options: &SliderOptions,
+    active_id: &mut Option<Uuid>,
+    thumb: &mut usize,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) {
+    for value in values.iter_mut() {
+        *value = value.max(min).min(max);
+    }
+
+    let control_id = control_state.get_id();
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let track = Track {
+        rect,
+        vertical: options.orientation == SliderOrientation::Vertical,
+    };
+    let positions: Vec<f32> = values
+        .iter()
+        .map(|value| options.curve.position_of(*value, min, max))
+        .collect();
+    *thumb = (*thumb).min(values.len() - 1);
+
+    if control_state.is_focused(control_id) {
+        let position = positions[*thumb];
+        let new_position = match keyboard::get_pressed_key(event) {
+            Some(VirtualKeyCode::Right | VirtualKeyCode::Up) => Some(position + options.key_step()),
+            Some(VirtualKeyCode::Left | VirtualKeyCode::Down) => {
+                Some(position - options.key_step())
+            }
+            Some(VirtualKeyCode::PageUp) => Some(position + options.page_step()),
+            Some(VirtualKeyCode::PageDown) => Some(position - options.page_step()),
+            Some(VirtualKeyCode::Home) => Some(0.0),
+            Some(VirtualKeyCode::End) => Some(1.0),
+            _ => None,
+        };
+        if let Some(new_position) = new_position {
+            move_thumb(values, *thumb, new_position, min, max, options);
+        }
+    }
+
+    match event {
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() {
+                *active_id = control_state.set_active(control_id);
+                if active_id.is_some() {
+                    control_state.set_focus(control_id);
+                    let position = track.position_at(control_state.last_cursor_position.unwrap());
+                    *thumb = closest_thumb(&positions, position);
+                    move_thumb(values, *thumb, position, min, max, options);
+                }
+            }
+
+            if mouse_input.is_left_released() {
+                *active_id = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if control_state.is_active(*active_id) {
+                let position = track.position_at(*corrected);
+                move_thumb(values, *thumb, position, min, max, options);
+            }
+        }
+        UIEvent::Update => {
+            let pin_size = scale.px(theme.metrics.slider_pin_size);
+            let control_rect = Rect {
+                position: rect.position,
+                size: track.oriented(track.length(), pin_size),
+            };
+
+            if control_state.is_hovered(control_id) {
+                let lines = control_state.take_wheel_delta();
+                if lines != 0.0 {
+                    let position = positions[*thumb] + lines * options.key_step();
+                    move_thumb(values, *thumb, position, min, max, options);
+                }
+            }
+
+            let labels: &[&str] = if values.len() > 1 {
+                &["Lower value", "Upper value"]
+            } else {
+                &[""]
+            };
+            for (value, label) in values.iter().zip(labels.iter()) {
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Slider, control_rect, label)
+                        .set_control(control_id)
+                        .set_range(*value, min, max),
+                );
+            }
+
+            if active_id.is_some() {
+                control_state.hold_active_state(active_id.unwrap());
+            } else if let Some(control_rect) = control_rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(control_id, &control_rect);
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let positions: Vec<f32> = values
+                .iter()
+                .map(|value| options.curve.position_of(*value, min, max))
+                .collect();
+            render_slider(
+                gui_rects,
+                &track,
+                mask,
+                values,
+                &positions,
+                options,
+                control_id,
+                active_id,
+                *thumb,
+                control_state,
+                theme,
+                public_data,
+            );
+        }
+        _ => {}
+    }
+}
+
+fn render_slider(
+    gui_rects: &mut GUIRects,
+    track: &Track,
+    mask: Rect,
+    values: &[f32],
+    positions: &[f32],
+    options: &SliderOptions,
+    control_id: Uiid,
+    active_id: &Option<Uuid>,
+    thumb: usize,
+    control_state: &mut ControlState,
+    theme: &Theme,
+    public_data: &PublicData,
+) {
+    let scale = control_state.scale;
+    let colors = &theme.colors;
+    let length = track.length();
+    let bg_height = scale.px(theme.metrics.slider_track_height);
+    let bg_size = track.oriented(length, bg_height);
+    let bg_radius = BorderRadius::ForAll(bg_height * 0.5);
+
+    // A single value is filled from the start of the track
+    let fill_start = if positions.len() > 1 {
+        positions[0]
+    } else {
+        0.0
+    };
+    let fill_end = positions[positions.len() - 1];
+
+    let state = control_state.get_control_state(get_current_control_id(control_id, active_id));
+    let hovered = !matches!(state, State::Inactive);
+
+    if steps_visible(options, length, scale.px(MIN_TICK_SPACING)) {
+        let steps = options.steps.unwrap();
+        let tick_length = scale.px(TICK_LENGTH);
+        let across = track.oriented(0.0, 1.0);
+        let offset = across * -(bg_height * 0.5 + scale.px(TICK_GAP) + tick_length * 0.5);
+        for step in 0..=steps {
+            let position = step as f32 / steps as f32;
+            let tick = ElementBuilder::new(
+                (track.point(position) + offset).round(),
+                track.oriented(scale.px(TICK_WIDTH).max(1.0), tick_length),
+            )
+            .set_color(colors.text_muted.into())
+            .set_rect_mask(mask.into());
+            control_state.layers.build(tick, gui_rects);
+        }
+    }
+
+    let filled_section = track.section(fill_start, fill_end, track.cross_size());
+    if let Some(filled_w_mask) = filled_section.combine_rects(&mask) {
+        let [fill_color_start, fill_color_end] = options
+            .fill_colors
+            .unwrap_or([colors.slider_fill_start, colors.slider_fill_end]);
+        let lin_grad = LinearGradient {
+            colors: [fill_color_start, fill_color_end],
+            start_position: track.axis() * (length * fill_end - length * 0.5),
+            end_position: track.axis() * (length * fill_start - length * 0.5),
+        };
+        let fill = ElementBuilder::new(track.rect.position, bg_size)
+            .set_linear_gradient(lin_grad.into())
+            .set_rect_mask(filled_w_mask.into())
+            .set_round_rect(bg_radius.into());
+        control_state.layers.build(fill, gui_rects);
+    }
+
+    let unfilled_sections = [
+        track.section(0.0, fill_start, track.cross_size()),
+        track.section(fill_end, 1.0, track.cross_size()),
+    ];
+    for section in unfilled_sections.iter() {
+        if let Some(unfilled_w_mask) = mask.combine_rects(section) {
+            let unfilled = ElementBuilder::new(track.rect.position, bg_size)
+                .set_color(if hovered {
+                    colors.slider_track_hover.into()
+                } else {
+                    colors.slider_track.into()
+                })
+                .set_rect_mask(unfilled_w_mask.into())
+                .set_round_rect(bg_radius.into());
+            control_state.layers.build(unfilled, gui_rects);
+        }
+    }
+
+    let slider_pin_size = Vec2::splat(scale.px(theme.metrics.slider_pin_size));
+    for (index, position) in positions.iter().enumerate() {
+        // Only the dragged thumb of a range is drawn as active
+        let (border_size, border_color) = match state {
+            State::Inactive => (4, colors.slider_pin_border),
+            State::Active if index == thumb => (2, colors.slider_pin_border_hover),
+            State::Hovered | State::Active => (4, colors.slider_pin_border_hover),
+        };
+        let pin = ElementBuilder::new(track.point(*position).round(), slider_pin_size)
+            .set_circle()
+            .set_color(colors.slider_pin.into())
+            .set_border(Some(Border {
+                size: scale.border(border_size),
+                color: border_color.into(),
+            }))
+            .set_rect_mask(mask.into());
+        control_state.layers.build(pin, gui_rects);
+    }
+
+    let show_label = hovered || control_state.is_focused(control_id);
+    if let Some(precision) = options.value_label.filter(|_| show_label) {
+        let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+        let font_size = scale.font(theme.fonts.size_small);
+        let gap = scale.px(VALUE_LABEL_GAP);
+        for (value, position) in values.iter().zip(positions.iter()) {
+            let (font_elements, text_rect) = create_single_line(
+                &format!("{:.*}", precision, value),
+                font_size,
+                font_collection,
+                0,
+                0.0,
+            );
+            // Above the thumb, or to its right on a vertical slider
+            let label_center = if track.vertical {
+                track.point(*position)
+                    + vec2(slider_pin_size.x * 0.5 + gap + text_rect.width() * 0.5, 0.0)
+            } else {
+                track.point(*position)
+                    + vec2(
+                        0.0,
+                        slider_pin_size.y * 0.5 + gap + text_rect.height() * 0.5,
+                    )
+            };
+            let text_origin = (label_center - text_rect.size * 0.5).round();
+            for element in font_elements {
+                let glyph =
+                    ElementBuilder::new_with_rect(element.rect.offset_position(text_origin))
+                        .set_sdffont(element.tx_slice.into())
+                        .set_color(colors.text.into())
+                        .set_rect_mask(mask.into());
+                control_state.layers.build(glyph, gui_rects);
+            }
+        }
+    }
+}
+
+/// Ticks are left out when they would be too close to each other
+fn steps_visible(options: &SliderOptions, length: f32, min_spacing: f32) -> bool {
+    match options.steps {
+        Some(steps) => length / steps as f32 >= min_spacing,
+        None => false,
+    }
+}
+
+/// Returns the new value. The slider takes the focus when clicked, then the arrow keys, Page
+/// Up/Down, Home and End move it, and so does the mouse wheel while it is hovered.
+pub fn slider(
+    rect: Rect,
+    mask: Rect,
+    value: f32,
+    min: f32,
+    max: f32,
+    options: &SliderOptions,
+    active_id: &mut Option<Uuid>,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> f32 {
+    let mut values = [value];
+    let mut thumb = 0;
+    slider_impl(
+        rect,
+        mask,
+        &mut values,
+        min,
+        max,
+        options,
+        active_id,
+        &mut thumb,
+        event,
+        control_state,
+        public_data,
+    );
+    values[0]
+}
+
+/// Slider with two thumbs, returns the new `(low, high)` range. The thumbs never cross, the one
+/// dragged last is moved by the keyboard and the mouse wheel.
+pub fn range_slider(
+    rect: Rect,
+    mask: Rect,
+    range: (f32, f32),
+    min: f32,
+    max: f32,
+    options: &SliderOptions,
+    state: &mut RangeSliderState,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> (f32, f32) {
+    let mut values = [range.0.min(range.1), range.0.max(range.1)];
+    slider_impl(
+        rect,
+        mask,
+        &mut values,
+        min,
+        max,
+        options,
+        &mut state.active_id,
+        &mut state.thumb,
+        event,
+        control_state,
+        public_data,
+    );
+    (values[0], values[1])
+}
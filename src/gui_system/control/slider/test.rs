@@ -0,0 +1,108 @@
+use super::{closest_thumb, move_thumb, SliderCurve, SliderOptions};
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+/// Every position maps to a value and back to the same position
+fn assert_round_trip(curve: SliderCurve, min: f32, max: f32) {
+    for position in [0.0, 0.1, 0.25, 0.5, 0.8, 1.0] {
+        let value = curve.value_at(position, min, max);
+        let back = curve.position_of(value, min, max);
+        assert!(close(back, position), "{curve:?}: {position} became {back}");
+    }
+}
+
+#[test]
+fn linear_curve_round_trips() {
+    let curve = SliderCurve::Linear;
+    assert!(close(curve.value_at(0.25, -10.0, 30.0), 0.0));
+    assert_round_trip(curve, -10.0, 30.0);
+}
+
+#[test]
+fn logarithmic_curve_multiplies_the_value() {
+    let curve = SliderCurve::Logarithmic;
+    assert!(close(curve.value_at(1.0 / 3.0, 1.0, 1000.0), 10.0));
+    assert!(close(curve.value_at(2.0 / 3.0, 1.0, 1000.0), 100.0));
+    assert!(close(curve.position_of(10.0, 1.0, 1000.0), 1.0 / 3.0));
+    assert_round_trip(curve, 1.0, 1000.0);
+}
+
+#[test]
+fn logarithmic_curve_is_linear_without_a_positive_range() {
+    let curve = SliderCurve::Logarithmic;
+    assert!(close(curve.value_at(0.5, -1.0, 1.0), 0.0));
+    assert!(close(curve.value_at(0.5, 0.0, 10.0), 5.0));
+    assert!(close(curve.position_of(5.0, 0.0, 10.0), 0.5));
+    assert_round_trip(curve, 0.0, 10.0);
+    assert_round_trip(curve, -5.0, 5.0);
+}
+
+#[test]
+fn power_curve_gives_the_low_values_more_room() {
+    let curve = SliderCurve::Power(2.0);
+    assert!(close(curve.value_at(0.5, 0.0, 100.0), 25.0));
+    assert!(close(curve.position_of(25.0, 0.0, 100.0), 0.5));
+    assert_round_trip(curve, 0.0, 100.0);
+    assert_round_trip(SliderCurve::Power(0.5), -20.0, 20.0);
+}
+
+#[test]
+fn positions_are_clamped_to_the_track() {
+    for curve in [
+        SliderCurve::Linear,
+        SliderCurve::Logarithmic,
+        SliderCurve::Power(3.0),
+    ] {
+        assert_eq!(curve.position_of(500.0, 1.0, 100.0), 1.0);
+        assert_eq!(curve.position_of(-5.0, 1.0, 100.0), 0.0);
+    }
+}
+
+#[test]
+fn snap_rounds_to_the_nearest_step() {
+    let options = SliderOptions::new().set_steps(4);
+    assert_eq!(options.snap(0.3), 0.25);
+    assert_eq!(options.snap(0.4), 0.5);
+    assert_eq!(options.snap(-0.2), 0.0);
+    assert_eq!(options.snap(1.3), 1.0);
+
+    let options = SliderOptions::new();
+    assert_eq!(options.snap(0.3), 0.3);
+    assert_eq!(options.snap(1.3), 1.0);
+}
+
+#[test]
+fn single_thumb_follows_the_position() {
+    let options = SliderOptions::new().set_steps(10);
+    let mut values = [50.0];
+    move_thumb(&mut values, 0, 0.32, 0.0, 100.0, &options);
+    assert!(close(values[0], 30.0));
+}
+
+#[test]
+fn range_thumbs_never_cross() {
+    let options = SliderOptions::new();
+    let mut values = [20.0, 60.0];
+    move_thumb(&mut values, 0, 0.8, 0.0, 100.0, &options);
+    assert!(close(values[0], 60.0));
+    move_thumb(&mut values, 1, 0.1, 0.0, 100.0, &options);
+    assert!(close(values[1], 60.0));
+
+    move_thumb(&mut values, 1, 0.9, 0.0, 100.0, &options);
+    move_thumb(&mut values, 0, 0.4, 0.0, 100.0, &options);
+    assert!(close(values[0], 40.0));
+    assert!(close(values[1], 90.0));
+}
+
+#[test]
+fn closest_thumb_picks_the_nearest_end() {
+    assert_eq!(closest_thumb(&[0.5], 0.9), 0);
+    assert_eq!(closest_thumb(&[0.2, 0.6], 0.3), 0);
+    assert_eq!(closest_thumb(&[0.2, 0.6], 0.5), 1);
+    // On top of each other the cursor side decides
+    assert_eq!(closest_thumb(&[0.5, 0.5], 0.4), 0);
+    assert_eq!(closest_thumb(&[0.5, 0.5], 0.6), 1);
+    assert_eq!(closest_thumb(&[0.5, 0.5], 0.5), 1);
+}
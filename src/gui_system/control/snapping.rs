@@ -0,0 +1,133 @@
+use rwge::{
+    glam::{vec2, Vec2},
+    gui::rect_ui::Rect,
+};
+
+#[derive(Copy, Clone)]
+pub struct SnapSettings {
+    /// Distance in pixels at which an edge gets pulled
+    pub threshold: f32,
+    /// Moving the cursor to the side of the screen previews a half or quarter of the screen
+    pub zones: bool,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 12.0,
+            zones: true,
+        }
+    }
+}
+
+pub enum Snap {
+    /// The element keeps its size and is moved to the position
+    Position(Vec2),
+    /// The element takes the whole rect once it is dropped
+    Zone(Rect),
+}
+
+/// Everything an element can snap to
+pub struct SnapTargets<'a> {
+    pub bounds: Rect,
+    pub others: &'a [Rect],
+    pub settings: SnapSettings,
+}
+
+impl<'a> SnapTargets<'a> {
+    /// `rect` is where the element would be without snapping
+    pub fn snap(&self, rect: Rect, cursor_position: Vec2) -> Option<Snap> {
+        if self.settings.zones {
+            if let Some(zone) = self.zone_under_cursor(cursor_position) {
+                return Some(Snap::Zone(zone));
+            }
+        }
+
+        let offset_x = self.snap_axis(rect, |v| v.x, |v| v.y);
+        let offset_y = self.snap_axis(rect, |v| v.y, |v| v.x);
+        if offset_x.is_none() && offset_y.is_none() {
+            return None;
+        }
+        let offset = vec2(offset_x.unwrap_or(0.0), offset_y.unwrap_or(0.0));
+        Some(Snap::Position(rect.position + offset))
+    }
+
+    /// Halves of the screen on the sides and quarters on the corners
+    fn zone_under_cursor(&self, cursor_position: Vec2) -> Option<Rect> {
+        let threshold = self.settings.threshold;
+        let (min, max) = bounds_of(&self.bounds);
+        let side = |value: f32, min: f32, max: f32| -> f32 {
+            if value - min <= threshold {
+                -1.0
+            } else if max - value <= threshold {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        let side = vec2(
+            side(cursor_position.x, min.x, max.x),
+            side(cursor_position.y, min.y, max.y),
+        );
+        if side == Vec2::ZERO {
+            return None;
+        }
+
+        let half_size = self.bounds.size * 0.5;
+        let size = vec2(
+            if side.x == 0.0 {
+                self.bounds.size.x
+            } else {
+                half_size.x
+            },
+            if side.y == 0.0 {
+                self.bounds.size.y
+            } else {
+                half_size.y
+            },
+        );
+        Some(Rect {
+            position: self.bounds.position + side * (self.bounds.size - size) * 0.5,
+            size,
+        })
+    }
+
+    /// Offset that moves the closest edge of `rect` onto an edge of the bounds or of another
+    /// element. Other elements only count if they are next to `rect` on the other axis.
+    fn snap_axis(
+        &self,
+        rect: Rect,
+        axis: impl Fn(Vec2) -> f32,
+        cross_axis: impl Fn(Vec2) -> f32,
+    ) -> Option<f32> {
+        let threshold = self.settings.threshold;
+        let (min, max) = bounds_of(&rect);
+        let (rect_min, rect_max) = (axis(min), axis(max));
+        let (rect_cross_min, rect_cross_max) = (cross_axis(min), cross_axis(max));
+
+        let mut lines = Vec::with_capacity(2 + self.others.len() * 2);
+        let (bounds_min, bounds_max) = bounds_of(&self.bounds);
+        lines.push(axis(bounds_min));
+        lines.push(axis(bounds_max));
+        for other in self.others {
+            let (other_min, other_max) = bounds_of(other);
+            let overlaps = cross_axis(other_min) <= rect_cross_max + threshold
+                && cross_axis(other_max) >= rect_cross_min - threshold;
+            if overlaps {
+                lines.push(axis(other_min));
+                lines.push(axis(other_max));
+            }
+        }
+
+        lines
+            .iter()
+            .flat_map(|line| [line - rect_min, line - rect_max])
+            .filter(|offset| offset.abs() <= threshold)
+            .min_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap())
+    }
+}
+
+fn bounds_of(rect: &Rect) -> (Vec2, Vec2) {
+    let half_size = rect.size * 0.5;
+    (rect.position - half_size, rect.position + half_size)
+}
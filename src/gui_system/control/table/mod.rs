@@ -0,0 +1,997 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use rwge::{
+    color::RGBA,
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, GUIRects, Rect,
+    },
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        layers::LayerManager,
+        theme::{get_theme, Theme},
+    },
+    runtime_data::{utils::get_font_collections, PublicData},
+};
+
+use super::{
+    cursor::{CursorIcon, CursorPriority},
+    scroll_area::{self, ScrollArea, ScrollView},
+    selection::Selection,
+    text_field::TextInputEvent,
+    ControlState, Uiid,
+};
+
+#[cfg(test)]
+mod test;
+
+const ROW_HEIGHT: f32 = 22.0;
+const HEADER_HEIGHT: f32 = 24.0;
+const CELL_PADDING: f32 = 6.0;
+/// Distance from the right edge of a header where a press resizes the column
+const RESIZE_MARGIN: f32 = 4.0;
+const DIVIDER_WIDTH: f32 = 1.0;
+const DEFAULT_MIN_WIDTH: f32 = 30.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Header of a column, the sizes are in pixels at 100% scale
+pub struct TableColumn {
+    pub title: String,
+    pub width: f32,
+    pub min_width: f32,
+    /// Clicking the header sorts the rows by the column
+    pub sortable: bool,
+    /// Double clicking a cell starts editing it, the caller draws the editor
+    pub editable: bool,
+}
+
+impl TableColumn {
+    pub fn new(title: &str, width: f32) -> Self {
+        Self {
+            title: String::from(title),
+            width,
+            min_width: DEFAULT_MIN_WIDTH,
+            sortable: true,
+            editable: false,
+        }
+    }
+
+    pub fn set_min_width(mut self, min_width: f32) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    pub fn set_sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
+    pub fn set_editable(mut self, editable: bool) -> Self {
+        self.editable = editable;
+        self
+    }
+}
+
+/// Chosen by the source, it has to stay the same while the row exists
+pub type TableRowId = u64;
+
+/// Rows shown by a table. Rows are read by their index in the source and the table keeps its own
+/// display order. The selection keeps the ids of the rows, so it stays on the same rows when
+/// others are inserted or removed. Only the cells of the visible rows are asked for.
+pub trait TableSource {
+    fn row_count(&self) -> usize;
+    fn row_id(&self, row: usize) -> TableRowId;
+    fn cell_text(&self, row: usize, column: usize) -> String;
+    /// Used to sort by `column`, compares the text by default
+    fn compare(&self, a: usize, b: usize, column: usize) -> Ordering {
+        self.cell_text(a, column).cmp(&self.cell_text(b, column))
+    }
+}
+
+/// What the user did with the table in the last event
+#[derive(Clone, Debug, PartialEq)]
+pub enum TableChange {
+    /// The new selection is read from `Table::selection`
+    SelectionChanged,
+    /// `None` is the order of the source
+    Sorted {
+        column: usize,
+        order: Option<SortOrder>,
+    },
+    ColumnResized(usize),
+    /// Double click or Enter on the row at this index of the source
+    Activated(usize),
+    /// The editor is drawn by the caller over `Table::editing`
+    EditStarted {
+        row: usize,
+        column: usize,
+    },
+}
+
+/// Cell being edited and where its editor goes
+#[derive(Clone, Copy)]
+pub struct CellEdit {
+    pub row_id: TableRowId,
+    /// Index of the row in the source, updated when the rows change
+    pub row: usize,
+    pub column: usize,
+    pub rect: Rect,
+    /// Visible part of the rows
+    pub mask: Rect,
+}
+
+struct ColumnResize {
+    active_id: Uuid,
+    column: usize,
+    start_x: f32,
+    start_width: f32,
+}
+
+/// Columns, sort, selection and scroll of a table, kept by the container between frames
+pub struct Table {
+    columns: Vec<TableColumn>,
+    scroll: ScrollArea,
+    sort: Option<(usize, SortOrder)>,
+    /// Source rows in the order they are shown
+    order: Vec<usize>,
+    /// Ids of the rows in `order`
+    row_ids: Vec<TableRowId>,
+    order_dirty: bool,
+    selection: Selection<TableRowId>,
+    resize: Option<ColumnResize>,
+    editing: Option<CellEdit>,
+    /// Set when an edit ends with Enter, the table takes the focus back on the next update
+    take_focus: bool,
+    last_clicked: Option<TableRowId>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<TableColumn>) -> Self {
+        Self {
+            columns,
+            scroll: ScrollArea::new(),
+            sort: None,
+            order: Vec::new(),
+            row_ids: Vec::new(),
+            order_dirty: true,
+            selection: Selection::new(),
+            resize: None,
+            editing: None,
+            take_focus: false,
+            last_clicked: None,
+        }
+    }
+
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+
+    pub fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn set_sort(&mut self, sort: Option<(usize, SortOrder)>) {
+        self.sort = sort;
+        self.order_dirty = true;
+    }
+
+    /// Reads the rows again on the next event. A change of the row count is noticed by the table,
+    /// any other change of the rows or their order in the source needs a refresh.
+    pub fn refresh(&mut self) {
+        self.order_dirty = true;
+    }
+
+    pub fn selection(&self) -> &HashSet<TableRowId> {
+        self.selection.ids()
+    }
+
+    pub fn set_selection(&mut self, selection: impl IntoIterator<Item = TableRowId>) {
+        self.selection.set(selection);
+    }
+
+    /// Cell the caller has to draw an editor over, the rect follows the scroll
+    pub fn editing(&self) -> Option<CellEdit> {
+        self.editing
+    }
+
+    /// Called by the caller once its editor is done. With `focus_table` the keyboard goes back
+    /// to the table.
+    pub fn stop_editing(&mut self, focus_table: bool) {
+        self.editing = None;
+        self.take_focus = focus_table;
+    }
+
+    /// `position` is the display row
+    fn start_editing(&mut self, position: usize, column: usize, changes: &mut Vec<TableChange>) {
+        let row = self.order[position];
+        self.editing = Some(CellEdit {
+            row_id: self.row_ids[position],
+            row,
+            column,
+            rect: Rect::default(),
+            mask: Rect::default(),
+        });
+        changes.push(TableChange::EditStarted { row, column });
+    }
+
+    /// Sorting is only done when the rows or the sort change, not every event. The rows that are
+    /// gone are dropped from the selection.
+    fn update_order(&mut self, source: &dyn TableSource) {
+        let row_count = source.row_count();
+        if !self.order_dirty && self.order.len() == row_count {
+            return;
+        }
+        self.order_dirty = false;
+        self.order = (0..row_count).collect();
+        if let Some((column, order)) = self.sort {
+            self.order.sort_by(|a, b| {
+                let ordering = source.compare(*a, *b, column);
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
+        }
+        self.row_ids = self.order.iter().map(|row| source.row_id(*row)).collect();
+
+        let rows: HashMap<TableRowId, usize> = (0..row_count)
+            .map(|row| (source.row_id(row), row))
+            .collect();
+        let selection = &mut self.selection;
+        selection.ids.retain(|id| rows.contains_key(id));
+        if selection.cursor.map_or(false, |id| !rows.contains_key(&id)) {
+            selection.cursor = None;
+        }
+        if selection.anchor.map_or(false, |id| !rows.contains_key(&id)) {
+            selection.anchor = None;
+        }
+        self.editing = self.editing.and_then(|edit| {
+            rows.get(&edit.row_id)
+                .map(|row| CellEdit { row: *row, ..edit })
+        });
+    }
+
+    /// Display position of a row. It goes through all of the rows, so it is only used on input
+    /// and while a cell is edited.
+    fn position_of(&self, id: Option<TableRowId>) -> Option<usize> {
+        id.and_then(|id| self.row_ids.iter().position(|other| *other == id))
+    }
+
+    /// Ascending, descending and back to the order of the source
+    fn cycle_sort(&mut self, column: usize) -> Option<SortOrder> {
+        let order = match self.sort {
+            Some((sorted, SortOrder::Ascending)) if sorted == column => Some(SortOrder::Descending),
+            Some((sorted, SortOrder::Descending)) if sorted == column => None,
+            _ => Some(SortOrder::Ascending),
+        };
+        self.set_sort(order.map(|order| (column, order)));
+        order
+    }
+}
+
+/// Where a press on the header lands
+#[derive(Clone, Copy, PartialEq)]
+enum HeaderHit {
+    Column(usize),
+    /// Right edge of the column
+    Resize(usize),
+}
+
+/// Sizes of the table in physical pixels
+struct TableLayout {
+    header_rect: Rect,
+    body_rect: Rect,
+    row_height: f32,
+    widths: Vec<f32>,
+}
+
+impl TableLayout {
+    fn new(
+        rect: Rect,
+        columns: &[TableColumn],
+        control_state: &ControlState,
+        theme: &Theme,
+    ) -> Self {
+        let scale = control_state.scale;
+        let border = scale.border(theme.metrics.border_width);
+        let inner = Rect {
+            position: rect.position,
+            size: (rect.size - Vec2::splat(border * 2.0)).max(Vec2::ZERO),
+        };
+        let header_height = scale.px(HEADER_HEIGHT).min(inner.height());
+        let top = inner.top_left_position().y;
+        Self {
+            header_rect: Rect {
+                position: vec2(inner.position.x, top - header_height * 0.5),
+                size: vec2(inner.width(), header_height),
+            },
+            body_rect: Rect {
+                position: vec2(inner.position.x, inner.position.y - header_height * 0.5),
+                size: vec2(inner.width(), inner.height() - header_height),
+            },
+            row_height: scale.px(ROW_HEIGHT),
+            widths: columns
+                .iter()
+                .map(|column| scale.px(column.width))
+                .collect(),
+        }
+    }
+
+    fn content_size(&self, row_count: usize) -> Vec2 {
+        vec2(self.widths.iter().sum(), row_count as f32 * self.row_height)
+    }
+
+    /// Left edge of `column` from the left of the content
+    fn column_left(&self, column: usize) -> f32 {
+        self.widths[..column].iter().sum()
+    }
+
+    fn column_at(&self, x: f32) -> Option<usize> {
+        let mut left = 0.0;
+        for (column, width) in self.widths.iter().enumerate() {
+            if x >= left && x < left + width {
+                return Some(column);
+            }
+            left += width;
+        }
+        None
+    }
+
+    fn header_cell(&self, column: usize, offset_x: f32) -> Rect {
+        let left = self.header_rect.left_position().x + self.column_left(column) - offset_x;
+        Rect {
+            position: vec2(
+                left + self.widths[column] * 0.5,
+                self.header_rect.position.y,
+            ),
+            size: vec2(self.widths[column], self.header_rect.height()),
+        }
+    }
+
+    /// The edges win over the columns, so a column can be resized from either side of its edge
+    fn header_hit(&self, x: f32, offset_x: f32, margin: f32) -> Option<HeaderHit> {
+        let x = x - self.header_rect.left_position().x + offset_x;
+        let mut right = 0.0;
+        for (column, width) in self.widths.iter().enumerate() {
+            right += width;
+            if (x - right).abs() <= margin {
+                return Some(HeaderHit::Resize(column));
+            }
+        }
+        self.column_at(x).map(HeaderHit::Column)
+    }
+
+    /// Display rows that are at least partly visible
+    fn visible_rows(&self, view: &ScrollView, row_count: usize) -> Range<usize> {
+        let start = ((view.offset.y / self.row_height).floor() as usize).min(row_count);
+        let end = (((view.offset.y + view.viewport.height()) / self.row_height).ceil() as usize)
+            .min(row_count);
+        start..end.max(start)
+    }
+
+    /// Row across the whole viewport
+    fn row_rect(&self, view: &ScrollView, position: usize) -> Rect {
+        let y = view
+            .to_screen(vec2(0.0, (position as f32 + 0.5) * self.row_height))
+            .y;
+        Rect {
+            position: vec2(view.viewport.position.x, y),
+            size: vec2(view.viewport.width(), self.row_height),
+        }
+    }
+
+    fn cell_rect(&self, view: &ScrollView, position: usize, column: usize) -> Rect {
+        let center = view.to_screen(vec2(
+            self.column_left(column) + self.widths[column] * 0.5,
+            (position as f32 + 0.5) * self.row_height,
+        ));
+        Rect {
+            position: center,
+            size: vec2(self.widths[column], self.row_height),
+        }
+    }
+}
+
+/// Text vertically centered in `rect`, after the padding
+fn draw_cell_text(
+    layers: &mut LayerManager,
+    gui_rects: &mut GUIRects,
+    text: &str,
+    rect: Rect,
+    padding: f32,
+    font_size: f32,
+    font_collection: &FontCollection,
+    color: RGBA,
+    mask: Rect,
+) {
+    let mask = match rect.combine_rects(&mask) {
+        Some(mask) => mask,
+        None => return,
+    };
+    let (font_elements, text_rect) = create_single_line(text, font_size, font_collection, 0, 0.0);
+    let origin = vec2(
+        rect.left_position().x + padding,
+        rect.position.y - text_rect.height() * 0.5,
+    )
+    .round();
+    for element in font_elements {
+        let glyph = ElementBuilder::new_with_rect(element.rect.offset_position(origin))
+            .set_sdffont(element.tx_slice.into())
+            .set_color(color.into())
+            .set_rect_mask(mask.into());
+        layers.build(glyph, gui_rects);
+    }
+}
+
+/// Keyboard navigation of the focused table. Returns true if the selection changed.
+fn handle_key(
+    state: &mut Table,
+    key: VirtualKeyCode,
+    shift: bool,
+    ctrl: bool,
+    layout: &TableLayout,
+    view: &ScrollView,
+    changes: &mut Vec<TableChange>,
+) -> bool {
+    if state.order.is_empty() {
+        return false;
+    }
+    let cursor_position = state.position_of(state.selection.cursor);
+    let last = state.order.len() - 1;
+    let page = ((view.viewport.height() / layout.row_height).floor() as usize).max(1);
+    let target = match key {
+        VirtualKeyCode::Up => cursor_position.map_or(last, |position| position.saturating_sub(1)),
+        VirtualKeyCode::Down => cursor_position.map_or(0, |position| (position + 1).min(last)),
+        VirtualKeyCode::PageUp => {
+            cursor_position.map_or(0, |position| position.saturating_sub(page))
+        }
+        VirtualKeyCode::PageDown => {
+            cursor_position.map_or(last, |position| (position + page).min(last))
+        }
+        VirtualKeyCode::Home => 0,
+        VirtualKeyCode::End => last,
+        VirtualKeyCode::Space => {
+            let id = state.row_ids[cursor_position.unwrap_or(0)];
+            if ctrl {
+                state.selection.toggle(id);
+                return true;
+            }
+            return state.selection.select_only(id);
+        }
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+            if let Some(position) = cursor_position {
+                changes.push(TableChange::Activated(state.order[position]));
+            }
+            return false;
+        }
+        VirtualKeyCode::F2 => {
+            let column = state.columns.iter().position(|column| column.editable);
+            if let (Some(position), Some(column)) = (cursor_position, column) {
+                state.start_editing(position, column, changes);
+            }
+            return false;
+        }
+        VirtualKeyCode::A if ctrl => {
+            state.selection.ids.extend(state.row_ids.iter().copied());
+            return true;
+        }
+        _ => return false,
+    };
+
+    let top = target as f32 * layout.row_height;
+    state
+        .scroll
+        .scroll_to_y(top, top + layout.row_height, view.viewport.height());
+    if shift {
+        state
+            .selection
+            .select_range(state.row_ids.iter().copied(), target, ctrl);
+        true
+    } else if ctrl {
+        // Only moves the cursor, Space selects
+        state.selection.cursor = Some(state.row_ids[target]);
+        false
+    } else {
+        state.selection.select_only(state.row_ids[target])
+    }
+}
+
+/// Rows of `source` under a fixed header, inside of a scroll area. Only the visible rows are
+/// read and built, so the cost does not grow with the row count. The headers sort the rows when
+/// clicked and resize their column when their edge is dragged. Rows are selected like in a list,
+/// and double clicking an editable cell starts editing it with a widget drawn by the caller.
+/// Returns what changed in this event.
+pub fn table(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    source: &dyn TableSource,
+    state: &mut Table,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Vec<TableChange> {
+    let table_id = control_state.get_id();
+
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+    let font_size = scale.font(theme.fonts.size);
+    let resize_margin = scale.px(RESIZE_MARGIN);
+
+    let mut changes = Vec::new();
+    let mut selection_changed = false;
+    state.update_order(source);
+    let row_count = state.order.len();
+    let layout = TableLayout::new(rect, &state.columns, control_state, theme);
+
+    // The header and the border, the scroll area takes the body over
+    match event {
+        UIEvent::Update => {
+            if state.take_focus {
+                state.take_focus = false;
+                control_state.set_focus(table_id);
+            }
+            if let Some(resize) = &state.resize {
+                control_state.hold_active_state(resize.active_id);
+            } else if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(table_id, &interact_rect);
+            }
+
+            let table_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::Table, rect, label)
+                    .set_control(table_id)
+                    .set_focused(control_state.is_focused(table_id)),
+            );
+            control_state.access.push_parent(table_access_id);
+            let offset_x = state.scroll.offset().x;
+            for (index, column) in state.columns.iter().enumerate() {
+                let mut node = AccessNode::new(
+                    AccessRole::ColumnHeader,
+                    layout.header_cell(index, offset_x),
+                    &column.title,
+                );
+                match state.sort {
+                    Some((sorted, SortOrder::Ascending)) if sorted == index => {
+                        node = node.set_value("Ascending")
+                    }
+                    Some((sorted, SortOrder::Descending)) if sorted == index => {
+                        node = node.set_value("Descending")
+                    }
+                    _ => {}
+                }
+                control_state.push_access_node(node);
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let colors = theme.colors;
+            let (border_width, border_color) = if control_state.is_focused(table_id) {
+                (theme.metrics.focus_border_width, colors.focus)
+            } else {
+                (theme.metrics.border_width, colors.border)
+            };
+            let background = ElementBuilder::new_with_rect(rect)
+                .set_color(colors.control.into())
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into())
+                .set_border(Some(Border {
+                    size: scale.border(border_width),
+                    color: border_color.into(),
+                }))
+                .set_rect_mask(mask.into());
+            control_state.layers.build(background, gui_rects);
+        }
+        _ => {}
+    }
+
+    let body_mask = layout.body_rect.combine_rects(&mask).unwrap_or_default();
+    let view = scroll_area::scroll_area(
+        layout.body_rect,
+        body_mask,
+        label,
+        layout.content_size(row_count),
+        &mut state.scroll,
+        event,
+        control_state,
+        public_data,
+    );
+
+    match event {
+        UIEvent::Update => {
+            if control_state.is_focused(table_id) {
+                for input in control_state.text_input.take_events() {
+                    if let TextInputEvent::Key { key, shift, ctrl } = input {
+                        selection_changed |=
+                            handle_key(state, key, shift, ctrl, &layout, &view, &mut changes);
+                    }
+                }
+            }
+
+            if let Some(view_access_id) = view.access_id {
+                control_state.access.push_parent(view_access_id);
+                for position in layout.visible_rows(&view, row_count) {
+                    let row = state.order[position];
+                    let row_access_id = control_state.push_access_node(
+                        AccessNode::new(AccessRole::Row, layout.row_rect(&view, position), "")
+                            .set_selected(state.selection.contains(state.row_ids[position])),
+                    );
+                    control_state.access.push_parent(row_access_id);
+                    for column in 0..state.columns.len() {
+                        control_state.push_access_node(AccessNode::new(
+                            AccessRole::Cell,
+                            layout.cell_rect(&view, position, column),
+                            &source.cell_text(row, column),
+                        ));
+                    }
+                    control_state.access.pop_parent();
+                }
+                control_state.access.pop_parent();
+            }
+            control_state.access.pop_parent();
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+            if mouse_input.is_left_pressed() && control_state.is_hovered(table_id) {
+                control_state.set_focus(table_id);
+                let on_header = layout.header_rect.inside_rect(cursor_pos);
+                match layout.header_hit(cursor_pos.x, view.offset.x, resize_margin) {
+                    Some(HeaderHit::Resize(column)) if on_header => {
+                        if let Some(active_id) = control_state.set_active(table_id) {
+                            state.resize = Some(ColumnResize {
+                                active_id,
+                                column,
+                                start_x: cursor_pos.x,
+                                start_width: state.columns[column].width,
+                            });
+                        }
+                    }
+                    Some(HeaderHit::Column(column))
+                        if on_header && state.columns[column].sortable =>
+                    {
+                        let order = state.cycle_sort(column);
+                        changes.push(TableChange::Sorted { column, order });
+                    }
+                    _ => {}
+                }
+            }
+            if mouse_input.is_left_pressed() && control_state.is_hovered(view.id) {
+                control_state.set_focus(table_id);
+                let shift = control_state.text_input.shift();
+                let ctrl = control_state.text_input.ctrl();
+                let content_pos = view.to_content(cursor_pos);
+                let position = (content_pos.y / layout.row_height).floor() as usize;
+                if content_pos.y >= 0.0 && position < row_count {
+                    let id = state.row_ids[position];
+                    let double_click =
+                        control_state.register_click(view.id) && state.last_clicked == Some(id);
+                    state.last_clicked = Some(id);
+                    if shift {
+                        state
+                            .selection
+                            .select_range(state.row_ids.iter().copied(), position, ctrl);
+                        selection_changed = true;
+                    } else if ctrl {
+                        state.selection.toggle(id);
+                        selection_changed = true;
+                    } else {
+                        selection_changed |= state.selection.select_only(id);
+                    }
+                    if double_click {
+                        match layout.column_at(content_pos.x) {
+                            Some(column) if state.columns[column].editable => {
+                                state.start_editing(position, column, &mut changes)
+                            }
+                            _ => changes.push(TableChange::Activated(state.order[position])),
+                        }
+                    }
+                } else if !ctrl && !state.selection.ids.is_empty() {
+                    state.selection.ids.clear();
+                    selection_changed = true;
+                }
+            }
+            if mouse_input.is_left_released() {
+                state.resize = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if let Some(resize) = &state.resize {
+                if control_state.is_active(Some(resize.active_id)) {
+                    let column = &mut state.columns[resize.column];
+                    let width = (resize.start_width
+                        + (corrected.x - resize.start_x) / scale.factor())
+                    .max(column.min_width);
+                    if width != column.width {
+                        column.width = width;
+                        changes.push(TableChange::ColumnResized(resize.column));
+                    }
+                }
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            render_header(
+                gui_rects,
+                mask,
+                state,
+                &layout,
+                &view,
+                table_id,
+                control_state,
+                theme,
+                font_size,
+                font_collection,
+            );
+            render_rows(
+                gui_rects,
+                body_mask,
+                source,
+                state,
+                &layout,
+                &view,
+                table_id,
+                control_state,
+                theme,
+                font_size,
+                font_collection,
+            );
+        }
+        _ => {}
+    }
+
+    // The editor follows the cell while the table is scrolled
+    if let Some(edit) = state.editing {
+        let position = state.position_of(Some(edit.row_id)).unwrap_or(0);
+        state.editing = Some(CellEdit {
+            rect: layout.cell_rect(&view, position, edit.column),
+            mask: body_mask,
+            ..edit
+        });
+    }
+
+    if selection_changed {
+        changes.push(TableChange::SelectionChanged);
+    }
+    changes
+}
+
+fn render_header(
+    gui_rects: &mut GUIRects,
+    mask: Rect,
+    state: &Table,
+    layout: &TableLayout,
+    view: &ScrollView,
+    table_id: Uiid,
+    control_state: &mut ControlState,
+    theme: &Theme,
+    font_size: f32,
+    font_collection: &FontCollection,
+) {
+    let scale = control_state.scale;
+    let colors = theme.colors;
+    let header_mask = match layout.header_rect.combine_rects(&mask) {
+        Some(header_mask) => header_mask,
+        None => return,
+    };
+    let header = ElementBuilder::new_with_rect(layout.header_rect)
+        .set_color(colors.button.into())
+        .set_rect_mask(header_mask.into());
+    control_state.layers.build(header, gui_rects);
+
+    let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+    let hit = if state.resize.is_none()
+        && control_state.is_hovered(table_id)
+        && layout.header_rect.inside_rect(cursor_pos)
+    {
+        layout.header_hit(cursor_pos.x, view.offset.x, scale.px(RESIZE_MARGIN))
+    } else {
+        None
+    };
+    if state.resize.is_some() {
+        control_state.request_cursor(CursorIcon::ColResize, CursorPriority::Active);
+    } else if let Some(HeaderHit::Resize(_)) = hit {
+        control_state.request_cursor(CursorIcon::ColResize, CursorPriority::Hover);
+    }
+
+    let padding = scale.px(CELL_PADDING);
+    let divider_width = scale.px(DIVIDER_WIDTH).max(1.0);
+    for (index, column) in state.columns.iter().enumerate() {
+        let cell = layout.header_cell(index, view.offset.x);
+        if hit == Some(HeaderHit::Column(index)) && column.sortable {
+            let hovered_cell = ElementBuilder::new_with_rect(cell)
+                .set_color(colors.button_hover.into())
+                .set_rect_mask(header_mask.into());
+            control_state.layers.build(hovered_cell, gui_rects);
+        }
+        let arrow = match state.sort {
+            Some((sorted, SortOrder::Ascending)) if sorted == index => Some("^"),
+            Some((sorted, SortOrder::Descending)) if sorted == index => Some("v"),
+            _ => None,
+        };
+        // The arrow takes the right side of the header, the title is cut before it
+        let mut title_cell = cell;
+        if let Some(arrow) = arrow {
+            let (arrow_elements, arrow_rect) =
+                create_single_line(arrow, font_size, font_collection, 0, 0.0);
+            let arrow_origin = vec2(
+                cell.left_position().x + cell.width() - padding - arrow_rect.width(),
+                cell.position.y - arrow_rect.height() * 0.5,
+            )
+            .round();
+            if let Some(cell_mask) = cell.combine_rects(&header_mask) {
+                for element in arrow_elements {
+                    let glyph =
+                        ElementBuilder::new_with_rect(element.rect.offset_position(arrow_origin))
+                            .set_sdffont(element.tx_slice.into())
+                            .set_color(colors.text_muted.into())
+                            .set_rect_mask(cell_mask.into());
+                    control_state.layers.build(glyph, gui_rects);
+                }
+            }
+            let arrow_space = arrow_rect.width() + padding;
+            title_cell = title_cell
+                .offset_size(vec2(-arrow_space, 0.0))
+                .offset_position(vec2(-arrow_space * 0.5, 0.0));
+        }
+        draw_cell_text(
+            &mut control_state.layers,
+            gui_rects,
+            &column.title,
+            title_cell,
+            padding,
+            font_size,
+            font_collection,
+            colors.text,
+            header_mask,
+        );
+
+        let divider_color = match (&state.resize, hit) {
+            (Some(resize), _) if resize.column == index => colors.divider_active,
+            (None, Some(HeaderHit::Resize(column))) if column == index => colors.divider_hover,
+            _ => colors.border,
+        };
+        let divider = ElementBuilder::new(
+            vec2(cell.left_position().x + cell.width(), cell.position.y).round(),
+            vec2(divider_width, cell.height()),
+        )
+        .set_color(divider_color.into())
+        .set_rect_mask(header_mask.into());
+        control_state.layers.build(divider, gui_rects);
+    }
+
+    let header_line = ElementBuilder::new(
+        vec2(
+            layout.header_rect.position.x,
+            layout.header_rect.position.y - layout.header_rect.height() * 0.5,
+        ),
+        vec2(layout.header_rect.width(), divider_width),
+    )
+    .set_color(colors.border.into())
+    .set_rect_mask(mask.into());
+    control_state.layers.build(header_line, gui_rects);
+}
+
+fn render_rows(
+    gui_rects: &mut GUIRects,
+    body_mask: Rect,
+    source: &dyn TableSource,
+    state: &Table,
+    layout: &TableLayout,
+    view: &ScrollView,
+    table_id: Uiid,
+    control_state: &mut ControlState,
+    theme: &Theme,
+    font_size: f32,
+    font_collection: &FontCollection,
+) {
+    let scale = control_state.scale;
+    let colors = theme.colors;
+    let content_mask = match view.viewport.combine_rects(&body_mask) {
+        Some(content_mask) => content_mask,
+        None => return,
+    };
+    let focused = control_state.is_focused(table_id);
+    let hovered_position = if control_state.is_hovered(view.id) {
+        control_state.last_cursor_position.and_then(|cursor_pos| {
+            let y = view.to_content(cursor_pos).y;
+            (y >= 0.0).then(|| (y / layout.row_height) as usize)
+        })
+    } else {
+        None
+    };
+    let padding = scale.px(CELL_PADDING);
+    let divider_width = scale.px(DIVIDER_WIDTH).max(1.0);
+    let visible_rows = layout.visible_rows(view, state.order.len());
+    let left = view.viewport.left_position().x;
+    let right = left + view.viewport.width();
+
+    for position in visible_rows.clone() {
+        let row = state.order[position];
+        let id = state.row_ids[position];
+        let row_rect = layout.row_rect(view, position);
+        let fill = if state.selection.contains(id) {
+            Some(colors.accent.set_alpha(0.35))
+        } else if hovered_position == Some(position) {
+            Some(colors.control_hover)
+        } else if position % 2 == 1 {
+            Some(colors.panel)
+        } else {
+            None
+        };
+        if let Some(fill) = fill {
+            let row_fill = ElementBuilder::new_with_rect(row_rect)
+                .set_color(fill.into())
+                .set_rect_mask(content_mask.into());
+            control_state.layers.build(row_fill, gui_rects);
+        }
+        if focused && state.selection.cursor == Some(id) {
+            let cursor_outline = ElementBuilder::new_with_rect(row_rect)
+                .set_color(RGBA::TRANSPARENT.into())
+                .set_border(Some(Border {
+                    size: scale.border(theme.metrics.border_width),
+                    color: colors.focus.into(),
+                }))
+                .set_rect_mask(content_mask.into());
+            control_state.layers.build(cursor_outline, gui_rects);
+        }
+
+        for column in 0..state.columns.len() {
+            let cell = layout.cell_rect(view, position, column);
+            let cell_left = cell.left_position().x;
+            if cell_left > right || cell_left + cell.width() < left {
+                continue;
+            }
+            let edited = state
+                .editing
+                .map_or(false, |edit| edit.row_id == id && edit.column == column);
+            if edited {
+                continue;
+            }
+            draw_cell_text(
+                &mut control_state.layers,
+                gui_rects,
+                &source.cell_text(row, column),
+                cell,
+                padding,
+                font_size,
+                font_collection,
+                colors.text,
+                content_mask,
+            );
+        }
+    }
+
+    // Column lines down to the last row
+    if let (Some(first), Some(last)) = (visible_rows.clone().next(), visible_rows.last()) {
+        let top = layout.row_rect(view, first).top_left_position().y;
+        let bottom = layout.row_rect(view, last).top_left_position().y - layout.row_height;
+        let mut x = view.to_screen(Vec2::ZERO).x;
+        for width in &layout.widths {
+            x += width;
+            if x < left || x > right {
+                continue;
+            }
+            let column_line = ElementBuilder::new(
+                vec2(x, (top + bottom) * 0.5).round(),
+                vec2(divider_width, top - bottom),
+            )
+            .set_color(colors.border.into())
+            .set_rect_mask(content_mask.into());
+            control_state.layers.build(column_line, gui_rects);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use super::{SortOrder, Table, TableColumn, TableRowId, TableSource};
+
+/// One column with the name of each row, the id is the row's number
+struct Names {
+    rows: Vec<(TableRowId, &'static str)>,
+}
+
+impl TableSource for Names {
+    fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn row_id(&self, row: usize) -> TableRowId {
+        self.rows[row].0
+    }
+
+    fn cell_text(&self, row: usize, _column: usize) -> String {
+        String::from(self.rows[row].1)
+    }
+}
+
+fn names() -> Names {
+    Names {
+        rows: vec![(1, "carrot"), (2, "apple"), (3, "daikon"), (4, "banana")],
+    }
+}
+
+fn table(source: &Names) -> Table {
+    let mut table = Table::new(vec![TableColumn::new("Name", 100.0)]);
+    table.update_order(source);
+    table
+}
+
+fn ids(ids: &[TableRowId]) -> HashSet<TableRowId> {
+    ids.iter().copied().collect()
+}
+
+#[test]
+fn sort_cycles_through_ascending_descending_and_source_order() {
+    let source = names();
+    let mut table = table(&source);
+    assert_eq!(table.order, vec![0, 1, 2, 3]);
+
+    assert_eq!(table.cycle_sort(0), Some(SortOrder::Ascending));
+    table.update_order(&source);
+    assert_eq!(table.order, vec![1, 3, 0, 2]);
+    assert_eq!(table.row_ids, vec![2, 4, 1, 3]);
+
+    assert_eq!(table.cycle_sort(0), Some(SortOrder::Descending));
+    table.update_order(&source);
+    assert_eq!(table.order, vec![2, 0, 3, 1]);
+
+    assert_eq!(table.cycle_sort(0), None);
+    table.update_order(&source);
+    assert_eq!(table.order, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn select_range_follows_the_display_order() {
+    let source = names();
+    let mut table = table(&source);
+    table.set_sort(Some((0, SortOrder::Ascending)));
+    table.update_order(&source);
+
+    // Shown as apple, banana, carrot, daikon
+    table.selection.select_only(4);
+    table
+        .selection
+        .select_range(table.row_ids.iter().copied(), 3, false);
+    assert_eq!(table.selection.ids, ids(&[4, 1, 3]));
+    assert_eq!(table.selection.cursor, Some(3));
+    assert_eq!(table.selection.anchor, Some(4));
+
+    table
+        .selection
+        .select_range(table.row_ids.iter().copied(), 0, false);
+    assert_eq!(table.selection.ids, ids(&[2, 4]));
+}
+
+#[test]
+fn toggle_and_select_only() {
+    let source = names();
+    let mut table = table(&source);
+
+    assert!(table.selection.select_only(1));
+    assert!(!table.selection.select_only(1));
+    table.selection.toggle(3);
+    assert_eq!(table.selection.ids, ids(&[1, 3]));
+    table.selection.toggle(1);
+    assert_eq!(table.selection.ids, ids(&[3]));
+    assert!(table.selection.select_only(2));
+    assert_eq!(table.selection.ids, ids(&[2]));
+}
+
+#[test]
+fn selection_stays_on_its_rows_when_rows_are_inserted() {
+    let mut source = names();
+    let mut table = table(&source);
+    table.selection.select_only(2);
+    table.selection.toggle(3);
+
+    source.rows.insert(0, (5, "eggplant"));
+    table.update_order(&source);
+    assert_eq!(table.selection.ids, ids(&[2, 3]));
+    assert_eq!(table.position_of(table.selection.cursor), Some(3));
+}
+
+#[test]
+fn removed_rows_leave_the_selection() {
+    let mut source = names();
+    let mut table = table(&source);
+    table.selection.select_only(1);
+    table.selection.toggle(3);
+
+    source.rows.remove(0);
+    table.update_order(&source);
+    assert_eq!(table.selection.ids, ids(&[3]));
+    assert_eq!(table.selection.cursor, Some(3));
+    assert_eq!(table.selection.anchor, Some(3));
+
+    table.selection.toggle(2);
+    source.rows.remove(0);
+    table.update_order(&source);
+    assert_eq!(table.selection.ids, ids(&[3]));
+    assert_eq!(table.selection.cursor, None);
+    assert_eq!(table.selection.anchor, None);
+}
+
+#[test]
+fn editing_follows_its_row() {
+    let mut source = names();
+    let mut table = table(&source);
+    let mut changes = Vec::new();
+    table.start_editing(2, 0, &mut changes);
+
+    source.rows.remove(0);
+    table.update_order(&source);
+    let edit = table.editing().unwrap();
+    assert_eq!((edit.row_id, edit.row), (3, 1));
+
+    source.rows.remove(1);
+    table.update_order(&source);
+    assert!(table.editing().is_none());
+}
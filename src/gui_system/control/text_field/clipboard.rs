@@ -0,0 +1,53 @@
+/// Where cut and copied text goes. The text fields only talk to this trait, so the OS clipboard
+/// can be plugged in by the platform layer.
+pub trait Clipboard {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: &str);
+}
+
+/// Keeps the text inside of the app, used until an OS clipboard is set and by headless tests
+#[derive(Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self { text: None }
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = Some(String::from(text));
+    }
+}
+
+/// Clipboard of the system, shared with the other apps
+pub struct OsClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+impl OsClipboard {
+    /// None when the platform has no clipboard, like a session without a display server
+    pub fn new() -> Option<Self> {
+        arboard::Clipboard::new()
+            .ok()
+            .map(|clipboard| Self { clipboard })
+    }
+}
+
+impl Clipboard for OsClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+
+    /// A failed copy keeps whatever the clipboard held before
+    fn set_text(&mut self, text: &str) {
+        let _ = self.clipboard.set_text(text);
+    }
+}
@@ -0,0 +1,396 @@
+use std::ops::Range;
+
+use super::clipboard::Clipboard;
+
+#[cfg(test)]
+mod test;
+
+/// Undo steps kept for each text field
+const MAX_UNDO_STEPS: usize = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Whitespace
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// Kind of the last edit, consecutive edits of the same kind are undone together
+#[derive(Clone, Copy, PartialEq)]
+enum EditKind {
+    None,
+    Insert,
+    Delete,
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    text: String,
+    caret: usize,
+    anchor: usize,
+}
+
+/// Text of a single line field with its caret, selection and undo history. Every position is a
+/// byte offset that is always on a char boundary.
+pub struct TextEditor {
+    text: String,
+    caret: usize,
+    /// The selection goes from the anchor to the caret
+    anchor: usize,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    last_edit: EditKind,
+}
+
+impl TextEditor {
+    pub fn new(text: &str) -> Self {
+        let text = sanitize(text);
+        let end = text.len();
+        Self {
+            text,
+            caret: end,
+            anchor: end,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_edit: EditKind::None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text from code, the undo history is cleared
+    pub fn set_text(&mut self, text: &str) {
+        *self = Self::new(text);
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    /// Moves the caret to `position`, with `select` the selection is extended up to it
+    pub fn set_caret(&mut self, position: usize, select: bool) {
+        self.caret = self.clamp_to_boundary(position);
+        if !select {
+            self.anchor = self.caret;
+        }
+        self.last_edit = EditKind::None;
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.caret = self.text.len();
+        self.last_edit = EditKind::None;
+    }
+
+    /// Selects the word, or run of spaces or symbols, around `position`
+    pub fn select_word_at(&mut self, position: usize) {
+        let word = word_range_at(&self.text, self.clamp_to_boundary(position));
+        self.anchor = word.start;
+        self.caret = word.end;
+        self.last_edit = EditKind::None;
+    }
+
+    /// Without `select` an existing selection collapses to its left side
+    pub fn move_left(&mut self, word: bool, select: bool) {
+        let target = if self.has_selection() && !select && !word {
+            self.selection().start
+        } else if word {
+            previous_word_boundary(&self.text, self.caret)
+        } else {
+            self.previous_char_boundary(self.caret)
+        };
+        self.set_caret(target, select);
+    }
+
+    /// Without `select` an existing selection collapses to its right side
+    pub fn move_right(&mut self, word: bool, select: bool) {
+        let target = if self.has_selection() && !select && !word {
+            self.selection().end
+        } else if word {
+            next_word_boundary(&self.text, self.caret)
+        } else {
+            self.next_char_boundary(self.caret)
+        };
+        self.set_caret(target, select);
+    }
+
+    pub fn move_home(&mut self, select: bool) {
+        self.set_caret(0, select);
+    }
+
+    pub fn move_end(&mut self, select: bool) {
+        self.set_caret(self.text.len(), select);
+    }
+
+    /// Replaces the selection with `text`, line breaks become spaces and other control characters
+    /// are dropped. Returns true if the text changed.
+    pub fn insert(&mut self, text: &str) -> bool {
+        let text = sanitize(text);
+        if text.is_empty() && !self.has_selection() {
+            return false;
+        }
+        self.begin_edit(EditKind::Insert);
+        let selection = self.selection();
+        self.text.replace_range(selection.clone(), &text);
+        self.caret = selection.start + text.len();
+        self.anchor = self.caret;
+        true
+    }
+
+    /// Removes the selection, or the char or word before the caret
+    pub fn backspace(&mut self, word: bool) -> bool {
+        if !self.has_selection() {
+            if self.caret == 0 {
+                return false;
+            }
+            self.anchor = if word {
+                previous_word_boundary(&self.text, self.caret)
+            } else {
+                self.previous_char_boundary(self.caret)
+            };
+        }
+        self.delete_selection()
+    }
+
+    /// Removes the selection, or the char or word after the caret
+    pub fn delete(&mut self, word: bool) -> bool {
+        if !self.has_selection() {
+            if self.caret == self.text.len() {
+                return false;
+            }
+            self.anchor = if word {
+                next_word_boundary(&self.text, self.caret)
+            } else {
+                self.next_char_boundary(self.caret)
+            };
+        }
+        self.delete_selection()
+    }
+
+    pub fn copy(&self, clipboard: &mut dyn Clipboard) {
+        if self.has_selection() {
+            clipboard.set_text(self.selected_text());
+        }
+    }
+
+    pub fn cut(&mut self, clipboard: &mut dyn Clipboard) -> bool {
+        if !self.has_selection() {
+            return false;
+        }
+        clipboard.set_text(self.selected_text());
+        self.last_edit = EditKind::None;
+        self.delete_selection()
+    }
+
+    pub fn paste(&mut self, clipboard: &mut dyn Clipboard) -> bool {
+        match clipboard.get_text() {
+            Some(text) => {
+                // A paste is always its own undo step
+                self.last_edit = EditKind::None;
+                let changed = self.insert(&text);
+                self.last_edit = EditKind::None;
+                changed
+            }
+            None => false,
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(snapshot) => {
+                let current = self.snapshot();
+                self.redo_stack.push(current);
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(snapshot) => {
+                let current = self.snapshot();
+                self.undo_stack.push(current);
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_stack.len() > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.redo_stack.len() > 0
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        if !self.has_selection() {
+            return false;
+        }
+        self.begin_edit(EditKind::Delete);
+        let selection = self.selection();
+        self.text.replace_range(selection.clone(), "");
+        self.caret = selection.start;
+        self.anchor = self.caret;
+        true
+    }
+
+    /// Saves the state before an edit, unless it continues the last one
+    fn begin_edit(&mut self, kind: EditKind) {
+        if kind != self.last_edit || self.has_selection() {
+            let snapshot = self.snapshot();
+            self.undo_stack.push(snapshot);
+            if self.undo_stack.len() > MAX_UNDO_STEPS {
+                self.undo_stack.remove(0);
+            }
+        }
+        self.redo_stack.clear();
+        self.last_edit = kind;
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            caret: self.caret,
+            anchor: self.anchor,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.caret = snapshot.caret;
+        self.anchor = snapshot.anchor;
+        self.last_edit = EditKind::None;
+    }
+
+    fn clamp_to_boundary(&self, position: usize) -> usize {
+        let mut position = position.min(self.text.len());
+        while !self.text.is_char_boundary(position) {
+            position -= 1;
+        }
+        position
+    }
+
+    fn previous_char_boundary(&self, position: usize) -> usize {
+        self.text[..position]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn next_char_boundary(&self, position: usize) -> usize {
+        self.text[position..]
+            .chars()
+            .next()
+            .map_or(position, |c| position + c.len_utf8())
+    }
+}
+
+/// Skips the spaces before `position` and then the word, or run of symbols, before them
+pub fn previous_word_boundary(text: &str, position: usize) -> usize {
+    let mut chars = text[..position].char_indices().rev().peekable();
+    while let Some((_, c)) = chars.peek() {
+        if char_class(*c) != CharClass::Whitespace {
+            break;
+        }
+        chars.next();
+    }
+    let class = match chars.peek() {
+        Some((_, c)) => char_class(*c),
+        None => return 0,
+    };
+    let mut boundary = position;
+    for (index, c) in chars {
+        if char_class(c) != class {
+            break;
+        }
+        boundary = index;
+    }
+    boundary
+}
+
+/// Skips the word, or run of symbols, after `position` and then the spaces after it
+pub fn next_word_boundary(text: &str, position: usize) -> usize {
+    let mut chars = text[position..].char_indices().peekable();
+    if let Some((_, first)) = chars.peek() {
+        let class = char_class(*first);
+        if class != CharClass::Whitespace {
+            while let Some((_, c)) = chars.peek() {
+                if char_class(*c) != class {
+                    break;
+                }
+                chars.next();
+            }
+        }
+    }
+    while let Some((_, c)) = chars.peek() {
+        if char_class(*c) != CharClass::Whitespace {
+            break;
+        }
+        chars.next();
+    }
+    chars
+        .peek()
+        .map_or(text.len(), |(index, _)| position + index)
+}
+
+/// Word, or run of spaces or symbols, around `position`
+pub fn word_range_at(text: &str, position: usize) -> Range<usize> {
+    let class = match text[position..].chars().next() {
+        Some(c) => char_class(c),
+        None => match text[..position].chars().next_back() {
+            Some(c) => char_class(c),
+            None => return position..position,
+        },
+    };
+    let start = text[..position]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| char_class(*c) == class)
+        .last()
+        .map_or(position, |(index, _)| index);
+    let end = text[position..]
+        .char_indices()
+        .find(|(_, c)| char_class(*c) != class)
+        .map_or(text.len(), |(index, _)| position + index);
+    start..end
+}
+
+/// The field only holds one line, line breaks become spaces and other control chars are dropped
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
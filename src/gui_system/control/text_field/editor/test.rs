@@ -0,0 +1,115 @@
+use crate::gui_system::control::text_field::MemoryClipboard;
+
+use super::TextEditor;
+
+#[test]
+fn selection_follows_the_anchor() {
+    let mut editor = TextEditor::new("hello world");
+    editor.move_home(false);
+    editor.move_right(true, true);
+    assert_eq!(editor.selected_text(), "hello ");
+    editor.move_right(false, false);
+    assert_eq!(editor.caret(), 6);
+    assert!(!editor.has_selection());
+
+    editor.move_end(true);
+    editor.move_left(false, true);
+    assert_eq!(editor.selection(), 6..10);
+    editor.move_left(false, false);
+    assert_eq!(editor.caret(), 6);
+}
+
+#[test]
+fn selection_is_replaced_by_insert() {
+    let mut editor = TextEditor::new("hello world");
+    editor.select_word_at(8);
+    assert_eq!(editor.selected_text(), "world");
+    assert!(editor.insert("there"));
+    assert_eq!(editor.text(), "hello there");
+    editor.select_all();
+    assert!(editor.backspace(false));
+    assert_eq!(editor.text(), "");
+    assert!(!editor.backspace(false));
+}
+
+#[test]
+fn positions_stay_on_char_boundaries() {
+    let mut editor = TextEditor::new("aé€");
+    editor.set_caret(2, false);
+    assert_eq!(editor.caret(), 1);
+    editor.move_right(false, false);
+    assert_eq!(editor.caret(), 3);
+    assert!(editor.backspace(false));
+    assert_eq!(editor.text(), "a€");
+}
+
+#[test]
+fn typing_is_undone_in_one_step() {
+    let mut editor = TextEditor::new("");
+    for c in ["a", "b", "c"] {
+        editor.insert(c);
+    }
+    editor.backspace(false);
+    assert_eq!(editor.text(), "ab");
+
+    assert!(editor.undo());
+    assert_eq!(editor.text(), "abc");
+    assert!(editor.undo());
+    assert_eq!(editor.text(), "");
+    assert!(!editor.undo());
+
+    assert!(editor.redo());
+    assert!(editor.redo());
+    assert_eq!(editor.text(), "ab");
+    assert!(!editor.can_redo());
+}
+
+#[test]
+fn moving_the_caret_starts_a_new_undo_step() {
+    let mut editor = TextEditor::new("");
+    editor.insert("a");
+    editor.move_home(false);
+    editor.insert("b");
+    assert_eq!(editor.text(), "ba");
+    editor.undo();
+    assert_eq!(editor.text(), "a");
+    assert_eq!(editor.caret(), 0);
+}
+
+#[test]
+fn edit_after_undo_clears_redo() {
+    let mut editor = TextEditor::new("a");
+    editor.insert("b");
+    editor.undo();
+    assert!(editor.can_redo());
+    editor.insert("c");
+    assert!(!editor.can_redo());
+    assert_eq!(editor.text(), "ac");
+}
+
+#[test]
+fn cut_and_paste_are_single_steps() {
+    let mut clipboard = MemoryClipboard::new();
+    let mut editor = TextEditor::new("one two");
+    editor.select_word_at(0);
+    assert!(editor.cut(&mut clipboard));
+    assert_eq!(editor.text(), " two");
+    editor.move_end(false);
+    assert!(editor.paste(&mut clipboard));
+    assert_eq!(editor.text(), " twoone");
+
+    editor.undo();
+    assert_eq!(editor.text(), " two");
+    editor.undo();
+    assert_eq!(editor.text(), "one two");
+    assert_eq!(editor.selected_text(), "one");
+}
+
+#[test]
+fn set_text_clears_the_history() {
+    let mut editor = TextEditor::new("a");
+    editor.insert("b");
+    editor.set_text("line\nbreak");
+    assert_eq!(editor.text(), "line break");
+    assert!(!editor.can_undo());
+}
@@ -0,0 +1,591 @@
+use std::{collections::HashMap, ops::Range};
+
+use rwge::{
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    uuid::Uuid,
+    winit::{dpi::PhysicalPosition, event::VirtualKeyCode},
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        accessibility::get_accessibility,
+        theme::get_theme,
+    },
+    runtime_data::{
+        utils::{get_engine_data, get_font_collections, get_time, get_window},
+        PublicData,
+    },
+};
+
+use super::{
+    cursor::{CursorIcon, CursorPriority},
+    ControlState,
+};
+
+pub mod clipboard;
+pub mod editor;
+
+pub use clipboard::{Clipboard, MemoryClipboard, OsClipboard};
+pub use editor::TextEditor;
+
+/// Drawn instead of every char in password mode
+pub const PASSWORD_CHAR: char = '*';
+
+const TEXT_FIELD_PADDING: f32 = 6.0;
+const CARET_WIDTH: f32 = 1.0;
+/// Seconds the caret stays on, and then off
+const CARET_BLINK_TIME: f32 = 0.5;
+
+pub enum TextInputEvent {
+    Char(char),
+    Key {
+        key: VirtualKeyCode,
+        shift: bool,
+        ctrl: bool,
+    },
+    /// Text the IME is composing, it is drawn at the caret but it is not part of the text yet.
+    /// `cursor` is a byte offset inside of it, an empty text ends the composition.
+    Preedit {
+        text: String,
+        cursor: Option<usize>,
+    },
+    /// The IME finished the composition, the text is inserted at the caret
+    Commit(String),
+}
+
+/// Keyboard input collected from the window events. The focused text field reads it on the next
+/// update, whatever is left after it is dropped.
+pub struct TextInputState {
+    events: Vec<TextInputEvent>,
+    shift: bool,
+    ctrl: bool,
+    clipboard: Box<dyn Clipboard>,
+    /// Caret of the focused field, the IME shows its candidates next to it
+    ime_position: Option<Vec2>,
+    applied_ime_position: Option<Vec2>,
+    /// Advance of every char measured so far, by font collection and font size
+    advances: HashMap<(char, usize, u32), f32>,
+}
+
+impl TextInputState {
+    pub fn new() -> Self {
+        Self {
+            events: Vec::with_capacity(10),
+            shift: false,
+            ctrl: false,
+            clipboard: Box::new(MemoryClipboard::new()),
+            ime_position: None,
+            applied_ime_position: None,
+            advances: HashMap::new(),
+        }
+    }
+
+    pub fn set_clipboard(&mut self, clipboard: Box<dyn Clipboard>) {
+        self.clipboard = clipboard;
+    }
+
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        self.clipboard.as_mut()
+    }
+
+    pub fn set_modifiers(&mut self, shift: bool, ctrl: bool) {
+        self.shift = shift;
+        self.ctrl = ctrl;
+    }
+
+    pub fn shift(&self) -> bool {
+        self.shift
+    }
+
+    pub fn ctrl(&self) -> bool {
+        self.ctrl
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.events.push(TextInputEvent::Char(c));
+    }
+
+    /// The modifiers are the ones set when the key was pressed
+    pub fn push_key(&mut self, key: VirtualKeyCode) {
+        self.events.push(TextInputEvent::Key {
+            key,
+            shift: self.shift,
+            ctrl: self.ctrl,
+        });
+    }
+
+    pub fn push_event(&mut self, event: TextInputEvent) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<TextInputEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn on_after_update(&mut self) {
+        self.events.clear();
+    }
+
+    /// Distance from the left of the text to every char boundary, the first one is always 0
+    pub fn char_offsets(
+        &mut self,
+        text: &str,
+        font_size: f32,
+        collection: usize,
+        font_collection: &FontCollection,
+    ) -> Vec<f32> {
+        let mut offsets = Vec::with_capacity(text.len() + 1);
+        let mut offset = 0.0;
+        offsets.push(offset);
+        for c in text.chars() {
+            offset += *self
+                .advances
+                .entry((c, collection, font_size.to_bits()))
+                .or_insert_with(|| char_advance(c, font_size, font_collection));
+            offsets.push(offset);
+        }
+        offsets
+    }
+
+    /// Called once per frame after the GUI was rendered, the window is only touched when the
+    /// caret moves
+    pub fn apply_ime_position(&mut self, public_data: &PublicData) {
+        let position = match self.ime_position.take() {
+            Some(position) => position,
+            None => return,
+        };
+        if self.applied_ime_position == Some(position) {
+            return;
+        }
+        self.applied_ime_position = Some(position);
+        let screen_height = get_engine_data(public_data).screen_size.as_vec2().y;
+        get_window(public_data).set_ime_position(PhysicalPosition::new(
+            position.x as f64,
+            (screen_height - position.y) as f64,
+        ));
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct TextFieldResponse {
+    /// The text was edited during the event
+    pub changed: bool,
+    /// Enter was pressed while the field had the focus
+    pub submitted: bool,
+    /// Escape was pressed while the field had the focus
+    pub cancelled: bool,
+}
+
+struct Composition {
+    text: String,
+    cursor: Option<usize>,
+}
+
+/// What is drawn inside of the field, the ranges and the caret are char indices
+struct DisplayText {
+    text: String,
+    caret: usize,
+    selection: Range<usize>,
+    composition: Option<Range<usize>>,
+}
+
+/// State of a single line text field, kept by the container between frames
+pub struct TextField {
+    pub editor: TextEditor,
+    /// Shown in place of the text while it is empty
+    pub placeholder: String,
+    /// The text is drawn with `PASSWORD_CHAR` and it cannot be copied
+    pub password: bool,
+    composition: Option<Composition>,
+    /// Horizontal scroll of the text, so the caret is always visible
+    scroll: f32,
+    drag_active_id: Option<Uuid>,
+    /// Time of the last caret move, the blink starts over from it
+    caret_time: f32,
+}
+
+impl TextField {
+    pub fn new(text: &str) -> Self {
+        Self {
+            editor: TextEditor::new(text),
+            placeholder: String::new(),
+            password: false,
+            composition: None,
+            scroll: 0.0,
+            drag_active_id: None,
+            caret_time: 0.0,
+        }
+    }
+
+    pub fn set_placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = String::from(placeholder);
+        self
+    }
+
+    pub fn set_password(mut self, password: bool) -> Self {
+        self.password = password;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        self.editor.text()
+    }
+
+    fn display_text(&self) -> DisplayText {
+        let mask = |text: &str| -> String {
+            if self.password {
+                text.chars().map(|_| PASSWORD_CHAR).collect()
+            } else {
+                String::from(text)
+            }
+        };
+        let text = self.editor.text();
+        let char_index = |position: usize| text[..position].chars().count();
+        let caret = char_index(self.editor.caret());
+        let selection = self.editor.selection();
+        let mut display = DisplayText {
+            text: mask(text),
+            caret,
+            selection: char_index(selection.start)..char_index(selection.end),
+            composition: None,
+        };
+
+        if let Some(composition) = &self.composition {
+            let start = display
+                .text
+                .char_indices()
+                .nth(caret)
+                .map_or(display.text.len(), |(index, _)| index);
+            display.text.insert_str(start, &mask(&composition.text));
+            let length = composition.text.chars().count();
+            display.caret = caret
+                + composition.cursor.map_or(length, |cursor| {
+                    composition.text[..cursor.min(composition.text.len())]
+                        .chars()
+                        .count()
+                });
+            display.selection = display.caret..display.caret;
+            display.composition = Some(caret..caret + length);
+        }
+        display
+    }
+
+    /// Byte offset of the text closest to `x`, measured from the left of the text. `offsets`
+    /// are the ones of the displayed text.
+    fn position_at(&self, x: f32, offsets: &[f32]) -> usize {
+        let text = self.editor.text();
+        let closest = offsets
+            .iter()
+            .enumerate()
+            .fold((0, f32::INFINITY), |closest, (index, offset)| {
+                let distance = (offset - x).abs();
+                if distance < closest.1 {
+                    (index, distance)
+                } else {
+                    closest
+                }
+            })
+            .0;
+        text.char_indices()
+            .nth(closest)
+            .map_or(text.len(), |(index, _)| index)
+    }
+
+    /// Applies the input queued since the last update
+    fn handle_input(
+        &mut self,
+        events: Vec<TextInputEvent>,
+        control_state: &mut ControlState,
+        response: &mut TextFieldResponse,
+    ) {
+        let editor = &mut self.editor;
+        for input in events {
+            match input {
+                TextInputEvent::Char(c) => {
+                    if !c.is_control() {
+                        response.changed |= editor.insert(c.encode_utf8(&mut [0; 4]));
+                    }
+                }
+                TextInputEvent::Key { key, shift, ctrl } => match key {
+                    VirtualKeyCode::Left => editor.move_left(ctrl, shift),
+                    VirtualKeyCode::Right => editor.move_right(ctrl, shift),
+                    VirtualKeyCode::Home => editor.move_home(shift),
+                    VirtualKeyCode::End => editor.move_end(shift),
+                    VirtualKeyCode::Back => response.changed |= editor.backspace(ctrl),
+                    VirtualKeyCode::Delete => response.changed |= editor.delete(ctrl),
+                    VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                        response.submitted = true
+                    }
+                    VirtualKeyCode::Escape => {
+                        response.cancelled = true;
+                        control_state.clear_focus();
+                    }
+                    VirtualKeyCode::A if ctrl => editor.select_all(),
+                    VirtualKeyCode::C if ctrl && !self.password => {
+                        editor.copy(control_state.text_input.clipboard())
+                    }
+                    VirtualKeyCode::X if ctrl && !self.password => {
+                        response.changed |= editor.cut(control_state.text_input.clipboard())
+                    }
+                    VirtualKeyCode::V if ctrl => {
+                        response.changed |= editor.paste(control_state.text_input.clipboard())
+                    }
+                    VirtualKeyCode::Z if ctrl && shift => response.changed |= editor.redo(),
+                    VirtualKeyCode::Z if ctrl => response.changed |= editor.undo(),
+                    VirtualKeyCode::Y if ctrl => response.changed |= editor.redo(),
+                    _ => {}
+                },
+                TextInputEvent::Preedit { text, cursor } => {
+                    self.composition = if text.is_empty() {
+                        None
+                    } else {
+                        Some(Composition { text, cursor })
+                    };
+                }
+                TextInputEvent::Commit(text) => {
+                    self.composition = None;
+                    response.changed |= editor.insert(&text);
+                }
+            }
+        }
+    }
+}
+
+/// Measured between two visible glyphs, so spaces get their width too and the bounds of the
+/// glyph do not count
+fn char_advance(c: char, font_size: f32, font_collection: &FontCollection) -> f32 {
+    let width = |text: &str| {
+        create_single_line(text, font_size, font_collection, 0, 0.0)
+            .1
+            .width()
+    };
+    width(&format!("|{c}|")) - width("||")
+}
+
+/// Single line text field. It takes the focus when clicked and then reads the queued keyboard
+/// input on every update.
+pub fn text_field(
+    rect: Rect,
+    mask: Rect,
+    field: &mut TextField,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> TextFieldResponse {
+    let mut response = TextFieldResponse::default();
+
+    let control_id = control_state.get_id();
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let colors = theme.colors;
+    let collection = theme.fonts.collection;
+    let font_collection = &get_font_collections(public_data)[collection];
+    let font_size = scale.font(theme.fonts.size);
+    let padding = scale.px(TEXT_FIELD_PADDING);
+    let inner_width = (rect.width() - padding * 2.0).max(0.0);
+    let text_left = rect.left_position().x + padding;
+    let time = get_time(public_data).time.0;
+
+    match event {
+        UIEvent::Update => {
+            if let Some(active_id) = field.drag_active_id {
+                control_state.hold_active_state(active_id);
+            } else if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(control_id, &interact_rect);
+            }
+
+            if control_state.is_focused(control_id) {
+                let events = control_state.text_input.take_events();
+                if events.len() > 0 {
+                    field.caret_time = time;
+                }
+                field.handle_input(events, control_state, &mut response);
+
+                // Keeps the caret inside of the field
+                let display = field.display_text();
+                let offsets = control_state.text_input.char_offsets(
+                    &display.text,
+                    font_size,
+                    collection,
+                    font_collection,
+                );
+                let caret_x = offsets[display.caret];
+                let text_width = offsets[offsets.len() - 1];
+                if caret_x - field.scroll > inner_width {
+                    field.scroll = caret_x - inner_width;
+                } else if caret_x < field.scroll {
+                    field.scroll = caret_x;
+                }
+                field.scroll = field.scroll.min((text_width - inner_width).max(0.0));
+            } else {
+                field.composition = None;
+            }
+
+            control_state.push_access_node(
+                AccessNode::new(AccessRole::TextInput, rect, &field.placeholder)
+                    .set_control(control_id)
+                    .set_value(&field.display_text().text),
+            );
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() && control_state.is_hovered(control_id) {
+                control_state.set_focus(control_id);
+                field.composition = None;
+                field.caret_time = time;
+                if let Some(cursor_pos) = control_state.last_cursor_position {
+                    let x = cursor_pos.x - text_left + field.scroll;
+                    let offsets = control_state.text_input.char_offsets(
+                        &field.display_text().text,
+                        font_size,
+                        collection,
+                        font_collection,
+                    );
+                    let position = field.position_at(x, &offsets);
+                    if control_state.register_click(control_id) {
+                        field.editor.select_word_at(position);
+                    } else {
+                        let select = control_state.text_input.shift();
+                        field.editor.set_caret(position, select);
+                        field.drag_active_id = control_state.set_active(control_id);
+                    }
+                }
+            }
+            if mouse_input.is_left_released() {
+                field.drag_active_id = None;
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            if control_state.is_active(field.drag_active_id) {
+                let x = corrected.x - text_left + field.scroll;
+                let offsets = control_state.text_input.char_offsets(
+                    &field.display_text().text,
+                    font_size,
+                    collection,
+                    font_collection,
+                );
+                let position = field.position_at(x, &offsets);
+                field.editor.set_caret(position, true);
+                field.caret_time = time;
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            let focused = control_state.is_focused(control_id);
+            let hovered = control_state.is_hovered(control_id);
+            if hovered || field.drag_active_id.is_some() {
+                control_state.request_cursor(CursorIcon::Text, CursorPriority::Hover);
+            }
+
+            let (border_width, border_color) = if focused {
+                (theme.metrics.focus_border_width, colors.focus)
+            } else {
+                (theme.metrics.border_width, colors.border)
+            };
+            let background = ElementBuilder::new_with_rect(rect)
+                .set_color(if hovered || focused {
+                    colors.control_hover.into()
+                } else {
+                    colors.control.into()
+                })
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+                .set_border(Some(Border {
+                    size: scale.border(border_width),
+                    color: border_color.into(),
+                }))
+                .set_rect_mask(mask.into());
+            control_state.layers.build(background, gui_rects);
+
+            let text_mask = match rect
+                .offset_size(-vec2(padding * 2.0, 0.0))
+                .combine_rects(&mask)
+            {
+                Some(text_mask) => text_mask,
+                None => return response,
+            };
+
+            let display = field.display_text();
+            let (text, text_color) = if display.text.is_empty() {
+                (field.placeholder.as_str(), colors.text_muted)
+            } else {
+                (display.text.as_str(), colors.text)
+            };
+            let (font_elements, text_rect) =
+                create_single_line(text, font_size, font_collection, 0, 0.0);
+            let text_origin = vec2(
+                text_left - field.scroll,
+                rect.position.y - text_rect.height() * 0.5,
+            );
+            let offsets = control_state.text_input.char_offsets(
+                &display.text,
+                font_size,
+                collection,
+                font_collection,
+            );
+            let offset_rect = |range: &Range<usize>, height: f32, y: f32| {
+                let start = text_origin.x + offsets[range.start];
+                let end = text_origin.x + offsets[range.end];
+                Rect {
+                    position: vec2((start + end) * 0.5, y),
+                    size: vec2(end - start, height),
+                }
+            };
+
+            if focused && display.selection.len() > 0 {
+                let selection = ElementBuilder::new_with_rect(offset_rect(
+                    &display.selection,
+                    font_size,
+                    rect.position.y,
+                ))
+                .set_color(colors.accent.set_alpha(0.4).into())
+                .set_rect_mask(text_mask.into());
+                control_state.layers.build(selection, gui_rects);
+            }
+
+            for element in font_elements {
+                let glyph = ElementBuilder::new_with_rect(
+                    element.rect.offset_position(text_origin.round()),
+                )
+                .set_sdffont(element.tx_slice.into())
+                .set_color(text_color.into())
+                .set_rect_mask(text_mask.into());
+                control_state.layers.build(glyph, gui_rects);
+            }
+
+            // The text being composed is underlined
+            if let Some(composition) = &display.composition {
+                let line_height = scale.px(1.0).max(1.0);
+                let y = rect.position.y - font_size * 0.5;
+                let underline =
+                    ElementBuilder::new_with_rect(offset_rect(composition, line_height, y))
+                        .set_color(colors.text.into())
+                        .set_rect_mask(text_mask.into());
+                control_state.layers.build(underline, gui_rects);
+            }
+
+            if focused {
+                let caret_rect = Rect {
+                    position: vec2(text_origin.x + offsets[display.caret], rect.position.y).round(),
+                    size: vec2(scale.px(CARET_WIDTH).max(1.0), font_size),
+                };
+                let blink = ((time - field.caret_time) / CARET_BLINK_TIME).floor() % 2.0;
+                if get_accessibility(public_data).animated(blink, 0.0) == 0.0 {
+                    let caret = ElementBuilder::new_with_rect(caret_rect)
+                        .set_color(colors.text.into())
+                        .set_rect_mask(text_mask.into());
+                    control_state.layers.build(caret, gui_rects);
+                }
+                // The IME opens its candidates under the bottom left corner of the caret
+                control_state.text_input.ime_position =
+                    Some(caret_rect.position - vec2(caret_rect.width(), caret_rect.height()) * 0.5);
+            }
+        }
+        _ => {}
+    }
+    response
+}
@@ -0,0 +1,171 @@
+use rwge::{
+    font::font_layout::create_single_line,
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        BorderRadius, Rect,
+    },
+};
+
+use crate::{
+    gui_system::{
+        layers::Layer,
+        theme::{get_theme, Theme},
+    },
+    runtime_data::{
+        utils::{get_engine_data, get_font_collections},
+        PublicData,
+    },
+};
+
+use super::{scale::GUIScale, ControlState, Uiid};
+
+pub const DEFAULT_TOOLTIP_DELAY: f32 = 0.6;
+
+const TOOLTIP_PADDING: Vec2 = Vec2::new(8.0, 5.0);
+const TOOLTIP_CURSOR_OFFSET: Vec2 = Vec2::new(14.0, -22.0);
+
+/// Builds the elements of a custom tooltip inside of the given rect
+pub type TooltipBuilder = Box<dyn FnOnce(Rect) -> Vec<ElementBuilder>>;
+
+pub enum TooltipContent {
+    /// Every line is laid out on its own
+    Text(String),
+    Custom {
+        size: Vec2,
+        build: TooltipBuilder,
+    },
+}
+
+pub struct TooltipState {
+    /// Seconds the cursor has to stay over a control before its tooltip shows up
+    pub delay: f32,
+    request: Option<TooltipContent>,
+}
+
+impl TooltipState {
+    pub fn new() -> Self {
+        Self {
+            delay: DEFAULT_TOOLTIP_DELAY,
+            request: None,
+        }
+    }
+}
+
+/// Shows `text` next to the cursor once `id` was hovered for longer than the tooltip delay.
+/// It only does something on the render event, but it can be called on any of them.
+pub fn tooltip(control_state: &mut ControlState, id: Uiid, text: &str) {
+    if tooltip_ready(control_state, id) {
+        control_state.tooltip.request = Some(TooltipContent::Text(String::from(text)));
+    }
+}
+
+pub fn tooltip_custom(
+    control_state: &mut ControlState,
+    id: Uiid,
+    size: Vec2,
+    build: TooltipBuilder,
+) {
+    if tooltip_ready(control_state, id) {
+        control_state.tooltip.request = Some(TooltipContent::Custom { size, build });
+    }
+}
+
+fn tooltip_ready(control_state: &ControlState, id: Uiid) -> bool {
+    control_state.is_hovered(id) && control_state.hover_duration() >= control_state.tooltip.delay
+}
+
+/// Keeps the tooltip inside of the screen, flipping it to the other side of the cursor if needed
+fn tooltip_rect(cursor_pos: Vec2, size: Vec2, screen_size: Vec2, scale: &GUIScale) -> Rect {
+    let cursor_offset = scale.vec(TOOLTIP_CURSOR_OFFSET);
+    let mut top_left = cursor_pos + cursor_offset;
+    if top_left.x + size.x > screen_size.x {
+        top_left.x = cursor_pos.x - cursor_offset.x - size.x;
+    }
+    if top_left.y - size.y < 0.0 {
+        top_left.y = cursor_pos.y - cursor_offset.y + size.y;
+    }
+    top_left.x = top_left.x.max(0.0).min(screen_size.x - size.x);
+    top_left.y = top_left.y.max(size.y).min(screen_size.y);
+
+    Rect {
+        position: (top_left + vec2(size.x, -size.y) * 0.5).round(),
+        size,
+    }
+}
+
+/// Called once all the controls were rendered
+pub fn push_tooltip_render_step(control_state: &mut ControlState, public_data: &PublicData) {
+    let content = match control_state.tooltip.request.take() {
+        Some(content) => content,
+        None => return,
+    };
+    let cursor_pos = match control_state.last_cursor_position {
+        Some(cursor_pos) => cursor_pos,
+        None => return,
+    };
+    let screen_size = get_engine_data(public_data).screen_size.as_vec2();
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let padding = scale.vec(TOOLTIP_PADDING);
+    let line_gap = scale.px(theme.metrics.spacing_small);
+
+    let mut render_elements = Vec::new();
+    match content {
+        TooltipContent::Text(text) => {
+            let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+            let font_size = scale.font(theme.fonts.size_small);
+            let lines: Vec<_> = text
+                .lines()
+                .map(|line| create_single_line(line, font_size, font_collection, 0, 0.0))
+                .collect();
+
+            let text_size = lines.iter().fold(Vec2::ZERO, |size, (_, line_rect)| {
+                vec2(
+                    size.x.max(line_rect.width()),
+                    size.y + line_rect.height() + line_gap,
+                )
+            }) - vec2(0.0, line_gap);
+            let rect = tooltip_rect(cursor_pos, text_size + padding * 2.0, screen_size, &scale);
+
+            render_elements.push(tooltip_background(rect, &scale, theme));
+            let mut line_top_left = rect.top_left_position() + vec2(padding.x, -padding.y);
+            for (font_elems, line_rect) in lines {
+                let line_offset = line_top_left - vec2(0.0, line_rect.height());
+                for elem in font_elems {
+                    render_elements.push(
+                        ElementBuilder::new_with_rect(elem.rect.offset_position(line_offset))
+                            .set_sdffont(elem.tx_slice.into())
+                            .set_color(theme.colors.text.into()),
+                    );
+                }
+                line_top_left.y -= line_rect.height() + line_gap;
+            }
+        }
+        TooltipContent::Custom { size, build } => {
+            let rect = tooltip_rect(cursor_pos, size + padding * 2.0, screen_size, &scale);
+            render_elements.push(tooltip_background(rect, &scale, theme));
+            render_elements.extend(build(rect.offset_size(-padding * 2.0)));
+        }
+    }
+
+    control_state.layers.push_top_render_step(
+        Layer::Tooltip,
+        0,
+        Box::new(move |gui_rects| {
+            for elem in render_elements {
+                elem.build(gui_rects);
+            }
+        }),
+    );
+}
+
+fn tooltip_background(rect: Rect, scale: &GUIScale, theme: &Theme) -> ElementBuilder {
+    ElementBuilder::new_with_rect(rect)
+        .set_color(theme.colors.tooltip.into())
+        .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+        .set_border(Some(Border {
+            size: scale.border(theme.metrics.border_width),
+            color: theme.colors.border.into(),
+        }))
+}
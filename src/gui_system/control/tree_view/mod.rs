@@ -0,0 +1,997 @@
+use std::collections::{HashMap, HashSet};
+
+use rwge::{
+    color::RGBA,
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, GUIRects, Rect,
+    },
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        layers::LayerManager,
+        theme::{get_theme, Theme},
+    },
+    runtime_data::{utils::get_font_collections, PublicData},
+};
+
+use super::{
+    cursor::{CursorIcon, CursorPriority},
+    selection::Selection,
+    text_field::TextInputEvent,
+    ControlState, Uiid,
+};
+
+#[cfg(test)]
+mod test;
+
+const ITEM_HEIGHT: f32 = 22.0;
+/// Width of a level of the tree, the expand arrow is centered in it
+const INDENT: f32 = 16.0;
+const LABEL_GAP: f32 = 4.0;
+const SCROLLBAR_WIDTH: f32 = 6.0;
+const GUIDE_WIDTH: f32 = 1.0;
+const DROP_LINE_HEIGHT: f32 = 2.0;
+/// Distance the cursor moves before a press on an item turns into a drag
+const DRAG_THRESHOLD: f32 = 4.0;
+const WHEEL_ROWS: f32 = 3.0;
+/// Rows scrolled on every update while an item is dragged over the first or the last row
+const AUTO_SCROLL_SPEED: f32 = 0.25;
+const LOADING: &str = "Loading...";
+
+/// Chosen by the caller, it has to be unique in the whole tree
+pub type TreeItemId = u64;
+
+/// Items shown by a tree view. The children of an item are only asked for while it is
+/// expanded, so they can be loaded once `TreeChange::Expanded` is received.
+pub trait TreeSource {
+    fn roots(&self) -> Vec<TreeItemId>;
+    fn children(&self, id: TreeItemId) -> Vec<TreeItemId>;
+    /// Shows the expand arrow before the children are loaded
+    fn has_children(&self, id: TreeItemId) -> bool;
+    fn label(&self, id: TreeItemId) -> String;
+    /// Whether `items` can be dropped inside of `parent`, `None` is the root of the tree
+    fn can_drop(&self, _items: &[TreeItemId], _parent: Option<TreeItemId>) -> bool {
+        true
+    }
+}
+
+/// What the user did with the tree in the last event
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeChange {
+    Expanded(TreeItemId),
+    Collapsed(TreeItemId),
+    /// The new selection is read from `TreeView::selection`
+    SelectionChanged,
+    /// Double click or Enter on the item
+    Activated(TreeItemId),
+    /// `items`, in the order they are shown, were dropped inside of `parent` before the child
+    /// that is at `index` right now. The tree view does not move anything, the caller does.
+    Moved {
+        items: Vec<TreeItemId>,
+        parent: Option<TreeItemId>,
+        index: usize,
+    },
+}
+
+/// Visible item, the expanded items are followed by their children
+struct Row {
+    id: TreeItemId,
+    parent: Option<TreeItemId>,
+    /// Position in the children of the parent
+    index: usize,
+    depth: usize,
+    has_children: bool,
+    expanded: bool,
+}
+
+fn flatten(source: &dyn TreeSource, expanded: &HashSet<TreeItemId>) -> Vec<Row> {
+    let mut rows = Vec::new();
+    push_rows(source, expanded, None, source.roots(), 0, &mut rows);
+    rows
+}
+
+fn push_rows(
+    source: &dyn TreeSource,
+    expanded: &HashSet<TreeItemId>,
+    parent: Option<TreeItemId>,
+    items: Vec<TreeItemId>,
+    depth: usize,
+    rows: &mut Vec<Row>,
+) {
+    for (index, id) in items.into_iter().enumerate() {
+        let has_children = source.has_children(id);
+        let is_expanded = has_children && expanded.contains(&id);
+        rows.push(Row {
+            id,
+            parent,
+            index,
+            depth,
+            has_children,
+            expanded: is_expanded,
+        });
+        if is_expanded {
+            push_rows(
+                source,
+                expanded,
+                Some(id),
+                source.children(id),
+                depth + 1,
+                rows,
+            );
+        }
+    }
+}
+
+fn row_of(rows: &[Row], id: Option<TreeItemId>) -> Option<usize> {
+    id.and_then(|id| rows.iter().position(|row| row.id == id))
+}
+
+/// How the drop target is drawn
+#[derive(Clone, Copy)]
+enum DropIndicator {
+    /// Line above or below the row, indented to `depth`
+    Line {
+        row: usize,
+        below: bool,
+        depth: usize,
+    },
+    /// The row is highlighted, the items become its last children
+    Into(usize),
+}
+
+#[derive(Clone, Copy)]
+struct DropTarget {
+    parent: Option<TreeItemId>,
+    /// Not known yet for `DropIndicator::Into`, the children are only read on the drop
+    index: usize,
+    indicator: DropIndicator,
+    valid: bool,
+}
+
+struct TreeDrag {
+    active_id: Uuid,
+    /// Item that was pressed
+    item: TreeItemId,
+    start_position: Vec2,
+    /// Set once the cursor moved further than `DRAG_THRESHOLD`
+    dragging: bool,
+    /// A press on a selected item only selects it alone if it is not dragged
+    select_on_release: bool,
+    target: Option<DropTarget>,
+}
+
+/// Expanded items, selection and scroll of a tree view, kept by the container between frames
+pub struct TreeView {
+    expanded: HashSet<TreeItemId>,
+    selection: Selection<TreeItemId>,
+    /// First visible row, in rows
+    scroll: f32,
+    drag: Option<TreeDrag>,
+    last_clicked: Option<TreeItemId>,
+}
+
+impl TreeView {
+    pub fn new() -> Self {
+        Self {
+            expanded: HashSet::new(),
+            selection: Selection::new(),
+            scroll: 0.0,
+            drag: None,
+            last_clicked: None,
+        }
+    }
+
+    pub fn is_expanded(&self, id: TreeItemId) -> bool {
+        self.expanded.contains(&id)
+    }
+
+    /// Does not report a `TreeChange`, the caller already knows
+    pub fn set_expanded(&mut self, id: TreeItemId, expanded: bool) {
+        if expanded {
+            self.expanded.insert(id);
+        } else {
+            self.expanded.remove(&id);
+        }
+    }
+
+    pub fn selection(&self) -> &HashSet<TreeItemId> {
+        self.selection.ids()
+    }
+
+    pub fn is_selected(&self, id: TreeItemId) -> bool {
+        self.selection.contains(id)
+    }
+
+    pub fn set_selection(&mut self, selection: impl IntoIterator<Item = TreeItemId>) {
+        self.selection.set(selection);
+    }
+
+    /// The cursor moves to the item if it was inside of the collapsed children
+    fn toggle_expanded(&mut self, rows: &[Row], row: usize, changes: &mut Vec<TreeChange>) {
+        let id = rows[row].id;
+        if rows[row].expanded {
+            self.expanded.remove(&id);
+            changes.push(TreeChange::Collapsed(id));
+            let cursor_row = row_of(rows, self.selection.cursor);
+            let subtree_end = subtree_end(rows, row);
+            if cursor_row.map_or(false, |cursor_row| {
+                cursor_row > row && cursor_row < subtree_end
+            }) {
+                self.selection.cursor = Some(id);
+            }
+        } else {
+            self.expanded.insert(id);
+            changes.push(TreeChange::Expanded(id));
+        }
+    }
+
+    /// Keeps `row` inside of the visible rows
+    fn scroll_to_row(&mut self, row: usize, visible_rows: f32) {
+        let row = row as f32;
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row + 1.0 > self.scroll + visible_rows {
+            self.scroll = row + 1.0 - visible_rows;
+        }
+    }
+}
+
+/// Row after the last descendant of `row`
+fn subtree_end(rows: &[Row], row: usize) -> usize {
+    let depth = rows[row].depth;
+    rows[row + 1..]
+        .iter()
+        .position(|other| other.depth <= depth)
+        .map_or(rows.len(), |offset| row + 1 + offset)
+}
+
+/// Selected rows in the order they are shown, without the ones that move with a selected parent
+fn dragged_items(rows: &[Row], selection: &HashSet<TreeItemId>) -> Vec<TreeItemId> {
+    let mut items = Vec::new();
+    let mut skip_until = 0;
+    for (index, row) in rows.iter().enumerate() {
+        if index < skip_until || !selection.contains(&row.id) {
+            continue;
+        }
+        items.push(row.id);
+        skip_until = subtree_end(rows, index);
+    }
+    items
+}
+
+/// `parent` is one of the dragged items or is inside of one of them
+fn is_inside_dragged(rows: &[Row], parent: Option<TreeItemId>, dragged: &[TreeItemId]) -> bool {
+    let parents: HashMap<TreeItemId, Option<TreeItemId>> =
+        rows.iter().map(|row| (row.id, row.parent)).collect();
+    let mut current = parent;
+    while let Some(id) = current {
+        if dragged.contains(&id) {
+            return true;
+        }
+        current = parents.get(&id).copied().flatten();
+    }
+    false
+}
+
+/// The top and bottom quarters of a row drop next to it, the middle drops inside of it. Below
+/// the last row the items go to the end of the root.
+fn drop_target(
+    rows: &[Row],
+    row: Option<usize>,
+    fraction: f32,
+    source: &dyn TreeSource,
+    dragged: &[TreeItemId],
+) -> Option<DropTarget> {
+    let (parent, index, indicator) = match row {
+        Some(row) => {
+            let current = &rows[row];
+            if fraction < 0.25 {
+                (
+                    current.parent,
+                    current.index,
+                    DropIndicator::Line {
+                        row,
+                        below: false,
+                        depth: current.depth,
+                    },
+                )
+            } else if fraction > 0.75 && current.expanded {
+                // Below an expanded item is above its first child
+                (
+                    Some(current.id),
+                    0,
+                    DropIndicator::Line {
+                        row,
+                        below: true,
+                        depth: current.depth + 1,
+                    },
+                )
+            } else if fraction > 0.75 {
+                (
+                    current.parent,
+                    current.index + 1,
+                    DropIndicator::Line {
+                        row,
+                        below: true,
+                        depth: current.depth,
+                    },
+                )
+            } else {
+                (Some(current.id), 0, DropIndicator::Into(row))
+            }
+        }
+        None => {
+            let last = rows.len().checked_sub(1)?;
+            let root_count = rows.iter().filter(|row| row.parent.is_none()).count();
+            (
+                None,
+                root_count,
+                DropIndicator::Line {
+                    row: last,
+                    below: true,
+                    depth: 0,
+                },
+            )
+        }
+    };
+    let valid = !is_inside_dragged(rows, parent, dragged) && source.can_drop(dragged, parent);
+    Some(DropTarget {
+        parent,
+        index,
+        indicator,
+        valid,
+    })
+}
+
+/// Where the tree is drawn and how it is split in rows
+struct TreeLayout {
+    inner_rect: Rect,
+    item_height: f32,
+    indent: f32,
+    /// Rows that fit in `inner_rect`, the last one can be cut
+    visible_rows: f32,
+    /// Only present when the rows do not fit
+    scrollbar: Option<Rect>,
+}
+
+impl TreeLayout {
+    fn new(rect: Rect, row_count: usize, control_state: &ControlState, theme: &Theme) -> Self {
+        let scale = control_state.scale;
+        let item_height = scale.px(ITEM_HEIGHT);
+        let padding = scale.px(theme.metrics.spacing_small);
+        let height = (rect.height() - padding * 2.0).max(0.0);
+        let visible_rows = height / item_height;
+        let scrollbar = (row_count as f32 > visible_rows).then(|| {
+            let width = scale.px(SCROLLBAR_WIDTH);
+            Rect {
+                position: vec2(
+                    rect.position.x + rect.width() * 0.5 - padding - width * 0.5,
+                    rect.position.y,
+                ),
+                size: vec2(width, height),
+            }
+        });
+        let scrollbar_width = scrollbar.map_or(0.0, |scrollbar| scrollbar.width() + padding);
+        Self {
+            inner_rect: Rect {
+                position: rect.position - vec2(scrollbar_width * 0.5, 0.0),
+                size: vec2(
+                    (rect.width() - padding * 2.0 - scrollbar_width).max(0.0),
+                    height,
+                ),
+            },
+            item_height,
+            indent: scale.px(INDENT),
+            visible_rows,
+            scrollbar,
+        }
+    }
+
+    fn row_rect(&self, row: usize, scroll: f32) -> Rect {
+        Rect {
+            position: vec2(
+                self.inner_rect.position.x,
+                self.inner_rect.top_left_position().y
+                    - (row as f32 - scroll + 0.5) * self.item_height,
+            ),
+            size: vec2(self.inner_rect.width(), self.item_height),
+        }
+    }
+
+    /// Row under `y` and how far down inside of it `y` is, from 0 to 1
+    fn row_at(&self, y: f32, scroll: f32, row_count: usize) -> Option<(usize, f32)> {
+        let row = (self.inner_rect.top_left_position().y - y) / self.item_height + scroll;
+        if row >= 0.0 && (row as usize) < row_count {
+            Some((row as usize, row.fract()))
+        } else {
+            None
+        }
+    }
+
+    /// Rows that are at least partly visible
+    fn visible_range(&self, scroll: f32, row_count: usize) -> std::ops::Range<usize> {
+        let start = (scroll.floor() as usize).min(row_count);
+        let end = ((scroll + self.visible_rows).ceil() as usize).min(row_count);
+        start..end.max(start)
+    }
+
+    fn max_scroll(&self, row_count: usize) -> f32 {
+        (row_count as f32 - self.visible_rows).max(0.0)
+    }
+
+    /// Left edge of the expand arrow of a row at `depth`
+    fn indent_x(&self, depth: usize) -> f32 {
+        self.inner_rect.left_position().x + self.indent * depth as f32
+    }
+
+    fn thumb_rect(&self, track: Rect, scroll: f32, row_count: usize) -> Rect {
+        let height = track.height() * (self.visible_rows / row_count as f32).min(1.0);
+        let max_scroll = self.max_scroll(row_count);
+        let progress = if max_scroll > 0.0 {
+            scroll / max_scroll
+        } else {
+            0.0
+        };
+        Rect {
+            position: vec2(
+                track.position.x,
+                track.top_left_position().y - height * 0.5 - progress * (track.height() - height),
+            ),
+            size: vec2(track.width(), height),
+        }
+    }
+}
+
+/// Text vertically centered in `rect`, starting at `x`
+fn draw_text(
+    layers: &mut LayerManager,
+    gui_rects: &mut GUIRects,
+    text: &str,
+    x: f32,
+    rect: Rect,
+    font_size: f32,
+    font_collection: &FontCollection,
+    color: RGBA,
+    mask: Rect,
+) -> f32 {
+    let (font_elements, text_rect) = create_single_line(text, font_size, font_collection, 0, 0.0);
+    let origin = vec2(x, rect.position.y - text_rect.height() * 0.5).round();
+    for element in font_elements {
+        let glyph = ElementBuilder::new_with_rect(element.rect.offset_position(origin))
+            .set_sdffont(element.tx_slice.into())
+            .set_color(color.into())
+            .set_rect_mask(mask.into());
+        layers.build(glyph, gui_rects);
+    }
+    text_rect.width()
+}
+
+/// Keyboard navigation of the focused tree. Returns true if the selection changed.
+fn handle_key(
+    state: &mut TreeView,
+    rows: &[Row],
+    key: VirtualKeyCode,
+    shift: bool,
+    ctrl: bool,
+    visible_rows: f32,
+    changes: &mut Vec<TreeChange>,
+) -> bool {
+    if rows.is_empty() {
+        return false;
+    }
+    let cursor_row = row_of(rows, state.selection.cursor);
+    let last = rows.len() - 1;
+    let page = (visible_rows.floor() as usize).max(1);
+    let target = match key {
+        VirtualKeyCode::Up => cursor_row.map_or(last, |row| row.saturating_sub(1)),
+        VirtualKeyCode::Down => cursor_row.map_or(0, |row| (row + 1).min(last)),
+        VirtualKeyCode::PageUp => cursor_row.map_or(0, |row| row.saturating_sub(page)),
+        VirtualKeyCode::PageDown => cursor_row.map_or(last, |row| (row + page).min(last)),
+        VirtualKeyCode::Home => 0,
+        VirtualKeyCode::End => last,
+        VirtualKeyCode::Left => match cursor_row {
+            Some(row) if rows[row].expanded => {
+                state.toggle_expanded(rows, row, changes);
+                return false;
+            }
+            Some(row) => match row_of(rows, rows[row].parent) {
+                Some(parent_row) => parent_row,
+                None => return false,
+            },
+            None => return false,
+        },
+        VirtualKeyCode::Right => match cursor_row {
+            Some(row) if rows[row].has_children && !rows[row].expanded => {
+                state.toggle_expanded(rows, row, changes);
+                return false;
+            }
+            // The first child, if it is loaded
+            Some(row)
+                if rows[row].expanded && row < last && rows[row + 1].depth > rows[row].depth =>
+            {
+                row + 1
+            }
+            _ => return false,
+        },
+        VirtualKeyCode::Space => {
+            let row = cursor_row.unwrap_or(0);
+            if ctrl {
+                state.selection.toggle(rows[row].id);
+                return true;
+            }
+            return state.selection.select_only(rows[row].id);
+        }
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+            if let Some(row) = cursor_row {
+                changes.push(TreeChange::Activated(rows[row].id));
+            }
+            return false;
+        }
+        VirtualKeyCode::A if ctrl => {
+            state.selection.ids.extend(rows.iter().map(|row| row.id));
+            return true;
+        }
+        _ => return false,
+    };
+
+    state.scroll_to_row(target, visible_rows);
+    if shift {
+        state
+            .selection
+            .select_range(rows.iter().map(|row| row.id), target, ctrl);
+        true
+    } else if ctrl {
+        // Only moves the cursor, Space selects
+        state.selection.cursor = Some(rows[target].id);
+        false
+    } else {
+        state.selection.select_only(rows[target].id)
+    }
+}
+
+/// Hierarchical list of the items of `source`. Items are expanded with their arrow, selected with
+/// clicks (Ctrl toggles, Shift selects a range) or the keyboard, and dragged over other items to
+/// reparent them. Returns what changed in this event, moved items still have to be moved by the
+/// caller.
+pub fn tree_view(
+    rect: Rect,
+    mask: Rect,
+    label: &str,
+    source: &dyn TreeSource,
+    state: &mut TreeView,
+    event: &mut UIEvent,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Vec<TreeChange> {
+    let control_id = control_state.get_id();
+
+    let scale = control_state.scale;
+    let theme = get_theme(public_data);
+    let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+    let font_size = scale.font(theme.fonts.size);
+
+    let mut changes = Vec::new();
+    let mut selection_changed = false;
+    let mut rows = flatten(source, &state.expanded);
+    let layout = TreeLayout::new(rect, rows.len(), control_state, theme);
+    state.scroll = state.scroll.min(layout.max_scroll(rows.len())).max(0.0);
+
+    match event {
+        UIEvent::Update => {
+            if let Some(drag) = &state.drag {
+                control_state.hold_active_state(drag.active_id);
+                // Scrolls while an item is dragged over the edges of the tree
+                let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+                let top = layout.inner_rect.top_left_position().y;
+                let bottom = top - layout.inner_rect.height();
+                if drag.dragging && cursor_pos.y > top - layout.item_height {
+                    state.scroll = (state.scroll - AUTO_SCROLL_SPEED).max(0.0);
+                } else if drag.dragging && cursor_pos.y < bottom + layout.item_height {
+                    state.scroll =
+                        (state.scroll + AUTO_SCROLL_SPEED).min(layout.max_scroll(rows.len()));
+                }
+            } else if let Some(interact_rect) = rect.combine_rects(&mask) {
+                control_state.set_hot_with_rect(control_id, &interact_rect);
+            }
+
+            if control_state.is_hovered(control_id) {
+                let lines = control_state.take_wheel_delta();
+                state.scroll = (state.scroll - lines * WHEEL_ROWS)
+                    .min(layout.max_scroll(rows.len()))
+                    .max(0.0);
+            }
+
+            if control_state.is_focused(control_id) {
+                for input in control_state.text_input.take_events() {
+                    if let TextInputEvent::Key { key, shift, ctrl } = input {
+                        if key == VirtualKeyCode::Escape {
+                            state.drag = None;
+                            continue;
+                        }
+                        selection_changed |= handle_key(
+                            state,
+                            &rows,
+                            key,
+                            shift,
+                            ctrl,
+                            layout.visible_rows,
+                            &mut changes,
+                        );
+                        rows = flatten(source, &state.expanded);
+                    }
+                }
+            }
+
+            let tree_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::Tree, rect, label)
+                    .set_control(control_id)
+                    .set_focused(control_state.is_focused(control_id)),
+            );
+            control_state.access.push_parent(tree_access_id);
+            for row in layout.visible_range(state.scroll, rows.len()) {
+                let item = &rows[row];
+                let node = AccessNode::new(
+                    AccessRole::TreeItem,
+                    layout.row_rect(row, state.scroll),
+                    &source.label(item.id),
+                )
+                .set_selected(state.selection.contains(item.id));
+                control_state.push_access_node(if item.has_children {
+                    node.set_expanded(item.expanded)
+                } else {
+                    node
+                });
+            }
+            control_state.access.pop_parent();
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() && control_state.is_hovered(control_id) {
+                control_state.set_focus(control_id);
+                let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+                let shift = control_state.text_input.shift();
+                let ctrl = control_state.text_input.ctrl();
+                match layout.row_at(cursor_pos.y, state.scroll, rows.len()) {
+                    Some((row, _)) => {
+                        let item = &rows[row];
+                        let arrow_left = layout.indent_x(item.depth);
+                        let on_arrow = item.has_children
+                            && cursor_pos.x >= arrow_left
+                            && cursor_pos.x < arrow_left + layout.indent;
+                        if on_arrow {
+                            state.toggle_expanded(&rows, row, &mut changes);
+                        } else {
+                            let id = item.id;
+                            let double_click = control_state.register_click(control_id)
+                                && state.last_clicked == Some(id);
+                            state.last_clicked = Some(id);
+                            let mut select_on_release = false;
+                            if shift {
+                                state.selection.select_range(
+                                    rows.iter().map(|row| row.id),
+                                    row,
+                                    ctrl,
+                                );
+                                selection_changed = true;
+                            } else if ctrl {
+                                state.selection.toggle(id);
+                                selection_changed = true;
+                            } else if state.selection.contains(id) {
+                                // Dragging keeps the rest of the selection
+                                select_on_release = true;
+                                state.selection.cursor = Some(id);
+                                state.selection.anchor = Some(id);
+                            } else {
+                                selection_changed |= state.selection.select_only(id);
+                            }
+                            if double_click {
+                                changes.push(TreeChange::Activated(id));
+                            }
+                            if let Some(active_id) = control_state.set_active(control_id) {
+                                state.drag = Some(TreeDrag {
+                                    active_id,
+                                    item: id,
+                                    start_position: cursor_pos,
+                                    dragging: false,
+                                    select_on_release,
+                                    target: None,
+                                });
+                            }
+                        }
+                    }
+                    None if !ctrl && !state.selection.ids.is_empty() => {
+                        state.selection.ids.clear();
+                        selection_changed = true;
+                    }
+                    None => {}
+                }
+            }
+            if mouse_input.is_left_released() {
+                if let Some(drag) = state.drag.take() {
+                    match drag.target {
+                        Some(target) if drag.dragging && target.valid => {
+                            let items = dragged_items(&rows, &state.selection.ids);
+                            let index = match target.indicator {
+                                DropIndicator::Into(row) => {
+                                    let id = rows[row].id;
+                                    if rows[row].has_children && !rows[row].expanded {
+                                        state.expanded.insert(id);
+                                        changes.push(TreeChange::Expanded(id));
+                                    }
+                                    source.children(id).len()
+                                }
+                                DropIndicator::Line { .. } => target.index,
+                            };
+                            changes.push(TreeChange::Moved {
+                                items,
+                                parent: target.parent,
+                                index,
+                            });
+                        }
+                        _ if !drag.dragging && drag.select_on_release => {
+                            selection_changed |= state.selection.select_only(drag.item);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            let cursor_pos = *corrected;
+            if let Some(drag) = &mut state.drag {
+                if control_state.is_active(Some(drag.active_id)) {
+                    if !drag.dragging
+                        && cursor_pos.distance(drag.start_position) > scale.px(DRAG_THRESHOLD)
+                    {
+                        drag.dragging = true;
+                        drag.select_on_release = false;
+                        // A ctrl click can unselect the pressed item, it is dragged anyway
+                        if !state.selection.contains(drag.item) {
+                            state.selection.select_only(drag.item);
+                            selection_changed = true;
+                        }
+                    }
+                    if drag.dragging {
+                        let dragged = dragged_items(&rows, &state.selection.ids);
+                        let hovered_row = layout.row_at(cursor_pos.y, state.scroll, rows.len());
+                        drag.target = drop_target(
+                            &rows,
+                            hovered_row.map(|(row, _)| row),
+                            hovered_row.map_or(0.0, |(_, fraction)| fraction),
+                            source,
+                            &dragged,
+                        );
+                    }
+                }
+            }
+        }
+        UIEvent::Render { gui_rects, .. } => {
+            render_tree(
+                gui_rects,
+                rect,
+                mask,
+                source,
+                state,
+                &rows,
+                &layout,
+                control_id,
+                control_state,
+                theme,
+                font_size,
+                font_collection,
+            );
+        }
+        _ => {}
+    }
+
+    if selection_changed {
+        changes.push(TreeChange::SelectionChanged);
+    }
+    changes
+}
+
+fn render_tree(
+    gui_rects: &mut GUIRects,
+    rect: Rect,
+    mask: Rect,
+    source: &dyn TreeSource,
+    state: &TreeView,
+    rows: &[Row],
+    layout: &TreeLayout,
+    control_id: Uiid,
+    control_state: &mut ControlState,
+    theme: &Theme,
+    font_size: f32,
+    font_collection: &FontCollection,
+) {
+    let scale = control_state.scale;
+    let colors = theme.colors;
+    let focused = control_state.is_focused(control_id);
+    let (border_width, border_color) = if focused {
+        (theme.metrics.focus_border_width, colors.focus)
+    } else {
+        (theme.metrics.border_width, colors.border)
+    };
+    let background = ElementBuilder::new_with_rect(rect)
+        .set_color(colors.control.into())
+        .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+        .set_border(Some(Border {
+            size: scale.border(border_width),
+            color: border_color.into(),
+        }))
+        .set_rect_mask(mask.into());
+    control_state.layers.build(background, gui_rects);
+
+    let content_mask = match layout.inner_rect.combine_rects(&mask) {
+        Some(content_mask) => content_mask,
+        None => return,
+    };
+    let cursor_pos = control_state.last_cursor_position.unwrap_or(Vec2::ZERO);
+    let hovered_row = if control_state.is_hovered(control_id) && state.drag.is_none() {
+        layout
+            .row_at(cursor_pos.y, state.scroll, rows.len())
+            .map(|(row, _)| row)
+    } else {
+        None
+    };
+    let cursor_row = row_of(rows, state.selection.cursor);
+    let guide_width = scale.px(GUIDE_WIDTH).max(1.0);
+    let label_gap = scale.px(LABEL_GAP);
+
+    for row in layout.visible_range(state.scroll, rows.len()) {
+        let item = &rows[row];
+        let row_rect = layout.row_rect(row, state.scroll);
+        let selected = state.selection.contains(item.id);
+        let fill = if selected {
+            Some(colors.accent.set_alpha(0.35))
+        } else if hovered_row == Some(row) {
+            Some(colors.control_hover)
+        } else {
+            None
+        };
+        if let Some(fill) = fill {
+            let row_fill = ElementBuilder::new_with_rect(row_rect)
+                .set_color(fill.into())
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into())
+                .set_rect_mask(content_mask.into());
+            control_state.layers.build(row_fill, gui_rects);
+        }
+        if focused && cursor_row == Some(row) {
+            let cursor_outline = ElementBuilder::new_with_rect(row_rect)
+                .set_color(RGBA::TRANSPARENT.into())
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into())
+                .set_border(Some(Border {
+                    size: scale.border(theme.metrics.border_width),
+                    color: colors.focus.into(),
+                }))
+                .set_rect_mask(content_mask.into());
+            control_state.layers.build(cursor_outline, gui_rects);
+        }
+
+        // One guide for every level above the item, they join into lines across the rows
+        for level in 0..item.depth {
+            let x = layout.indent_x(level) + layout.indent * 0.5;
+            let guide = ElementBuilder::new(
+                vec2(x, row_rect.position.y).round(),
+                vec2(guide_width, row_rect.height()),
+            )
+            .set_color(colors.border.into())
+            .set_rect_mask(content_mask.into());
+            control_state.layers.build(guide, gui_rects);
+        }
+
+        let arrow_left = layout.indent_x(item.depth);
+        if item.has_children {
+            let arrow = if item.expanded { "v" } else { ">" };
+            let (arrow_elements, arrow_rect) =
+                create_single_line(arrow, font_size, font_collection, 0, 0.0);
+            let arrow_origin =
+                vec2(arrow_left + layout.indent * 0.5, row_rect.position.y) - arrow_rect.size * 0.5;
+            for element in arrow_elements {
+                let glyph = ElementBuilder::new_with_rect(
+                    element.rect.offset_position(arrow_origin.round()),
+                )
+                .set_sdffont(element.tx_slice.into())
+                .set_color(colors.text_muted.into())
+                .set_rect_mask(content_mask.into());
+                control_state.layers.build(glyph, gui_rects);
+            }
+        }
+
+        let label_x = arrow_left + layout.indent + label_gap;
+        let label_width = draw_text(
+            &mut control_state.layers,
+            gui_rects,
+            &source.label(item.id),
+            label_x,
+            row_rect,
+            font_size,
+            font_collection,
+            colors.text,
+            content_mask,
+        );
+        // Expanded, but the children did not arrive yet
+        let loading = item.expanded
+            && rows
+                .get(row + 1)
+                .map_or(true, |next| next.depth <= item.depth);
+        if loading {
+            draw_text(
+                &mut control_state.layers,
+                gui_rects,
+                LOADING,
+                label_x + label_width + label_gap * 2.0,
+                row_rect,
+                font_size,
+                font_collection,
+                colors.text_muted,
+                content_mask,
+            );
+        }
+    }
+
+    if let Some(drag) = state.drag.as_ref().filter(|drag| drag.dragging) {
+        let valid = drag.target.map_or(false, |target| target.valid);
+        let icon = if valid {
+            CursorIcon::Grabbing
+        } else {
+            CursorIcon::NotAllowed
+        };
+        control_state.request_cursor(icon, CursorPriority::Active);
+
+        match drag
+            .target
+            .filter(|target| target.valid)
+            .map(|target| target.indicator)
+        {
+            Some(DropIndicator::Line { row, below, depth }) => {
+                let row_rect = layout.row_rect(row, state.scroll);
+                let y = if below {
+                    row_rect.position.y - row_rect.height() * 0.5
+                } else {
+                    row_rect.position.y + row_rect.height() * 0.5
+                };
+                let left = layout.indent_x(depth) + layout.indent;
+                let right = layout.inner_rect.left_position().x + layout.inner_rect.width();
+                let drop_line = ElementBuilder::new(
+                    vec2((left + right) * 0.5, y).round(),
+                    vec2((right - left).max(0.0), scale.px(DROP_LINE_HEIGHT)),
+                )
+                .set_color(colors.accent.into())
+                .set_rect_mask(content_mask.into());
+                control_state.layers.build(drop_line, gui_rects);
+            }
+            Some(DropIndicator::Into(row)) => {
+                let drop_outline =
+                    ElementBuilder::new_with_rect(layout.row_rect(row, state.scroll))
+                        .set_color(RGBA::TRANSPARENT.into())
+                        .set_round_rect(
+                            BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into(),
+                        )
+                        .set_border(Some(Border {
+                            size: scale.border(theme.metrics.focus_border_width),
+                            color: colors.accent.into(),
+                        }))
+                        .set_rect_mask(content_mask.into());
+                control_state.layers.build(drop_outline, gui_rects);
+            }
+            None => {}
+        }
+    }
+
+    if let Some(track) = layout.scrollbar {
+        let thumb =
+            ElementBuilder::new_with_rect(layout.thumb_rect(track, state.scroll, rows.len()))
+                .set_color(colors.border.into())
+                .set_round_rect(BorderRadius::ForAll(track.width() * 0.5).into())
+                .set_rect_mask(mask.into());
+        control_state.layers.build(thumb, gui_rects);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{drop_target, flatten, DropIndicator, Row, TreeItemId, TreeSource, TreeView};
+
+/// Items 2 and 20 are files, nothing is dropped inside of them
+struct Folders {
+    roots: Vec<TreeItemId>,
+    children: HashMap<TreeItemId, Vec<TreeItemId>>,
+}
+
+impl TreeSource for Folders {
+    fn roots(&self) -> Vec<TreeItemId> {
+        self.roots.clone()
+    }
+
+    fn children(&self, id: TreeItemId) -> Vec<TreeItemId> {
+        self.children.get(&id).cloned().unwrap_or_default()
+    }
+
+    fn has_children(&self, id: TreeItemId) -> bool {
+        self.children.contains_key(&id)
+    }
+
+    fn label(&self, id: TreeItemId) -> String {
+        id.to_string()
+    }
+
+    fn can_drop(&self, _items: &[TreeItemId], parent: Option<TreeItemId>) -> bool {
+        parent != Some(2) && parent != Some(20)
+    }
+}
+
+/// Shown as 1, 10, 100, 11, 2, 3 with 1 and 10 expanded and 3 collapsed
+fn tree() -> (Folders, Vec<Row>) {
+    let source = Folders {
+        roots: vec![1, 2, 3],
+        children: HashMap::from([(1, vec![10, 11]), (10, vec![100]), (3, vec![30])]),
+    };
+    let expanded = HashSet::from([1, 10]);
+    let rows = flatten(&source, &expanded);
+    (source, rows)
+}
+
+fn selection(tree_view: &TreeView) -> Vec<TreeItemId> {
+    let mut selection: Vec<TreeItemId> = tree_view.selection().iter().copied().collect();
+    selection.sort();
+    selection
+}
+
+#[test]
+fn rows_follow_the_expanded_items() {
+    let (_, rows) = tree();
+    let ids: Vec<TreeItemId> = rows.iter().map(|row| row.id).collect();
+    assert_eq!(ids, [1, 10, 100, 11, 2, 3]);
+    let depths: Vec<usize> = rows.iter().map(|row| row.depth).collect();
+    assert_eq!(depths, [0, 1, 2, 1, 0, 0]);
+    assert!(rows[5].has_children && !rows[5].expanded);
+}
+
+fn select_range(tree_view: &mut TreeView, rows: &[Row], row: usize, add: bool) {
+    let ids = rows.iter().map(|row| row.id);
+    tree_view.selection.select_range(ids, row, add);
+}
+
+#[test]
+fn range_goes_from_the_anchor_down() {
+    let (_, rows) = tree();
+    let mut tree_view = TreeView::new();
+    tree_view.selection.select_only(10);
+    select_range(&mut tree_view, &rows, 4, false);
+    assert_eq!(selection(&tree_view), [2, 10, 11, 100]);
+    assert_eq!(tree_view.selection.cursor, Some(2));
+    assert_eq!(tree_view.selection.anchor, Some(10));
+}
+
+#[test]
+fn range_goes_from_the_anchor_up() {
+    let (_, rows) = tree();
+    let mut tree_view = TreeView::new();
+    tree_view.selection.select_only(11);
+    select_range(&mut tree_view, &rows, 0, false);
+    assert_eq!(selection(&tree_view), [1, 10, 11, 100]);
+    assert_eq!(tree_view.selection.cursor, Some(1));
+}
+
+#[test]
+fn range_keeps_the_selection_when_added() {
+    let (_, rows) = tree();
+    let mut tree_view = TreeView::new();
+    tree_view.selection.select_only(1);
+    tree_view.selection.toggle(3);
+    select_range(&mut tree_view, &rows, 3, true);
+    assert_eq!(selection(&tree_view), [1, 2, 3, 11]);
+
+    select_range(&mut tree_view, &rows, 4, false);
+    assert_eq!(selection(&tree_view), [2, 3]);
+}
+
+#[test]
+fn range_without_an_anchor_starts_at_the_row() {
+    let (_, rows) = tree();
+    let mut tree_view = TreeView::new();
+    select_range(&mut tree_view, &rows, 2, false);
+    assert_eq!(selection(&tree_view), [100]);
+    assert_eq!(tree_view.selection.anchor, Some(100));
+
+    // A hidden anchor is treated like a missing one
+    tree_view.selection.select_only(30);
+    select_range(&mut tree_view, &rows, 1, false);
+    assert_eq!(selection(&tree_view), [10]);
+}
+
+#[test]
+fn top_quarter_drops_above_the_row() {
+    let (source, rows) = tree();
+    let target = drop_target(&rows, Some(3), 0.1, &source, &[2]).unwrap();
+    assert_eq!((target.parent, target.index), (Some(1), 1));
+    assert!(target.valid);
+    assert!(matches!(
+        target.indicator,
+        DropIndicator::Line {
+            row: 3,
+            below: false,
+            depth: 1
+        }
+    ));
+}
+
+#[test]
+fn bottom_quarter_drops_below_the_row() {
+    let (source, rows) = tree();
+    let target = drop_target(&rows, Some(4), 0.9, &source, &[100]).unwrap();
+    assert_eq!((target.parent, target.index), (None, 2));
+    assert!(matches!(
+        target.indicator,
+        DropIndicator::Line {
+            row: 4,
+            below: true,
+            depth: 0
+        }
+    ));
+
+    // Below an expanded item the items become its first children
+    let target = drop_target(&rows, Some(1), 0.9, &source, &[2]).unwrap();
+    assert_eq!((target.parent, target.index), (Some(10), 0));
+    assert!(matches!(
+        target.indicator,
+        DropIndicator::Line {
+            row: 1,
+            below: true,
+            depth: 2
+        }
+    ));
+}
+
+#[test]
+fn middle_drops_inside_of_the_row() {
+    let (source, rows) = tree();
+    let target = drop_target(&rows, Some(5), 0.5, &source, &[2]).unwrap();
+    assert_eq!(target.parent, Some(3));
+    assert!(target.valid);
+    assert!(matches!(target.indicator, DropIndicator::Into(5)));
+}
+
+#[test]
+fn below_the_last_row_drops_at_the_end_of_the_root() {
+    let (source, rows) = tree();
+    let target = drop_target(&rows, None, 0.0, &source, &[100]).unwrap();
+    assert_eq!((target.parent, target.index), (None, 3));
+    assert!(target.valid);
+    assert!(drop_target(&[], None, 0.0, &source, &[100]).is_none());
+}
+
+#[test]
+fn items_are_not_dropped_inside_of_themselves() {
+    let (source, rows) = tree();
+    assert!(
+        !drop_target(&rows, Some(0), 0.5, &source, &[1])
+            .unwrap()
+            .valid
+    );
+    assert!(
+        !drop_target(&rows, Some(2), 0.5, &source, &[1])
+            .unwrap()
+            .valid
+    );
+    assert!(
+        !drop_target(&rows, Some(2), 0.1, &source, &[10])
+            .unwrap()
+            .valid
+    );
+    assert!(
+        drop_target(&rows, Some(3), 0.5, &source, &[10])
+            .unwrap()
+            .valid
+    );
+}
+
+#[test]
+fn source_can_reject_the_parent() {
+    let (source, rows) = tree();
+    assert!(
+        !drop_target(&rows, Some(4), 0.5, &source, &[11])
+            .unwrap()
+            .valid
+    );
+    assert!(
+        drop_target(&rows, Some(4), 0.1, &source, &[11])
+            .unwrap()
+            .valid
+    );
+}
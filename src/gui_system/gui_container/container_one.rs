@@ -1,4 +1,4 @@
-use std::{collections::HashMap, string};
+use std::{cell::Cell, collections::HashMap, rc::Rc, string};
 
 use rwge::{
     color::{HSLA, RGBA},
@@ -15,8 +15,22 @@ use rwge::{
 
 use crate::{
     gui_system::{
+        accessibility::get_accessibility,
         control,
-        control::{slider, ControlId, ControlState, State, Uiid},
+        control::{
+            choice::{self, ToggleSwitch},
+            color_picker::{self, ColorPicker},
+            drag_value::{self, VectorEditor},
+            dropdown::{self, Dropdown},
+            scale::GUIScale,
+            slider::{self, SliderOptions},
+            text_field::{self, TextField},
+            tooltip, ControlId, ControlState, State, Uiid,
+        },
+        layers::Layer,
+        modal::{ModalDialog, ModalResponse, ModalResult},
+        popup::MenuItem,
+        theme::get_theme,
         window_layout::depth_offset,
         ContainerInfo,
     },
@@ -76,13 +90,30 @@ pub struct ContainerOne {
 
     slider_instance: Uuid,
     slider_active_id: Option<Uuid>,
+    name_field: TextField,
+    /// Color the container was created with, it is the first preset
+    default_color: RGBA,
+    color_preset: usize,
+    color_dropdown: Dropdown,
+    color_picker: ColorPicker,
+    /// Moves the box grid from where it is laid out
+    grid_offset: Vec2,
+    grid_offset_editor: VectorEditor,
+    /// Index into `BOX_SIZES`
+    box_size_index: usize,
+    spin_boxes: bool,
+    gradients_switch: ToggleSwitch,
     anim_data: AnimationData,
 
+    reset_requested: bool,
+    reset_confirmed: Rc<Cell<bool>>,
+
     pub instance_id: Uuid,
 }
 
 impl ContainerOne {
     pub fn new(name: String, value: f32, color: RGBA, count: usize) -> Self {
+        let name_field = TextField::new(&name).set_placeholder("Tab name");
         Self {
             name,
             value,
@@ -90,18 +121,62 @@ impl ContainerOne {
             count,
             slider_instance: Uuid::nil(),
             slider_active_id: None,
+            name_field,
+            default_color: color,
+            color_preset: 0,
+            color_dropdown: Dropdown::new(),
+            color_picker: ColorPicker::new(),
+            grid_offset: Vec2::ZERO,
+            grid_offset_editor: VectorEditor::new(2)
+                .set_step(1.0)
+                .set_precision(0)
+                .set_suffix(" px"),
+            box_size_index: 1,
+            spin_boxes: true,
+            gradients_switch: ToggleSwitch::new(true),
             anim_data: AnimationData::new(count, Vec2::ZERO, color),
+            reset_requested: false,
+            reset_confirmed: Rc::new(Cell::new(false)),
             instance_id: Uuid::new_v4(),
         }
     }
+
+    fn preset_color(&self, index: usize) -> RGBA {
+        match index {
+            0 => self.default_color,
+            _ => {
+                let [r, g, b] = COLOR_PRESETS[index - 1].1;
+                RGBA::rgb(r, g, b)
+            }
+        }
+    }
 }
 
-fn selection_rect(start_pos: Vec2, end_pos: Vec2, container_rect: &Rect) -> Rect {
+/// Offered after the default color of the container
+const COLOR_PRESETS: [(&str, [f32; 3]); 9] = [
+    ("Crimson", [0.75, 0.15, 0.25]),
+    ("Orange", [0.9, 0.5, 0.15]),
+    ("Amber", [0.95, 0.75, 0.2]),
+    ("Lime", [0.55, 0.8, 0.2]),
+    ("Teal", [0.15, 0.6, 0.55]),
+    ("Sky", [0.3, 0.6, 0.9]),
+    ("Indigo", [0.3, 0.25, 0.7]),
+    ("Violet", [0.6, 0.3, 0.75]),
+    ("Slate", [0.4, 0.45, 0.5]),
+];
+
+/// Size of the grid cells, picked with the box size radio group
+const BOX_SIZES: [(&str, f32); 3] = [("Small", 20.0), ("Medium", 30.0), ("Large", 45.0)];
+
+const ACTION_RESET_COLORS: u32 = 0;
+const ACTION_RANDOM_COLORS: u32 = 1;
+
+fn selection_rect(start_pos: Vec2, end_pos: Vec2, container_rect: &Rect, scale: &GUIScale) -> Rect {
     let position = lerp_vec2(start_pos, end_pos, vec2(0.5, 0.5));
     let size = Vec2::abs((start_pos - position) * 2.0);
 
     Rect { position, size }
-        .combine_rects(&container_rect.offset_size(-vec2(4.0, 4.0)))
+        .combine_rects(&container_rect.offset_size(-scale.vec(vec2(4.0, 4.0))))
         .unwrap_or(Rect {
             position: Vec2::ZERO,
             size: Vec2::ZERO,
@@ -113,6 +188,31 @@ impl GUIContainer for ContainerOne {
         self.name.as_str()
     }
 
+    fn context_menu_items(&self) -> Vec<MenuItem<u32>> {
+        vec![
+            MenuItem::new("Reset colors", ACTION_RESET_COLORS),
+            MenuItem::new("Random colors", ACTION_RANDOM_COLORS),
+        ]
+    }
+
+    fn on_context_menu_action(&mut self, action: u32) {
+        match action {
+            // Asks for confirmation on the next update
+            ACTION_RESET_COLORS => self.reset_requested = true,
+            _ => {
+                for box_color in self.anim_data.target_values.box_color.iter_mut() {
+                    *box_color = HSLA {
+                        h: rwge::rand::random::<f32>() * 360.0,
+                        s: 0.6,
+                        l: 0.5,
+                        a: 1.0,
+                    }
+                    .into();
+                }
+            }
+        }
+    }
+
     fn handle_event(
         &mut self,
         event: &mut UIEvent,
@@ -122,13 +222,36 @@ impl GUIContainer for ContainerOne {
     ) {
         const CONTAINER_MARGIN: f32 = 10.0;
 
+        let scale = control_state.scale;
+        let colors = &get_theme(public_data).colors;
+        let container_margin = scale.px(CONTAINER_MARGIN);
+
         let container_size = container_info.rect.size;
         let mut position_offset = vec2(0.0, 0.0);
         let mut top_left_position = container_info.top_left_position();
 
         match event {
             UIEvent::Render { gui_rects, .. } => {
-                render_container_background(gui_rects, &container_info);
+                render_container_background(gui_rects, &container_info, get_theme(public_data));
+            }
+            UIEvent::Update => {
+                if self.reset_requested {
+                    self.reset_requested = false;
+                    let reset_confirmed = self.reset_confirmed.clone();
+                    control_state.modals.open(ModalDialog::confirm(
+                        "Reset colors",
+                        format!("Reset the color of every box in \"{}\"?", self.name).as_str(),
+                        "Reset",
+                        ModalResponse::Callback(Box::new(move |result| {
+                            reset_confirmed.set(result == ModalResult::Button(1));
+                        })),
+                    ));
+                }
+                if self.reset_confirmed.replace(false) {
+                    for box_color in self.anim_data.target_values.box_color.iter_mut() {
+                        *box_color = self.color;
+                    }
+                }
             }
             _ => {}
         }
@@ -190,6 +313,7 @@ impl GUIContainer for ContainerOne {
                             data.start_position,
                             data.end_position,
                             &container_info.rect,
+                            &scale,
                         );
                         for (box_pos, box_hover) in data
                             .current_values
@@ -221,7 +345,12 @@ impl GUIContainer for ContainerOne {
                 const SLIDER_WIDTH_MIN: f32 = 100.0;
                 const SLIDER_WIDTH_MAX: f32 = 500.0;
 
-                position_offset += vec2(CONTAINER_MARGIN, -CONTAINER_MARGIN);
+                let text_width = scale.px(TEXT_WIDTH);
+                let text_height = scale.px(TEXT_HEIGHT);
+                let slider_width_min = scale.px(SLIDER_WIDTH_MIN);
+                let slider_width_max = scale.px(SLIDER_WIDTH_MAX);
+
+                position_offset += vec2(container_margin, -container_margin);
 
                 //render slider
                 if let UIEvent::Render {
@@ -231,16 +360,22 @@ impl GUIContainer for ContainerOne {
                 {
                     let font_collection = &get_font_collections(public_data)[0];
 
-                    let (font_elem, font_rect) =
-                        create_single_line("Side Margin", 18.0, font_collection, 0, 0.0);
+                    let (font_elem, font_rect) = create_single_line(
+                        "Side Margin",
+                        scale.font(18.0),
+                        font_collection,
+                        0,
+                        0.0,
+                    );
 
-                    let height_offset = -TEXT_HEIGHT * 0.5 - font_rect.height() * 0.5;
+                    let height_offset = -text_height * 0.5 - font_rect.height() * 0.5;
 
                     for element in font_elem {
                         ElementBuilder::new_with_rect(element.rect.offset_position(
                             top_left_position + position_offset + vec2(0.0, height_offset),
                         ))
                         .set_sdffont(element.tx_slice.into())
+                        .set_color(colors.text.into())
                         .set_rect_mask(container_info.rect.into())
                         .build(gui_rects);
                     }
@@ -250,25 +385,25 @@ impl GUIContainer for ContainerOne {
                     let side_margin = (self.value * 100.0).round() / 100.0;
                     let (font_elem, font_rect) = create_single_line(
                         format!("{}", side_margin).as_str(),
-                        18.0,
+                        scale.font(18.0),
                         font_collection,
                         0,
                         0.0,
                     );
 
-                    let height_offset = -TEXT_HEIGHT * 0.5 - font_rect.height() * 0.5;
+                    let height_offset = -text_height * 0.5 - font_rect.height() * 0.5;
 
                     let pos_offset = top_left_position
                         + position_offset
-                        + vec2(label_width + 20.0, height_offset);
+                        + vec2(label_width + scale.px(20.0), height_offset);
 
                     let bg_rect = font_rect;
                     let bg_rect = bg_rect
-                        .offset_size(vec2(20.0, 10.0))
+                        .offset_size(scale.vec(vec2(20.0, 10.0)))
                         .offset_position(pos_offset);
 
                     ElementBuilder::new_with_rect(bg_rect)
-                        .set_color(RGBA::rrr1(0.1).into())
+                        .set_color(colors.control.into())
                         .set_round_rect(BorderRadius::ForAll(bg_rect.height() * 0.5).into())
                         .set_rect_mask(container_info.rect.into())
                         .build(gui_rects);
@@ -276,23 +411,24 @@ impl GUIContainer for ContainerOne {
                     for element in font_elem {
                         ElementBuilder::new_with_rect(element.rect.offset_position(pos_offset))
                             .set_sdffont(element.tx_slice.into())
+                            .set_color(colors.text.into())
                             .set_rect_mask(container_info.rect.into())
                             .build(gui_rects);
                     }
                 }
 
-                position_offset.x += TEXT_WIDTH;
+                position_offset.x += text_width;
 
                 let available_size = container_size.x - position_offset.x;
                 let slider_width =
-                    available_size.max(SLIDER_WIDTH_MIN).min(SLIDER_WIDTH_MAX) - CONTAINER_MARGIN;
+                    available_size.max(slider_width_min).min(slider_width_max) - container_margin;
 
                 let slider_rect = Rect {
                     position: vec2(
                         top_left_position.x + slider_width * 0.5,
-                        top_left_position.y - TEXT_HEIGHT * 0.5,
+                        top_left_position.y - text_height * 0.5,
                     ) + position_offset,
-                    size: vec2(slider_width, TEXT_HEIGHT),
+                    size: vec2(slider_width, text_height),
                 };
 
                 let mut slider_active_id = if self.slider_active_id.is_some()
@@ -309,10 +445,15 @@ impl GUIContainer for ContainerOne {
                     self.value,
                     0.0,
                     600.0,
+                    &SliderOptions::new(),
                     &mut slider_active_id,
                     event,
                     control_state,
+                    public_data,
                 );
+                let slider_id = control_state.get_last_id();
+                control_state.access.set_last_label("Side Margin");
+                tooltip::tooltip(control_state, slider_id, "Margin left of the box grid");
                 if let UIEvent::MouseButton(..) = event {
                     if self.slider_active_id.is_some() && self.slider_instance == self.instance_id {
                         if slider_active_id.is_none() {
@@ -326,33 +467,36 @@ impl GUIContainer for ContainerOne {
                     }
                 }
 
-                top_left_position -= vec2(0.0, TEXT_HEIGHT + CONTAINER_MARGIN);
+                top_left_position -= vec2(0.0, text_height + container_margin);
 
                 const BUTTON_WIDTH: f32 = 150.0;
                 const BUTTON_HEIGHT: f32 = 40.0;
+                let button_width = scale.px(BUTTON_WIDTH);
+                let button_height = scale.px(BUTTON_HEIGHT);
                 let font_collection = &get_font_collections(public_data)[0];
 
+                let accessibility = get_accessibility(public_data);
                 let color_1: RGBA = RGBA::rgb(0.5, 0.2, 0.2);
-                let c_param = get_time(public_data).sin_time(2.0) * 0.5 + 0.5;
+                let c_param = accessibility
+                    .animated(get_time(public_data).sin_time(2.0) * 0.5 + 0.5, 1.0);
                 let color_2: RGBA = RGBA::rgb(lerp_f32(0.8, 1.0, c_param), 0.4, 0.2);
 
                 let b_color_1: RGBA = RGBA::rgb(0.2, 0.2, 0.5);
-                let c_param = get_time(public_data).sin_time(2.0) * 0.5 + 0.5;
                 let b_color_2: RGBA = RGBA::rgb(0.2, 0.4, lerp_f32(0.8, 1.0, c_param));
 
                 if control::button(
                     Rect {
                         position: (top_left_position
-                            + vec2(CONTAINER_MARGIN, -CONTAINER_MARGIN)
-                            + vec2(BUTTON_WIDTH, -BUTTON_HEIGHT) * 0.5),
-                        size: vec2(BUTTON_WIDTH, BUTTON_HEIGHT),
+                            + vec2(container_margin, -container_margin)
+                            + vec2(button_width, -button_height) * 0.5),
+                        size: vec2(button_width, button_height),
                     },
                     container_info.rect,
                     "Randomize",
                     event,
                     control_state,
-                    BorderRadius::ForAll(BUTTON_HEIGHT * 0.5),
-                    20.0,
+                    BorderRadius::ForAll(button_height * 0.5),
+                    scale.font(20.0),
                     font_collection,
                     0,
                     0.0,
@@ -370,14 +514,219 @@ impl GUIContainer for ContainerOne {
                     }
                 }
 
-                top_left_position -= vec2(0.0, BUTTON_HEIGHT + CONTAINER_MARGIN);
+                let randomize_id = control_state.get_last_id();
+                tooltip::tooltip(control_state, randomize_id, "Shuffles the boxes");
+
+                top_left_position -= vec2(0.0, button_height + container_margin);
+
+                const NAME_FIELD_WIDTH: f32 = 200.0;
+                const NAME_FIELD_HEIGHT: f32 = 28.0;
+                let name_field_width = scale.px(NAME_FIELD_WIDTH);
+                let name_field_height = scale.px(NAME_FIELD_HEIGHT);
+                let name_response = text_field::text_field(
+                    Rect {
+                        position: top_left_position
+                            + vec2(container_margin, -container_margin)
+                            + vec2(name_field_width, -name_field_height) * 0.5,
+                        size: vec2(name_field_width, name_field_height),
+                    },
+                    container_info.rect,
+                    &mut self.name_field,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                // The tab keeps its last name while the field is empty
+                if name_response.changed && !self.name_field.text().trim().is_empty() {
+                    self.name = String::from(self.name_field.text());
+                }
+                let name_field_id = control_state.get_last_id();
+                tooltip::tooltip(control_state, name_field_id, "Name of the tab");
+
+                top_left_position -= vec2(0.0, name_field_height + container_margin);
+
+                const COLOR_DROPDOWN_WIDTH: f32 = 200.0;
+                const COLOR_DROPDOWN_HEIGHT: f32 = 28.0;
+                let color_dropdown_width = scale.px(COLOR_DROPDOWN_WIDTH);
+                let color_dropdown_height = scale.px(COLOR_DROPDOWN_HEIGHT);
+                let theme = get_theme(public_data);
+                let font_size = control_state.scale.font(theme.fonts.size);
+                let theme_font_collection =
+                    &get_font_collections(public_data)[theme.fonts.collection];
+                let preset_names: Vec<&str> = std::iter::once("Default")
+                    .chain(COLOR_PRESETS.iter().map(|(name, _)| *name))
+                    .collect();
+                let preset_colors: Vec<RGBA> = (0..preset_names.len())
+                    .map(|index| self.preset_color(index))
+                    .collect();
+                // Every option shows a swatch of its color before its name
+                let color_item = |index: usize, item_rect: Rect| {
+                    let swatch_size = item_rect.height() * 0.6;
+                    let mut elements = vec![ElementBuilder::new(
+                        vec2(
+                            item_rect.left_position().x + swatch_size * 0.5,
+                            item_rect.position.y,
+                        ),
+                        Vec2::splat(swatch_size),
+                    )
+                    .set_color(preset_colors[index].into())
+                    .set_round_rect(BorderRadius::ForAll(swatch_size * 0.25).into())];
+                    let (font_elements, label_rect) = create_single_line(
+                        preset_names[index],
+                        font_size,
+                        theme_font_collection,
+                        0,
+                        0.0,
+                    );
+                    let label_origin = vec2(
+                        item_rect.left_position().x + swatch_size * 1.5,
+                        item_rect.position.y - label_rect.height() * 0.5,
+                    )
+                    .round();
+                    for element in font_elements {
+                        elements.push(
+                            ElementBuilder::new_with_rect(
+                                element.rect.offset_position(label_origin),
+                            )
+                            .set_sdffont(element.tx_slice.into())
+                            .set_color(theme.colors.text.into()),
+                        );
+                    }
+                    elements
+                };
+                let color_preset = dropdown::dropdown_custom(
+                    Rect {
+                        position: top_left_position
+                            + vec2(container_margin, -container_margin)
+                            + vec2(color_dropdown_width, -color_dropdown_height) * 0.5,
+                        size: vec2(color_dropdown_width, color_dropdown_height),
+                    },
+                    container_info.rect,
+                    "Box color",
+                    &preset_names,
+                    self.color_preset,
+                    &mut self.color_dropdown,
+                    &color_item,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                if color_preset != self.color_preset {
+                    self.color_preset = color_preset;
+                    self.color = preset_colors[color_preset];
+                    for box_color in self.anim_data.target_values.box_color.iter_mut() {
+                        *box_color = self.color;
+                    }
+                }
+
+                // Any other color is picked next to the presets
+                const COLOR_BUTTON_WIDTH: f32 = 48.0;
+                let color_button_width = scale.px(COLOR_BUTTON_WIDTH);
+                let picked_color = color_picker::color_button(
+                    Rect {
+                        position: top_left_position
+                            + vec2(color_dropdown_width + container_margin * 2.0, -container_margin)
+                            + vec2(color_button_width, -color_dropdown_height) * 0.5,
+                        size: vec2(color_button_width, color_dropdown_height),
+                    },
+                    container_info.rect,
+                    "Custom box color",
+                    self.color,
+                    &mut self.color_picker,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                if let Some(color) = picked_color {
+                    self.color = color;
+                    for box_color in self.anim_data.target_values.box_color.iter_mut() {
+                        *box_color = self.color;
+                    }
+                }
+
+                top_left_position -= vec2(0.0, color_dropdown_height + container_margin);
+
+                const OFFSET_EDITOR_WIDTH: f32 = 200.0;
+                const OFFSET_EDITOR_HEIGHT: f32 = 24.0;
+                let offset_editor_width = scale.px(OFFSET_EDITOR_WIDTH);
+                let offset_editor_height = scale.px(OFFSET_EDITOR_HEIGHT);
+                self.grid_offset = drag_value::vec2_editor(
+                    Rect {
+                        position: top_left_position
+                            + vec2(container_margin, -container_margin)
+                            + vec2(offset_editor_width, -offset_editor_height) * 0.5,
+                        size: vec2(offset_editor_width, offset_editor_height),
+                    },
+                    container_info.rect,
+                    "Grid offset",
+                    self.grid_offset,
+                    &mut self.grid_offset_editor,
+                    event,
+                    control_state,
+                    public_data,
+                );
+
+                top_left_position -= vec2(0.0, offset_editor_height + container_margin);
+
+                const OPTION_WIDTH: f32 = 200.0;
+                const OPTION_HEIGHT: f32 = 24.0;
+                let option_width = scale.px(OPTION_WIDTH);
+                let option_height = scale.px(OPTION_HEIGHT);
+                let option_rect = |top_left_position: Vec2, rows: usize| {
+                    let size = vec2(option_width, option_height * rows as f32);
+                    Rect {
+                        position: top_left_position
+                            + vec2(container_margin, -container_margin)
+                            + vec2(size.x, -size.y) * 0.5,
+                        size,
+                    }
+                };
+                self.spin_boxes = choice::checkbox(
+                    option_rect(top_left_position, 1),
+                    container_info.rect,
+                    "Spin boxes",
+                    self.spin_boxes,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                top_left_position -= vec2(0.0, option_height + container_margin);
+
+                choice::toggle_switch(
+                    option_rect(top_left_position, 1),
+                    container_info.rect,
+                    "Gradients",
+                    &mut self.gradients_switch,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                top_left_position -= vec2(0.0, option_height + container_margin);
+
+                let size_names: Vec<&str> = BOX_SIZES.iter().map(|(name, _)| *name).collect();
+                self.box_size_index = choice::radio_group(
+                    option_rect(top_left_position, size_names.len()),
+                    container_info.rect,
+                    "Box size",
+                    &size_names,
+                    self.box_size_index,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                top_left_position -=
+                    vec2(0.0, option_height * size_names.len() as f32 + container_margin);
             }
 
             // Grid component?
-            const GRID_RECT_SIZE: f32 = 30.0;
             const GRID_RECT_PADDING: f32 = 10.0;
             const GRID_MARGIN: f32 = CONTAINER_MARGIN;
 
+            let grid_rect_size = scale.px(BOX_SIZES[self.box_size_index].1);
+            let grid_rect_padding = scale.px(GRID_RECT_PADDING);
+            let grid_margin = scale.px(GRID_MARGIN);
+            let side_margin = scale.px(self.value);
+
             let mut controls = vec![Uiid::default(); self.count as usize];
 
             for control in controls.iter_mut() {
@@ -404,24 +753,25 @@ impl GUIContainer for ContainerOne {
                 let anim_data = &mut self.anim_data;
                 //Update animation values
                 // Generate rectangle positions
-                let allowed_horizontal_size = container_size.x - (GRID_MARGIN * 2.0) - self.value;
+                let allowed_horizontal_size = container_size.x - (grid_margin * 2.0) - side_margin;
 
                 let horizontal_rect_count = (allowed_horizontal_size
-                    / (GRID_RECT_SIZE + GRID_RECT_PADDING))
+                    / (grid_rect_size + grid_rect_padding))
                     .floor()
                     .max(1.0);
                 let required_rect_size = (allowed_horizontal_size / horizontal_rect_count)
-                    .max(GRID_RECT_SIZE + GRID_RECT_PADDING);
+                    .max(grid_rect_size + grid_rect_padding);
 
-                let v_scaler = ((container_info.rect.size.x - 2.0 * GRID_MARGIN - GRID_RECT_SIZE)
-                    / (self.value.max(0.1)))
+                let v_scaler = ((container_info.rect.size.x - 2.0 * grid_margin - grid_rect_size)
+                    / (side_margin.max(0.1)))
                 .min(1.0);
                 let start_position = top_left_position
-                    + vec2(GRID_MARGIN + self.value * 0.5 * v_scaler, -GRID_MARGIN);
+                    + vec2(grid_margin + side_margin * 0.5 * v_scaler, -grid_margin)
+                    + scale.vec(self.grid_offset);
 
                 let horizontal_rect_count = horizontal_rect_count as u32;
                 let size_padded = required_rect_size;
-                let size_elem = required_rect_size - GRID_RECT_PADDING;
+                let size_elem = required_rect_size - grid_rect_padding;
 
                 anim_data.target_values.box_size = size_elem;
                 for (index, target) in anim_data.target_values.box_positions.iter_mut().enumerate()
@@ -436,11 +786,14 @@ impl GUIContainer for ContainerOne {
 
                 //println!("Current delta time {}", (get_time(public_data).delta_time_millis));
                 let anim_scaler = (get_time(public_data).delta_time_millis.0) / 11.0;
+                let accessibility = get_accessibility(public_data);
+                let move_factor = accessibility.transition(0.1 * anim_scaler);
+                let color_factor = accessibility.transition(0.05 * anim_scaler);
                 //Update current values
                 anim_data.current_values.box_size = lerp_f32(
                     anim_data.current_values.box_size,
                     anim_data.target_values.box_size,
-                    0.1 * anim_scaler,
+                    move_factor,
                 );
                 for (current, target) in anim_data
                     .current_values
@@ -448,7 +801,7 @@ impl GUIContainer for ContainerOne {
                     .iter_mut()
                     .zip(anim_data.target_values.box_positions.iter())
                 {
-                    *current = Vec2::lerp(*current, *target, 0.1 * anim_scaler);
+                    *current = Vec2::lerp(*current, *target, move_factor);
                 }
 
                 for (current, target) in anim_data
@@ -457,12 +810,13 @@ impl GUIContainer for ContainerOne {
                     .iter_mut()
                     .zip(anim_data.target_values.box_color.iter())
                 {
-                    *current = current.lerp_rgba(target, 0.05 * anim_scaler);
+                    *current = current.lerp_rgba(target, color_factor);
                 }
             }
 
             if let UIEvent::Update = event {
                 let anim_data = &mut self.anim_data.current_values;
+                let accessibility = get_accessibility(public_data);
                 for (index, position) in anim_data.box_positions.iter().enumerate() {
                     let control_id = controls[index];
 
@@ -472,6 +826,7 @@ impl GUIContainer for ContainerOne {
                         .sin_time_phase(6.0, (i as f32) / 2.0)
                         * 0.5
                         + 0.5;
+                    let scaler_param = accessibility.animated(scaler_param, 1.0);
                     let size_elem_anim =
                         lerp_f32(anim_data.box_size * 0.5, anim_data.box_size, scaler_param);
 
@@ -495,6 +850,7 @@ impl GUIContainer for ContainerOne {
                 let instance_data = &mut self.anim_data;
                 let ref anim_data = instance_data.current_values;
                 let box_size = anim_data.box_size;
+                let accessibility = get_accessibility(public_data);
                 for (control_index, (((position, color), select_hover), index)) in anim_data
                     .box_positions
                     .iter()
@@ -512,20 +868,24 @@ impl GUIContainer for ContainerOne {
                     {
                         (
                             vec2(anim_data.box_size, anim_data.box_size),
-                            5.0,
+                            scale.px(5.0),
                             *color * 2.0,
                         )
                     } else if *select_hover {
                         let hover_scale_param = get_time(public_data).sin_time(4.0) * 0.5 + 0.5;
-                        let hover_size =
-                            lerp_f32(box_size * 0.75, box_size * 1.15, hover_scale_param);
-                        (vec2(hover_size, hover_size), 5.0, *color)
+                        let hover_size = accessibility.animated(
+                            lerp_f32(box_size * 0.75, box_size * 1.15, hover_scale_param),
+                            box_size * 1.15,
+                        );
+                        (vec2(hover_size, hover_size), scale.px(5.0), *color)
                     } else {
                         let s_phase = (i as f32) / 2.0;
-                        let scaler_param =
-                            get_time(public_data).sin_time_phase(6.0, s_phase) * 0.5 + 0.5;
+                        let scaler_param = accessibility.animated(
+                            get_time(public_data).sin_time_phase(6.0, s_phase) * 0.5 + 0.5,
+                            1.0,
+                        );
                         let size_elem_anim = lerp_f32(box_size * 0.5, box_size, scaler_param);
-                        (vec2(size_elem_anim, size_elem_anim), 5.0, *color)
+                        (vec2(size_elem_anim, size_elem_anim), scale.px(5.0), *color)
                     };
 
                     let box_rect = Rect {
@@ -535,7 +895,7 @@ impl GUIContainer for ContainerOne {
 
                     if box_rect.position.y < container_info.rect.position.y {
                         if !box_rect
-                            .offset_size(vec2(10.0, 10.0))
+                            .offset_size(scale.vec(vec2(10.0, 10.0)))
                             .intersecting_rect(&container_info.rect)
                         {
                             continue;
@@ -548,7 +908,11 @@ impl GUIContainer for ContainerOne {
                         .set_round_rect(BorderRadius::ForAll(roundness).into());
 
                     element_builder = {
-                        if i % 2 == 0 && !control_state.is_hovered(control_id) && !select_hover {
+                        if self.spin_boxes
+                            && i % 2 == 0
+                            && !control_state.is_hovered(control_id)
+                            && !select_hover
+                        {
                             let rot =
                                 get_engine_data(public_data).time.time * (2.0 + (i % 7) as f32);
                             element_builder.set_rotation(rot)
@@ -558,7 +922,9 @@ impl GUIContainer for ContainerOne {
                     }
                     .set_ui_mask((control_index % 256) as u16);
 
-                    if i % 4 == 0 {
+                    if !self.gradients_switch.on {
+                        element_builder
+                    } else if i % 4 == 0 {
                         let linear_gradient = LinearGradient {
                             colors: [box_color, box_color * 0.5],
                             start_position: vec2(0.0, rect_size.y * 0.5),
@@ -594,13 +960,14 @@ impl GUIContainer for ContainerOne {
                             data.start_position,
                             data.end_position,
                             &container_info.rect,
+                            &scale,
                         );
 
                         ElementBuilder::new_with_rect(select_rect)
-                            .set_color(RGBA::GREEN.set_alpha(0.2).into())
+                            .set_color(colors.accent.set_alpha(0.2).into())
                             .set_border(Some(Border {
-                                size: 2,
-                                color: RGBA::GREEN.set_alpha(0.5).into(),
+                                size: scale.border(2),
+                                color: colors.accent.set_alpha(0.5).into(),
                             }))
                             .set_rect_mask(container_info.rect.into())
                             .build(gui_rects);
@@ -615,26 +982,34 @@ impl GUIContainer for ContainerOne {
 
                         let font_collection = &get_font_collections(public_data)[0];
                         let text = format!("count {hover_count}");
-                        let (font_elems, font_rect) =
-                            create_single_line(text.as_str(), 16.0, font_collection, 0, 0.0);
+                        let (font_elems, font_rect) = create_single_line(
+                            text.as_str(),
+                            scale.font(16.0),
+                            font_collection,
+                            0,
+                            0.0,
+                        );
                         const TEXT_PADDING_H: f32 = 12.0;
                         const TEXT_PADDING_V: f32 = 6.0;
 
+                        let text_padding_h = scale.px(TEXT_PADDING_H);
+                        let text_padding_v = scale.px(TEXT_PADDING_V);
+
                         let mut label_box_elements = Vec::new();
                         label_box_elements.push(
                             ElementBuilder::new_with_rect(Rect {
                                 position: select_rect.position,
                                 size: font_rect.size
-                                    + vec2(TEXT_PADDING_H * 2.0, TEXT_PADDING_V * 2.0),
+                                    + vec2(text_padding_h * 2.0, text_padding_v * 2.0),
                             })
-                            .set_color(RGBA::rrr1(0.15).into())
+                            .set_color(colors.panel.into())
                             .set_round_rect(
-                                BorderRadius::ForAll(TEXT_PADDING_V + font_rect.size.y * 0.5)
+                                BorderRadius::ForAll(text_padding_v + font_rect.size.y * 0.5)
                                     .into(),
                             )
                             .set_border(Some(Border {
-                                size: 2,
-                                color: RGBA::WHITE.into(),
+                                size: scale.border(2),
+                                color: colors.border.into(),
                             })),
                         );
 
@@ -645,7 +1020,8 @@ impl GUIContainer for ContainerOne {
                                         select_rect.position - font_rect.size * 0.5,
                                     ),
                                 )
-                                .set_sdffont(elem.tx_slice.into()),
+                                .set_sdffont(elem.tx_slice.into())
+                                .set_color(colors.text.into()),
                             );
                         }
 
@@ -655,7 +1031,9 @@ impl GUIContainer for ContainerOne {
                                     elem.build(gui_rects);
                                 }
                             }),
-                            container_info.depth_range.0 + depth_offset::SELECT_COUNT,
+                            container_info
+                                .layers
+                                .depth(Layer::ContainerContent, depth_offset::SELECT_COUNT),
                         )
                     }
                 }
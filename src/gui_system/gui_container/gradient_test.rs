@@ -0,0 +1,75 @@
+use rwge::{
+    color::RGBA,
+    glam::{vec2, Vec2},
+    gui::rect_ui::{event::UIEvent, Rect},
+};
+
+use crate::{
+    gui_system::{
+        control::{
+            gradient_editor::{self, GradientEditor, GradientValue},
+            ControlState,
+        },
+        theme::get_theme,
+        ContainerInfo,
+    },
+    runtime_data::PublicData,
+};
+
+use super::{render_container_background, GUIContainer};
+
+const MARGIN: f32 = 10.0;
+
+/// Playground for tuning gradients before pasting them in the code or a theme
+pub struct GradientTest {
+    editor: GradientEditor,
+}
+
+impl GradientTest {
+    pub fn new() -> Self {
+        Self {
+            editor: GradientEditor::new(GradientValue::linear(
+                [RGBA::new(0.9, 0.3, 0.4, 1.0), RGBA::new(0.2, 0.4, 0.9, 1.0)],
+                vec2(-100.0, 60.0),
+                vec2(100.0, -60.0),
+            )),
+        }
+    }
+}
+
+impl GUIContainer for GradientTest {
+    fn get_name(&self) -> &str {
+        "Gradients"
+    }
+
+    fn get_description(&self) -> Option<&str> {
+        Some("Drag the points and radii of a gradient and copy it as code")
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+        container_info: ContainerInfo,
+        control_state: &mut ControlState,
+    ) {
+        if let UIEvent::Render { gui_rects, .. } = event {
+            render_container_background(gui_rects, &container_info, get_theme(public_data));
+        }
+
+        let margin = control_state.scale.px(MARGIN);
+        let editor_rect = Rect {
+            position: container_info.rect.position,
+            size: (container_info.rect.size - Vec2::splat(margin * 2.0)).max(Vec2::ZERO),
+        };
+        gradient_editor::gradient_editor(
+            editor_rect,
+            container_info.rect,
+            "Gradient",
+            &mut self.editor,
+            event,
+            control_state,
+            public_data,
+        );
+    }
+}
@@ -1,7 +1,11 @@
 pub mod container_one;
+pub mod gradient_test;
 pub mod performance_monitor;
+pub mod table_test;
 pub mod text_layout_test;
 pub mod text_animation;
+pub mod text_writer;
+pub mod tree_test;
 
 use std::any::Any;
 
@@ -13,10 +17,14 @@ use rwge::{
 
 use crate::{as_any::AsAny, runtime_data::{ PublicData}};
 
-use super::{control::ControlState, ContainerInfo, window_layout::GUI_ACTIVE_COLOR};
+use super::{control::ControlState, popup::MenuItem, theme::Theme, ContainerInfo};
 
 pub trait GUIContainer: AsAny {
     fn get_name(&self) -> &str;
+    /// Shown on the tooltip of the container tab, under the name
+    fn get_description(&self) -> Option<&str> {
+        None
+    }
     fn handle_event(
         &mut self,
         event: &mut UIEvent,
@@ -24,6 +32,13 @@ pub trait GUIContainer: AsAny {
         container_info: ContainerInfo,
         control_state: &mut ControlState,
     );
+
+    /// Items shown when right clicking the container or its tab. The selected action is sent
+    /// back through `on_context_menu_action`.
+    fn context_menu_items(&self) -> Vec<MenuItem<u32>> {
+        Vec::new()
+    }
+    fn on_context_menu_action(&mut self, _action: u32) {}
 }
 
 impl<T: GUIContainer + 'static> AsAny for T {
@@ -36,9 +51,13 @@ impl<T: GUIContainer + 'static> AsAny for T {
     }
 }
 
-pub fn render_container_background(gui_rects: &mut GUIRects, container_info: &ContainerInfo) {
+pub fn render_container_background(
+    gui_rects: &mut GUIRects,
+    container_info: &ContainerInfo,
+    theme: &Theme,
+) {
     ElementBuilder::new(container_info.rect.position, container_info.rect.size)
-        .set_color(GUI_ACTIVE_COLOR.into())
+        .set_color(theme.colors.background.into())
         .set_rect_mask(
             container_info.rect
             .into(),
@@ -13,7 +13,9 @@ use rwge::{
 };
 
 use crate::{
-    gui_system::gui_container::render_container_background,
+    gui_system::{
+        control::scale::GUIScale, gui_container::render_container_background, theme::get_theme,
+    },
     runtime_data::{utils::get_engine_data, RuntimeData, PublicData},
 };
 
@@ -100,6 +102,10 @@ impl GUIContainer for PerformanceMonitor {
         "Perf Monitor"
     }
 
+    fn get_description(&self) -> Option<&str> {
+        Some("Frame, render and update times averaged over 60 frames")
+    }
+
     fn handle_event(
         &mut self,
         event: &mut rwge::gui::rect_ui::event::UIEvent,
@@ -118,6 +124,11 @@ impl GUIContainer for PerformanceMonitor {
 
         const CHAR_SPACING: f32 = 0.05;
 
+        let scale = control_state.scale;
+        let margin = scale.px(MARGIN);
+        let column_height = scale.px(COLUMN_HEIGHT);
+        let box_margin = scale.px(BOX_MARGIN);
+
         match event {
             rwge::gui::rect_ui::event::UIEvent::Update => {
                 let op_time = &get_engine_data(public_data).operation_time;
@@ -135,18 +146,24 @@ impl GUIContainer for PerformanceMonitor {
                 gui_rects,
                 extra_render_steps,
             } => {
-                render_container_background(gui_rects, &container_info);
+                let theme = get_theme(public_data);
+                render_container_background(gui_rects, &container_info, theme);
 
                 let mut top_left_box_pos =
-                    container_info.top_left_position() + vec2(MARGIN, -MARGIN);
+                    container_info.top_left_position() + vec2(margin, -margin);
 
                 let font_collection =
                     &public_data.get::<Vec<FontCollection>>().unwrap()[0];
 
                 let rect_mask_data_index = push_rect_mask(container_info.rect, gui_rects) as u16;
 
-                let (font_elems, font_rect) =
-                    create_single_line("Average Frame Time (ms)", 18.0, font_collection, 0, CHAR_SPACING);
+                let (font_elems, font_rect) = create_single_line(
+                    "Average Frame Time (ms)",
+                    scale.font(18.0),
+                    font_collection,
+                    0,
+                    CHAR_SPACING,
+                );
 
                 for elem in font_elems {
                     let char_rect = elem
@@ -155,20 +172,21 @@ impl GUIContainer for PerformanceMonitor {
                         .offset_position(-vec2(0.0, font_rect.height()));
                     ElementBuilder::new_with_rect(char_rect)
                         .set_sdffont(elem.tx_slice.into())
+                        .set_color(theme.colors.text.into())
                         .set_rect_mask(rect_mask_data_index.into())
                         .build(gui_rects);
                 }
 
-                top_left_box_pos.x += MARGIN;
-                top_left_box_pos.y -= MARGIN + font_rect.height();
+                top_left_box_pos.x += margin;
+                top_left_box_pos.y -= margin + font_rect.height();
 
-                let color_index = push_color(gui_rects, RGBA::rrr1(0.75)) as u16;
+                let color_index = push_color(gui_rects, theme.colors.text_muted) as u16;
 
                 {
                     let current_avg = self.render_timer.get_most_recent_two_dec();
                     let (font_elems, font_rect) = create_single_line(
                         format!("Average computed over 60 frames",).as_str(),
-                        14.0,
+                        scale.font(14.0),
                         font_collection,
                         1,
                         CHAR_SPACING,
@@ -181,18 +199,19 @@ impl GUIContainer for PerformanceMonitor {
                             .offset_position(-vec2(0.0, font_rect.height()));
                         ElementBuilder::new_with_rect(char_rect)
                             .set_sdffont(elem.tx_slice.into())
+                            .set_color(color_index.into())
                             .set_rect_mask(rect_mask_data_index.into())
                             .build(gui_rects);
                     }
 
-                    top_left_box_pos.y -= MARGIN + font_rect.height();
+                    top_left_box_pos.y -= margin + font_rect.height();
                 }
 
                 {
                     let current_avg = self.render_timer.get_most_recent_two_dec();
                     let (font_elems, font_rect) = create_single_line(
                         format!("Render Avg. {current_avg} (ms)",).as_str(),
-                        18.0,
+                        scale.font(18.0),
                         font_collection,
                         0,
                         CHAR_SPACING,
@@ -210,14 +229,14 @@ impl GUIContainer for PerformanceMonitor {
                             .build(gui_rects);
                     }
 
-                    top_left_box_pos.y -= MARGIN + font_rect.height();
+                    top_left_box_pos.y -= margin + font_rect.height();
                 }
 
                 {
                     let current_avg = self.gpu_lock_time.get_most_recent_two_dec();
                     let (font_elems, font_rect) = create_single_line(
                         format!("GPU lock Avg. {current_avg} (ms)",).as_str(),
-                        18.0,
+                        scale.font(18.0),
                         font_collection,
                         0,
                         CHAR_SPACING,
@@ -235,14 +254,14 @@ impl GUIContainer for PerformanceMonitor {
                             .build(gui_rects);
                     }
 
-                    top_left_box_pos.y -= MARGIN + font_rect.height();
+                    top_left_box_pos.y -= margin + font_rect.height();
                 }
 
                 {
                     let current_avg = self.cpu_timer.get_most_recent_two_dec();
                     let (font_elems, font_rect) = create_single_line(
                         format!("Update + W Event Avg. {current_avg} (ms)",).as_str(),
-                        18.0,
+                        scale.font(18.0),
                         font_collection,
                         0,
                         CHAR_SPACING,
@@ -260,15 +279,15 @@ impl GUIContainer for PerformanceMonitor {
                             .build(gui_rects);
                     }
 
-                    top_left_box_pos.y -= MARGIN + font_rect.height();
+                    top_left_box_pos.y -= margin + font_rect.height();
                 }
 
                 {
-                    top_left_box_pos.y -= MARGIN;
+                    top_left_box_pos.y -= margin;
                     let current_avg = self.frame_timer.get_most_recent_two_dec();
                     let (font_elems, font_rect) = create_single_line(
                         format!("Total frame time avg. {current_avg} (ms)",).as_str(),
-                        20.0,
+                        scale.font(20.0),
                         font_collection,
                         1,
                         CHAR_SPACING,
@@ -281,21 +300,24 @@ impl GUIContainer for PerformanceMonitor {
                             .offset_position(-vec2(0.0, font_rect.height()));
                         ElementBuilder::new_with_rect(char_rect)
                             .set_sdffont(elem.tx_slice.into())
+                            .set_color(theme.colors.text.into())
                             .set_rect_mask(rect_mask_data_index.into())
                             .build(gui_rects);
                     }
 
-                    top_left_box_pos.y -= MARGIN + font_rect.height();
+                    top_left_box_pos.y -= margin + font_rect.height();
                 }
 
-                top_left_box_pos.x -= MARGIN;
+                top_left_box_pos.x -= margin;
 
-                let mut box_width = container_info.rect.size.x - (MARGIN * 2.0);
-                box_width = box_width.max(MIN_BOX_WIDTH).min(MAX_BOX_WIDTH);
+                let mut box_width = container_info.rect.size.x - (margin * 2.0);
+                box_width = box_width
+                    .max(scale.px(MIN_BOX_WIDTH))
+                    .min(scale.px(MAX_BOX_WIDTH));
 
                 let box_center =
-                    top_left_box_pos + vec2(box_width * 0.5, -(COLUMN_HEIGHT * 0.5 + BOX_MARGIN));
-                let box_size = vec2(box_width, COLUMN_HEIGHT + BOX_MARGIN * 2.0);
+                    top_left_box_pos + vec2(box_width * 0.5, -(column_height * 0.5 + box_margin));
+                let box_size = vec2(box_width, column_height + box_margin * 2.0);
 
                 let box_rect = Rect {
                     position: box_center,
@@ -303,30 +325,30 @@ impl GUIContainer for PerformanceMonitor {
                 };
 
                 ElementBuilder::new_with_rect(box_rect)
-                    .set_round_rect(BorderRadius::ForAll(5.0).into())
-                    .set_color(RGBA::rrr1(0.1).into())
+                    .set_round_rect(BorderRadius::ForAll(scale.px(5.0)).into())
+                    .set_color(theme.colors.panel.into())
                     .set_rect_mask(container_info.rect.into())
                     .set_border(Some(Border {
-                        size: 1,
-                        color: RGBA::rrr1(0.75).into(),
+                        size: scale.border(1),
+                        color: theme.colors.border.into(),
                     }))
                     .build(gui_rects);
 
                 let bars_rect = box_rect;
-                let bars_rect = bars_rect.offset_size(-Vec2::splat(BOX_MARGIN * 2.00));
+                let bars_rect = bars_rect.offset_size(-Vec2::splat(box_margin * 2.00));
 
                 let mask_rect = box_rect;
                 let mask_rect = container_info.rect.combine_rects(&mask_rect);
 
                 let avg_iter = self.frame_timer.get_average_times();
-                bar_graph(bars_rect, &avg_iter, event, mask_rect.unwrap_or_default());
+                bar_graph(bars_rect, &avg_iter, event, mask_rect.unwrap_or_default(), &scale);
             }
             _ => {}
         }
     }
 }
 
-fn bar_graph(rect: Rect, values: &[f32], event: &mut UIEvent, mask: Rect) {
+fn bar_graph(rect: Rect, values: &[f32], event: &mut UIEvent, mask: Rect, scale: &GUIScale) {
     const MIN_BOX_WIDTH: f32 = 10.0;
     const GAP_SIZE: f32 = 5.0;
 
@@ -340,11 +362,12 @@ fn bar_graph(rect: Rect, values: &[f32], event: &mut UIEvent, mask: Rect) {
     //bar cound = bc
     //bar gap width = bgw
 
-    let mbw = MIN_BOX_WIDTH + GAP_SIZE;
+    let gap_size = scale.px(GAP_SIZE);
+    let mbw = scale.px(MIN_BOX_WIDTH) + gap_size;
     let bc = rect.width() / mbw;
     let bc = bc.floor().min(values.len() as f32);
 
-    let bw = (rect.width() - (bc - 1.0) * GAP_SIZE) / bc;
+    let bw = (rect.width() - (bc - 1.0) * gap_size) / bc;
 
     let test_iter = values.iter();
 
@@ -367,7 +390,7 @@ fn bar_graph(rect: Rect, values: &[f32], event: &mut UIEvent, mask: Rect) {
                         position: bot_left_pos + vec2(horizontal_pos + bw * 0.5, height * 0.5),
                         size: vec2(bw, height),
                     };
-                    horizontal_pos += bw + GAP_SIZE;
+                    horizontal_pos += bw + gap_size;
                     ElementBuilder::new_with_rect(bar_rect)
                         .set_rect_mask(mask.into())
                         .set_linear_gradient(
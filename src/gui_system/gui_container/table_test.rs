@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+
+use rwge::{
+    glam::{vec2, Vec2},
+    gui::rect_ui::{event::UIEvent, Rect},
+};
+
+use crate::{
+    gui_system::{
+        control::{
+            drag_value::{self, DragValue},
+            table::{self, Table, TableChange, TableColumn, TableRowId, TableSource},
+            text_field::{self, TextField},
+            ControlState,
+        },
+        theme::get_theme,
+        ContainerInfo,
+    },
+    runtime_data::PublicData,
+};
+
+use super::{render_container_background, GUIContainer};
+
+const MARGIN: f32 = 10.0;
+const ENTITY_COUNT: usize = 5000;
+const KINDS: [&str; 5] = ["Player", "Enemy", "Prop", "Light", "Camera"];
+
+const ID_COLUMN: usize = 0;
+const NAME_COLUMN: usize = 1;
+const KIND_COLUMN: usize = 2;
+const HEALTH_COLUMN: usize = 3;
+
+struct Entity {
+    id: u32,
+    name: String,
+    kind: &'static str,
+    health: f32,
+    position: Vec2,
+}
+
+/// Generated entities, the same ones on every run
+struct EntityList {
+    entities: Vec<Entity>,
+}
+
+impl EntityList {
+    fn new() -> Self {
+        let entities = (0..ENTITY_COUNT)
+            .map(|index| {
+                // Cheap hash so the values look scattered
+                let seed = (index as u32).wrapping_mul(2_654_435_761);
+                let kind = KINDS[(seed >> 4) as usize % KINDS.len()];
+                Entity {
+                    id: index as u32,
+                    name: format!("{}_{:04}", kind.to_lowercase(), index),
+                    kind,
+                    health: (seed % 1001) as f32 / 10.0,
+                    position: vec2(
+                        ((seed >> 8) % 2001) as f32 / 10.0 - 100.0,
+                        ((seed >> 16) % 2001) as f32 / 10.0 - 100.0,
+                    ),
+                }
+            })
+            .collect();
+        Self { entities }
+    }
+}
+
+impl TableSource for EntityList {
+    fn row_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    fn row_id(&self, row: usize) -> TableRowId {
+        self.entities[row].id as TableRowId
+    }
+
+    fn cell_text(&self, row: usize, column: usize) -> String {
+        let entity = &self.entities[row];
+        match column {
+            ID_COLUMN => entity.id.to_string(),
+            NAME_COLUMN => entity.name.clone(),
+            KIND_COLUMN => String::from(entity.kind),
+            HEALTH_COLUMN => format!("{:.1}", entity.health),
+            _ => format!("{:.1}, {:.1}", entity.position.x, entity.position.y),
+        }
+    }
+
+    /// Numbers are compared as numbers, not as text
+    fn compare(&self, a: usize, b: usize, column: usize) -> Ordering {
+        let (a, b) = (&self.entities[a], &self.entities[b]);
+        match column {
+            ID_COLUMN => a.id.cmp(&b.id),
+            NAME_COLUMN => a.name.cmp(&b.name),
+            KIND_COLUMN => a.kind.cmp(b.kind),
+            HEALTH_COLUMN => a.health.partial_cmp(&b.health).unwrap_or(Ordering::Equal),
+            _ => a
+                .position
+                .length()
+                .partial_cmp(&b.position.length())
+                .unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+/// Widget drawn over the edited cell
+enum CellEditor {
+    Name(TextField),
+    Health(DragValue),
+}
+
+/// Playground for the table, thousands of entities where only the visible rows are built
+pub struct TableTest {
+    entities: EntityList,
+    table: Table,
+    editor: Option<CellEditor>,
+    /// The editor takes the focus on the first update after it was created
+    focus_editor: bool,
+}
+
+impl TableTest {
+    pub fn new() -> Self {
+        Self {
+            entities: EntityList::new(),
+            table: Table::new(vec![
+                TableColumn::new("Id", 60.0),
+                TableColumn::new("Name", 140.0).set_editable(true),
+                TableColumn::new("Kind", 90.0),
+                TableColumn::new("Health", 80.0).set_editable(true),
+                TableColumn::new("Position", 120.0).set_min_width(60.0),
+            ]),
+            editor: None,
+            focus_editor: false,
+        }
+    }
+
+    fn start_editing(&mut self, row: usize, column: usize) {
+        self.editor = match column {
+            NAME_COLUMN => {
+                let mut field = TextField::new(&self.entities.entities[row].name);
+                field.editor.select_all();
+                Some(CellEditor::Name(field))
+            }
+            HEALTH_COLUMN => {
+                let mut field = DragValue::new();
+                field.step = 0.5;
+                field.precision = 1;
+                field.range = Some((0.0, 100.0));
+                Some(CellEditor::Health(field))
+            }
+            _ => None,
+        };
+        self.focus_editor = true;
+    }
+
+    /// Edits are written to the entity as they happen, the rows are sorted again at the end
+    fn edit_cell(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+        control_state: &mut ControlState,
+    ) {
+        let (edit, editor) = match (self.table.editing(), self.editor.as_mut()) {
+            (Some(edit), Some(editor)) => (edit, editor),
+            (Some(_), None) => {
+                // The column has no editor
+                self.table.stop_editing(false);
+                return;
+            }
+            _ => {
+                self.editor = None;
+                return;
+            }
+        };
+        let entity = &mut self.entities.entities[edit.row];
+        let submitted = match editor {
+            CellEditor::Name(field) => {
+                let response = text_field::text_field(
+                    edit.rect,
+                    edit.mask,
+                    field,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                if response.changed {
+                    entity.name = String::from(field.text());
+                }
+                response.submitted
+            }
+            CellEditor::Health(field) => {
+                entity.health = drag_value::drag_value(
+                    edit.rect,
+                    edit.mask,
+                    "Health",
+                    entity.health,
+                    field,
+                    event,
+                    control_state,
+                    public_data,
+                );
+                false
+            }
+        };
+
+        let editor_id = control_state.get_last_id();
+        if let UIEvent::Update = event {
+            if self.focus_editor {
+                self.focus_editor = false;
+                control_state.set_focus(editor_id);
+            } else if submitted || !control_state.is_focused(editor_id) {
+                // Enter gives the keyboard back to the table, a click elsewhere keeps the focus
+                // where it went
+                self.table.stop_editing(submitted);
+                self.table.refresh();
+                self.editor = None;
+            }
+        }
+    }
+}
+
+impl GUIContainer for TableTest {
+    fn get_name(&self) -> &str {
+        "Table"
+    }
+
+    fn get_description(&self) -> Option<&str> {
+        Some("Thousands of entities with sortable, resizable and editable columns")
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+        container_info: ContainerInfo,
+        control_state: &mut ControlState,
+    ) {
+        if let UIEvent::Render { gui_rects, .. } = event {
+            render_container_background(gui_rects, &container_info, get_theme(public_data));
+        }
+
+        let margin = control_state.scale.px(MARGIN);
+        let table_rect = Rect {
+            position: container_info.rect.position,
+            size: (container_info.rect.size - Vec2::splat(margin * 2.0)).max(Vec2::ZERO),
+        };
+        let changes = table::table(
+            table_rect,
+            container_info.rect,
+            "Entities",
+            &self.entities,
+            &mut self.table,
+            event,
+            control_state,
+            public_data,
+        );
+        for change in changes {
+            if let TableChange::EditStarted { row, column } = change {
+                self.start_editing(row, column);
+            }
+        }
+        self.edit_cell(event, public_data, control_state);
+    }
+}
@@ -13,7 +13,10 @@ use rwge::{
 };
 
 use crate::{
-    gui_system::window_layout::depth_offset,
+    gui_system::{
+        accessibility::get_accessibility, control::scale::GUIScale, layers::Layer,
+        theme::get_theme, window_layout::depth_offset,
+    },
     runtime_data::{self, utils::get_time, RuntimeData, PublicData},
 };
 
@@ -141,6 +144,10 @@ impl GUIContainer for TextAnimation {
         "Text Anim"
     }
 
+    fn get_description(&self) -> Option<&str> {
+        Some("Plays the words clicked on the Text Layout panel")
+    }
+
     fn handle_event(
         &mut self,
         event: &mut rwge::gui::rect_ui::event::UIEvent,
@@ -166,6 +173,10 @@ impl GUIContainer for TextAnimation {
                 let current_time = get_time(public_data).time;
                 let word_anim = get_instance_word_anim(public_data, self.uuid);
                 let mut anim_instances = word_anim.anims.borrow_mut();
+                if get_accessibility(public_data).reduced_motion {
+                    // The letters would fly across the whole panel, the words are dropped instead
+                    anim_instances.clear();
+                }
 
                 let mut delete_ad_indices = Vec::with_capacity(anim_instances.len());
                 for (ad_index, anim) in anim_instances.iter_mut().enumerate() {
@@ -207,6 +218,7 @@ impl GUIContainer for TextAnimation {
                                         final_offset,
                                         container_info.rect.width(),
                                         anim.rand_value,
+                                        &control_state.scale,
                                     );
                                 }
                             }
@@ -244,7 +256,7 @@ impl GUIContainer for TextAnimation {
             extra_render_steps,
         } = event
         {
-            render_container_background(gui_rects, &container_info);
+            render_container_background(gui_rects, &container_info, get_theme(public_data));
 
             if contains_instance_anim(public_data, self.uuid) {
                 let mut render_elements = render_word_animtion(public_data, self.uuid);
@@ -255,7 +267,9 @@ impl GUIContainer for TextAnimation {
                             elem.build(gui_rects);
                         }
                     }),
-                    container_info.depth_range.0 + depth_offset::FONT_ANIM_OFFSET,
+                    container_info
+                        .layers
+                        .depth(Layer::ContainerContent, depth_offset::FONT_ANIM_OFFSET),
                 );
             }
         }
@@ -302,6 +316,7 @@ pub fn letter_animation(
     final_offset: Vec2,
     container_width: f32,
     anim_rand_value: f32,
+    gui_scale: &GUIScale,
 ) {
     let char_time = current_time - (ANIM_OFFSET * char_index as f32);
 
@@ -322,8 +337,10 @@ pub fn letter_animation(
     let to_center_fade = easeInBack(to_center_fade);
 
     let font_position = font_elem.rect.position + initial_offset;
-    let rotation =
-        current_time + anim_rand_value * 6.28 + (font_elem.rect.position.x as f32) * 0.01;
+    // The phase follows the position of the letter in the line, not its size in pixels
+    let rotation = current_time
+        + anim_rand_value * 6.28
+        + gui_scale.to_logical(font_elem.rect.position.x) * 0.01;
     let rotation_offset = vec2(f32::sin(rotation), f32::cos(rotation)) * container_width * 0.35;
 
     *offset = (final_offset - font_position) * to_box_time
@@ -16,8 +16,16 @@ use rwge::{
 
 use crate::{
     gui_system::{
-        control::slider,
+        access_tree::{AccessNode, AccessRole},
+        control::{
+            choice,
+            scale::GUIScale,
+            slider::{self, SliderCurve, SliderOptions},
+            tooltip,
+        },
         gui_container::text_animation::{TextAnimationData, WordAnimData, WordAnimation},
+        popup::MenuItem,
+        theme::get_theme,
         ContainerInfo,
     },
     runtime_data::{utils::get_time, PublicData},
@@ -28,6 +36,8 @@ use super::{render_container_background, GUIContainer};
 pub struct TextLayoutTest {
     pub text: String,
     pub last_update_width: f32,
+    /// Font size in pixels the text was laid out with, it changes with the GUI scale
+    last_font_size: f32,
     pub text_height: f32,
     pub first_line_height: f32,
     pub font_elements: Option<Vec<FontElement>>,
@@ -37,7 +47,6 @@ pub struct TextLayoutTest {
     update_font: bool,
     pub font_index: usize,
     pub font_param: f32,
-    pub font_param_corrected: f32,
     pub font_active_id: Option<Uuid>,
     pub hovered_word: Option<WordRect>,
 
@@ -52,6 +61,7 @@ impl TextLayoutTest {
         Self {
             text: include_str!("../../../res/lorem_ipsum.txt").to_owned(),
             last_update_width: 0.0,
+            last_font_size: 0.0,
             text_height: 0.0,
             font_elements: None,
             word_rects: None,
@@ -61,7 +71,6 @@ impl TextLayoutTest {
             update_font: false,
             font_index: 0,
             font_param: 0.0,
-            font_param_corrected: 0.0,
             font_active_id: None,
             hovered_word: None,
 
@@ -82,13 +91,20 @@ const SCROLL_RIGHT_MARGIN: f32 = 8.0;
 const WORD_RECT_PAD_MIN: (f32, f32) = (14.0, 8.0);
 const WORD_RECT_PAD_MAX: (f32, f32) = (100.0, 80.0);
 
-fn get_padding(font_param: f32) -> Vec2 {
-    vec2(
+fn get_padding(font_param: f32, scale: &GUIScale) -> Vec2 {
+    scale.vec(vec2(
         lerp_f32(WORD_RECT_PAD_MIN.0, WORD_RECT_PAD_MAX.1, font_param),
         lerp_f32(WORD_RECT_PAD_MIN.1, WORD_RECT_PAD_MAX.1, font_param),
-    )
+    ))
 }
 
+const FONT_NAMES: [&str; 3] = ["Neo Sans Medium Italic", "Lato Bold", "Lobster Regular"];
+const FONT_LABELS: [&str; 3] = ["Neo", "Lato", "Lobster"];
+const FONT_SPACINGS: [f32; 3] = [0.05, 0.02, 0.1];
+
+const ACTION_RESET_VIEW: u32 = 0;
+const ACTION_SELECT_FONT: u32 = 1;
+
 const FONT_SIZE_MIN_MAX: (f32, f32) = (14.0, 1024.0);
 const LINE_HEIGHT_MIN_MAX: (f32, f32) = (FONT_SIZE_MIN_MAX.0, FONT_SIZE_MIN_MAX.1);
 const PARA_SEP_MIN_MAX: (f32, f32) = (LINE_HEIGHT_MIN_MAX.0 + 2.0, LINE_HEIGHT_MIN_MAX.1 + 2.0);
@@ -100,6 +116,38 @@ impl GUIContainer for TextLayoutTest {
         "Text Layout"
     }
 
+    fn get_description(&self) -> Option<&str> {
+        Some("Multi line layout of res/lorem_ipsum.txt")
+    }
+
+    fn context_menu_items(&self) -> Vec<MenuItem<u32>> {
+        vec![
+            MenuItem::new("Reset view", ACTION_RESET_VIEW),
+            MenuItem::new_submenu(
+                "Font",
+                (0..3)
+                    .map(|index| {
+                        MenuItem::new(FONT_NAMES[index as usize], ACTION_SELECT_FONT + index)
+                    })
+                    .collect(),
+            ),
+        ]
+    }
+
+    fn on_context_menu_action(&mut self, action: u32) {
+        match action {
+            ACTION_RESET_VIEW => {
+                self.scroll_offset = 0.0;
+                self.font_param = 0.0;
+                self.update_font = true;
+            }
+            _ => {
+                self.font_index = (action - ACTION_SELECT_FONT) as usize;
+                self.update_font = true;
+            }
+        }
+    }
+
     fn handle_event(
         &mut self,
         event: &mut rwge::gui::rect_ui::event::UIEvent,
@@ -108,162 +156,93 @@ impl GUIContainer for TextLayoutTest {
         control_state: &mut crate::gui_system::control::ControlState,
     ) {
         let scroll_control_id = control_state.get_id();
+        let scale = control_state.scale;
+        let colors = &get_theme(public_data).colors;
 
         if let UIEvent::Render { gui_rects, .. } = event {
-            render_container_background(gui_rects, &container_info);
+            render_container_background(gui_rects, &container_info, get_theme(public_data));
         }
 
+        const TOP_RECT_HEIGHT: f32 = 50.0;
+        let top_rect_height = scale.px(TOP_RECT_HEIGHT);
+
         let cont_rect = container_info
             .rect
-            .offset_size(vec2(0.0, -50.0))
-            .offset_position(vec2(0.0, -25.0));
-
-        const TOP_RECT_HEIGHT: f32 = 50.0;
+            .offset_size(vec2(0.0, -top_rect_height))
+            .offset_position(vec2(0.0, -top_rect_height * 0.5));
 
         let top_cont_rect = Rect {
             position: container_info.rect.position
                 + vec2(
                     0.0,
-                    container_info.rect.size.y * 0.5 - TOP_RECT_HEIGHT * 0.5,
+                    container_info.rect.size.y * 0.5 - top_rect_height * 0.5,
                 ),
-            size: vec2(container_info.rect.size.x, TOP_RECT_HEIGHT),
+            size: vec2(container_info.rect.size.x, top_rect_height),
         };
 
-        const BTN_WIDTH: f32 = 50.0;
-        const BTN_HEIGHT: f32 = 20.0;
-        const BTN_MARGIN: f32 = 14.0;
-        const BTN_GAP: f32 = 2.0;
+        let btn_width = scale.px(50.0);
+        let btn_height = scale.px(20.0);
+        let btn_margin = scale.px(14.0);
+        let btn_gap = scale.px(2.0);
 
         let slider_rect = Rect {
             position: top_cont_rect.position
-                + vec2(BTN_WIDTH * 3.0 + BTN_MARGIN + BTN_GAP * 2.0, 0.0) * 0.5,
+                + vec2(btn_width * 3.0 + btn_margin + btn_gap * 2.0, 0.0) * 0.5,
             size: top_cont_rect.size
-                - vec2(BTN_WIDTH * 3.0 + BTN_MARGIN + BTN_GAP * 2.0 + 28.0, 0.0),
+                - vec2(btn_width * 3.0 + btn_margin + btn_gap * 2.0 + scale.px(28.0), 0.0),
         };
-        self.font_param = slider::slider(
+        // The curve gives more room to the small sizes
+        let font_param = slider::slider(
             slider_rect,
             container_info.rect,
             self.font_param,
             0.0,
             1.0,
+            &SliderOptions::new().set_curve(SliderCurve::Power(3.5)),
             &mut self.font_active_id,
             event,
             control_state,
+            public_data,
         );
-        self.font_param_corrected = f32::powf(self.font_param, 3.5);
-
-        match event {
-            UIEvent::MouseMove { .. } | UIEvent::MouseButton(..) => {
-                if self.font_active_id.is_some() {
-                    self.update_font = true;
-                }
-            }
-            _ => {}
+        let font_slider_id = control_state.get_last_id();
+        control_state.access.set_last_label("Font size");
+        tooltip::tooltip(control_state, font_slider_id, "Font size");
+        if font_param != self.font_param {
+            self.font_param = font_param;
+            self.update_font = true;
         }
 
-        const SELECTED_BTN: RGBA = RGBA::rgb(0.15, 0.4, 0.8);
-        const HOVERED_BTN: RGBA = RGBA::rrr1(0.5);
-        const INACTIVE_BTN: RGBA = RGBA::rrr1(0.3);
-
-        let top_cont_rect = top_cont_rect.offset_position(vec2(BTN_MARGIN, -BTN_MARGIN));
-
-        let font_selectors = vec![
-            (
-                Rect {
-                    position: top_cont_rect.top_left_position()
-                        + vec2(BTN_WIDTH, -BTN_HEIGHT) * 0.5,
-                    size: vec2(BTN_WIDTH, BTN_HEIGHT),
-                },
-                Some(BorderRadius::ForLeftRight {
-                    left: BTN_HEIGHT * 0.5,
-                    right: 0.0,
-                }),
-                control_state.get_id(),
-                0.05,
-            ),
-            (
-                Rect {
-                    position: top_cont_rect.top_left_position()
-                        + vec2(BTN_WIDTH * 1.5, -BTN_HEIGHT * 0.5)
-                        + vec2(BTN_GAP, 0.0),
-                    size: vec2(BTN_WIDTH, BTN_HEIGHT),
-                },
-                None,
-                control_state.get_id(),
-                0.02,
-            ),
-            (
-                Rect {
-                    position: top_cont_rect.top_left_position()
-                        + vec2(BTN_WIDTH * 2.5, -BTN_HEIGHT * 0.5)
-                        + vec2(BTN_GAP * 2.0, 0.0),
-                    size: vec2(BTN_WIDTH, BTN_HEIGHT),
-                },
-                Some(BorderRadius::ForLeftRight {
-                    left: 0.0,
-                    right: BTN_HEIGHT * 0.5,
-                }),
-                control_state.get_id(),
-                0.1,
-            ),
-        ];
-
-        {
-            // TOP RECT
-
-            if let UIEvent::Update = event {
-                for (s_rect, _, control_id, ..) in font_selectors.iter() {
-                    let interactable_rect = s_rect.combine_rects(&container_info.rect);
-                    if let Some(rect) = interactable_rect {
-                        control_state.set_hot_with_rect(*control_id, &rect);
-                    }
-                }
-            }
-
-            if let UIEvent::MouseButton(mouse_input) = event {
-                if mouse_input.is_left_pressed() {
-                    for (index, (_, _, control_id, ..)) in font_selectors.iter().enumerate() {
-                        if control_state.is_hovered(*control_id) {
-                            self.font_index = index;
-                            self.update_font = true;
-                        }
-                    }
-                }
-            }
+        let top_cont_rect = top_cont_rect.offset_position(vec2(btn_margin, -btn_margin));
 
-            if let UIEvent::Render { gui_rects, .. } = event {
-                for (index, (s_rect, s_border, control_id, ..)) in font_selectors.iter().enumerate()
-                {
-                    let mut elem_builder = ElementBuilder::new_with_rect(*s_rect);
-                    elem_builder = {
-                        if index == self.font_index {
-                            elem_builder.set_color(SELECTED_BTN.into())
-                        } else if control_state.is_hovered(*control_id) {
-                            elem_builder.set_color(HOVERED_BTN.into())
-                        } else {
-                            elem_builder.set_color(INACTIVE_BTN.into())
-                        }
-                    };
-                    elem_builder = {
-                        if let Some(border) = *s_border {
-                            elem_builder.set_round_rect(border.into())
-                        }else{
-                            elem_builder
-                        }
-                    };
-                    elem_builder
-                        .set_rect_mask(container_info.rect.into())
-                        .build(gui_rects);
-                }
+        let font_selector_rect = Rect {
+            position: top_cont_rect.top_left_position()
+                + vec2(btn_width * 3.0 + btn_gap * 2.0, -btn_height) * 0.5,
+            size: vec2(btn_width * 3.0 + btn_gap * 2.0, btn_height),
+        };
+        let font_index = choice::segmented_control(
+            font_selector_rect,
+            container_info.rect,
+            "Font",
+            &FONT_LABELS,
+            &FONT_NAMES,
+            self.font_index,
+            event,
+            control_state,
+            public_data,
+        );
+        if font_index != self.font_index {
+            self.font_index = font_index;
+            self.update_font = true;
+        }
 
-                let top_pos = cont_rect.position + vec2(0.0, cont_rect.size.y * 0.5 + 2.0);
-                ElementBuilder::new_with_rect(Rect {
-                    position: top_pos,
-                    size: vec2(cont_rect.size.x, 4.0),
-                })
-                .set_color(RGBA::rrr1(0.15).into())
-                .build(gui_rects);
-            }
+        if let UIEvent::Render { gui_rects, .. } = event {
+            let top_pos = cont_rect.position + vec2(0.0, cont_rect.size.y * 0.5 + scale.px(2.0));
+            ElementBuilder::new_with_rect(Rect {
+                position: top_pos,
+                size: vec2(cont_rect.size.x, scale.px(4.0)),
+            })
+            .set_color(colors.border.into())
+            .build(gui_rects);
         }
 
         {
@@ -279,7 +258,8 @@ impl GUIContainer for TextLayoutTest {
                             cont_rect.height(),
                             self.text_height,
                             self.scroll_offset,
-                            self.first_line_height
+                            self.first_line_height,
+                            &scale,
                         ) + cont_rect.top_left_position();
 
                         if let UIEvent::Update = event {
@@ -323,14 +303,15 @@ impl GUIContainer for TextLayoutTest {
                         cont_rect.height(),
                         self.text_height,
                         self.scroll_offset,
-                        self.first_line_height
+                        self.first_line_height,
+                        &scale,
                     ) + cont_rect.top_left_position();
 
                     if let Some(hovered) = self.hovered_word {
                         let w_rect = hovered
                             .rect
                             .offset_position(text_render_offset)
-                            .offset_size(get_padding(self.font_param_corrected));
+                            .offset_size(get_padding(self.font_param, &scale));
                         ElementBuilder::new_with_rect(w_rect)
                             .set_linear_gradient(
                                 LinearGradient {
@@ -353,22 +334,31 @@ impl GUIContainer for TextLayoutTest {
         {
             // TEXT LAYOUT RECT
             if let UIEvent::Update = event {
-                if f32::abs(self.last_update_width - cont_rect.width()) > 0.5 || self.update_font {
+                let font_size =
+                    lerp_f32(FONT_SIZE_MIN_MAX.0, FONT_SIZE_MIN_MAX.1, self.font_param);
+                let font_size_px = scale.font(font_size);
+                if f32::abs(self.last_update_width - cont_rect.width()) > 0.5
+                    || self.last_font_size != font_size_px
+                    || self.update_font
+                {
+                    // Lines grow with the font when it is raised to the minimum font size
+                    let line_scale = font_size_px / font_size;
                     let font_collection =
                         &public_data.get::<Vec<FontCollection>>().unwrap()[0];
                     let (font_elems, word_rects, text_height, first_line_height) = create_multi_line(
                         &self.text,
-                        lerp_f32(FONT_SIZE_MIN_MAX.0, FONT_SIZE_MIN_MAX.1, self.font_param_corrected),
+                        font_size_px,
                         font_collection,
                         self.font_index,
-                        font_selectors[self.font_index].3,
-                        cont_rect.width() - LEFT_MARGIN - RIGHT_MARGIN,
+                        FONT_SPACINGS[self.font_index],
+                        cont_rect.width() - scale.px(LEFT_MARGIN) - scale.px(RIGHT_MARGIN),
                         lerp_f32(
                             LINE_HEIGHT_MIN_MAX.0,
                             LINE_HEIGHT_MIN_MAX.1,
-                            self.font_param_corrected,
-                        ),
-                        lerp_f32(PARA_SEP_MIN_MAX.0, PARA_SEP_MIN_MAX.1, self.font_param_corrected),
+                            self.font_param,
+                        ) * line_scale,
+                        lerp_f32(PARA_SEP_MIN_MAX.0, PARA_SEP_MIN_MAX.1, self.font_param)
+                            * line_scale,
                     );
 
                     self.first_line_height = first_line_height;
@@ -377,6 +367,7 @@ impl GUIContainer for TextLayoutTest {
                     self.word_rects = Some(word_rects);
                     self.text_height = text_height;
                     self.last_update_width = cont_rect.width();
+                    self.last_font_size = font_size_px;
 
                     self.update_font = false;
                 }
@@ -387,12 +378,22 @@ impl GUIContainer for TextLayoutTest {
                     }
                 } else {
                     let (_, scroll_rect, ..) =
-                        scroll_control(cont_rect, self.text_height, self.scroll_offset);
+                        scroll_control(cont_rect, self.text_height, self.scroll_offset, &scale);
                     control_state.set_hot_with_rect(
                         scroll_control_id,
-                        &(scroll_rect.offset_size(vec2(6.0, 6.0))),
+                        &(scroll_rect.offset_size(scale.vec(vec2(6.0, 6.0)))),
                     );
                 }
+
+                let scroll_access_id = control_state.push_access_node(
+                    AccessNode::new(AccessRole::ScrollView, cont_rect, "")
+                        .set_control(scroll_control_id)
+                        .set_range(self.scroll_offset, 0.0, 1.0),
+                );
+                control_state.access.push_parent(scroll_access_id);
+                control_state
+                    .push_access_node(AccessNode::new(AccessRole::Label, cont_rect, &self.text));
+                control_state.access.pop_parent();
             }
 
             if let UIEvent::MouseButton(mouse_input) = event {
@@ -413,7 +414,7 @@ impl GUIContainer for TextLayoutTest {
                     let move_delta = *corrected - self.mouse_prev_pos;
 
                     let (_, scroll_rect, height_limit) =
-                        scroll_control(cont_rect, self.text_height, self.scroll_offset);
+                        scroll_control(cont_rect, self.text_height, self.scroll_offset, &scale);
 
                     let missing_height = height_limit - scroll_rect.height();
                     self.scroll_offset -= move_delta.y / missing_height;
@@ -424,13 +425,14 @@ impl GUIContainer for TextLayoutTest {
 
             if let UIEvent::Render { gui_rects, .. } = event {
                 let (side_bar_rect, scroll_rect, height_limit) =
-                    scroll_control(cont_rect, self.text_height, self.scroll_offset);
+                    scroll_control(cont_rect, self.text_height, self.scroll_offset, &scale);
 
                 let text_render_offset = get_text_render_object(
                     cont_rect.height(),
                     self.text_height,
                     self.scroll_offset,
-                    self.first_line_height
+                    self.first_line_height,
+                    &scale,
                 );
 
                 let rect_mask_index = push_rect_mask(cont_rect, gui_rects) as u16;
@@ -442,10 +444,10 @@ impl GUIContainer for TextLayoutTest {
                     for (index, elem) in font_elems.iter().enumerate() {
                         let elem_rect = elem.rect.offset_position(text_start_position);
 
+                        let bounds = cont_rect.offset_size(Vec2::splat(scale.px(30.0)));
                         if !removed
                             && elem_rect.position.y < cont_rect.position.y
-                            && !elem_rect
-                                .intersecting_rect(&(cont_rect.offset_size(Vec2::splat(30.0))))
+                            && !elem_rect.intersecting_rect(&bounds)
                         {
                             out_of_bounds_count += 1;
                             if out_of_bounds_count > 10 {
@@ -481,20 +483,20 @@ impl GUIContainer for TextLayoutTest {
                 }
 
                 ElementBuilder::new_with_rect(side_bar_rect)
-                    .set_color(RGBA::rrr1(0.1).into())
-                    .set_round_rect(BorderRadius::ForAll(6.0).into())
+                    .set_color(colors.control.into())
+                    .set_round_rect(BorderRadius::ForAll(scale.px(6.0)).into())
                     .build(gui_rects);
 
                 let color = if control_state.is_hovered(scroll_control_id) {
-                    RGBA::rrr1(0.8)
+                    colors.control_hover
                 } else if control_state.is_active(self.scroll_active_id) {
-                    RGBA::rgb(0.2, 0.85, 0.1)
+                    colors.accent
                 } else {
-                    RGBA::rrr1(0.6)
+                    colors.border
                 };
                 ElementBuilder::new_with_rect(scroll_rect)
                     .set_color(color.into())
-                    .set_round_rect(BorderRadius::ForAll(4.0).into())
+                    .set_round_rect(BorderRadius::ForAll(scale.px(4.0)).into())
                     .build(gui_rects);
             }
         }
@@ -506,32 +508,37 @@ fn get_text_render_object(
     text_height: f32,
     scroll_offset: f32,
     first_line: f32,
+    scale: &GUIScale,
 ) -> Vec2 {
-    let max_height = container_height - TEXT_START_OFFSET - TOP_MARGIN;
+    let max_height = container_height - scale.px(TEXT_START_OFFSET) - scale.px(TOP_MARGIN);
     let missing_height = text_height - max_height;
-    let text_vertical_offset = missing_height * scroll_offset + - first_line - TOP_MARGIN;
-    vec2(LEFT_MARGIN, text_vertical_offset)
+    let text_vertical_offset =
+        missing_height * scroll_offset + -first_line - scale.px(TOP_MARGIN);
+    vec2(scale.px(LEFT_MARGIN), text_vertical_offset)
 }
 
 pub fn scroll_control(
     container_rect: Rect,
     elem_height: f32,
     scroll_offset: f32,
+    scale: &GUIScale,
 ) -> (Rect, Rect, f32) {
     const SCROLL_RIGHT_MARGIN: f32 = 8.0;
     const TOP_MARGIN: f32 = 14.0;
 
     let cont_rect = container_rect;
     let side_bar_rect = Rect {
-        position: (cont_rect.position + vec2(cont_rect.size.x * 0.5 - SCROLL_RIGHT_MARGIN, 0.0))
-            .round(),
-        size: vec2(12.0, cont_rect.size.y - 8.0),
+        position: (cont_rect.position
+            + vec2(cont_rect.size.x * 0.5 - scale.px(SCROLL_RIGHT_MARGIN), 0.0))
+        .round(),
+        size: vec2(scale.px(12.0), cont_rect.size.y - scale.px(8.0)),
     };
 
-    let view_proportion = (cont_rect.size.y - 18.0 - TOP_MARGIN) / elem_height;
-    let bar_height_limit = side_bar_rect.height() - 8.0;
+    let view_proportion =
+        (cont_rect.size.y - scale.px(18.0) - scale.px(TOP_MARGIN)) / elem_height;
+    let bar_height_limit = side_bar_rect.height() - scale.px(8.0);
     let scroll_heigth = bar_height_limit * (view_proportion.min(1.0));
-    let scroll_heigth = scroll_heigth.max(12.0);
+    let scroll_heigth = scroll_heigth.max(scale.px(12.0));
 
     let missing_height = bar_height_limit - scroll_heigth;
     let position_offset = missing_height * scroll_offset;
@@ -540,9 +547,10 @@ pub fn scroll_control(
         position: side_bar_rect.position
             + vec2(
                 0.0,
-                side_bar_rect.height() * 0.5 - 4.0 - scroll_heigth * 0.5 - position_offset,
+                side_bar_rect.height() * 0.5 - scale.px(4.0) - scroll_heigth * 0.5
+                    - position_offset,
             ),
-        size: vec2(6.0, scroll_heigth),
+        size: vec2(scale.px(6.0), scroll_heigth),
     };
 
     (side_bar_rect, scroll_rect, bar_height_limit)
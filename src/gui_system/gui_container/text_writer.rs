@@ -1,3 +0,0 @@
-pub struct TextWriter{
-	
-}
\ No newline at end of file
@@ -0,0 +1,407 @@
+use crate::gui_system::control::text_field::editor;
+
+use super::{gap_buffer::GapBuffer, layout::LineLayout};
+
+#[cfg(test)]
+mod test;
+
+const MAX_UNDO_STEPS: usize = 500;
+
+/// A position inside of the document, `offset` is a byte offset into the line that is always on a
+/// char boundary
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub struct TextPosition {
+    pub line: usize,
+    pub offset: usize,
+}
+
+impl TextPosition {
+    pub fn new(line: usize, offset: usize) -> Self {
+        Self { line, offset }
+    }
+}
+
+/// A paragraph of the document, the layout is dropped every time its text changes
+pub struct Line {
+    pub text: String,
+    pub layout: Option<LineLayout>,
+}
+
+impl Line {
+    fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+            layout: None,
+        }
+    }
+}
+
+/// Consecutive edits of the same kind are undone together
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+struct Edit {
+    start: TextPosition,
+    removed: String,
+    inserted: String,
+    kind: EditKind,
+}
+
+pub struct Document {
+    lines: GapBuffer<Line>,
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    /// The next edit starts a new undo step even if it is of the same kind
+    break_undo_group: bool,
+    /// Set by every edit, cleared once the text is saved
+    pub modified: bool,
+    /// `\r\n` if the text had it, the lines are joined with it again when the text is saved
+    line_ending: &'static str,
+}
+
+impl Document {
+    pub fn new(text: &str) -> Self {
+        let mut lines = GapBuffer::new();
+        for line in sanitize(text).split('\n') {
+            lines.push(Line::new(line));
+        }
+        Self {
+            lines,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            break_undo_group: false,
+            modified: false,
+            line_ending: if text.contains("\r\n") { "\r\n" } else { "\n" },
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&self, index: usize) -> &Line {
+        self.lines.get(index).expect("Line out of bounds")
+    }
+
+    pub fn line_mut(&mut self, index: usize) -> &mut Line {
+        self.lines.get_mut(index).expect("Line out of bounds")
+    }
+
+    pub fn line_text(&self, index: usize) -> &str {
+        &self.line(index).text
+    }
+
+    /// Layouts are built again the next time the lines are visible
+    pub fn clear_layouts(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.layout = None;
+        }
+    }
+
+    /// Text to save, with the line ending the document was created with
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(self.line_ending)
+    }
+
+    pub fn start(&self) -> TextPosition {
+        TextPosition::new(0, 0)
+    }
+
+    pub fn end(&self) -> TextPosition {
+        let line = self.line_count() - 1;
+        TextPosition::new(line, self.line_text(line).len())
+    }
+
+    pub fn slice(&self, start: TextPosition, end: TextPosition) -> String {
+        if start.line == end.line {
+            return String::from(&self.line_text(start.line)[start.offset..end.offset]);
+        }
+        let mut text = String::from(&self.line_text(start.line)[start.offset..]);
+        for line in (start.line + 1)..end.line {
+            text.push('\n');
+            text.push_str(self.line_text(line));
+        }
+        text.push('\n');
+        text.push_str(&self.line_text(end.line)[..end.offset]);
+        text
+    }
+
+    /// The start of a line goes back to the end of the previous one
+    pub fn previous_position(&self, position: TextPosition) -> TextPosition {
+        if position.offset == 0 {
+            if position.line == 0 {
+                return position;
+            }
+            let line = position.line - 1;
+            return TextPosition::new(line, self.line_text(line).len());
+        }
+        let offset = self.line_text(position.line)[..position.offset]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index);
+        TextPosition::new(position.line, offset)
+    }
+
+    /// The end of a line goes on to the start of the next one
+    pub fn next_position(&self, position: TextPosition) -> TextPosition {
+        let text = self.line_text(position.line);
+        match text[position.offset..].chars().next() {
+            Some(c) => TextPosition::new(position.line, position.offset + c.len_utf8()),
+            None if position.line + 1 < self.line_count() => {
+                TextPosition::new(position.line + 1, 0)
+            }
+            None => position,
+        }
+    }
+
+    pub fn previous_word(&self, position: TextPosition) -> TextPosition {
+        if position.offset == 0 {
+            return self.previous_position(position);
+        }
+        let text = self.line_text(position.line);
+        TextPosition::new(
+            position.line,
+            editor::previous_word_boundary(text, position.offset),
+        )
+    }
+
+    pub fn next_word(&self, position: TextPosition) -> TextPosition {
+        let text = self.line_text(position.line);
+        if position.offset == text.len() {
+            return self.next_position(position);
+        }
+        TextPosition::new(
+            position.line,
+            editor::next_word_boundary(text, position.offset),
+        )
+    }
+
+    pub fn word_range_at(&self, position: TextPosition) -> (TextPosition, TextPosition) {
+        let range = editor::word_range_at(self.line_text(position.line), position.offset);
+        (
+            TextPosition::new(position.line, range.start),
+            TextPosition::new(position.line, range.end),
+        )
+    }
+
+    /// Replaces the text between `start` and `end` and returns the end of the new text
+    pub fn replace(
+        &mut self,
+        start: TextPosition,
+        end: TextPosition,
+        text: &str,
+        kind: EditKind,
+    ) -> TextPosition {
+        let text = sanitize(text);
+        if start == end && text.is_empty() {
+            return end;
+        }
+        let removed = self.raw_delete(start, end);
+        let new_end = self.raw_insert(start, &text);
+        self.push_undo(Edit {
+            start,
+            removed,
+            inserted: text,
+            kind,
+        });
+        self.modified = true;
+        new_end
+    }
+
+    /// The next edit is undone on its own
+    pub fn break_undo_group(&mut self) {
+        self.break_undo_group = true;
+    }
+
+    /// Returns the end of the restored text, where the caret goes
+    pub fn undo(&mut self) -> Option<TextPosition> {
+        let edit = self.undo_stack.pop()?;
+        let inserted_end = advance(edit.start, &edit.inserted);
+        self.raw_delete(edit.start, inserted_end);
+        let caret = self.raw_insert(edit.start, &edit.removed);
+        self.redo_stack.push(edit);
+        self.break_undo_group = true;
+        self.modified = true;
+        Some(caret)
+    }
+
+    pub fn redo(&mut self) -> Option<TextPosition> {
+        let edit = self.redo_stack.pop()?;
+        let removed_end = advance(edit.start, &edit.removed);
+        self.raw_delete(edit.start, removed_end);
+        let caret = self.raw_insert(edit.start, &edit.inserted);
+        self.undo_stack.push(edit);
+        self.break_undo_group = true;
+        self.modified = true;
+        Some(caret)
+    }
+
+    /// Next match of `query` after `from`, it wraps around the end of the document. Only ASCII
+    /// letters are compared without case.
+    pub fn find(&self, query: &str, from: TextPosition) -> Option<(TextPosition, TextPosition)> {
+        let query = query.to_ascii_lowercase();
+        if query.is_empty() || query.contains('\n') {
+            return None;
+        }
+        let line_count = self.line_count();
+        for step in 0..=line_count {
+            let line = (from.line + step) % line_count;
+            let text = self.line_text(line).to_ascii_lowercase();
+            let search_start = if step == 0 { from.offset } else { 0 };
+            let found = if step == line_count {
+                // Back on the first line, only the part before `from` is left
+                text[..from.offset].find(&query)
+            } else {
+                text[search_start..]
+                    .find(&query)
+                    .map(|offset| offset + search_start)
+            };
+            if let Some(offset) = found {
+                return Some((
+                    TextPosition::new(line, offset),
+                    TextPosition::new(line, offset + query.len()),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Previous match of `query` before `from`, it wraps around the start of the document
+    pub fn find_previous(
+        &self,
+        query: &str,
+        from: TextPosition,
+    ) -> Option<(TextPosition, TextPosition)> {
+        let query = query.to_ascii_lowercase();
+        if query.is_empty() || query.contains('\n') {
+            return None;
+        }
+        let line_count = self.line_count();
+        for step in 0..=line_count {
+            let line = (from.line + line_count * 2 - step) % line_count;
+            let text = self.line_text(line).to_ascii_lowercase();
+            let found = if step == 0 {
+                text[..from.offset].rfind(&query)
+            } else if step == line_count {
+                text[from.offset..]
+                    .rfind(&query)
+                    .map(|offset| offset + from.offset)
+            } else {
+                text.rfind(&query)
+            };
+            if let Some(offset) = found {
+                return Some((
+                    TextPosition::new(line, offset),
+                    TextPosition::new(line, offset + query.len()),
+                ));
+            }
+        }
+        None
+    }
+
+    fn push_undo(&mut self, edit: Edit) {
+        self.redo_stack.clear();
+        let merge = !std::mem::take(&mut self.break_undo_group);
+        if let Some(last) = self.undo_stack.last_mut().filter(|_| merge) {
+            if last.kind == edit.kind && edit.kind != EditKind::Other {
+                let last_end = advance(last.start, &last.inserted);
+                // Typing after the last typed text
+                if edit.kind == EditKind::Typing
+                    && edit.removed.is_empty()
+                    && last_end == edit.start
+                {
+                    last.inserted.push_str(&edit.inserted);
+                    return;
+                }
+                if edit.kind == EditKind::Deleting && edit.inserted.is_empty() {
+                    // Backspace, the removed text was right before the last one
+                    if advance(edit.start, &edit.removed) == last.start {
+                        last.removed.insert_str(0, &edit.removed);
+                        last.start = edit.start;
+                        return;
+                    }
+                    // Delete, the removed text was right after the last one
+                    if edit.start == last.start {
+                        last.removed.push_str(&edit.removed);
+                        return;
+                    }
+                }
+            }
+        }
+        self.undo_stack.push(edit);
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    fn raw_insert(&mut self, position: TextPosition, text: &str) -> TextPosition {
+        let mut parts = text.split('\n');
+        let first = parts.next().unwrap_or("");
+        let line = self.line_mut(position.line);
+        line.layout = None;
+        let tail = line.text.split_off(position.offset);
+        line.text.push_str(first);
+
+        let mut end = TextPosition::new(position.line, position.offset + first.len());
+        for part in parts {
+            end = TextPosition::new(end.line + 1, part.len());
+            self.lines.insert(end.line, Line::new(part));
+        }
+        self.line_mut(end.line).text.push_str(&tail);
+        end
+    }
+
+    fn raw_delete(&mut self, start: TextPosition, end: TextPosition) -> String {
+        let removed = self.slice(start, end);
+        if start.line == end.line {
+            let line = self.line_mut(start.line);
+            line.text.replace_range(start.offset..end.offset, "");
+            line.layout = None;
+            return removed;
+        }
+        let tail = String::from(&self.line_text(end.line)[end.offset..]);
+        for _ in (start.line + 1)..=end.line {
+            self.lines.remove(start.line + 1);
+        }
+        let line = self.line_mut(start.line);
+        line.text.truncate(start.offset);
+        line.text.push_str(&tail);
+        line.layout = None;
+        removed
+    }
+}
+
+/// Position at the end of `text` when it is inserted at `position`
+fn advance(position: TextPosition, text: &str) -> TextPosition {
+    match text.rfind('\n') {
+        Some(last_break) => TextPosition::new(
+            position.line + text.matches('\n').count(),
+            text.len() - last_break - 1,
+        ),
+        None => TextPosition::new(position.line, position.offset + text.len()),
+    }
+}
+
+/// Line breaks are kept as `\n`, tabs and lone carriage returns are kept as they are and other
+/// control chars are dropped. The layout expands the tabs.
+fn sanitize(text: &str) -> String {
+    let mut sanitized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\t' | '\r' => sanitized.push(c),
+            c if c.is_control() => {}
+            c => sanitized.push(c),
+        }
+    }
+    sanitized
+}
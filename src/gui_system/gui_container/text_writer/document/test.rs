@@ -0,0 +1,108 @@
+use super::{Document, EditKind, TextPosition};
+
+fn position(line: usize, offset: usize) -> TextPosition {
+    TextPosition::new(line, offset)
+}
+
+#[test]
+fn text_is_saved_the_way_it_was_read() {
+    for text in [
+        "one\ntwo\n",
+        "one\r\ntwo\r\n",
+        "\tindented\ttab",
+        "lone\rreturn",
+    ] {
+        assert_eq!(Document::new(text).text(), text);
+    }
+    let document = Document::new("a\u{7}b\r\nc");
+    assert_eq!(document.line_count(), 2);
+    assert_eq!(document.line_text(0), "ab");
+    assert_eq!(document.text(), "ab\r\nc");
+}
+
+#[test]
+fn replace_spans_lines() {
+    let mut document = Document::new("first\nsecond\nthird");
+    let end = document.replace(position(0, 2), position(2, 3), "X\nY", EditKind::Other);
+    assert_eq!(end, position(1, 1));
+    assert_eq!(document.text(), "fiX\nYrd");
+    assert!(document.modified);
+    assert_eq!(document.slice(position(0, 1), position(1, 2)), "iX\nYr");
+}
+
+#[test]
+fn pasted_line_endings_follow_the_document() {
+    let mut document = Document::new("a\r\nb");
+    document.replace(position(1, 1), position(1, 1), "c\r\nd", EditKind::Other);
+    assert_eq!(document.line_count(), 3);
+    assert_eq!(document.text(), "a\r\nbc\r\nd");
+}
+
+#[test]
+fn typing_is_undone_together() {
+    let mut document = Document::new("");
+    let mut caret = document.start();
+    for c in ["a", "b", "c"] {
+        caret = document.replace(caret, caret, c, EditKind::Typing);
+    }
+    document.replace(position(0, 2), caret, "", EditKind::Deleting);
+    assert_eq!(document.text(), "ab");
+
+    assert_eq!(document.undo(), Some(position(0, 3)));
+    assert_eq!(document.text(), "abc");
+    assert_eq!(document.undo(), Some(position(0, 0)));
+    assert_eq!(document.text(), "");
+    assert_eq!(document.undo(), None);
+
+    assert_eq!(document.redo(), Some(position(0, 3)));
+    assert_eq!(document.text(), "abc");
+}
+
+#[test]
+fn backspaces_are_undone_together() {
+    let mut document = Document::new("abcd");
+    for offset in (1..4).rev() {
+        document.replace(
+            position(0, offset),
+            position(0, offset + 1),
+            "",
+            EditKind::Deleting,
+        );
+    }
+    assert_eq!(document.text(), "a");
+    document.undo();
+    assert_eq!(document.text(), "abcd");
+}
+
+#[test]
+fn break_undo_group_starts_a_new_step() {
+    let mut document = Document::new("");
+    document.replace(position(0, 0), position(0, 0), "a", EditKind::Typing);
+    document.break_undo_group();
+    document.replace(position(0, 1), position(0, 1), "b", EditKind::Typing);
+    document.undo();
+    assert_eq!(document.text(), "a");
+}
+
+#[test]
+fn positions_cross_lines() {
+    let document = Document::new("ab\ncd");
+    assert_eq!(document.next_position(position(0, 2)), position(1, 0));
+    assert_eq!(document.previous_position(position(1, 0)), position(0, 2));
+    assert_eq!(document.previous_position(position(0, 0)), position(0, 0));
+    assert_eq!(document.next_position(document.end()), document.end());
+    assert_eq!(document.next_word(position(0, 0)), position(0, 2));
+}
+
+#[test]
+fn find_wraps_around() {
+    let document = Document::new("Alpha beta\ngamma ALPHA");
+    let found = document.find("alpha", position(0, 1));
+    assert_eq!(found, Some((position(1, 6), position(1, 11))));
+    let found = document.find("alpha", position(1, 7));
+    assert_eq!(found, Some((position(0, 0), position(0, 5))));
+    let found = document.find_previous("alpha", position(0, 4));
+    assert_eq!(found, Some((position(1, 6), position(1, 11))));
+    assert_eq!(document.find("delta", position(0, 0)), None);
+    assert_eq!(document.find("", position(0, 0)), None);
+}
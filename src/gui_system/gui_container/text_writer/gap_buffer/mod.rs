@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod test;
+
+/// Sequence with a gap at the last edit point. Inserting or removing next to the previous edit
+/// only moves the items between both points, so typing does not shift the rest of the document.
+pub struct GapBuffer<T> {
+    /// Items before the gap
+    before: Vec<T>,
+    /// Items after the gap, in reverse order so the gap is at the end of both vectors
+    after: Vec<T>,
+}
+
+impl<T> GapBuffer<T> {
+    pub fn new() -> Self {
+        Self {
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.before.len() + self.after.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.before.len() {
+            self.before.get(index)
+        } else {
+            let after_index = self
+                .after
+                .len()
+                .checked_sub(index - self.before.len() + 1)?;
+            self.after.get(after_index)
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.before.len() {
+            self.before.get_mut(index)
+        } else {
+            let after_index = self
+                .after
+                .len()
+                .checked_sub(index - self.before.len() + 1)?;
+            self.after.get_mut(after_index)
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, item: T) {
+        self.move_gap(index);
+        self.before.push(item);
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.insert(self.len(), item);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        self.move_gap(index);
+        self.after.pop()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.before.iter().chain(self.after.iter().rev())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.before.iter_mut().chain(self.after.iter_mut().rev())
+    }
+
+    /// After the move the gap is right before the item at `index`
+    fn move_gap(&mut self, index: usize) {
+        let index = index.min(self.len());
+        while self.before.len() > index {
+            self.after.push(self.before.pop().unwrap());
+        }
+        while self.before.len() < index {
+            self.before.push(self.after.pop().unwrap());
+        }
+    }
+}
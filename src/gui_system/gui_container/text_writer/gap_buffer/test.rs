@@ -0,0 +1,51 @@
+use super::GapBuffer;
+
+fn items(buffer: &GapBuffer<u32>) -> Vec<u32> {
+    buffer.iter().copied().collect()
+}
+
+#[test]
+fn keeps_the_order_around_the_gap() {
+    let mut buffer = GapBuffer::new();
+    for item in 0..5 {
+        buffer.push(item);
+    }
+    buffer.insert(2, 10);
+    buffer.insert(0, 11);
+    buffer.insert(4, 12);
+    assert_eq!(items(&buffer), [11, 0, 1, 10, 12, 2, 3, 4]);
+    assert_eq!(buffer.len(), 8);
+    for (index, item) in items(&buffer).into_iter().enumerate() {
+        assert_eq!(buffer.get(index), Some(&item));
+    }
+    assert_eq!(buffer.get(8), None);
+}
+
+#[test]
+fn remove_returns_the_item() {
+    let mut buffer = GapBuffer::new();
+    for item in 0..5 {
+        buffer.push(item);
+    }
+    assert_eq!(buffer.remove(3), Some(3));
+    assert_eq!(buffer.remove(0), Some(0));
+    assert_eq!(buffer.remove(3), None);
+    assert_eq!(items(&buffer), [1, 2, 4]);
+    buffer.insert(3, 5);
+    assert_eq!(items(&buffer), [1, 2, 4, 5]);
+}
+
+#[test]
+fn get_mut_reaches_both_sides() {
+    let mut buffer = GapBuffer::new();
+    for item in 0..4 {
+        buffer.push(item);
+    }
+    buffer.insert(2, 9);
+    *buffer.get_mut(0).unwrap() += 10;
+    *buffer.get_mut(4).unwrap() += 10;
+    for item in buffer.iter_mut() {
+        *item += 1;
+    }
+    assert_eq!(items(&buffer), [11, 2, 10, 3, 14]);
+}
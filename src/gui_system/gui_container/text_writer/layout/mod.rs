@@ -0,0 +1,354 @@
+use std::ops::Range;
+
+use rwge::{
+    font::{
+        font_layout::{create_multi_line, create_single_line, FontElement},
+        font_load_gpu::FontCollection,
+    },
+    glam::{vec2, Vec2},
+    gui::rect_ui::Rect,
+};
+
+#[cfg(test)]
+mod test;
+
+/// A tab is drawn as this many spaces
+const TAB_SPACES: &str = "    ";
+
+/// Everything the layout of a paragraph depends on besides its text, the layouts are built again
+/// when it changes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LayoutStyle {
+    pub font_size: f32,
+    pub line_height: f32,
+    /// Paragraphs wrap at this width
+    pub width: f32,
+}
+
+impl LayoutStyle {
+    fn text_width(&self, text: &str, font_collection: &FontCollection) -> f32 {
+        if text.is_empty() {
+            return 0.0;
+        }
+        create_single_line(text, self.font_size, font_collection, 0, 0.0)
+            .1
+            .width()
+    }
+
+    /// Spaces have no font element, their width is what they add between two chars
+    fn space_width(&self, font_collection: &FontCollection) -> f32 {
+        self.text_width("x x", font_collection) - self.text_width("xx", font_collection)
+    }
+}
+
+/// Text of a paragraph as it is drawn. Tabs are expanded and carriage returns are not drawn, the
+/// paragraph keeps both so the file is saved the way it was read.
+struct DisplayText {
+    text: String,
+    /// Display offset of every byte offset of the paragraph
+    display_offsets: Vec<usize>,
+    /// Paragraph offset of every byte offset of the display text
+    paragraph_offsets: Vec<usize>,
+}
+
+impl DisplayText {
+    fn new(paragraph: &str) -> Self {
+        let mut text = String::with_capacity(paragraph.len());
+        let mut display_offsets = Vec::with_capacity(paragraph.len() + 1);
+        let mut paragraph_offsets = Vec::with_capacity(paragraph.len() + 1);
+        for (index, c) in paragraph.char_indices() {
+            display_offsets.resize(index + c.len_utf8(), text.len());
+            match c {
+                '\t' => text.push_str(TAB_SPACES),
+                '\r' => {}
+                c => text.push(c),
+            }
+            paragraph_offsets.resize(text.len(), index);
+        }
+        display_offsets.push(text.len());
+        paragraph_offsets.push(paragraph.len());
+        Self {
+            text,
+            display_offsets,
+            paragraph_offsets,
+        }
+    }
+
+    fn to_display(&self, offset: usize) -> usize {
+        self.display_offsets[offset.min(self.display_offsets.len() - 1)]
+    }
+
+    fn to_paragraph(&self, offset: usize) -> usize {
+        self.paragraph_offsets[offset.min(self.paragraph_offsets.len() - 1)]
+    }
+}
+
+struct WordLayout {
+    /// Byte range of the word in the displayed text
+    range: Range<usize>,
+    /// Relative to the top left of the paragraph
+    rect: Rect,
+    /// Visual line the word was wrapped to
+    line: usize,
+}
+
+/// Wrapped layout of a single paragraph. Positions are relative to its top left corner, with y
+/// going up like in the rest of the GUI. The offsets taken and returned are the ones of the
+/// paragraph, the ones stored are of the displayed text.
+pub struct LineLayout {
+    pub font_elements: Vec<FontElement>,
+    display: DisplayText,
+    words: Vec<WordLayout>,
+    /// Byte offset every visual line starts at
+    line_starts: Vec<usize>,
+    space_width: f32,
+    line_height: f32,
+}
+
+impl LineLayout {
+    pub fn new(text: &str, style: &LayoutStyle, font_collection: &FontCollection) -> Self {
+        let display = DisplayText::new(text);
+        let (mut font_elements, word_rects, _, first_line_height) = create_multi_line(
+            &display.text,
+            style.font_size,
+            font_collection,
+            0,
+            0.0,
+            style.width,
+            style.line_height,
+            style.line_height,
+        );
+        // `create_multi_line` puts the first baseline at 0
+        let origin = vec2(0.0, -first_line_height);
+        for element in font_elements.iter_mut() {
+            element.rect = element.rect.offset_position(origin);
+        }
+
+        // The word rects come in the same order as the words of the text
+        let mut words: Vec<WordLayout> = Vec::with_capacity(word_rects.len());
+        let mut line_starts = vec![0];
+        for (range, word_rect) in word_ranges(&display.text).zip(word_rects.iter()) {
+            let rect = word_rect.rect.offset_position(origin);
+            if let Some(previous) = words.last() {
+                // A word that starts left of the previous one was wrapped to a new line
+                if rect.left_position().x < previous.rect.left_position().x {
+                    line_starts.push(range.start);
+                }
+            }
+            words.push(WordLayout {
+                range,
+                rect,
+                line: line_starts.len() - 1,
+            });
+        }
+
+        Self {
+            font_elements,
+            display,
+            words,
+            line_starts,
+            space_width: style.space_width(font_collection),
+            line_height: style.line_height,
+        }
+    }
+
+    pub fn visual_line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    pub fn height(&self) -> f32 {
+        self.visual_line_count() as f32 * self.line_height
+    }
+
+    /// Byte range of a visual line, the spaces it wrapped at are part of it
+    pub fn visual_line_range(&self, line: usize) -> Range<usize> {
+        let range = self.display_line_range(line);
+        self.display.to_paragraph(range.start)..self.display.to_paragraph(range.end)
+    }
+
+    /// Last caret position of a visual line, before the space it wrapped at
+    pub fn visual_line_end(&self, line: usize) -> usize {
+        let range = self.display_line_range(line);
+        if line + 1 == self.visual_line_count() {
+            return self.display.to_paragraph(range.end);
+        }
+        let end = self.display.text[range.clone()]
+            .char_indices()
+            .next_back()
+            .map_or(range.start, |(index, _)| range.start + index);
+        self.display.to_paragraph(end)
+    }
+
+    pub fn visual_line_of(&self, offset: usize) -> usize {
+        self.display_line_of(self.display.to_display(offset))
+    }
+
+    /// Center of the visual line the caret at `offset` is in, with the x of the caret
+    pub fn caret_position(
+        &self,
+        offset: usize,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) -> Vec2 {
+        self.display_caret_position(self.display.to_display(offset), style, font_collection)
+    }
+
+    fn display_line_range(&self, line: usize) -> Range<usize> {
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.display.text.len());
+        start..end
+    }
+
+    fn display_line_of(&self, offset: usize) -> usize {
+        self.line_starts
+            .iter()
+            .rposition(|start| *start <= offset)
+            .unwrap_or(0)
+    }
+
+    fn display_caret_position(
+        &self,
+        offset: usize,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) -> Vec2 {
+        let text = &self.display.text;
+        let line = self.display_line_of(offset);
+        let y = -(line as f32 + 0.5) * self.line_height;
+        let word_index = self
+            .words
+            .iter()
+            .rposition(|word| word.range.start <= offset);
+        let x = match word_index {
+            Some(index) => {
+                let word = &self.words[index];
+                if offset <= word.range.end {
+                    word.rect.left_position().x
+                        + style.text_width(&text[word.range.start..offset], font_collection)
+                } else {
+                    // In the spaces after the word, they never go past the next word
+                    let spaces = text[word.range.end..offset].chars().count() as f32;
+                    let x =
+                        word.rect.left_position().x + word.rect.width() + self.space_width * spaces;
+                    match self.words.get(index + 1) {
+                        Some(next) if next.line == word.line => x.min(next.rect.left_position().x),
+                        _ => x,
+                    }
+                }
+            }
+            None => self.space_width * text[..offset].chars().count() as f32,
+        };
+        vec2(x, y)
+    }
+
+    /// One rect for every visual line `selection` covers. With `past_end` the selection goes on
+    /// to the next paragraph, so the line break is drawn as a space.
+    pub fn selection_rects(
+        &self,
+        selection: Range<usize>,
+        past_end: bool,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) -> Vec<Rect> {
+        let text = &self.display.text;
+        let selection =
+            self.display.to_display(selection.start)..self.display.to_display(selection.end);
+        let mut rects = Vec::new();
+        for line in 0..self.visual_line_count() {
+            let range = self.display_line_range(line);
+            let is_last = line + 1 == self.visual_line_count();
+            let start = selection.start.max(range.start);
+            let end = selection.end.min(range.end);
+            // The selection goes through the space the line wrapped at
+            let wraps = !is_last && start < range.end && end == range.end;
+            let through_end = is_last && past_end;
+            if start > end || (start == end && !through_end) {
+                continue;
+            }
+
+            let x_start = self.display_caret_position(start, style, font_collection).x;
+            let x_end = if wraps {
+                let last_char = text[..range.end]
+                    .char_indices()
+                    .next_back()
+                    .map_or(range.start, |(index, _)| index);
+                self.display_caret_position(last_char, style, font_collection)
+                    .x
+                    + self.space_width
+            } else {
+                let extra = if through_end { self.space_width } else { 0.0 };
+                self.display_caret_position(end, style, font_collection).x + extra
+            };
+            rects.push(Rect {
+                position: vec2(
+                    (x_start + x_end) * 0.5,
+                    -(line as f32 + 0.5) * self.line_height,
+                ),
+                size: vec2(x_end - x_start, self.line_height),
+            });
+        }
+        rects
+    }
+
+    /// Byte offset closest to `x` on a visual line
+    pub fn offset_at_x(
+        &self,
+        line: usize,
+        x: f32,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) -> usize {
+        let text = &self.display.text;
+        let line = line.min(self.visual_line_count() - 1);
+        let range = self.display_line_range(line);
+        let is_last = line + 1 == self.visual_line_count();
+        let boundaries = text[range.clone()]
+            .char_indices()
+            .map(|(index, _)| range.start + index)
+            .chain(is_last.then(|| range.end));
+
+        let mut closest = (range.start, f32::INFINITY);
+        for offset in boundaries {
+            let caret_x = self
+                .display_caret_position(offset, style, font_collection)
+                .x;
+            let distance = (caret_x - x).abs();
+            if distance < closest.1 {
+                closest = (offset, distance);
+            }
+        }
+        self.display.to_paragraph(closest.0)
+    }
+
+    /// Byte offset closest to a point relative to the top left of the paragraph
+    pub fn offset_at(
+        &self,
+        point: Vec2,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) -> usize {
+        let line = (-point.y / self.line_height).max(0.0) as usize;
+        self.offset_at_x(line, point.x, style, font_collection)
+    }
+}
+
+/// Byte ranges of the words of `text`, split at whitespace like `create_multi_line` does
+fn word_ranges(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = None;
+    text.char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+        .filter_map(move |(index, c)| {
+            if c.is_whitespace() {
+                start.take().map(|start| start..index)
+            } else {
+                if start.is_none() {
+                    start = Some(index);
+                }
+                None
+            }
+        })
+}
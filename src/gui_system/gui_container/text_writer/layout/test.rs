@@ -0,0 +1,25 @@
+use super::DisplayText;
+
+#[test]
+fn tabs_are_expanded_and_returns_hidden() {
+    let display = DisplayText::new("a\tb\rc");
+    assert_eq!(display.text, "a    bc");
+    assert_eq!(display.to_display(1), 1);
+    assert_eq!(display.to_display(2), 5);
+    assert_eq!(display.to_display(4), 6);
+    assert_eq!(display.to_display(5), 7);
+    // Inside of the tab goes back to the tab
+    assert_eq!(display.to_paragraph(3), 1);
+    assert_eq!(display.to_paragraph(5), 2);
+    assert_eq!(display.to_paragraph(6), 4);
+    assert_eq!(display.to_paragraph(7), 5);
+}
+
+#[test]
+fn multi_byte_chars_map_to_their_start() {
+    let display = DisplayText::new("é\t");
+    assert_eq!(display.text, "é    ");
+    assert_eq!(display.to_display(2), 2);
+    assert_eq!(display.to_paragraph(2), 2);
+    assert_eq!(display.to_paragraph(6), 3);
+}
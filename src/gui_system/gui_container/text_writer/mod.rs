@@ -0,0 +1,1000 @@
+use std::{cell::Cell, path::Path, rc::Rc};
+
+use rwge::{
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, push_rect_mask},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    uuid::Uuid,
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    gui_system::{
+        access_tree::{AccessNode, AccessRole},
+        accessibility::get_accessibility,
+        control::{
+            cursor::{CursorIcon, CursorPriority},
+            text_field::{self, TextField, TextInputEvent},
+            tooltip, ControlState,
+        },
+        modal::{ModalDialog, ModalResponse, ModalResult},
+        popup::MenuItem,
+        theme::get_theme,
+        ContainerInfo,
+    },
+    runtime_data::{
+        utils::{get_font_collections, get_time},
+        PublicData,
+    },
+};
+
+use self::{
+    document::{Document, EditKind, TextPosition},
+    layout::{LayoutStyle, LineLayout},
+};
+
+use super::{render_container_background, GUIContainer};
+
+pub mod document;
+pub mod gap_buffer;
+pub mod layout;
+
+/// Opened when the container is created, relative to the working directory
+pub const DEFAULT_FILE_PATH: &str = "res/lorem_ipsum.txt";
+
+const MARGIN: f32 = 10.0;
+const FIND_FIELD_WIDTH: f32 = 200.0;
+const FIND_FIELD_HEIGHT: f32 = 28.0;
+const GUTTER_PADDING: f32 = 8.0;
+const SCROLL_BAR_WIDTH: f32 = 12.0;
+const LINE_HEIGHT_FACTOR: f32 = 1.4;
+/// Lines scrolled by every line of the mouse wheel
+const WHEEL_LINES: f32 = 3.0;
+const CARET_WIDTH: f32 = 1.0;
+/// Seconds the caret stays on, and then off
+const CARET_BLINK_TIME: f32 = 0.5;
+/// Paragraphs this close to the view are laid out too, so scrolling does not make the text jump
+const LAYOUT_OVERSCAN: f32 = 200.0;
+/// Average char width in font sizes, for the height of the paragraphs that are not laid out yet
+const ESTIMATED_CHAR_WIDTH: f32 = 0.5;
+
+const ACTION_SAVE: u32 = 0;
+const ACTION_RELOAD: u32 = 1;
+const ACTION_TOGGLE_LINE_NUMBERS: u32 = 2;
+
+/// Multi line text editor for a UTF-8 file
+pub struct TextWriter {
+    file_path: String,
+    /// Name of the file, marked while there are unsaved changes
+    title: String,
+    document: Document,
+    /// Saving is blocked until the file was read, so a file that failed to load is never replaced
+    loaded: bool,
+    caret: TextPosition,
+    /// The selection goes from the anchor to the caret
+    anchor: TextPosition,
+    /// x the caret keeps while it moves up and down through shorter lines
+    desired_x: Option<f32>,
+    /// Time of the last caret move, the blink starts over from it
+    caret_time: f32,
+    scroll_to_caret: bool,
+
+    /// Top of every paragraph, measured down from the top of the text. Built on every update.
+    line_tops: Vec<f32>,
+    text_height: f32,
+    /// Style the current layouts were built with
+    style: Option<LayoutStyle>,
+    pub show_line_numbers: bool,
+
+    //Scroll controls
+    scroll_offset: f32,
+    mouse_prev_pos: Vec2,
+    scroll_active_id: Option<Uuid>,
+    drag_active_id: Option<Uuid>,
+
+    find_field: TextField,
+
+    //Handled on the next update, where the modals can be opened
+    reload_requested: bool,
+    reload_confirmed: Rc<Cell<bool>>,
+    error: Option<(&'static str, String)>,
+}
+
+impl TextWriter {
+    pub fn new() -> Self {
+        Self::open(DEFAULT_FILE_PATH)
+    }
+
+    /// If the file cannot be read the document starts empty and the error is shown on the first
+    /// update
+    pub fn open(path: &str) -> Self {
+        let mut text_writer = Self {
+            file_path: String::from(path),
+            title: String::new(),
+            document: Document::new(""),
+            loaded: false,
+            caret: TextPosition::default(),
+            anchor: TextPosition::default(),
+            desired_x: None,
+            caret_time: 0.0,
+            scroll_to_caret: false,
+
+            line_tops: Vec::new(),
+            text_height: 0.0,
+            style: None,
+            show_line_numbers: true,
+
+            //Scroll controls
+            scroll_offset: 0.0,
+            mouse_prev_pos: Vec2::ZERO,
+            scroll_active_id: None,
+            drag_active_id: None,
+
+            find_field: TextField::new("").set_placeholder("Find"),
+
+            reload_requested: false,
+            reload_confirmed: Rc::new(Cell::new(false)),
+            error: None,
+        };
+        text_writer.reload();
+        text_writer
+    }
+
+    /// Reads the file again, the unsaved changes are lost
+    pub fn reload(&mut self) {
+        match std::fs::read_to_string(&self.file_path) {
+            Ok(text) => {
+                self.document = Document::new(&text);
+                self.loaded = true;
+                self.caret = TextPosition::default();
+                self.anchor = self.caret;
+                self.desired_x = None;
+                self.scroll_offset = 0.0;
+                self.line_tops.clear();
+            }
+            Err(error) => {
+                self.error = Some(("File not loaded", format!("{}\n{error}", self.file_path)))
+            }
+        }
+        self.update_title();
+    }
+
+    pub fn save(&mut self) {
+        if !self.loaded {
+            let message = "The file was not loaded, saving would replace it";
+            self.error = Some(("File not saved", format!("{}\n{message}", self.file_path)));
+            return;
+        }
+        match std::fs::write(&self.file_path, self.document.text()) {
+            Ok(()) => self.document.modified = false,
+            Err(error) => {
+                self.error = Some(("File not saved", format!("{}\n{error}", self.file_path)))
+            }
+        }
+        self.update_title();
+    }
+
+    fn update_title(&mut self) {
+        let file_name = Path::new(&self.file_path)
+            .file_name()
+            .map_or(self.file_path.clone(), |name| {
+                name.to_string_lossy().into_owned()
+            });
+        self.title = if self.document.modified {
+            format!("{file_name} *")
+        } else {
+            file_name
+        };
+    }
+
+    fn selection(&self) -> (TextPosition, TextPosition) {
+        (self.caret.min(self.anchor), self.caret.max(self.anchor))
+    }
+
+    fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    /// Moves the caret to `position`, with `select` the selection is extended up to it
+    fn set_caret(&mut self, position: TextPosition, select: bool) {
+        self.caret = position;
+        if !select {
+            self.anchor = position;
+        }
+        self.desired_x = None;
+        self.scroll_to_caret = true;
+        self.document.break_undo_group();
+    }
+
+    fn set_selection(&mut self, start: TextPosition, end: TextPosition) {
+        self.set_caret(start, false);
+        self.set_caret(end, true);
+    }
+
+    /// Collapses the selection at the end of an edit
+    fn set_caret_after_edit(&mut self, position: TextPosition) {
+        self.caret = position;
+        self.anchor = position;
+        self.desired_x = None;
+        self.scroll_to_caret = true;
+    }
+
+    fn replace_selection(&mut self, text: &str, kind: EditKind) {
+        let (start, end) = self.selection();
+        let caret = self.document.replace(start, end, text, kind);
+        self.set_caret_after_edit(caret);
+    }
+
+    /// Removes the selection, or the text between the caret and `target`
+    fn delete_to(&mut self, target: TextPosition) {
+        if !self.has_selection() {
+            self.anchor = target;
+        }
+        self.replace_selection("", EditKind::Deleting);
+    }
+
+    /// Selects the next match of the find field after `from`
+    fn find(&mut self, from: TextPosition, backwards: bool) {
+        let query = self.find_field.text();
+        let found = if backwards {
+            self.document.find_previous(query, from)
+        } else {
+            self.document.find(query, from)
+        };
+        if let Some((start, end)) = found {
+            self.set_selection(start, end);
+        }
+    }
+
+    /// Moves the caret by visual lines, keeping its x
+    fn move_vertical(
+        &mut self,
+        lines: isize,
+        select: bool,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) {
+        let caret = self.caret;
+        let layout = laid_out_line(&mut self.document, caret.line, style, font_collection);
+        let x = self.desired_x.unwrap_or_else(|| {
+            layout
+                .caret_position(caret.offset, style, font_collection)
+                .x
+        });
+
+        let mut paragraph = caret.line;
+        let mut visual_line = layout.visual_line_of(caret.offset) as isize + lines;
+        while visual_line < 0 && paragraph > 0 {
+            paragraph -= 1;
+            let layout = laid_out_line(&mut self.document, paragraph, style, font_collection);
+            visual_line += layout.visual_line_count() as isize;
+        }
+        let line_count = self.document.line_count();
+        let target = loop {
+            let layout = laid_out_line(&mut self.document, paragraph, style, font_collection);
+            let visual_line_count = layout.visual_line_count() as isize;
+            if visual_line < 0 {
+                break TextPosition::new(0, 0);
+            }
+            if visual_line < visual_line_count {
+                let offset = layout.offset_at_x(visual_line as usize, x, style, font_collection);
+                break TextPosition::new(paragraph, offset);
+            }
+            if paragraph + 1 == line_count {
+                break self.document.end();
+            }
+            visual_line -= visual_line_count;
+            paragraph += 1;
+        };
+        self.set_caret(target, select);
+        self.desired_x = Some(x);
+    }
+
+    /// Start or end of the visual line the caret is in
+    fn move_to_line_edge(
+        &mut self,
+        end: bool,
+        select: bool,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) {
+        let caret = self.caret;
+        let layout = laid_out_line(&mut self.document, caret.line, style, font_collection);
+        let visual_line = layout.visual_line_of(caret.offset);
+        let offset = if end {
+            layout.visual_line_end(visual_line)
+        } else {
+            layout.visual_line_range(visual_line).start
+        };
+        self.set_caret(TextPosition::new(caret.line, offset), select);
+    }
+
+    /// Applies the input queued since the last update
+    fn handle_input(
+        &mut self,
+        events: Vec<TextInputEvent>,
+        page_lines: isize,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+        control_state: &mut ControlState,
+    ) {
+        for input in events {
+            match input {
+                TextInputEvent::Char(c) => {
+                    if !c.is_control() {
+                        self.replace_selection(c.encode_utf8(&mut [0; 4]), EditKind::Typing);
+                    }
+                }
+                TextInputEvent::Key { key, shift, ctrl } => {
+                    let caret = self.caret;
+                    match key {
+                        VirtualKeyCode::Left => {
+                            let target = if self.has_selection() && !shift && !ctrl {
+                                self.selection().0
+                            } else if ctrl {
+                                self.document.previous_word(caret)
+                            } else {
+                                self.document.previous_position(caret)
+                            };
+                            self.set_caret(target, shift);
+                        }
+                        VirtualKeyCode::Right => {
+                            let target = if self.has_selection() && !shift && !ctrl {
+                                self.selection().1
+                            } else if ctrl {
+                                self.document.next_word(caret)
+                            } else {
+                                self.document.next_position(caret)
+                            };
+                            self.set_caret(target, shift);
+                        }
+                        VirtualKeyCode::Up => self.move_vertical(-1, shift, style, font_collection),
+                        VirtualKeyCode::Down => {
+                            self.move_vertical(1, shift, style, font_collection)
+                        }
+                        VirtualKeyCode::PageUp => {
+                            self.move_vertical(-page_lines, shift, style, font_collection)
+                        }
+                        VirtualKeyCode::PageDown => {
+                            self.move_vertical(page_lines, shift, style, font_collection)
+                        }
+                        VirtualKeyCode::Home if ctrl => {
+                            self.set_caret(self.document.start(), shift)
+                        }
+                        VirtualKeyCode::End if ctrl => self.set_caret(self.document.end(), shift),
+                        VirtualKeyCode::Home => {
+                            self.move_to_line_edge(false, shift, style, font_collection)
+                        }
+                        VirtualKeyCode::End => {
+                            self.move_to_line_edge(true, shift, style, font_collection)
+                        }
+                        VirtualKeyCode::Back => {
+                            let target = if ctrl {
+                                self.document.previous_word(caret)
+                            } else {
+                                self.document.previous_position(caret)
+                            };
+                            self.delete_to(target);
+                        }
+                        VirtualKeyCode::Delete => {
+                            let target = if ctrl {
+                                self.document.next_word(caret)
+                            } else {
+                                self.document.next_position(caret)
+                            };
+                            self.delete_to(target);
+                        }
+                        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                            self.replace_selection("\n", EditKind::Typing)
+                        }
+                        VirtualKeyCode::Tab => self.replace_selection("\t", EditKind::Typing),
+                        VirtualKeyCode::Escape => control_state.clear_focus(),
+                        VirtualKeyCode::F3 => {
+                            let (start, end) = self.selection();
+                            self.find(if shift { start } else { end }, shift);
+                        }
+                        VirtualKeyCode::A if ctrl => {
+                            self.anchor = self.document.start();
+                            self.caret = self.document.end();
+                            self.document.break_undo_group();
+                        }
+                        VirtualKeyCode::C if ctrl => self.copy(control_state),
+                        VirtualKeyCode::X if ctrl && self.has_selection() => {
+                            self.copy(control_state);
+                            self.document.break_undo_group();
+                            self.replace_selection("", EditKind::Other);
+                        }
+                        VirtualKeyCode::V if ctrl => {
+                            if let Some(text) = control_state.text_input.clipboard().get_text() {
+                                // A paste is always its own undo step
+                                self.document.break_undo_group();
+                                self.replace_selection(&text, EditKind::Other);
+                            }
+                        }
+                        VirtualKeyCode::Z if ctrl && shift => self.redo(),
+                        VirtualKeyCode::Z if ctrl => self.undo(),
+                        VirtualKeyCode::Y if ctrl => self.redo(),
+                        VirtualKeyCode::S if ctrl => self.save(),
+                        _ => {}
+                    }
+                }
+                // Only the single line fields draw the text being composed
+                TextInputEvent::Preedit { .. } => {}
+                TextInputEvent::Commit(text) => self.replace_selection(&text, EditKind::Other),
+            }
+        }
+    }
+
+    fn copy(&self, control_state: &mut ControlState) {
+        if self.has_selection() {
+            let (start, end) = self.selection();
+            let text = self.document.slice(start, end);
+            control_state.text_input.clipboard().set_text(&text);
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(caret) = self.document.undo() {
+            self.set_caret_after_edit(caret);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(caret) = self.document.redo() {
+            self.set_caret_after_edit(caret);
+        }
+    }
+
+    /// Lays out the paragraphs around the view and estimates the height of the rest
+    fn update_line_tops(
+        &mut self,
+        view_height: f32,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) {
+        let view_top = self.scroll_offset - LAYOUT_OVERSCAN;
+        let view_bottom = self.scroll_offset + view_height + LAYOUT_OVERSCAN;
+
+        self.line_tops.clear();
+        let mut top = 0.0;
+        for index in 0..self.document.line_count() {
+            self.line_tops.push(top);
+            let line = self.document.line(index);
+            let height = match &line.layout {
+                Some(layout) => layout.height(),
+                None => estimate_height(&line.text, style),
+            };
+            // The caret line is always laid out, the caret is moved with its layout
+            let height =
+                if (top + height >= view_top && top <= view_bottom) || index == self.caret.line {
+                    laid_out_line(&mut self.document, index, style, font_collection).height()
+                } else {
+                    height
+                };
+            top += height;
+        }
+        self.text_height = top;
+    }
+
+    fn max_scroll(&self, view_height: f32) -> f32 {
+        (self.text_height - view_height).max(0.0)
+    }
+
+    fn scroll_caret_into_view(
+        &mut self,
+        view_height: f32,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) {
+        let caret = self.caret;
+        let line_top = match self.line_tops.get(caret.line) {
+            Some(line_top) => *line_top,
+            None => return,
+        };
+        let layout = laid_out_line(&mut self.document, caret.line, style, font_collection);
+        let caret_y = line_top
+            - layout
+                .caret_position(caret.offset, style, font_collection)
+                .y;
+        let caret_top = caret_y - style.line_height * 0.5;
+        let caret_bottom = caret_y + style.line_height * 0.5;
+        if caret_top < self.scroll_offset {
+            self.scroll_offset = caret_top;
+        } else if caret_bottom > self.scroll_offset + view_height {
+            self.scroll_offset = caret_bottom - view_height;
+        }
+    }
+
+    /// Index of the paragraph at `y`, measured down from the top of the text
+    fn line_at(&self, y: f32) -> usize {
+        let line_count = self.line_tops.len().min(self.document.line_count());
+        let index = self.line_tops.partition_point(|top| *top <= y).max(1) - 1;
+        index.min(line_count.max(1) - 1)
+    }
+
+    /// Text position under a point on the screen
+    fn position_at(
+        &mut self,
+        point: Vec2,
+        text_rect: Rect,
+        style: &LayoutStyle,
+        font_collection: &FontCollection,
+    ) -> TextPosition {
+        if self.line_tops.is_empty() {
+            return self.caret;
+        }
+        let y = text_rect.top_left_position().y - point.y + self.scroll_offset;
+        let index = self.line_at(y);
+        let local_point = vec2(
+            point.x - text_rect.left_position().x,
+            self.line_tops[index] - y,
+        );
+        let layout = laid_out_line(&mut self.document, index, style, font_collection);
+        TextPosition::new(index, layout.offset_at(local_point, style, font_collection))
+    }
+
+    /// Opens the dialogs requested since the last update
+    fn handle_requests(&mut self, control_state: &mut ControlState) {
+        if self.reload_requested {
+            self.reload_requested = false;
+            if self.document.modified {
+                let reload_confirmed = self.reload_confirmed.clone();
+                control_state.modals.open(ModalDialog::confirm(
+                    "Reload file",
+                    format!("Discard the unsaved changes to \"{}\"?", self.file_path).as_str(),
+                    "Reload",
+                    ModalResponse::Callback(Box::new(move |result| {
+                        reload_confirmed.set(result == ModalResult::Button(1));
+                    })),
+                ));
+            } else {
+                self.reload();
+            }
+        }
+        if self.reload_confirmed.replace(false) {
+            self.reload();
+        }
+        if let Some((title, message)) = self.error.take() {
+            control_state.modals.open(
+                ModalDialog::new(title, &message, ModalResponse::Callback(Box::new(|_| {})))
+                    .add_button("Close")
+                    .set_default_button(0)
+                    .set_cancel_button(0),
+            );
+        }
+    }
+}
+
+impl GUIContainer for TextWriter {
+    fn get_name(&self) -> &str {
+        &self.title
+    }
+
+    fn get_description(&self) -> Option<&str> {
+        Some(&self.file_path)
+    }
+
+    fn context_menu_items(&self) -> Vec<MenuItem<u32>> {
+        vec![
+            if self.loaded {
+                MenuItem::new("Save", ACTION_SAVE)
+            } else {
+                MenuItem::new_disabled("Save")
+            },
+            MenuItem::new("Reload", ACTION_RELOAD),
+            MenuItem::new(
+                if self.show_line_numbers {
+                    "Hide line numbers"
+                } else {
+                    "Show line numbers"
+                },
+                ACTION_TOGGLE_LINE_NUMBERS,
+            ),
+        ]
+    }
+
+    fn on_context_menu_action(&mut self, action: u32) {
+        match action {
+            ACTION_SAVE => self.save(),
+            // Asks for confirmation on the next update if there are unsaved changes
+            ACTION_RELOAD => self.reload_requested = true,
+            _ => self.show_line_numbers = !self.show_line_numbers,
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+        container_info: ContainerInfo,
+        control_state: &mut ControlState,
+    ) {
+        let text_id = control_state.get_id();
+        let scroll_id = control_state.get_id();
+
+        let theme = get_theme(public_data);
+        let colors = theme.colors;
+        let scale = control_state.scale;
+        let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+        let font_size = scale.font(theme.fonts.size);
+        let time = get_time(public_data).time.0;
+
+        if let UIEvent::Render { gui_rects, .. } = event {
+            render_container_background(gui_rects, &container_info, theme);
+        }
+
+        // Find bar on top, then the line numbers, the text and the scroll bar from left to right
+        let rect = container_info.rect;
+        let margin = scale.px(MARGIN);
+        let find_size = scale.vec(vec2(FIND_FIELD_WIDTH, FIND_FIELD_HEIGHT));
+        let find_rect = Rect {
+            position: rect.top_left_position()
+                + vec2(margin, -margin)
+                + vec2(1.0, -1.0) * find_size * 0.5,
+            size: find_size,
+        };
+        let body_top = rect.top_left_position().y - find_size.y - margin * 2.0;
+        let body_bottom = rect.position.y - rect.height() * 0.5;
+        let gutter_width = if self.show_line_numbers {
+            let digits = "0".repeat(self.document.line_count().to_string().len());
+            let (_, digits_rect) = create_single_line(&digits, font_size, font_collection, 0, 0.0);
+            digits_rect.width() + scale.px(GUTTER_PADDING) * 2.0
+        } else {
+            0.0
+        };
+        let scroll_bar_width = scale.px(SCROLL_BAR_WIDTH);
+        let left = rect.left_position().x;
+        let right = left + rect.width();
+        let body_rect = rect_from_edges(left, body_top, right - scroll_bar_width, body_bottom);
+        let gutter_rect = rect_from_edges(left, body_top, left + gutter_width, body_bottom);
+        let text_rect = rect_from_edges(
+            left + gutter_width + margin,
+            body_top,
+            right - scroll_bar_width - margin,
+            body_bottom,
+        );
+        let track_rect = rect_from_edges(right - scroll_bar_width, body_top, right, body_bottom)
+            .offset_size(-vec2(4.0, 4.0));
+        let view_height = text_rect.height();
+
+        let line_height = (font_size * LINE_HEIGHT_FACTOR).round();
+        let style = LayoutStyle {
+            font_size,
+            line_height,
+            width: text_rect.width().max(font_size),
+        };
+        let page_lines = ((view_height / line_height) as isize).max(1);
+
+        let find_response = text_field::text_field(
+            find_rect,
+            rect,
+            &mut self.find_field,
+            event,
+            control_state,
+            public_data,
+        );
+        let find_field_id = control_state.get_last_id();
+        tooltip::tooltip(
+            control_state,
+            find_field_id,
+            "Find, Enter goes to the next match",
+        );
+        if find_response.submitted {
+            let from = self.selection().1;
+            self.find(from, false);
+        } else if find_response.changed {
+            let from = self.selection().0;
+            self.find(from, false);
+        }
+
+        match event {
+            UIEvent::Update => {
+                self.handle_requests(control_state);
+                if self.style != Some(style) {
+                    self.document.clear_layouts();
+                    self.style = Some(style);
+                }
+
+                if let Some(active_id) = self.drag_active_id.or(self.scroll_active_id) {
+                    if !control_state.hold_active_state(active_id) {
+                        self.drag_active_id = None;
+                        self.scroll_active_id = None;
+                    }
+                } else {
+                    if let Some(interact_rect) = body_rect.combine_rects(&rect) {
+                        control_state.set_hot_with_rect(text_id, &interact_rect);
+                    }
+                    control_state.set_hot_with_rect(scroll_id, &track_rect);
+                }
+
+                if control_state.is_focused(text_id) {
+                    let events = control_state.text_input.take_events();
+                    if events.len() > 0 {
+                        self.caret_time = time;
+                    }
+                    self.handle_input(events, page_lines, &style, font_collection, control_state);
+                }
+
+                self.update_line_tops(view_height, &style, font_collection);
+                let scroll_offset = self.scroll_offset;
+                if control_state.is_hovered(text_id) || control_state.is_hovered(scroll_id) {
+                    let lines = control_state.take_wheel_delta();
+                    self.scroll_offset -= lines * WHEEL_LINES * line_height;
+                }
+                if self.scroll_to_caret {
+                    self.scroll_to_caret = false;
+                    self.scroll_caret_into_view(view_height, &style, font_collection);
+                }
+                self.scroll_offset = self
+                    .scroll_offset
+                    .max(0.0)
+                    .min(self.max_scroll(view_height));
+                // The paragraphs that just scrolled into view need their layout
+                if self.scroll_offset != scroll_offset {
+                    self.update_line_tops(view_height, &style, font_collection);
+                }
+                self.update_title();
+
+                let scroll_access_id = control_state.push_access_node(
+                    AccessNode::new(AccessRole::ScrollView, body_rect, "")
+                        .set_control(scroll_id)
+                        .set_range(self.scroll_offset, 0.0, self.max_scroll(view_height)),
+                );
+                control_state.access.push_parent(scroll_access_id);
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::TextInput, text_rect, &self.title)
+                        .set_control(text_id)
+                        .set_value(self.document.line_text(self.caret.line)),
+                );
+                control_state.access.pop_parent();
+            }
+            UIEvent::MouseButton(mouse_input) => {
+                if mouse_input.is_left_pressed() {
+                    if control_state.is_hovered(text_id) {
+                        control_state.set_focus(text_id);
+                        self.caret_time = time;
+                        if let Some(cursor_pos) = control_state.last_cursor_position {
+                            let position =
+                                self.position_at(cursor_pos, text_rect, &style, font_collection);
+                            if control_state.register_click(text_id) {
+                                let (start, end) = self.document.word_range_at(position);
+                                self.set_selection(start, end);
+                            } else {
+                                let select = control_state.text_input.shift();
+                                self.set_caret(position, select);
+                                self.drag_active_id = control_state.set_active(text_id);
+                            }
+                        }
+                    }
+
+                    self.scroll_active_id = control_state.set_active(scroll_id);
+                    if self.scroll_active_id.is_some() {
+                        self.mouse_prev_pos = control_state.last_cursor_position.unwrap();
+                    }
+                }
+
+                if mouse_input.is_left_released() {
+                    self.drag_active_id = None;
+                    self.scroll_active_id = None;
+                }
+            }
+            UIEvent::MouseMove { corrected, .. } => {
+                if control_state.is_active(self.drag_active_id) {
+                    let position = self.position_at(*corrected, text_rect, &style, font_collection);
+                    self.set_caret(position, true);
+                    self.caret_time = time;
+                }
+
+                if control_state.is_active(self.scroll_active_id) {
+                    let move_delta = *corrected - self.mouse_prev_pos;
+                    let max_scroll = self.max_scroll(view_height);
+                    let thumb_rect = scroll_thumb(
+                        track_rect,
+                        self.text_height,
+                        view_height,
+                        self.scroll_offset,
+                    );
+                    let free_height = track_rect.height() - thumb_rect.height();
+                    if free_height > 0.0 {
+                        self.scroll_offset -= move_delta.y * max_scroll / free_height;
+                        self.scroll_offset = self.scroll_offset.max(0.0).min(max_scroll);
+                    }
+                    self.mouse_prev_pos = *corrected;
+                }
+            }
+            UIEvent::Render { gui_rects, .. } => {
+                if control_state.is_hovered(text_id) || self.drag_active_id.is_some() {
+                    control_state.request_cursor(CursorIcon::Text, CursorPriority::Hover);
+                }
+
+                let body_mask = match body_rect.combine_rects(&rect) {
+                    Some(body_mask) => body_mask,
+                    None => return,
+                };
+                if self.show_line_numbers {
+                    ElementBuilder::new_with_rect(gutter_rect)
+                        .set_color(colors.panel.into())
+                        .set_rect_mask(body_mask.into())
+                        .build(gui_rects);
+                }
+
+                let rect_mask_index = push_rect_mask(body_mask, gui_rects) as u16;
+                let focused = control_state.is_focused(text_id);
+                let (selection_start, selection_end) = self.selection();
+                let text_top = text_rect.top_left_position().y + self.scroll_offset;
+                let gutter_right = left + gutter_width - scale.px(GUTTER_PADDING);
+
+                let line_count = self.line_tops.len().min(self.document.line_count());
+                for index in self.line_at(self.scroll_offset)..line_count {
+                    let line_top = self.line_tops[index];
+                    if line_top > self.scroll_offset + view_height {
+                        break;
+                    }
+                    let origin = vec2(text_rect.left_position().x, text_top - line_top);
+                    let line = self.document.line(index);
+
+                    if self.show_line_numbers {
+                        let (number_elements, number_rect) = create_single_line(
+                            &(index + 1).to_string(),
+                            font_size,
+                            font_collection,
+                            0,
+                            0.0,
+                        );
+                        let number_origin = vec2(
+                            gutter_right - number_rect.width(),
+                            origin.y - (line_height + number_rect.height()) * 0.5,
+                        );
+                        let number_color = if index == self.caret.line {
+                            colors.text
+                        } else {
+                            colors.text_muted
+                        };
+                        for element in number_elements {
+                            ElementBuilder::new_with_rect(
+                                element.rect.offset_position(number_origin.round()),
+                            )
+                            .set_sdffont(element.tx_slice.into())
+                            .set_color(number_color.into())
+                            .set_rect_mask(rect_mask_index.into())
+                            .build(gui_rects);
+                        }
+                    }
+
+                    let layout = match &line.layout {
+                        Some(layout) => layout,
+                        None => continue,
+                    };
+
+                    if self.has_selection()
+                        && index >= selection_start.line
+                        && index <= selection_end.line
+                    {
+                        let start = if index == selection_start.line {
+                            selection_start.offset
+                        } else {
+                            0
+                        };
+                        let end = if index == selection_end.line {
+                            selection_end.offset
+                        } else {
+                            line.text.len()
+                        };
+                        let past_end = index < selection_end.line;
+                        for selection_rect in
+                            layout.selection_rects(start..end, past_end, &style, font_collection)
+                        {
+                            ElementBuilder::new_with_rect(selection_rect.offset_position(origin))
+                                .set_color(colors.accent.set_alpha(0.4).into())
+                                .set_rect_mask(rect_mask_index.into())
+                                .build(gui_rects);
+                        }
+                    }
+
+                    for element in layout.font_elements.iter() {
+                        ElementBuilder::new_with_rect(element.rect.offset_position(origin.round()))
+                            .set_sdffont(element.tx_slice.into())
+                            .set_color(colors.text.into())
+                            .set_rect_mask(rect_mask_index.into())
+                            .build(gui_rects);
+                    }
+
+                    if focused && index == self.caret.line {
+                        let caret_position = origin
+                            + layout.caret_position(self.caret.offset, &style, font_collection);
+                        let blink = ((time - self.caret_time) / CARET_BLINK_TIME).floor() % 2.0;
+                        if get_accessibility(public_data).animated(blink, 0.0) == 0.0 {
+                            ElementBuilder::new(
+                                caret_position.round(),
+                                vec2(scale.px(CARET_WIDTH).max(1.0), font_size),
+                            )
+                            .set_color(colors.text.into())
+                            .set_rect_mask(rect_mask_index.into())
+                            .build(gui_rects);
+                        }
+                        control_state.text_input.ime_position =
+                            Some(caret_position - vec2(0.0, line_height * 0.5));
+                    }
+                }
+
+                if self.text_height > view_height {
+                    ElementBuilder::new_with_rect(track_rect)
+                        .set_color(colors.control.into())
+                        .set_round_rect(BorderRadius::ForAll(track_rect.width() * 0.5).into())
+                        .build(gui_rects);
+
+                    let thumb_rect = scroll_thumb(
+                        track_rect,
+                        self.text_height,
+                        view_height,
+                        self.scroll_offset,
+                    );
+                    let thumb_color = if control_state.is_active(self.scroll_active_id) {
+                        colors.accent
+                    } else if control_state.is_hovered(scroll_id) {
+                        colors.control_hover
+                    } else {
+                        colors.text_muted
+                    };
+                    ElementBuilder::new_with_rect(thumb_rect.offset_size(-vec2(4.0, 4.0)))
+                        .set_color(thumb_color.into())
+                        .set_round_rect(BorderRadius::ForAll(track_rect.width() * 0.5).into())
+                        .build(gui_rects);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Layout of a paragraph, built first if it does not have one
+fn laid_out_line<'a>(
+    document: &'a mut Document,
+    index: usize,
+    style: &LayoutStyle,
+    font_collection: &FontCollection,
+) -> &'a LineLayout {
+    let line = document.line_mut(index);
+    if line.layout.is_none() {
+        line.layout = Some(LineLayout::new(&line.text, style, font_collection));
+    }
+    line.layout.as_ref().unwrap()
+}
+
+fn estimate_height(text: &str, style: &LayoutStyle) -> f32 {
+    let text_width = text.chars().count() as f32 * style.font_size * ESTIMATED_CHAR_WIDTH;
+    let lines = (text_width / style.width).ceil().max(1.0);
+    lines * style.line_height
+}
+
+fn rect_from_edges(left: f32, top: f32, right: f32, bottom: f32) -> Rect {
+    let size = vec2((right - left).max(0.0), (top - bottom).max(0.0));
+    Rect {
+        position: vec2(left, bottom) + size * 0.5,
+        size,
+    }
+}
+
+/// Part of the track that stands for the view
+fn scroll_thumb(track_rect: Rect, text_height: f32, view_height: f32, scroll_offset: f32) -> Rect {
+    let view_proportion = (view_height / text_height.max(1.0)).min(1.0);
+    let thumb_height = (track_rect.height() * view_proportion)
+        .max(track_rect.width())
+        .min(track_rect.height());
+    let max_scroll = (text_height - view_height).max(0.0);
+    let progress = if max_scroll > 0.0 {
+        scroll_offset / max_scroll
+    } else {
+        0.0
+    };
+    let thumb_top =
+        track_rect.top_left_position().y - (track_rect.height() - thumb_height) * progress;
+    Rect {
+        position: vec2(track_rect.position.x, thumb_top - thumb_height * 0.5),
+        size: vec2(track_rect.width(), thumb_height),
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use rwge::{
+    glam::Vec2,
+    gui::rect_ui::{event::UIEvent, Rect},
+};
+
+use crate::{
+    gui_system::{
+        control::{
+            tree_view::{self, TreeChange, TreeItemId, TreeSource, TreeView},
+            ControlState,
+        },
+        theme::get_theme,
+        ContainerInfo,
+    },
+    runtime_data::PublicData,
+};
+
+use super::{render_container_background, GUIContainer};
+
+const MARGIN: f32 = 10.0;
+/// Children of the lazy folder, created the first time it is expanded
+const LAZY_CHILD_COUNT: usize = 50;
+
+struct Node {
+    label: String,
+    parent: Option<TreeItemId>,
+    children: Vec<TreeItemId>,
+    /// The children are created when the item is expanded
+    lazy: bool,
+}
+
+/// Folders and files kept in memory, the moves reported by the tree view are applied to it
+struct DemoTree {
+    nodes: HashMap<TreeItemId, Node>,
+    roots: Vec<TreeItemId>,
+    next_id: TreeItemId,
+}
+
+impl DemoTree {
+    fn new() -> Self {
+        let mut tree = Self {
+            nodes: HashMap::new(),
+            roots: Vec::new(),
+            next_id: 0,
+        };
+        let assets = tree.push("assets", None);
+        let fonts = tree.push("fonts", Some(assets));
+        tree.push("Roboto.ttf", Some(fonts));
+        tree.push("FiraCode.ttf", Some(fonts));
+        let textures = tree.push("textures", Some(assets));
+        tree.push("noise.png", Some(textures));
+        tree.push("checker.png", Some(textures));
+        let shaders = tree.push("shaders", Some(assets));
+        tree.push("rect.wgsl", Some(shaders));
+        tree.push("sdf_font.wgsl", Some(shaders));
+        let generated = tree.push("generated (lazy)", None);
+        tree.nodes.get_mut(&generated).unwrap().lazy = true;
+        let layout = tree.push("layout", None);
+        let window = tree.push("Main window", Some(layout));
+        tree.push("Top bar", Some(window));
+        tree.push("Tabs", Some(window));
+        tree.push("Floating window", Some(layout));
+        tree.push("README.md", None);
+        tree
+    }
+
+    fn push(&mut self, label: &str, parent: Option<TreeItemId>) -> TreeItemId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                label: String::from(label),
+                parent,
+                children: Vec::new(),
+                lazy: false,
+            },
+        );
+        match parent {
+            Some(parent) => self.nodes.get_mut(&parent).unwrap().children.push(id),
+            None => self.roots.push(id),
+        }
+        id
+    }
+
+    fn load_children(&mut self, id: TreeItemId) {
+        let lazy = self.nodes.get(&id).map_or(false, |node| node.lazy);
+        if !lazy {
+            return;
+        }
+        self.nodes.get_mut(&id).unwrap().lazy = false;
+        for index in 0..LAZY_CHILD_COUNT {
+            self.push(&format!("item_{:02}", index), Some(id));
+        }
+    }
+
+    fn siblings_mut(&mut self, parent: Option<TreeItemId>) -> &mut Vec<TreeItemId> {
+        match parent {
+            Some(parent) => &mut self.nodes.get_mut(&parent).unwrap().children,
+            None => &mut self.roots,
+        }
+    }
+
+    /// `index` is counted before the items are taken out of their parents
+    fn move_items(&mut self, items: &[TreeItemId], parent: Option<TreeItemId>, mut index: usize) {
+        for item in items {
+            let old_parent = self.nodes[item].parent;
+            let siblings = self.siblings_mut(old_parent);
+            if let Some(position) = siblings.iter().position(|id| id == item) {
+                siblings.remove(position);
+                if old_parent == parent && position < index {
+                    index -= 1;
+                }
+            }
+        }
+        let siblings = self.siblings_mut(parent);
+        let index = index.min(siblings.len());
+        siblings.splice(index..index, items.iter().copied());
+        for item in items {
+            self.nodes.get_mut(item).unwrap().parent = parent;
+        }
+    }
+}
+
+impl TreeSource for DemoTree {
+    fn roots(&self) -> Vec<TreeItemId> {
+        self.roots.clone()
+    }
+
+    fn children(&self, id: TreeItemId) -> Vec<TreeItemId> {
+        self.nodes[&id].children.clone()
+    }
+
+    fn has_children(&self, id: TreeItemId) -> bool {
+        let node = &self.nodes[&id];
+        node.lazy || !node.children.is_empty()
+    }
+
+    fn label(&self, id: TreeItemId) -> String {
+        self.nodes[&id].label.clone()
+    }
+
+    /// Files can not have children
+    fn can_drop(&self, _items: &[TreeItemId], parent: Option<TreeItemId>) -> bool {
+        parent.map_or(true, |parent| !self.nodes[&parent].label.contains('.'))
+    }
+}
+
+/// Playground for the tree view, the items can be expanded, selected and dragged around
+pub struct TreeTest {
+    tree: DemoTree,
+    tree_view: TreeView,
+}
+
+impl TreeTest {
+    pub fn new() -> Self {
+        let tree = DemoTree::new();
+        let mut tree_view = TreeView::new();
+        if let Some(first) = tree.roots.first() {
+            tree_view.set_expanded(*first, true);
+        }
+        Self { tree, tree_view }
+    }
+}
+
+impl GUIContainer for TreeTest {
+    fn get_name(&self) -> &str {
+        "Tree view"
+    }
+
+    fn get_description(&self) -> Option<&str> {
+        Some("Nested items with lazy children, multi selection and drag and drop")
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+        container_info: ContainerInfo,
+        control_state: &mut ControlState,
+    ) {
+        if let UIEvent::Render { gui_rects, .. } = event {
+            render_container_background(gui_rects, &container_info, get_theme(public_data));
+        }
+
+        let margin = control_state.scale.px(MARGIN);
+        let tree_rect = Rect {
+            position: container_info.rect.position,
+            size: (container_info.rect.size - Vec2::splat(margin * 2.0)).max(Vec2::ZERO),
+        };
+        let changes = tree_view::tree_view(
+            tree_rect,
+            container_info.rect,
+            "Files",
+            &self.tree,
+            &mut self.tree_view,
+            event,
+            control_state,
+            public_data,
+        );
+        for change in changes {
+            match change {
+                TreeChange::Expanded(id) => self.tree.load_children(id),
+                TreeChange::Moved {
+                    items,
+                    parent,
+                    index,
+                } => self.tree.move_items(&items, parent, index),
+                _ => {}
+            }
+        }
+    }
+}
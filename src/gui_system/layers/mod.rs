@@ -0,0 +1,177 @@
+use rwge::gui::rect_ui::{element::builder::ElementBuilder, GUIRects};
+
+#[cfg(test)]
+mod test;
+
+pub type RenderStep = Box<dyn FnOnce(&mut GUIRects) -> ()>;
+
+/// Named depth layers. The first three are allocated once per window, the rest are shared by
+/// every window and are always placed above all of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Base,
+    ContainerContent,
+    Overlay,
+    Popup,
+    Modal,
+    Tooltip,
+    DragPreview,
+}
+
+pub const LAYER_COUNT: usize = 7;
+pub const WINDOW_LAYER_COUNT: usize = 3;
+
+impl Layer {
+    pub const ALL: [Layer; LAYER_COUNT] = [
+        Layer::Base,
+        Layer::ContainerContent,
+        Layer::Overlay,
+        Layer::Popup,
+        Layer::Modal,
+        Layer::Tooltip,
+        Layer::DragPreview,
+    ];
+
+    pub fn index(&self) -> usize {
+        match self {
+            Layer::Base => 0,
+            Layer::ContainerContent => 1,
+            Layer::Overlay => 2,
+            Layer::Popup => 3,
+            Layer::Modal => 4,
+            Layer::Tooltip => 5,
+            Layer::DragPreview => 6,
+        }
+    }
+
+    pub fn is_window_layer(&self) -> bool {
+        self.index() < WINDOW_LAYER_COUNT
+    }
+}
+
+/// Depth ranges of every layer as seen from a single window
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WindowLayers {
+    ranges: [(u32, u32); LAYER_COUNT],
+}
+
+impl WindowLayers {
+    pub fn range(&self, layer: Layer) -> (u32, u32) {
+        self.ranges[layer.index()]
+    }
+
+    /// An offset past the end of the layer is a bug in the caller. Debug builds panic on it,
+    /// release builds clamp it so it never leaks into the next layer.
+    pub fn depth(&self, layer: Layer, offset: u32) -> u32 {
+        let (start, end) = self.range(layer);
+        debug_assert!(
+            start + offset <= end,
+            "Offset {} does not fit in {:?}, it only has {} depth values",
+            offset,
+            layer,
+            end - start + 1
+        );
+        (start + offset).min(end)
+    }
+
+    /// Range covered by the layers that belong to this window
+    pub fn window_range(&self) -> (u32, u32) {
+        (
+            self.ranges[0].0,
+            self.ranges[WINDOW_LAYER_COUNT - 1].1,
+        )
+    }
+}
+
+pub struct LayerManager {
+    layer_sizes: [u32; LAYER_COUNT],
+    window_count: u32,
+    top_render_steps: Vec<(u32, RenderStep)>,
+    /// Present while the elements of the controls are kept instead of drawn
+    captured: Option<Vec<ElementBuilder>>,
+}
+
+impl LayerManager {
+    pub fn new() -> Self {
+        Self {
+            layer_sizes: [2, 4, 4, 8, 4, 2, 2],
+            window_count: 1,
+            top_render_steps: Vec::with_capacity(10),
+            captured: None,
+        }
+    }
+
+    pub fn layer_size(&self, layer: Layer) -> u32 {
+        self.layer_sizes[layer.index()]
+    }
+
+    pub fn window_slice_size(&self) -> u32 {
+        self.layer_sizes[..WINDOW_LAYER_COUNT].iter().sum()
+    }
+
+    /// Called before the windows handle an event
+    pub fn begin_frame(&mut self, window_count: usize) {
+        self.window_count = (window_count as u32).max(1);
+        self.top_render_steps.clear();
+    }
+
+    pub fn window_layers(&self, window_index: usize) -> WindowLayers {
+        let mut ranges = [(0, 0); LAYER_COUNT];
+
+        let mut start = window_index as u32 * self.window_slice_size();
+        for layer in Layer::ALL[..WINDOW_LAYER_COUNT].iter() {
+            let size = self.layer_size(*layer);
+            ranges[layer.index()] = (start, start + size - 1);
+            start += size;
+        }
+
+        let mut start = self.window_count * self.window_slice_size();
+        for layer in Layer::ALL[WINDOW_LAYER_COUNT..].iter() {
+            let size = self.layer_size(*layer);
+            ranges[layer.index()] = (start, start + size - 1);
+            start += size;
+        }
+
+        WindowLayers { ranges }
+    }
+
+    /// Depth of a layer shared by all the windows
+    pub fn top_layer_depth(&self, layer: Layer, offset: u32) -> u32 {
+        assert!(
+            !layer.is_window_layer(),
+            "Window layers depend on the window, use `WindowLayers::depth` instead"
+        );
+        self.window_layers(0).depth(layer, offset)
+    }
+
+    /// Render steps pushed here are executed after every window was rendered
+    pub fn push_top_render_step(&mut self, layer: Layer, offset: u32, step: RenderStep) {
+        let depth = self.top_layer_depth(layer, offset);
+        self.top_render_steps.push((depth, step));
+    }
+
+    pub fn execute_top_render_steps(&mut self, gui_rects: &mut GUIRects) {
+        self.top_render_steps.sort_by_key(|(depth, _)| *depth);
+        for (_, step) in self.top_render_steps.drain(..) {
+            step(gui_rects);
+        }
+    }
+
+    /// Controls inside of a popup are rendered between this and `end_capture`, so the popup can
+    /// draw their elements in its own render step
+    pub fn begin_capture(&mut self) {
+        self.captured = Some(Vec::new());
+    }
+
+    pub fn end_capture(&mut self) -> Vec<ElementBuilder> {
+        self.captured.take().unwrap_or_default()
+    }
+
+    /// Draws the element, or keeps it while capturing
+    pub fn build(&mut self, element: ElementBuilder, gui_rects: &mut GUIRects) {
+        match self.captured.as_mut() {
+            Some(captured) => captured.push(element),
+            None => element.build(gui_rects),
+        }
+    }
+}
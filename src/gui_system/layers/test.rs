@@ -0,0 +1,79 @@
+use super::{Layer, LayerManager, WINDOW_LAYER_COUNT};
+
+#[test]
+fn window_layers_do_not_overlap() {
+    let mut layers = LayerManager::new();
+    layers.begin_frame(3);
+
+    let mut previous_end = None;
+    for window_index in 0..3 {
+        let window_layers = layers.window_layers(window_index);
+        for layer in Layer::ALL[..WINDOW_LAYER_COUNT].iter() {
+            let (start, end) = window_layers.range(*layer);
+            assert!(start <= end);
+            if let Some(previous_end) = previous_end {
+                assert_eq!(start, previous_end + 1, "{:?} is not contiguous", layer);
+            }
+            previous_end = Some(end);
+        }
+    }
+}
+
+#[test]
+fn top_layers_are_above_every_window() {
+    let mut layers = LayerManager::new();
+    layers.begin_frame(4);
+
+    let (_, last_window_end) = layers.window_layers(3).window_range();
+    let popup = layers.top_layer_depth(Layer::Popup, 0);
+    let tooltip = layers.top_layer_depth(Layer::Tooltip, 0);
+    let drag_preview = layers.top_layer_depth(Layer::DragPreview, 0);
+
+    assert!(popup > last_window_end);
+    assert!(tooltip > layers.window_layers(0).range(Layer::Popup).1);
+    assert!(drag_preview > tooltip);
+}
+
+#[test]
+fn top_layers_are_the_same_for_every_window() {
+    let mut layers = LayerManager::new();
+    layers.begin_frame(2);
+
+    for layer in Layer::ALL[WINDOW_LAYER_COUNT..].iter() {
+        assert_eq!(
+            layers.window_layers(0).range(*layer),
+            layers.window_layers(1).range(*layer)
+        );
+    }
+}
+
+#[test]
+fn depth_is_offset_from_the_layer_start() {
+    let layers = LayerManager::new();
+    let window_layers = layers.window_layers(0);
+    let (start, end) = window_layers.range(Layer::Overlay);
+
+    assert_eq!(window_layers.depth(Layer::Overlay, 0), start);
+    assert_eq!(window_layers.depth(Layer::Overlay, end - start), end);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn depth_past_the_layer_end_panics() {
+    let layers = LayerManager::new();
+    let window_layers = layers.window_layers(0);
+    let (start, end) = window_layers.range(Layer::Overlay);
+
+    window_layers.depth(Layer::Overlay, end - start + 1);
+}
+
+#[test]
+fn modals_are_above_popups() {
+    let layers = LayerManager::new();
+    let (_, popup_end) = layers.window_layers(0).range(Layer::Popup);
+
+    // A popup opened behind a dialog must not take its input
+    assert!(layers.top_layer_depth(Layer::Modal, 0) > popup_end);
+    assert!(layers.top_layer_depth(Layer::Tooltip, 0) > layers.top_layer_depth(Layer::Modal, 1));
+}
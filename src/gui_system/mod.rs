@@ -1,14 +1,21 @@
 mod testing_structure;
 use testing_structure::test_screen;
 
+pub mod access_tree;
+pub mod accessibility;
 mod control;
 pub mod gui_container;
+pub mod layers;
+pub mod modal;
+mod pixel_readback;
+pub mod popup;
+pub mod theme;
 mod window_layout;
 
 #[derive(Copy, Clone)]
 pub struct ContainerInfo {
     rect: Rect,
-    depth_range: (u32, u32),
+    layers: WindowLayers,
     //Maybe more in the future
 }
 
@@ -20,38 +27,54 @@ impl ContainerInfo {
 
 use rwge::{
     color::*,
-    glam::{UVec2, Vec2},
+    glam::{uvec2, vec2, UVec2, Vec2},
     gui::rect_ui::{
         event::UIEvent,
         GUIRects, Rect,
     },
     slotmap::Slotmap,
-    Engine,
+    winit::event::{ElementState, Event, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    Engine, EngineEvent,
 };
 
 use crate::{
     gui_system::{
-        gui_container::{text_animation::TextAnimation, text_layout_test::TextLayoutTest},
+        gui_container::{
+            gradient_test::GradientTest, table_test::TableTest, text_animation::TextAnimation,
+            text_layout_test::TextLayoutTest, text_writer::TextWriter, tree_test::TreeTest,
+        },
+        layers::WindowLayers,
         window_layout::TabsSlotKey,
     },
     runtime_data::{
-        utils::{get_engine_data, get_render_texture},
+        utils::{get_engine_data, get_render_texture, get_window},
         RuntimeData, PublicData,
     },
 };
 
 use self::{
+    accessibility::get_accessibility,
+    control::text_field::OsClipboard,
     gui_container::{
         container_one::ContainerOne, performance_monitor::PerformanceMonitor, GUIContainer,
     },
-    window_layout::{DividedElement, GUIContainerSlotkey, WindowSystem},
+    pixel_readback::PixelReadback,
+    window_layout::{
+        Breakpoint, BreakpointAction, DividedElement, GUIContainerSlotkey, WindowSystem,
+    },
 };
 
+/// Pixels of a touchpad scroll that count as one line of the mouse wheel
+const WHEEL_LINE_PIXELS: f32 = 20.0;
+
 /// This version of the window system is only going to work with windowed spaces. This needs to be refactored in the future to support docking.
 pub struct GUISystem {
     pub window_layouting: WindowSystem,
     pub container_collection: Slotmap<Box<dyn GUIContainer>>,
     pub screen_size: UVec2,
+    ctrl_pressed: bool,
+    /// Reads the pixel under the eyedropper from the rendered GUI
+    pixel_readback: PixelReadback,
 }
 
 impl GUISystem {
@@ -59,6 +82,13 @@ impl GUISystem {
         let mut container_collection = Slotmap::<Box<dyn GUIContainer>>::with_capacity(30);
 
         let mut window_layouting = WindowSystem::new();
+        // Copy and paste go through the OS, the fields keep their own clipboard without one
+        if let Some(clipboard) = OsClipboard::new() {
+            window_layouting
+                .control_state
+                .text_input
+                .set_clipboard(Box::new(clipboard));
+        }
 
         //const ELLIPSIS: char = '…';
         const COL_1: RGBA = RGBA::rgb(0.0, 0.5, 0.5);
@@ -132,10 +162,24 @@ impl GUISystem {
         let text_animation_key = window_layouting
             .push_gui_container(Box::new(TextAnimation::new()))
             .unwrap();
+        let text_writer_key = window_layouting
+            .push_gui_container(Box::new(TextWriter::new()))
+            .unwrap();
+        let gradient_key = window_layouting
+            .push_gui_container(Box::new(GradientTest::new()))
+            .unwrap();
+        let tree_key = window_layouting
+            .push_gui_container(Box::new(TreeTest::new()))
+            .unwrap();
+        let table_key = window_layouting
+            .push_gui_container(Box::new(TableTest::new()))
+            .unwrap();
 
-        let perf_tab = window_layouting.create_tab(vec![perf_key]);
-        let text_layout_tab = window_layouting.create_tab(vec![text_layout_key]);
-        let text_animation_tab = window_layouting.create_tab(vec![text_animation_key]);
+        let perf_tab = window_layouting.create_tab(vec![perf_key, tree_key]);
+        let text_layout_tab =
+            window_layouting.create_tab(vec![text_layout_key, text_writer_key, table_key]);
+        let text_animation_tab =
+            window_layouting.create_tab(vec![text_animation_key, gradient_key]);
 
         let mut wl = window_layouting;
         let v1 = DividedElement::new_layout(
@@ -146,7 +190,8 @@ impl GUISystem {
             ])
             .unwrap(),
             1.0,
-        );
+        )
+        .set_priority(1);
 
         let v2 = {
             let d_1 = DividedElement::new_layout(
@@ -157,12 +202,17 @@ impl GUISystem {
                 .unwrap(),
                 1.0,
             );
-            let tl = DividedElement::new_tab(text_layout_tab, 1.2);
-            let d_2 = DividedElement::new_layout(
-                wl.push_horizontal(vec![d_1, tl])
-                    .unwrap(),
-                2.0,
+            let tl = DividedElement::new_tab(text_layout_tab, 1.2)
+                .set_size_limits(250.0, f32::INFINITY);
+            let d_2_key = wl.push_horizontal(vec![d_1, tl]).unwrap();
+            wl.set_layout_breakpoints(
+                d_2_key,
+                vec![Breakpoint {
+                    min_size: 600.0,
+                    action: BreakpointAction::SwitchOrientation,
+                }],
             );
+            let d_2 = DividedElement::new_layout(d_2_key, 2.0);
 
             DividedElement::new_layout(
                 wl.push_vertical(vec![
@@ -172,6 +222,7 @@ impl GUISystem {
                 .unwrap(),
                 2.0,
             )
+            .set_priority(2)
         };
 
         let h1 = DividedElement::new_layout(
@@ -190,6 +241,20 @@ impl GUISystem {
         );
 
         let horizontal_2 = wl.push_horizontal(vec![v1, v2, v3]).unwrap();
+        // The side columns make room for the center one as the window gets narrower
+        wl.set_layout_breakpoints(
+            horizontal_2,
+            vec![
+                Breakpoint {
+                    min_size: 1100.0,
+                    action: BreakpointAction::CollapseChildren { below_priority: 1 },
+                },
+                Breakpoint {
+                    min_size: 800.0,
+                    action: BreakpointAction::HideChildren { below_priority: 2 },
+                },
+            ],
+        );
 
         let _ = wl.create_window(
             horizontal_2,
@@ -197,10 +262,68 @@ impl GUISystem {
             screen_size.as_vec2() * 0.5,
         );
 
+        let floating_key = wl
+            .push_gui_container(Box::new(ContainerOne::new(
+                String::from("W | 18"),
+                0.0,
+                COL_3,
+                100,
+            )))
+            .unwrap();
+        let floating_tab = wl.create_tab(vec![floating_key]);
+        let floating_layout = wl
+            .push_vertical(vec![DividedElement::new_tab(floating_tab, 1.0)])
+            .unwrap();
+        let _ = wl.create_floating_window(
+            floating_layout,
+            vec2(320.0, 240.0),
+            screen_size.as_vec2() * 0.5,
+        );
+
+        // Presets reuse the same containers in their own tab groups
+        let text_workspace = wl.create_workspace("Text");
+        wl.switch_workspace(text_workspace);
+        let text_tabs = [
+            wl.create_tab(vec![text_writer_key, text_layout_key]),
+            wl.create_tab(vec![text_animation_key, perf_key]),
+        ];
+        let text_horizontal = wl
+            .push_horizontal(vec![
+                DividedElement::new_tab(text_tabs[0], 2.0),
+                DividedElement::new_tab(text_tabs[1], 1.0),
+            ])
+            .unwrap();
+        let _ = wl.create_window(
+            text_horizontal,
+            screen_size.as_vec2(),
+            screen_size.as_vec2() * 0.5,
+        );
+
+        let profiling_workspace = wl.create_workspace("Profiling");
+        wl.switch_workspace(profiling_workspace);
+        let profiling_tabs = [
+            wl.create_tab(vec![perf_key]),
+            wl.create_tab(vec![window_box_keys[0], window_box_keys[1]]),
+        ];
+        let profiling_vertical = wl
+            .push_vertical(vec![
+                DividedElement::new_tab(profiling_tabs[0], 1.0),
+                DividedElement::new_tab(profiling_tabs[1], 1.0),
+            ])
+            .unwrap();
+        let _ = wl.create_window(
+            profiling_vertical,
+            screen_size.as_vec2(),
+            screen_size.as_vec2() * 0.5,
+        );
+        wl.switch_workspace(0);
+
         Self {
             window_layouting: wl,
             container_collection,
             screen_size,
+            ctrl_pressed: false,
+            pixel_readback: PixelReadback::new(),
         }
     }
 
@@ -215,7 +338,105 @@ impl GUISystem {
         self.window_layouting.handle_event(event, public_data)
     }
 
+    /// Zoom shortcuts are read from the window events, `UIEvent` does not carry the modifiers
+    /// or the mouse wheel. The scale also follows the window when it moves to a monitor with
+    /// another scale factor. Returns true if the event changed the scale.
+    pub fn handle_zoom_input(&mut self, event: &EngineEvent) -> bool {
+        let scale = &mut self.window_layouting.control_state.scale;
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    scale.set_dpi(*scale_factor as f32);
+                    return true;
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.ctrl_pressed = modifiers.ctrl();
+                }
+                WindowEvent::MouseWheel { delta, .. } if self.ctrl_pressed => {
+                    let scroll = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        MouseScrollDelta::PixelDelta(position) => position.y as f32,
+                    };
+                    if scroll != 0.0 {
+                        scale.zoom_by(scroll.signum());
+                    }
+                    return true;
+                }
+                WindowEvent::KeyboardInput { input, .. }
+                    if self.ctrl_pressed && input.state == ElementState::Pressed =>
+                {
+                    match input.virtual_keycode {
+                        Some(VirtualKeyCode::Key0) | Some(VirtualKeyCode::Numpad0) => {
+                            scale.reset_zoom()
+                        }
+                        Some(VirtualKeyCode::Equals)
+                        | Some(VirtualKeyCode::Plus)
+                        | Some(VirtualKeyCode::NumpadAdd) => scale.zoom_by(1.0),
+                        Some(VirtualKeyCode::Minus) | Some(VirtualKeyCode::NumpadSubtract) => {
+                            scale.zoom_by(-1.0)
+                        }
+                        _ => return false,
+                    }
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Typed chars and pressed keys are queued for the focused text field, the event still reaches
+    /// the rest of the GUI
+    ///
+    /// The winit version of the engine has no IME events, the text the IME commits arrives as
+    /// `ReceivedCharacter` and only the position of the candidate window can be set. `Preedit` and
+    /// `Commit` are ready for when the engine moves to a winit with `WindowEvent::Ime`.
+    pub fn handle_text_input(&mut self, event: &EngineEvent) {
+        let text_input = &mut self.window_layouting.control_state.text_input;
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    text_input.set_modifiers(modifiers.shift(), modifiers.ctrl());
+                }
+                WindowEvent::ReceivedCharacter(c) => text_input.push_char(*c),
+                WindowEvent::KeyboardInput { input, .. }
+                    if input.state == ElementState::Pressed =>
+                {
+                    if let Some(key) = input.virtual_keycode {
+                        text_input.push_key(key);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Mouse wheel lines are queued for the hovered control. With Ctrl held the wheel zooms
+    /// instead, see `handle_zoom_input`.
+    pub fn handle_wheel_input(&mut self, event: &EngineEvent) {
+        if let Event::WindowEvent {
+            event: WindowEvent::MouseWheel { delta, .. },
+            ..
+        } = event
+        {
+            if self.ctrl_pressed {
+                return;
+            }
+            let lines = match delta {
+                MouseScrollDelta::LineDelta(_, y) => *y,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32 / WHEEL_LINE_PIXELS,
+            };
+            self.window_layouting.control_state.push_wheel_delta(lines);
+        }
+    }
+
     pub fn update(&mut self, public_data: &PublicData) {
+        // Follows the monitor the window is on
+        let dpi = get_window(public_data).scale_factor() as f32;
+        let scale = &mut self.window_layouting.control_state.scale;
+        scale.set_dpi(dpi);
+        scale.set_min_font_size(get_accessibility(public_data).min_font_size);
+
         /* Nothing yet - The UIEvent to be sent to the GUI containers is going to be created here */
         let mut event = UIEvent::Update;
         self.window_layouting.handle_event(&mut event, public_data)
@@ -232,6 +453,14 @@ impl GUISystem {
         encoder: &mut rwge::wgpu::CommandEncoder,
         public_data: &mut PublicData,
     ) {
+        // Once the GPU is done with a copied pixel the eyedropper shows it, nothing waits for it
+        if let Some(color) = self.pixel_readback.read(&engine.graphics.device) {
+            self.window_layouting
+                .control_state
+                .color_sampler
+                .set_sample(color);
+        }
+
         gui_rects.rect_collection.clear_buffers();
         {
             self.window_layouting.render_event(public_data, gui_rects);
@@ -255,6 +484,25 @@ impl GUISystem {
                 &color_rt.texture_view,
                 &mask_rt.texture_view,
             );
+
+            // The GUI is laid out with y going up, the texture rows go down
+            let requested = self.window_layouting.control_state.color_sampler.take_request();
+            let screen_size = get_engine_data(public_data).screen_size;
+            if let Some(position) = requested {
+                let pixel = position.floor();
+                if pixel.x >= 0.0
+                    && pixel.y >= 0.0
+                    && pixel.x < screen_size.x as f32
+                    && pixel.y < screen_size.y as f32
+                {
+                    self.pixel_readback.copy(
+                        &engine.graphics.device,
+                        encoder,
+                        &color_rt.texture,
+                        uvec2(pixel.x as u32, screen_size.y - 1 - pixel.y as u32),
+                    );
+                }
+            }
         }
     }
 }
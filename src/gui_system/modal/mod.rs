@@ -0,0 +1,475 @@
+use rwge::{
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    anymap::Anymap,
+    runtime_data::{
+        utils::{get_engine_data, get_font_collections},
+        PublicData,
+    },
+};
+
+use super::{
+    access_tree::{AccessNode, AccessRole},
+    control::{keyboard, scale::GUIScale, ControlState, Uiid},
+    layers::Layer,
+    theme::{get_theme, Theme},
+};
+
+const DIALOG_MIN_WIDTH: f32 = 320.0;
+const BUTTON_HEIGHT: f32 = 30.0;
+const BUTTON_MIN_WIDTH: f32 = 90.0;
+const BUTTON_LABEL_MARGIN: f32 = 14.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModalResult {
+    /// Index of the pressed button
+    Button(usize),
+    /// Escape was pressed on a dialog without a cancel button
+    Cancelled,
+}
+
+impl ModalResult {
+    pub fn button(&self) -> Option<usize> {
+        match self {
+            ModalResult::Button(index) => Some(*index),
+            ModalResult::Cancelled => None,
+        }
+    }
+}
+
+/// How the result gets back to whoever opened the dialog
+pub enum ModalResponse {
+    /// Called as soon as the dialog closes
+    Callback(Box<dyn FnOnce(ModalResult)>),
+    /// Queued on the `PublicData` mutations, so it is applied with the rest of them
+    Mutation(Box<dyn FnMut(ModalResult, &mut Anymap)>),
+}
+
+pub struct ModalDialog {
+    title: String,
+    message: String,
+    buttons: Vec<String>,
+    default_button: Option<usize>,
+    cancel_button: Option<usize>,
+    response: ModalResponse,
+}
+
+impl ModalDialog {
+    pub fn new(title: &str, message: &str, response: ModalResponse) -> Self {
+        Self {
+            title: String::from(title),
+            message: String::from(message),
+            buttons: Vec::with_capacity(2),
+            default_button: None,
+            cancel_button: None,
+            response,
+        }
+    }
+
+    /// Two buttons, "Cancel" (index 0) bound to Escape and `confirm_label` (index 1) bound to
+    /// Enter
+    pub fn confirm(
+        title: &str,
+        message: &str,
+        confirm_label: &str,
+        response: ModalResponse,
+    ) -> Self {
+        Self::new(title, message, response)
+            .add_button("Cancel")
+            .add_button(confirm_label)
+            .set_cancel_button(0)
+            .set_default_button(1)
+    }
+
+    /// Buttons are laid out from left to right in the order they are added
+    pub fn add_button(mut self, label: &str) -> Self {
+        self.buttons.push(String::from(label));
+        self
+    }
+
+    /// Focused when the dialog opens, so Enter presses it
+    pub fn set_default_button(mut self, index: usize) -> Self {
+        self.default_button = Some(index);
+        self
+    }
+
+    /// Pressed by Escape
+    pub fn set_cancel_button(mut self, index: usize) -> Self {
+        self.cancel_button = Some(index);
+        self
+    }
+}
+
+struct OpenDialog {
+    dialog: ModalDialog,
+    focused_button: usize,
+}
+
+/// Dialogs are stacked, only the last one opened receives input. While any of them is open the
+/// rest of the GUI is blocked.
+pub struct ModalSystem {
+    dialogs: Vec<OpenDialog>,
+}
+
+impl ModalSystem {
+    pub fn new() -> Self {
+        Self {
+            dialogs: Vec::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.dialogs.len() > 0
+    }
+
+    pub fn open(&mut self, dialog: ModalDialog) {
+        let focused_button = dialog.default_button.unwrap_or(0);
+        self.dialogs.push(OpenDialog {
+            dialog,
+            focused_button,
+        });
+    }
+}
+
+struct DialogLayout {
+    rect: Rect,
+    button_rects: Vec<Rect>,
+}
+
+/// Handles the top dialog, centered over `parent_rect`. Returns true if a dialog was open, in
+/// which case keyboard input should not reach the rest of the GUI.
+pub fn handle_modal_event(
+    event: &mut UIEvent,
+    public_data: &PublicData,
+    control_state: &mut ControlState,
+    parent_rect: Rect,
+) -> bool {
+    let mut open_dialog = match control_state.modals.dialogs.pop() {
+        Some(open_dialog) => open_dialog,
+        None => return false,
+    };
+    let button_count = open_dialog.dialog.buttons.len();
+
+    let screen_size = get_engine_data(public_data).screen_size.as_vec2();
+    let theme = get_theme(public_data);
+    let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+    let layout = compute_layout(
+        &open_dialog.dialog,
+        font_collection,
+        parent_rect,
+        &control_state.scale,
+        theme,
+    );
+
+    // The backdrop catches the cursor everywhere outside of the dialog
+    control_state.set_depth_and_save(control_state.layers.top_layer_depth(Layer::Modal, 0));
+    let backdrop_id = control_state.get_id();
+    control_state.restore_depth();
+
+    control_state.set_depth_and_save(control_state.layers.top_layer_depth(Layer::Modal, 1));
+    let button_ids: Vec<Uiid> = (0..button_count).map(|_| control_state.get_id()).collect();
+    control_state.restore_depth();
+
+    let mut result = None;
+
+    match keyboard::get_pressed_key(event) {
+        Some(VirtualKeyCode::Escape) => {
+            result = Some(match open_dialog.dialog.cancel_button {
+                Some(index) => ModalResult::Button(index),
+                None => ModalResult::Cancelled,
+            });
+        }
+        Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) => {
+            if button_count > 0 {
+                result = Some(ModalResult::Button(open_dialog.focused_button));
+            }
+        }
+        // Focus never leaves the dialog, it wraps around its buttons
+        Some(VirtualKeyCode::Tab) | Some(VirtualKeyCode::Right) => {
+            if button_count > 0 {
+                open_dialog.focused_button = (open_dialog.focused_button + 1) % button_count;
+            }
+        }
+        Some(VirtualKeyCode::Left) => {
+            if button_count > 0 {
+                open_dialog.focused_button =
+                    (open_dialog.focused_button + button_count - 1) % button_count;
+            }
+        }
+        _ => {}
+    }
+
+    match event {
+        UIEvent::Update => {
+            control_state.set_hot_with_rect(
+                backdrop_id,
+                &Rect {
+                    position: screen_size * 0.5,
+                    size: screen_size,
+                },
+            );
+            for (button_rect, id) in layout.button_rects.iter().zip(button_ids.iter()) {
+                control_state.set_hot_with_rect(*id, button_rect);
+            }
+
+            let dialog = &open_dialog.dialog;
+            let dialog_access_id = control_state.push_access_node(AccessNode::new(
+                AccessRole::Dialog,
+                layout.rect,
+                &dialog.title,
+            ));
+            control_state.access.push_parent(dialog_access_id);
+            control_state.push_access_node(AccessNode::new(
+                AccessRole::Label,
+                layout.rect,
+                &dialog.message,
+            ));
+            for (index, (button_rect, id)) in
+                layout.button_rects.iter().zip(button_ids.iter()).enumerate()
+            {
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Button, *button_rect, &dialog.buttons[index])
+                        .set_control(*id)
+                        .set_focused(index == open_dialog.focused_button),
+                );
+            }
+            control_state.access.pop_parent();
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() {
+                for (index, id) in button_ids.iter().enumerate() {
+                    if control_state.is_hovered(*id) {
+                        open_dialog.focused_button = index;
+                        result = Some(ModalResult::Button(index));
+                    }
+                }
+            }
+        }
+        UIEvent::Render { .. } => {
+            let render_elements = render_dialog(
+                &open_dialog,
+                &layout,
+                &button_ids,
+                screen_size,
+                control_state,
+                font_collection,
+                theme,
+            );
+            control_state.layers.push_top_render_step(
+                Layer::Modal,
+                0,
+                Box::new(move |gui_rects| {
+                    for elem in render_elements {
+                        elem.build(gui_rects);
+                    }
+                }),
+            );
+        }
+        _ => {}
+    }
+
+    match result {
+        Some(result) => match open_dialog.dialog.response {
+            ModalResponse::Callback(callback) => callback(result),
+            ModalResponse::Mutation(mut mutation) => {
+                public_data.push_mut(Box::new(move |anymap| mutation(result, anymap)));
+            }
+        },
+        None => control_state.modals.dialogs.push(open_dialog),
+    }
+    true
+}
+
+fn compute_layout(
+    dialog: &ModalDialog,
+    font_collection: &FontCollection,
+    parent_rect: Rect,
+    scale: &GUIScale,
+    theme: &Theme,
+) -> DialogLayout {
+    let padding = scale.px(theme.metrics.spacing_large);
+    let button_height = scale.px(BUTTON_HEIGHT);
+    let button_gap = scale.px(theme.metrics.spacing);
+    let message_font_size = scale.font(theme.fonts.size);
+
+    let (_, title_rect) = create_single_line(
+        &dialog.title,
+        scale.font(theme.fonts.size_title),
+        font_collection,
+        0,
+        0.0,
+    );
+    let message_size = dialog.message.lines().fold(Vec2::ZERO, |size, line| {
+        let (_, line_rect) = create_single_line(line, message_font_size, font_collection, 0, 0.0);
+        vec2(
+            size.x.max(line_rect.width()),
+            size.y + line_rect.height() + scale.px(theme.metrics.spacing_small),
+        )
+    });
+
+    let button_widths: Vec<f32> = dialog
+        .buttons
+        .iter()
+        .map(|label| {
+            let (_, label_rect) =
+                create_single_line(label, message_font_size, font_collection, 0, 0.0);
+            (label_rect.width() + scale.px(BUTTON_LABEL_MARGIN * 2.0))
+                .max(scale.px(BUTTON_MIN_WIDTH))
+                .round()
+        })
+        .collect();
+    let buttons_width =
+        button_widths.iter().sum::<f32>() + button_gap * (button_widths.len().max(1) - 1) as f32;
+
+    let width = scale
+        .px(DIALOG_MIN_WIDTH)
+        .max(title_rect.width())
+        .max(message_size.x)
+        .max(buttons_width)
+        .round()
+        + padding * 2.0;
+    let height = (title_rect.height() + message_size.y).round() + button_height + padding * 4.0;
+
+    let rect = Rect {
+        position: parent_rect.position.round(),
+        size: vec2(width, height),
+    };
+
+    let bottom_right = rect.position + vec2(width, -height) * 0.5;
+    let mut button_x = bottom_right.x - padding - buttons_width;
+    let button_y = bottom_right.y + padding + button_height * 0.5;
+    let button_rects = button_widths
+        .iter()
+        .map(|button_width| {
+            let button_rect = Rect {
+                position: vec2(button_x + button_width * 0.5, button_y),
+                size: vec2(*button_width, button_height),
+            };
+            button_x += button_width + button_gap;
+            button_rect
+        })
+        .collect();
+
+    DialogLayout { rect, button_rects }
+}
+
+fn render_dialog(
+    open_dialog: &OpenDialog,
+    layout: &DialogLayout,
+    button_ids: &Vec<Uiid>,
+    screen_size: Vec2,
+    control_state: &ControlState,
+    font_collection: &FontCollection,
+    theme: &Theme,
+) -> Vec<ElementBuilder> {
+    let dialog = &open_dialog.dialog;
+    let scale = control_state.scale;
+    let colors = &theme.colors;
+    let metrics = &theme.metrics;
+    let padding = scale.px(metrics.spacing_large);
+    let message_font_size = scale.font(theme.fonts.size);
+    let mut elements = Vec::new();
+
+    // Dimmed backdrop
+    elements.push(
+        ElementBuilder::new_with_rect(Rect {
+            position: screen_size * 0.5,
+            size: screen_size,
+        })
+        .set_color(colors.backdrop.into()),
+    );
+
+    elements.push(
+        ElementBuilder::new_with_rect(layout.rect)
+            .set_color(colors.panel.into())
+            .set_round_rect(BorderRadius::ForAll(scale.px(metrics.radius_large)).into())
+            .set_border(Some(Border {
+                size: scale.border(metrics.border_width),
+                color: colors.border.into(),
+            })),
+    );
+
+    let mut line_top_left = layout.rect.top_left_position() + vec2(padding, -padding);
+
+    let (font_elems, title_rect) = create_single_line(
+        &dialog.title,
+        scale.font(theme.fonts.size_title),
+        font_collection,
+        0,
+        0.0,
+    );
+    let title_offset = line_top_left - vec2(0.0, title_rect.height());
+    for elem in font_elems {
+        elements.push(
+            ElementBuilder::new_with_rect(elem.rect.offset_position(title_offset))
+                .set_sdffont(elem.tx_slice.into())
+                .set_color(colors.text.into()),
+        );
+    }
+    line_top_left.y -= title_rect.height() + padding;
+
+    for line in dialog.message.lines() {
+        let (font_elems, line_rect) =
+            create_single_line(line, message_font_size, font_collection, 0, 0.0);
+        let line_offset = line_top_left - vec2(0.0, line_rect.height());
+        for elem in font_elems {
+            elements.push(
+                ElementBuilder::new_with_rect(elem.rect.offset_position(line_offset))
+                    .set_sdffont(elem.tx_slice.into())
+                    .set_color(colors.text_muted.into()),
+            );
+        }
+        line_top_left.y -= line_rect.height() + scale.px(metrics.spacing_small);
+    }
+
+    for (index, (button_rect, id)) in layout
+        .button_rects
+        .iter()
+        .zip(button_ids.iter())
+        .enumerate()
+    {
+        let color = if control_state.is_hovered(*id) {
+            colors.button_hover
+        } else if dialog.default_button == Some(index) {
+            colors.accent
+        } else {
+            colors.button
+        };
+        let mut builder = ElementBuilder::new_with_rect(*button_rect)
+            .set_color(color.into())
+            .set_round_rect(BorderRadius::ForAll(scale.px(metrics.radius)).into());
+        if open_dialog.focused_button == index {
+            builder = builder.set_border(Some(Border {
+                size: scale.border(metrics.focus_border_width),
+                color: colors.focus.into(),
+            }));
+        }
+        elements.push(builder);
+
+        let (font_elems, label_rect) = create_single_line(
+            &dialog.buttons[index],
+            message_font_size,
+            font_collection,
+            0,
+            0.0,
+        );
+        let label_offset = button_rect.position - label_rect.size * 0.5;
+        for elem in font_elems {
+            elements.push(
+                ElementBuilder::new_with_rect(elem.rect.offset_position(label_offset))
+                    .set_sdffont(elem.tx_slice.into())
+                    .set_color(colors.text.into()),
+            );
+        }
+    }
+    elements
+}
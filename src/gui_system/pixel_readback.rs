@@ -0,0 +1,124 @@
+use std::{
+    num::NonZeroU32,
+    sync::mpsc::{self, Receiver, TryRecvError},
+};
+
+use rwge::{
+    color::RGBA,
+    glam::UVec2,
+    wgpu::{
+        Buffer, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoder, Device, Extent3d,
+        ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Maintain, MapMode, Origin3d, Texture,
+        TextureAspect, COPY_BYTES_PER_ROW_ALIGNMENT,
+    },
+};
+
+enum ReadbackState {
+    Idle,
+    /// The copy is recorded, the buffer is mapped once the encoder was submitted
+    Copied,
+    /// Receives the result of the mapping
+    Mapping(Receiver<Result<(), BufferAsyncError>>),
+}
+
+/// Copies a pixel of the rendered GUI to a buffer the CPU can read. The copy is recorded after the
+/// GUI is rendered, the buffer is mapped on the next frame and read once the GPU is done with it,
+/// so the CPU never waits for the GPU.
+pub struct PixelReadback {
+    /// Created the first time a pixel is copied
+    buffer: Option<Buffer>,
+    state: ReadbackState,
+}
+
+impl PixelReadback {
+    pub fn new() -> Self {
+        Self {
+            buffer: None,
+            state: ReadbackState::Idle,
+        }
+    }
+
+    /// `pixel` is counted from the top left corner of the texture
+    pub fn copy(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+        pixel: UVec2,
+    ) {
+        // A copy that was not read yet is kept
+        if !matches!(self.state, ReadbackState::Idle) {
+            return;
+        }
+        let buffer = self.buffer.get_or_insert_with(|| {
+            device.create_buffer(&BufferDescriptor {
+                label: Some("GUI pixel readback"),
+                size: COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        });
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: pixel.x,
+                    y: pixel.y,
+                    z: 0,
+                },
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.state = ReadbackState::Copied;
+    }
+
+    /// Called once per frame, returns the color of the last copy once the GPU finished it. The GUI
+    /// render texture holds 8 bit RGBA colors as they were drawn, the result is opaque since it
+    /// is what was seen on screen.
+    pub fn read(&mut self, device: &Device) -> Option<RGBA> {
+        let buffer = self.buffer.as_ref()?;
+        if let ReadbackState::Copied = self.state {
+            let (sender, receiver) = mpsc::channel();
+            buffer.slice(..).map_async(MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+            self.state = ReadbackState::Mapping(receiver);
+        }
+        let receiver = match &self.state {
+            ReadbackState::Mapping(receiver) => receiver,
+            _ => return None,
+        };
+
+        device.poll(Maintain::Poll);
+        let mapped = match receiver.try_recv() {
+            Ok(result) => result.is_ok(),
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => false,
+        };
+        self.state = ReadbackState::Idle;
+        if !mapped {
+            return None;
+        }
+        let color = {
+            let bytes = buffer.slice(..).get_mapped_range();
+            let channel = |index: usize| bytes[index] as f32 / 255.0;
+            RGBA::new(channel(0), channel(1), channel(2), 1.0)
+        };
+        buffer.unmap();
+        Some(color)
+    }
+}
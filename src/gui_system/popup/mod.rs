@@ -0,0 +1,453 @@
+use rwge::{
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
+    glam::{vec2, Vec2},
+    gui::rect_ui::{
+        element::{builder::ElementBuilder, Border},
+        event::UIEvent,
+        BorderRadius, Rect,
+    },
+    winit::event::VirtualKeyCode,
+};
+
+use crate::runtime_data::{
+    utils::{get_engine_data, get_font_collections},
+    PublicData,
+};
+
+use super::{
+    access_tree::{AccessNode, AccessRole},
+    control::{keyboard, scale::GUIScale, ControlState, Uiid},
+    layers::Layer,
+    theme::{get_theme, Theme},
+};
+
+const ITEM_HEIGHT: f32 = 26.0;
+const MENU_MIN_WIDTH: f32 = 140.0;
+const LABEL_MARGIN: f32 = 12.0;
+const SUBMENU_ARROW_WIDTH: f32 = 16.0;
+
+pub struct MenuItem<A: Copy> {
+    pub label: String,
+    pub action: Option<A>,
+    pub children: Vec<MenuItem<A>>,
+}
+
+impl<A: Copy> MenuItem<A> {
+    pub fn new(label: &str, action: A) -> Self {
+        Self {
+            label: String::from(label),
+            action: Some(action),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn new_submenu(label: &str, children: Vec<MenuItem<A>>) -> Self {
+        Self {
+            label: String::from(label),
+            action: None,
+            children,
+        }
+    }
+
+    /// Items without action nor children are drawn greyed out and cannot be selected
+    pub fn new_disabled(label: &str) -> Self {
+        Self {
+            label: String::from(label),
+            action: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn map_action<B: Copy>(self, map: &dyn Fn(A) -> B) -> MenuItem<B> {
+        MenuItem {
+            label: self.label,
+            action: self.action.map(map),
+            children: self
+                .children
+                .into_iter()
+                .map(|child| child.map_action(map))
+                .collect(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.action.is_some() || self.children.len() > 0
+    }
+}
+
+struct MenuPanel {
+    level: usize,
+    rect: Rect,
+    item_rects: Vec<Rect>,
+}
+
+struct OpenMenu<A: Copy> {
+    items: Vec<MenuItem<A>>,
+    position: Vec2,
+    /// Index of the item whose submenu is open on each level
+    open_path: Vec<usize>,
+}
+
+/// What an event did to the open menu
+pub struct PopupResponse<A: Copy> {
+    /// Action of the selected item
+    pub action: Option<A>,
+    /// The menu used the event, it should not reach the content under it
+    pub consumed: bool,
+}
+
+/// Menus opened at the cursor position. They are drawn on `Layer::Popup` and they block every
+/// control that is under them until they are closed.
+pub struct PopupSystem<A: Copy> {
+    open_menu: Option<OpenMenu<A>>,
+}
+
+impl<A: Copy> PopupSystem<A> {
+    pub fn new() -> Self {
+        Self { open_menu: None }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open_menu.is_some()
+    }
+
+    /// The top left corner of the menu is placed at `position`
+    pub fn open(&mut self, items: Vec<MenuItem<A>>, position: Vec2) {
+        if items.len() == 0 {
+            return;
+        }
+        self.open_menu = Some(OpenMenu {
+            items,
+            position,
+            open_path: Vec::new(),
+        });
+    }
+
+    pub fn close(&mut self) {
+        self.open_menu = None;
+    }
+
+    /// This should run before the rest of the GUI handles the event, so a click that closes the
+    /// menu does not reach the content under it. The Escape that closes the menu is consumed.
+    pub fn handle_event(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+        control_state: &mut ControlState,
+    ) -> PopupResponse<A> {
+        if !self.is_open() {
+            return PopupResponse {
+                action: None,
+                consumed: false,
+            };
+        }
+
+        if keyboard::key_pressed(event, VirtualKeyCode::Escape) {
+            self.close();
+            return PopupResponse {
+                action: None,
+                consumed: true,
+            };
+        }
+
+        let open_menu = self.open_menu.as_mut().unwrap();
+
+        let screen_size = get_engine_data(public_data).screen_size.as_vec2();
+        let theme = get_theme(public_data);
+        let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+        let panels = compute_panels(
+            open_menu,
+            font_collection,
+            screen_size,
+            &control_state.scale,
+            theme,
+        );
+
+        // Catches the cursor everywhere outside of the menu panels
+        control_state
+            .set_depth_and_save(control_state.layers.top_layer_depth(Layer::Popup, 0));
+        let blocker_id = control_state.get_id();
+        control_state.restore_depth();
+
+        let mut item_ids = Vec::<Vec<Uiid>>::with_capacity(panels.len());
+        for panel in panels.iter() {
+            control_state.set_depth_and_save(
+                control_state
+                    .layers
+                    .top_layer_depth(Layer::Popup, panel.level as u32 + 1),
+            );
+            item_ids.push(panel.item_rects.iter().map(|_| control_state.get_id()).collect());
+            control_state.restore_depth();
+        }
+
+        let mut selected = None;
+        let mut close = false;
+
+        match event {
+            UIEvent::Update => {
+                control_state.set_hot_with_rect(
+                    blocker_id,
+                    &Rect {
+                        position: screen_size * 0.5,
+                        size: screen_size,
+                    },
+                );
+                for (panel, ids) in panels.iter().zip(item_ids.iter()) {
+                    for (item_rect, id) in panel.item_rects.iter().zip(ids.iter()) {
+                        control_state.set_hot_with_rect(*id, item_rect);
+                    }
+                }
+
+                for (panel, ids) in panels.iter().zip(item_ids.iter()) {
+                    let items =
+                        get_level_items(&open_menu.items, &open_menu.open_path, panel.level);
+                    let menu_access_id = control_state
+                        .push_access_node(AccessNode::new(AccessRole::Menu, panel.rect, ""));
+                    control_state.access.push_parent(menu_access_id);
+                    for ((item, item_rect), id) in
+                        items.iter().zip(panel.item_rects.iter()).zip(ids.iter())
+                    {
+                        control_state.push_access_node(
+                            AccessNode::new(AccessRole::MenuItem, *item_rect, &item.label)
+                                .set_control(*id)
+                                .set_disabled(!item.is_enabled()),
+                        );
+                    }
+                    control_state.access.pop_parent();
+                }
+
+                let mut hovered_item = None;
+                for (panel, ids) in panels.iter().zip(item_ids.iter()) {
+                    for (index, id) in ids.iter().enumerate() {
+                        if control_state.is_hovered(*id) {
+                            hovered_item = Some((panel.level, index));
+                        }
+                    }
+                }
+
+                // Hovering an item closes the submenus of its siblings, and opens its own
+                if let Some((level, index)) = hovered_item {
+                    let items = get_level_items(&open_menu.items, &open_menu.open_path, level);
+                    let has_children = items[index].children.len() > 0;
+                    open_menu.open_path.truncate(level);
+                    if has_children {
+                        open_menu.open_path.push(index);
+                    }
+                }
+            }
+            UIEvent::MouseButton(mouse_input) => {
+                if mouse_input.is_left_pressed() || keyboard::is_right_pressed(mouse_input) {
+                    let mut clicked_item = false;
+                    for (panel, ids) in panels.iter().zip(item_ids.iter()) {
+                        let items =
+                            get_level_items(&open_menu.items, &open_menu.open_path, panel.level);
+                        for (index, id) in ids.iter().enumerate() {
+                            if control_state.is_hovered(*id) {
+                                clicked_item = true;
+                                if let Some(action) = items[index].action {
+                                    selected = Some(action);
+                                    close = true;
+                                }
+                            }
+                        }
+                    }
+                    let inside_panel = match control_state.last_cursor_position {
+                        Some(cursor_pos) => panels
+                            .iter()
+                            .any(|panel| panel.rect.inside_rect(cursor_pos)),
+                        None => false,
+                    };
+                    if !clicked_item && !inside_panel {
+                        close = true;
+                    }
+                }
+            }
+            UIEvent::Render { .. } => {
+                for (panel, ids) in panels.iter().zip(item_ids.iter()) {
+                    let items = get_level_items(&open_menu.items, &open_menu.open_path, panel.level);
+                    let open_child = open_menu.open_path.get(panel.level).copied();
+                    let render_elements = render_menu_panel(
+                        panel,
+                        items,
+                        ids,
+                        open_child,
+                        control_state,
+                        font_collection,
+                        theme,
+                    );
+                    control_state.layers.push_top_render_step(
+                        Layer::Popup,
+                        panel.level as u32 + 1,
+                        Box::new(move |gui_rects| {
+                            for elem in render_elements {
+                                elem.build(gui_rects);
+                            }
+                        }),
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        if close {
+            self.open_menu = None;
+        }
+        PopupResponse {
+            action: selected,
+            consumed: false,
+        }
+    }
+}
+
+fn get_level_items<'a, A: Copy>(
+    items: &'a Vec<MenuItem<A>>,
+    open_path: &Vec<usize>,
+    level: usize,
+) -> &'a Vec<MenuItem<A>> {
+    let mut current = items;
+    for index in open_path[..level].iter() {
+        current = &current[*index].children;
+    }
+    current
+}
+
+fn compute_panels<A: Copy>(
+    open_menu: &OpenMenu<A>,
+    font_collection: &FontCollection,
+    screen_size: Vec2,
+    scale: &GUIScale,
+    theme: &Theme,
+) -> Vec<MenuPanel> {
+    let item_height = scale.px(ITEM_HEIGHT);
+    let menu_padding = scale.px(theme.metrics.spacing_small);
+    let font_size = scale.font(theme.fonts.size);
+    let mut panels = Vec::<MenuPanel>::with_capacity(open_menu.open_path.len() + 1);
+    let mut top_left = open_menu.position;
+
+    for level in 0..(open_menu.open_path.len() + 1) {
+        let items = get_level_items(&open_menu.items, &open_menu.open_path, level);
+
+        let width = items.iter().fold(scale.px(MENU_MIN_WIDTH), |width, item| {
+            let (_, label_rect) =
+                create_single_line(&item.label, font_size, font_collection, 0, 0.0);
+            width.max(
+                (label_rect.width() + scale.px(LABEL_MARGIN * 2.0 + SUBMENU_ARROW_WIDTH)).round(),
+            )
+        });
+        let height = items.len() as f32 * item_height + menu_padding * 2.0;
+
+        if let Some(parent) = panels.last() {
+            //Submenus try to open to the right of their parent, and to the left if they do not fit
+            let parent_item = parent.item_rects[open_menu.open_path[level - 1]];
+            top_left = vec2(
+                parent.rect.top_left_position().x + parent.rect.width(),
+                parent_item.top_left_position().y + menu_padding,
+            );
+            if top_left.x + width > screen_size.x {
+                top_left.x = parent.rect.top_left_position().x - width;
+            }
+        }
+        top_left.x = top_left.x.min(screen_size.x - width).max(0.0);
+        top_left.y = top_left.y.max(height).min(screen_size.y);
+
+        let rect = Rect {
+            position: top_left + vec2(width, -height) * 0.5,
+            size: vec2(width, height),
+        };
+        let item_rects = (0..items.len())
+            .map(|index| Rect {
+                position: top_left
+                    + vec2(
+                        width * 0.5,
+                        -menu_padding - item_height * (index as f32 + 0.5),
+                    ),
+                size: vec2(width - menu_padding * 2.0, item_height),
+            })
+            .collect();
+
+        panels.push(MenuPanel {
+            level,
+            rect,
+            item_rects,
+        });
+    }
+    panels
+}
+
+fn render_menu_panel<A: Copy>(
+    panel: &MenuPanel,
+    items: &Vec<MenuItem<A>>,
+    ids: &Vec<Uiid>,
+    open_child: Option<usize>,
+    control_state: &ControlState,
+    font_collection: &FontCollection,
+    theme: &Theme,
+) -> Vec<ElementBuilder> {
+    let scale = control_state.scale;
+    let colors = &theme.colors;
+    let font_size = scale.font(theme.fonts.size);
+    let label_margin = scale.px(LABEL_MARGIN);
+    let mut elements = Vec::new();
+    elements.push(
+        ElementBuilder::new_with_rect(panel.rect)
+            .set_color(colors.panel.into())
+            .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius)).into())
+            .set_border(Some(Border {
+                size: scale.border(theme.metrics.border_width),
+                color: colors.border.into(),
+            })),
+    );
+
+    for (index, (item, item_rect)) in items.iter().zip(panel.item_rects.iter()).enumerate() {
+        let highlighted = item.is_enabled()
+            && (control_state.is_hovered(ids[index]) || open_child == Some(index));
+        if highlighted {
+            elements.push(
+                ElementBuilder::new_with_rect(*item_rect)
+                    .set_color(colors.accent.into())
+                    .set_round_rect(
+                        BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into(),
+                    ),
+            );
+        }
+
+        let text_color = if item.is_enabled() {
+            colors.text
+        } else {
+            colors.text_disabled
+        };
+
+        let (font_elems, font_rect) =
+            create_single_line(&item.label, font_size, font_collection, 0, 0.0);
+        let label_offset = vec2(
+            item_rect.top_left_position().x + label_margin,
+            item_rect.position.y - font_rect.height() * 0.5,
+        );
+        for elem in font_elems {
+            elements.push(
+                ElementBuilder::new_with_rect(elem.rect.offset_position(label_offset))
+                    .set_sdffont(elem.tx_slice.into())
+                    .set_color(text_color.into()),
+            );
+        }
+
+        if item.children.len() > 0 {
+            let (font_elems, font_rect) =
+                create_single_line(">", font_size, font_collection, 0, 0.0);
+            let arrow_offset = vec2(
+                item_rect.top_left_position().x + item_rect.width()
+                    - label_margin
+                    - font_rect.width(),
+                item_rect.position.y - font_rect.height() * 0.5,
+            );
+            for elem in font_elems {
+                elements.push(
+                    ElementBuilder::new_with_rect(elem.rect.offset_position(arrow_offset))
+                        .set_sdffont(elem.tx_slice.into())
+                        .set_color(text_color.into()),
+                );
+            }
+        }
+    }
+    elements
+}
@@ -0,0 +1,461 @@
+use std::fmt;
+
+use rwge::color::RGBA;
+
+use crate::runtime_data::PublicData;
+
+#[cfg(test)]
+mod test;
+
+/// Loaded from the context menu, relative to the working directory
+pub const CUSTOM_THEME_PATH: &str = "res/themes/custom.theme";
+
+/// Colors, sizes and fonts used by every widget and container. Sizes are written for a 100%
+/// display, the widgets multiply them by the `GUIScale`.
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    pub colors: ThemeColors,
+    pub metrics: ThemeMetrics,
+    pub fonts: ThemeFonts,
+}
+
+#[derive(Copy, Clone)]
+pub struct ThemeColors {
+    /// Background of the containers and of the active tab
+    pub background: RGBA,
+    /// Background of the floating windows, visible when a layout does not fill them
+    pub window_background: RGBA,
+    pub tab_bar: RGBA,
+    pub tab_inactive: RGBA,
+    pub tab_hover: RGBA,
+    /// Gradients under the tab bar and on the top of the active tab
+    pub shadow: RGBA,
+    pub text: RGBA,
+    pub text_muted: RGBA,
+    pub text_disabled: RGBA,
+    /// Selected items, default buttons and highlighted menu entries
+    pub accent: RGBA,
+    pub control: RGBA,
+    pub control_hover: RGBA,
+    pub button: RGBA,
+    pub button_hover: RGBA,
+    /// Background of menus and dialogs
+    pub panel: RGBA,
+    pub tooltip: RGBA,
+    pub border: RGBA,
+    pub focus: RGBA,
+    /// Dims everything under a modal dialog
+    pub backdrop: RGBA,
+    pub top_bar: RGBA,
+    pub top_bar_hover: RGBA,
+    pub top_bar_active: RGBA,
+    pub divider_hover: RGBA,
+    pub divider_active: RGBA,
+    pub handle_hover: RGBA,
+    pub handle_active: RGBA,
+    pub snap_preview: RGBA,
+    pub slider_fill_start: RGBA,
+    pub slider_fill_end: RGBA,
+    pub slider_track: RGBA,
+    pub slider_track_hover: RGBA,
+    pub slider_pin: RGBA,
+    pub slider_pin_border: RGBA,
+    pub slider_pin_border_hover: RGBA,
+}
+
+#[derive(Copy, Clone)]
+pub struct ThemeMetrics {
+    pub spacing_small: f32,
+    pub spacing: f32,
+    pub spacing_large: f32,
+    pub tab_gap: f32,
+    pub radius_small: f32,
+    pub radius: f32,
+    pub radius_large: f32,
+    pub border_width: u32,
+    pub focus_border_width: u32,
+    pub slider_pin_size: f32,
+    pub slider_track_height: f32,
+}
+
+#[derive(Copy, Clone)]
+pub struct ThemeFonts {
+    /// Index in the font collections of `PublicData`
+    pub collection: usize,
+    pub size_small: f32,
+    pub size: f32,
+    pub size_title: f32,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ThemePreset {
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 3] = [
+        ThemePreset::Dark,
+        ThemePreset::Light,
+        ThemePreset::HighContrast,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemePreset::Dark => "Dark",
+            ThemePreset::Light => "Light",
+            ThemePreset::HighContrast => "High contrast",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(error) => write!(f, "could not read the theme file: {error}"),
+            ThemeError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Dark => Self::dark(),
+            ThemePreset::Light => Self::light(),
+            ThemePreset::HighContrast => Self::high_contrast(),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            name: String::from(ThemePreset::Dark.name()),
+            colors: ThemeColors {
+                background: RGBA::rrr1(0.2),
+                window_background: RGBA::rrr1(0.1),
+                tab_bar: RGBA::rrr1(0.25),
+                tab_inactive: RGBA::rrr1(0.35),
+                tab_hover: RGBA::rgb(0.4, 0.9, 0.0),
+                shadow: RGBA::rrr1(0.1),
+                text: RGBA::WHITE,
+                text_muted: RGBA::rrr1(0.8),
+                text_disabled: RGBA::rrr1(0.45),
+                accent: RGBA::rgb(0.15, 0.4, 0.8),
+                control: RGBA::rrr1(0.3),
+                control_hover: RGBA::rrr1(0.5),
+                button: RGBA::rrr1(0.22),
+                button_hover: RGBA::rrr1(0.3),
+                panel: RGBA::rrr1(0.14),
+                tooltip: RGBA::rrr1(0.08),
+                border: RGBA::rrr1(0.45),
+                focus: RGBA::rrr1(0.9),
+                backdrop: RGBA::new(0.0, 0.0, 0.0, 0.45),
+                top_bar: RGBA::rrr1(0.25),
+                top_bar_hover: RGBA::rrr1(0.45),
+                top_bar_active: RGBA::GREEN,
+                divider_hover: RGBA::rgb(0.0, 0.25, 0.75),
+                divider_active: RGBA::RED,
+                handle_hover: RGBA::RED,
+                handle_active: RGBA::GREEN,
+                snap_preview: RGBA::rgb(0.3, 0.6, 1.0),
+                slider_fill_start: RGBA::WHITE,
+                slider_fill_end: RGBA::rgb(0.0, 0.25, 0.75),
+                slider_track: RGBA::rrr1(0.15),
+                slider_track_hover: RGBA::rrr1(0.25),
+                slider_pin: RGBA::BLACK,
+                slider_pin_border: RGBA::rrr1(0.5),
+                slider_pin_border_hover: RGBA::rrr1(0.75),
+            },
+            metrics: ThemeMetrics {
+                spacing_small: 4.0,
+                spacing: 8.0,
+                spacing_large: 16.0,
+                tab_gap: 5.0,
+                radius_small: 3.0,
+                radius: 4.0,
+                radius_large: 6.0,
+                border_width: 1,
+                focus_border_width: 1,
+                slider_pin_size: 22.0,
+                slider_track_height: 10.0,
+            },
+            fonts: ThemeFonts {
+                collection: 0,
+                size_small: 15.0,
+                size: 16.0,
+                size_title: 20.0,
+            },
+        }
+    }
+
+    pub fn light() -> Self {
+        let dark = Self::dark();
+        Self {
+            name: String::from(ThemePreset::Light.name()),
+            colors: ThemeColors {
+                background: RGBA::rrr1(0.94),
+                window_background: RGBA::rrr1(0.85),
+                tab_bar: RGBA::rrr1(0.82),
+                tab_inactive: RGBA::rrr1(0.72),
+                tab_hover: RGBA::rgb(0.55, 0.8, 1.0),
+                shadow: RGBA::rrr1(0.6),
+                text: RGBA::rrr1(0.1),
+                text_muted: RGBA::rrr1(0.3),
+                text_disabled: RGBA::rrr1(0.6),
+                accent: RGBA::rgb(0.2, 0.5, 0.95),
+                control: RGBA::rrr1(0.8),
+                control_hover: RGBA::rrr1(0.7),
+                button: RGBA::rrr1(0.86),
+                button_hover: RGBA::rrr1(0.78),
+                panel: RGBA::rrr1(0.97),
+                tooltip: RGBA::rgb(1.0, 1.0, 0.9),
+                border: RGBA::rrr1(0.6),
+                focus: RGBA::rgb(0.1, 0.35, 0.8),
+                backdrop: RGBA::new(0.0, 0.0, 0.0, 0.25),
+                top_bar: RGBA::rrr1(0.78),
+                top_bar_hover: RGBA::rrr1(0.68),
+                top_bar_active: RGBA::rgb(0.2, 0.5, 0.95),
+                divider_hover: RGBA::rgb(0.2, 0.5, 0.95),
+                divider_active: RGBA::rgb(0.1, 0.35, 0.8),
+                handle_hover: RGBA::rgb(0.2, 0.5, 0.95),
+                handle_active: RGBA::rgb(0.1, 0.35, 0.8),
+                snap_preview: RGBA::rgb(0.2, 0.5, 0.95),
+                slider_fill_start: RGBA::rgb(0.1, 0.35, 0.8),
+                slider_fill_end: RGBA::rgb(0.55, 0.8, 1.0),
+                slider_track: RGBA::rrr1(0.8),
+                slider_track_hover: RGBA::rrr1(0.72),
+                slider_pin: RGBA::WHITE,
+                slider_pin_border: RGBA::rrr1(0.55),
+                slider_pin_border_hover: RGBA::rgb(0.2, 0.5, 0.95),
+            },
+            ..dark
+        }
+    }
+
+    /// Black and white with a yellow accent, every control has a visible outline
+    pub fn high_contrast() -> Self {
+        let dark = Self::dark();
+        let accent = RGBA::rgb(1.0, 0.85, 0.0);
+        Self {
+            name: String::from(ThemePreset::HighContrast.name()),
+            colors: ThemeColors {
+                background: RGBA::BLACK,
+                window_background: RGBA::BLACK,
+                tab_bar: RGBA::BLACK,
+                tab_inactive: RGBA::rrr1(0.2),
+                tab_hover: accent,
+                shadow: RGBA::BLACK,
+                text: RGBA::WHITE,
+                text_muted: RGBA::WHITE,
+                text_disabled: RGBA::rrr1(0.6),
+                accent,
+                control: RGBA::rrr1(0.2),
+                control_hover: RGBA::rrr1(0.4),
+                button: RGBA::BLACK,
+                button_hover: RGBA::rrr1(0.3),
+                panel: RGBA::BLACK,
+                tooltip: RGBA::BLACK,
+                border: RGBA::WHITE,
+                focus: accent,
+                backdrop: RGBA::new(0.0, 0.0, 0.0, 0.7),
+                top_bar: RGBA::rrr1(0.2),
+                top_bar_hover: RGBA::rrr1(0.4),
+                top_bar_active: accent,
+                divider_hover: RGBA::WHITE,
+                divider_active: accent,
+                handle_hover: RGBA::WHITE,
+                handle_active: accent,
+                snap_preview: accent,
+                slider_fill_start: accent,
+                slider_fill_end: accent,
+                slider_track: RGBA::rrr1(0.3),
+                slider_track_hover: RGBA::rrr1(0.45),
+                slider_pin: RGBA::BLACK,
+                slider_pin_border: RGBA::WHITE,
+                slider_pin_border_hover: accent,
+            },
+            metrics: ThemeMetrics {
+                border_width: 2,
+                focus_border_width: 3,
+                ..dark.metrics
+            },
+            fonts: ThemeFonts {
+                size_small: 16.0,
+                size: 17.0,
+                size_title: 22.0,
+                ..dark.fonts
+            },
+        }
+    }
+
+    /// Reads a theme file made of `key = value` lines, see `res/themes/custom.theme`. Keys that
+    /// are not in the file keep the value of the `base` preset, which is the dark theme by default.
+    /// `base` replaces every value set before it, so it goes at the top of the file.
+    pub fn load(path: &str) -> Result<Self, ThemeError> {
+        let text = std::fs::read_to_string(path).map_err(ThemeError::Io)?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ThemeError> {
+        let mut theme = Self::dark();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: String| ThemeError::Parse {
+                line: index + 1,
+                message,
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| parse_error(String::from("expected `key = value`")))?;
+            theme
+                .set_value(key.trim(), value.trim())
+                .map_err(parse_error)?;
+        }
+        Ok(theme)
+    }
+
+    fn set_value(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key.split_once('.') {
+            None if key == "name" => self.name = String::from(value),
+            None if key == "base" => {
+                let name = std::mem::take(&mut self.name);
+                *self = Self::preset(parse_preset(value)?);
+                self.name = name;
+            }
+            Some(("color", name)) => *self.color_mut(name)? = parse_color(value)?,
+            Some(("metric", "border_width")) => self.metrics.border_width = parse_number(value)?,
+            Some(("metric", "focus_border_width")) => {
+                self.metrics.focus_border_width = parse_number(value)?
+            }
+            Some(("metric", name)) => *self.metric_mut(name)? = parse_number(value)?,
+            Some(("font", "collection")) => self.fonts.collection = parse_number(value)?,
+            Some(("font", name)) => *self.font_size_mut(name)? = parse_number(value)?,
+            _ => return Err(format!("unknown key `{key}`")),
+        }
+        Ok(())
+    }
+
+    fn color_mut(&mut self, name: &str) -> Result<&mut RGBA, String> {
+        let colors = &mut self.colors;
+        Ok(match name {
+            "background" => &mut colors.background,
+            "window_background" => &mut colors.window_background,
+            "tab_bar" => &mut colors.tab_bar,
+            "tab_inactive" => &mut colors.tab_inactive,
+            "tab_hover" => &mut colors.tab_hover,
+            "shadow" => &mut colors.shadow,
+            "text" => &mut colors.text,
+            "text_muted" => &mut colors.text_muted,
+            "text_disabled" => &mut colors.text_disabled,
+            "accent" => &mut colors.accent,
+            "control" => &mut colors.control,
+            "control_hover" => &mut colors.control_hover,
+            "button" => &mut colors.button,
+            "button_hover" => &mut colors.button_hover,
+            "panel" => &mut colors.panel,
+            "tooltip" => &mut colors.tooltip,
+            "border" => &mut colors.border,
+            "focus" => &mut colors.focus,
+            "backdrop" => &mut colors.backdrop,
+            "top_bar" => &mut colors.top_bar,
+            "top_bar_hover" => &mut colors.top_bar_hover,
+            "top_bar_active" => &mut colors.top_bar_active,
+            "divider_hover" => &mut colors.divider_hover,
+            "divider_active" => &mut colors.divider_active,
+            "handle_hover" => &mut colors.handle_hover,
+            "handle_active" => &mut colors.handle_active,
+            "snap_preview" => &mut colors.snap_preview,
+            "slider_fill_start" => &mut colors.slider_fill_start,
+            "slider_fill_end" => &mut colors.slider_fill_end,
+            "slider_track" => &mut colors.slider_track,
+            "slider_track_hover" => &mut colors.slider_track_hover,
+            "slider_pin" => &mut colors.slider_pin,
+            "slider_pin_border" => &mut colors.slider_pin_border,
+            "slider_pin_border_hover" => &mut colors.slider_pin_border_hover,
+            _ => return Err(format!("unknown color `{name}`")),
+        })
+    }
+
+    fn metric_mut(&mut self, name: &str) -> Result<&mut f32, String> {
+        let metrics = &mut self.metrics;
+        Ok(match name {
+            "spacing_small" => &mut metrics.spacing_small,
+            "spacing" => &mut metrics.spacing,
+            "spacing_large" => &mut metrics.spacing_large,
+            "tab_gap" => &mut metrics.tab_gap,
+            "radius_small" => &mut metrics.radius_small,
+            "radius" => &mut metrics.radius,
+            "radius_large" => &mut metrics.radius_large,
+            "slider_pin_size" => &mut metrics.slider_pin_size,
+            "slider_track_height" => &mut metrics.slider_track_height,
+            _ => return Err(format!("unknown metric `{name}`")),
+        })
+    }
+
+    fn font_size_mut(&mut self, name: &str) -> Result<&mut f32, String> {
+        let fonts = &mut self.fonts;
+        Ok(match name {
+            "size_small" => &mut fonts.size_small,
+            "size" => &mut fonts.size,
+            "size_title" => &mut fonts.size_title,
+            _ => return Err(format!("unknown font setting `{name}`")),
+        })
+    }
+}
+
+fn parse_preset(value: &str) -> Result<ThemePreset, String> {
+    match value {
+        "dark" => Ok(ThemePreset::Dark),
+        "light" => Ok(ThemePreset::Light),
+        "high_contrast" => Ok(ThemePreset::HighContrast),
+        _ => Err(format!("unknown preset `{value}`")),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a valid number"))
+}
+
+/// `#RRGGBB` or `#RRGGBBAA`
+pub(crate) fn parse_color(value: &str) -> Result<RGBA, String> {
+    let invalid = || format!("`{value}` is not a color, expected #RRGGBB or #RRGGBBAA");
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let channel = |index: usize| -> Result<f32, String> {
+        u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+            .map(|channel| channel as f32 / 255.0)
+            .map_err(|_| invalid())
+    };
+    let alpha = if hex.len() == 8 { channel(3)? } else { 1.0 };
+    Ok(RGBA::new(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+pub fn get_theme(public_data: &PublicData) -> &Theme {
+    public_data.get().expect("Theme not found")
+}
+
+/// The theme is replaced once the current event finished, every widget reads the new one from
+/// the next event on
+pub fn set_theme(public_data: &PublicData, theme: Theme) {
+    let mut theme = Some(theme);
+    public_data.push_mut(Box::new(move |public_data| {
+        if let Some(theme) = theme.take() {
+            *public_data.get_mut::<Theme>().unwrap() = theme;
+        }
+    }));
+}
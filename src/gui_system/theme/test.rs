@@ -0,0 +1,127 @@
+use rwge::color::RGBA;
+
+use super::{parse_color, Theme, ThemeError};
+
+fn same_color(a: RGBA, b: RGBA) -> bool {
+    a.r == b.r && a.g == b.g && a.b == b.b && a.a == b.a
+}
+
+fn error_line(text: &str) -> (usize, String) {
+    match Theme::parse(text) {
+        Err(ThemeError::Parse { line, message }) => (line, message),
+        Err(error) => panic!("unexpected error: {error}"),
+        Ok(_) => panic!("`{text}` was accepted"),
+    }
+}
+
+#[test]
+fn empty_file_is_the_dark_theme() {
+    let dark = Theme::dark();
+    let theme = Theme::parse("# only a comment\n\n   \n").unwrap();
+    assert_eq!(theme.name, dark.name);
+    assert_eq!(theme.fonts.size, dark.fonts.size);
+    assert_eq!(theme.metrics.border_width, dark.metrics.border_width);
+    assert!(same_color(theme.colors.accent, dark.colors.accent));
+}
+
+#[test]
+fn missing_keys_keep_the_base_values() {
+    let light = Theme::light();
+    let theme = Theme::parse("name = Mine\nbase = light\nmetric.radius = 9\n").unwrap();
+    assert_eq!(theme.name, "Mine");
+    assert_eq!(theme.metrics.radius, 9.0);
+    assert_eq!(theme.metrics.spacing, light.metrics.spacing);
+    assert_eq!(theme.fonts.size_title, light.fonts.size_title);
+    assert!(same_color(theme.colors.background, light.colors.background));
+}
+
+#[test]
+fn base_replaces_earlier_values() {
+    let theme = Theme::parse("font.size = 30\nbase = light\n").unwrap();
+    assert_eq!(theme.fonts.size, Theme::light().fonts.size);
+}
+
+#[test]
+fn values_are_set() {
+    let text = "color.accent = #FF000080\nmetric.border_width = 3\nfont.collection = 1\n\
+                font.size_small = 11.5\n";
+    let theme = Theme::parse(text).unwrap();
+    assert!(same_color(
+        theme.colors.accent,
+        RGBA::new(1.0, 0.0, 0.0, 128.0 / 255.0)
+    ));
+    assert_eq!(theme.metrics.border_width, 3);
+    assert_eq!(theme.fonts.collection, 1);
+    assert_eq!(theme.fonts.size_small, 11.5);
+}
+
+#[test]
+fn line_without_equal_sign_is_rejected() {
+    let (line, message) = error_line("name = Mine\n\n# comment\ncolor.accent #FFFFFF\n");
+    assert_eq!(line, 4);
+    assert!(message.contains("key = value"), "{message}");
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let cases = [
+        ("size = 3", "unknown key"),
+        ("colour.accent = #FFFFFF", "unknown key"),
+        ("color.acent = #FFFFFF", "unknown color"),
+        ("metric.width = 3", "unknown metric"),
+        ("font.weight = 3", "unknown font setting"),
+        ("base = sepia", "unknown preset"),
+    ];
+    for (text, expected) in cases {
+        let (line, message) = error_line(text);
+        assert_eq!(line, 1);
+        assert!(message.contains(expected), "`{text}` gave `{message}`");
+    }
+}
+
+#[test]
+fn invalid_numbers_are_rejected() {
+    for text in [
+        "metric.border_width = -1",
+        "metric.radius = abc",
+        "font.collection = 1.5",
+        "font.size =",
+    ] {
+        let (_, message) = error_line(text);
+        assert!(
+            message.contains("not a valid number"),
+            "`{text}` gave `{message}`"
+        );
+    }
+}
+
+#[test]
+fn invalid_colors_are_rejected() {
+    for value in [
+        "FFFFFF",
+        "#FFF",
+        "#FFFFFFF",
+        "#GGGGGG",
+        "#FFFFÉ",
+        "#FFFFFFFFFF",
+    ] {
+        assert!(parse_color(value).is_err(), "`{value}` was accepted");
+    }
+    let (_, message) = error_line("color.text = red");
+    assert!(message.contains("not a color"), "{message}");
+}
+
+#[test]
+fn alpha_defaults_to_opaque() {
+    let color = parse_color("#336699").unwrap();
+    assert!(same_color(
+        color,
+        RGBA::new(
+            0x33 as f32 / 255.0,
+            0x66 as f32 / 255.0,
+            0x99 as f32 / 255.0,
+            1.0
+        )
+    ));
+    assert_eq!(parse_color("#33669900").unwrap().a, 0.0);
+}
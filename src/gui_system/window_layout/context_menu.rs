@@ -0,0 +1,226 @@
+use crate::{
+    gui_system::{
+        accessibility::{
+            set_high_contrast, set_min_font_size, set_reduced_motion, AccessibilitySettings,
+            MIN_FONT_SIZES,
+        },
+        gui_container::GUIContainer,
+        modal::{ModalDialog, ModalResponse},
+        popup::MenuItem,
+        theme::{set_theme, Theme, ThemePreset, CUSTOM_THEME_PATH},
+    },
+    runtime_data::PublicData,
+};
+
+use super::{GUIContainerSlotkey, TabsSlotKey, WindowSystem};
+
+#[derive(Clone, Copy)]
+pub enum ContextAction {
+    CloseTab {
+        tabs_key: TabsSlotKey,
+        index: usize,
+    },
+    SplitRight {
+        tabs_key: TabsSlotKey,
+        index: usize,
+    },
+    ToggleMaximize {
+        tabs_key: TabsSlotKey,
+    },
+    SwitchWorkspace {
+        index: usize,
+    },
+    SaveWorkspace,
+    SetTheme {
+        preset: ThemePreset,
+    },
+    /// Loads `CUSTOM_THEME_PATH` again, so the file can be edited while the GUI is open
+    LoadThemeFile,
+    SetReducedMotion {
+        enabled: bool,
+    },
+    SetHighContrast {
+        enabled: bool,
+    },
+    SetMinFontSize {
+        size: f32,
+    },
+    /// Actions defined by the container itself
+    Container {
+        key: GUIContainerSlotkey,
+        action: u32,
+    },
+}
+
+pub fn container_context_menu(
+    key: GUIContainerSlotkey,
+    container: &dyn GUIContainer,
+) -> Vec<MenuItem<ContextAction>> {
+    container
+        .context_menu_items()
+        .into_iter()
+        .map(|item| item.map_action(&|action| ContextAction::Container { key, action }))
+        .collect()
+}
+
+pub fn tab_context_menu(
+    tabs_key: TabsSlotKey,
+    index: usize,
+    tab_count: usize,
+    maximized: bool,
+    container_key: GUIContainerSlotkey,
+    container: &dyn GUIContainer,
+) -> Vec<MenuItem<ContextAction>> {
+    let mut items = Vec::with_capacity(4);
+    items.push(MenuItem::new(
+        "Close tab",
+        ContextAction::CloseTab { tabs_key, index },
+    ));
+    if tab_count > 1 {
+        items.push(MenuItem::new(
+            "Split right",
+            ContextAction::SplitRight { tabs_key, index },
+        ));
+    } else {
+        items.push(MenuItem::new_disabled("Split right"));
+    }
+    items.push(MenuItem::new(
+        if maximized { "Restore" } else { "Maximize" },
+        ContextAction::ToggleMaximize { tabs_key },
+    ));
+
+    let container_items = container_context_menu(container_key, container);
+    if container_items.len() > 0 {
+        items.push(MenuItem::new_submenu(container.get_name(), container_items));
+    }
+    items
+}
+
+/// The active theme is disabled, like the active workspace
+pub fn theme_menu(active_theme: &str) -> MenuItem<ContextAction> {
+    let mut items: Vec<MenuItem<ContextAction>> = ThemePreset::ALL
+        .iter()
+        .map(|preset| {
+            if preset.name() == active_theme {
+                MenuItem::new_disabled(preset.name())
+            } else {
+                MenuItem::new(preset.name(), ContextAction::SetTheme { preset: *preset })
+            }
+        })
+        .collect();
+    items.push(MenuItem::new(
+        "Load theme file",
+        ContextAction::LoadThemeFile,
+    ));
+    MenuItem::new_submenu("Theme", items)
+}
+
+pub fn accessibility_menu(settings: &AccessibilitySettings) -> MenuItem<ContextAction> {
+    let font_sizes = MIN_FONT_SIZES
+        .iter()
+        .map(|size| {
+            let label = if *size == 0.0 {
+                String::from("Theme sizes")
+            } else {
+                format!("At least {size}")
+            };
+            if *size == settings.min_font_size {
+                MenuItem::new_disabled(&label)
+            } else {
+                MenuItem::new(&label, ContextAction::SetMinFontSize { size: *size })
+            }
+        })
+        .collect();
+
+    MenuItem::new_submenu(
+        "Accessibility",
+        vec![
+            MenuItem::new(
+                if settings.reduced_motion {
+                    "Allow motion"
+                } else {
+                    "Reduce motion"
+                },
+                ContextAction::SetReducedMotion {
+                    enabled: !settings.reduced_motion,
+                },
+            ),
+            MenuItem::new(
+                if settings.high_contrast() {
+                    "Turn off high contrast"
+                } else {
+                    "High contrast"
+                },
+                ContextAction::SetHighContrast {
+                    enabled: !settings.high_contrast(),
+                },
+            ),
+            MenuItem::new_submenu("Minimum font size", font_sizes),
+        ],
+    )
+}
+
+impl WindowSystem {
+    pub fn apply_context_action(&mut self, action: ContextAction, public_data: &PublicData) {
+        match action {
+            ContextAction::CloseTab { tabs_key, index } => {
+                let (tabs_key, index) = self.resolve_merged_tab(tabs_key, index);
+                self.close_tab(tabs_key, index);
+            }
+            ContextAction::SplitRight { tabs_key, index } => {
+                let (tabs_key, index) = self.resolve_merged_tab(tabs_key, index);
+                self.split_tab_right(tabs_key, index);
+            }
+            ContextAction::ToggleMaximize { tabs_key } => {
+                let active_tab = self
+                    .tabs_slotmap
+                    .get_value(&tabs_key)
+                    .map_or(0, |tabs| tabs.active_tab());
+                let (tabs_key, _) = self.resolve_merged_tab(tabs_key, active_tab);
+                self.toggle_maximize(tabs_key);
+            }
+            ContextAction::SwitchWorkspace { index } => {
+                self.switch_workspace(index);
+            }
+            ContextAction::SaveWorkspace => {
+                let name = format!("Workspace {}", self.workspaces.len() + 1);
+                self.save_workspace_as(&name);
+            }
+            // Picking a theme replaces the high contrast one
+            ContextAction::SetTheme { preset } => {
+                set_high_contrast(public_data, false);
+                set_theme(public_data, Theme::preset(preset));
+            }
+            ContextAction::LoadThemeFile => match Theme::load(CUSTOM_THEME_PATH) {
+                Ok(theme) => {
+                    set_high_contrast(public_data, false);
+                    set_theme(public_data, theme);
+                }
+                Err(error) => self.control_state.modals.open(
+                    ModalDialog::new(
+                        "Theme not loaded",
+                        format!("{CUSTOM_THEME_PATH}\n{error}").as_str(),
+                        ModalResponse::Callback(Box::new(|_| {})),
+                    )
+                    .add_button("Close")
+                    .set_default_button(0)
+                    .set_cancel_button(0),
+                ),
+            },
+            ContextAction::SetReducedMotion { enabled } => {
+                set_reduced_motion(public_data, enabled);
+            }
+            ContextAction::SetHighContrast { enabled } => {
+                set_high_contrast(public_data, enabled);
+            }
+            ContextAction::SetMinFontSize { size } => {
+                set_min_font_size(public_data, size);
+            }
+            ContextAction::Container { key, action } => {
+                if let Some(container) = self.gui_container_slotmap.get_value_mut(&key) {
+                    container.on_context_menu_action(action);
+                }
+            }
+        }
+    }
+}
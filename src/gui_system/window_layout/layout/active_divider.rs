@@ -1,8 +1,8 @@
 use rwge::uuid::Uuid;
 
-use crate::gui_system::window_layout::tabs_container;
+use crate::gui_system::control::scale::GUIScale;
 
-use super::Orientation;
+use super::{DividedElement, Orientation};
 
 pub struct ActiveDivider {
     pub active_id: Uuid,
@@ -15,12 +15,13 @@ impl ActiveDivider {
         active_id: Uuid,
         index: usize,
         div_data: [DivData; 2],
+        weight_per_px: f32,
         start_cursor_position: f32,
     ) -> Self {
         Self {
             active_id,
             index,
-            drag_divider: LayoutDragDivider::new(div_data, start_cursor_position),
+            drag_divider: LayoutDragDivider::new(div_data, weight_per_px, start_cursor_position),
         }
     }
 }
@@ -28,6 +29,32 @@ impl ActiveDivider {
 pub struct DivData {
     pub div_size: f32,
     pub div_px_size: f32,
+    pub min_px_size: f32,
+    pub max_px_size: f32,
+    pub fixed: bool,
+    scale: GUIScale,
+}
+
+impl DivData {
+    pub fn new(element: &DividedElement, px_size: f32, scale: &GUIScale) -> Self {
+        Self {
+            div_size: element.size,
+            div_px_size: px_size,
+            min_px_size: scale.px(element.min_size),
+            max_px_size: scale.px(element.max_size),
+            fixed: element.fixed,
+            scale: *scale,
+        }
+    }
+
+    /// Converts back to the units of `DividedElement::size`
+    fn size_from_px(&self, px_size: f32, weight_per_px: f32) -> f32 {
+        if self.fixed {
+            self.scale.to_logical(px_size)
+        } else {
+            px_size * weight_per_px
+        }
+    }
 }
 
 pub struct LayoutDragDivider {
@@ -35,19 +62,20 @@ pub struct LayoutDragDivider {
     start_cursor_position: f32,
     current_cursor_position: f32,
     total_px_size: f32,
-    total_div_size: f32,
+    /// Weight of a pixel for the elements that are not fixed. Converting with the same ratio
+    /// keeps the rest of the elements in place.
+    weight_per_px: f32,
 }
 
 impl LayoutDragDivider {
-    pub fn new(div_data: [DivData; 2], start_cursor_position: f32) -> Self {
+    pub fn new(div_data: [DivData; 2], weight_per_px: f32, start_cursor_position: f32) -> Self {
         let total_px_size = div_data[0].div_px_size + div_data[1].div_px_size;
-        let total_div_size = div_data[0].div_size + div_data[1].div_size;
         Self {
             div_data,
             start_cursor_position,
             current_cursor_position: start_cursor_position,
             total_px_size,
-            total_div_size,
+            weight_per_px,
         }
     }
 
@@ -60,28 +88,27 @@ impl LayoutDragDivider {
         if let Orientation::Vertical = orientation {
             cursor_movement *= -1.0;
         };
-        let (div_px_size_0, div_px_size_1) = if cursor_movement < 0.0 {
-            //negative means moving into the first node
-            let abs_movement = cursor_movement.abs();
-            let clamped_movement = abs_movement
-                .max(0.0)
-                .min(self.div_data[0].div_px_size - tabs_container::TAB_SIZE);
-            (
-                self.div_data[0].div_px_size - clamped_movement,
-                self.div_data[1].div_px_size + clamped_movement,
-            )
+
+        // Both elements have to stay inside of their limits
+        let [div_0, div_1] = &self.div_data;
+        let min_px_size_0 = div_0
+            .min_px_size
+            .max(self.total_px_size - div_1.max_px_size);
+        let max_px_size_0 = div_0
+            .max_px_size
+            .min(self.total_px_size - div_1.min_px_size);
+        let div_px_size_0 = if min_px_size_0 <= max_px_size_0 {
+            (div_0.div_px_size + cursor_movement)
+                .max(min_px_size_0)
+                .min(max_px_size_0)
         } else {
-            let clamped_movement = cursor_movement
-                .max(0.0)
-                .min(self.div_data[1].div_px_size - tabs_container::TAB_SIZE);
-            (
-                self.div_data[0].div_px_size + clamped_movement,
-                self.div_data[1].div_px_size - clamped_movement,
-            )
+            div_0.div_px_size
         };
+        let div_px_size_1 = self.total_px_size - div_px_size_0;
+
         (
-            (div_px_size_0 / self.total_px_size) * self.total_div_size,
-            (div_px_size_1 / self.total_px_size) * self.total_div_size,
+            div_0.size_from_px(div_px_size_0, self.weight_per_px),
+            div_1.size_from_px(div_px_size_1, self.weight_per_px),
         )
     }
-}
\ No newline at end of file
+}
@@ -3,20 +3,46 @@ use rwge::{
     glam::{vec2, Vec2},
     gui::rect_ui::{element::builder::ElementBuilder, event::UIEvent, Rect},
     slotmap::prelude::*,
+    winit::event::VirtualKeyCode,
 };
 
-use crate::gui_system::{
-    control::{self, ControlId, ControlState, State, Uiid},
-    ContainerInfo,
+use crate::{
+    gui_system::{
+        control::{
+            self,
+            cursor::{CursorIcon, CursorPriority},
+            keyboard,
+            scale::GUIScale,
+            ControlId, ControlState, State, Uiid,
+        },
+        layers::Layer,
+        theme::get_theme,
+        ContainerInfo,
+    },
+    runtime_data::PublicData,
 };
 
-use self::active_divider::{ActiveDivider, DivData};
+use self::active_divider::{ActiveDivider, DivData, LayoutDragDivider};
 
-use super::{depth_offset, LayoutOrTabInfo, LayoutOrTabKey, LayoutSlotKey, TabsSlotKey};
+use super::{
+    depth_offset, tabs_container, LayoutOrTabInfo, LayoutOrTabKey, LayoutSlotKey, TabsSlotKey,
+};
 
+#[derive(Clone)]
 pub struct DividedElement {
     pub layout_or_tab_key: LayoutOrTabKey,
+    /// Proportional weight of the element, or its size in pixels if it is fixed
     pub size: f32,
+    pub min_size: f32,
+    pub max_size: f32,
+    /// Fixed elements keep their size in pixels when the window is resized
+    pub fixed: bool,
+    /// Collapsed elements only keep space for their tab bar. Their size is kept so they can be
+    /// expanded back.
+    pub collapsed: bool,
+    /// Used by the breakpoints of the parent layout, lower priority elements are collapsed or
+    /// hidden first
+    pub priority: u32,
 }
 
 impl DividedElement {
@@ -24,32 +50,160 @@ impl DividedElement {
         Self {
             layout_or_tab_key: key,
             size,
+            min_size: tabs_container::TAB_SIZE,
+            max_size: f32::INFINITY,
+            fixed: false,
+            collapsed: false,
+            priority: 0,
         }
     }
     pub fn new_layout(key: LayoutSlotKey, size: f32) -> Self {
-        Self {
-            layout_or_tab_key: key.into(),
-            size,
-        }
+        Self::new(key.into(), size)
     }
     pub fn new_tab(key: TabsSlotKey, size: f32) -> Self {
-        Self {
-            layout_or_tab_key: key.into(),
-            size,
+        Self::new(key.into(), size)
+    }
+
+    /// Sizes are in logical (unscaled) GUI units
+    pub fn set_size_limits(mut self, min_size: f32, max_size: f32) -> Self {
+        self.min_size = min_size.max(COLLAPSED_SIZE);
+        self.max_size = max_size.max(self.min_size);
+        self
+    }
+
+    pub fn set_fixed_size(mut self, px_size: f32) -> Self {
+        self.size = px_size;
+        self.fixed = true;
+        self
+    }
+
+    pub fn set_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum BreakpointAction {
+    /// Children are laid out in the other orientation
+    SwitchOrientation,
+    /// Children with a priority lower than the given one are merged into one tab group
+    CollapseChildren { below_priority: u32 },
+    /// Children with a priority lower than the given one are not laid out
+    HideChildren { below_priority: u32 },
+}
+
+/// Applied while the layout is smaller than `min_size` along its orientation, in logical
+/// (unscaled) GUI units. The children are never modified, so the original arrangement comes back
+/// once the layout grows.
+#[derive(Clone, Copy)]
+pub struct Breakpoint {
+    pub min_size: f32,
+    pub action: BreakpointAction,
+}
+
+#[derive(Clone, Default)]
+pub struct Responsive {
+    /// Sorted from the biggest `min_size` to the smallest, so the applied breakpoints are always
+    /// the first ones
+    breakpoints: Vec<Breakpoint>,
+    applied_count: usize,
+    /// Tab group with the tabs of the collapsed children, kept up to date by the window system
+    merged_tabs: Option<TabsSlotKey>,
+    /// Drawn in place of the collapsed children, keeps the size its dividers are dragged to
+    merged_element: Option<DividedElement>,
+}
+
+/// Children laid out while some breakpoints are applied
+struct BreakpointView {
+    orientation: Orientation,
+    /// Indices of the children that are laid out on their own
+    visible: Vec<usize>,
+    /// Indices of the children merged into one tab group
+    merged: Vec<usize>,
+}
+
+impl Responsive {
+    /// Number of breakpoints applied to a layout of `size` pixels
+    fn count_applied(&self, orientation: Orientation, size: Vec2, scale: &GUIScale) -> usize {
+        // Breakpoints are in the same units as the rest of the GUI sizes, not in pixels
+        let layout_size = scale.to_logical(match orientation {
+            Orientation::Horizontal => size.x,
+            Orientation::Vertical => size.y,
+        });
+        self.breakpoints
+            .iter()
+            .take_while(|breakpoint| layout_size < breakpoint.min_size)
+            .count()
+    }
+
+    /// Children shown with the first `applied_count` breakpoints applied
+    fn view(
+        &self,
+        children: &Vec<DividedElement>,
+        orientation: Orientation,
+        applied_count: usize,
+    ) -> BreakpointView {
+        let mut view_orientation = orientation;
+        let mut visible: Vec<usize> = (0..children.len()).collect();
+        let mut collapsed = vec![false; children.len()];
+        for breakpoint in self.breakpoints[..applied_count].iter() {
+            match breakpoint.action {
+                BreakpointAction::SwitchOrientation => {
+                    view_orientation = match view_orientation {
+                        Orientation::Horizontal => Orientation::Vertical,
+                        Orientation::Vertical => Orientation::Horizontal,
+                    }
+                }
+                BreakpointAction::CollapseChildren { below_priority } => {
+                    for (index, child) in children.iter().enumerate() {
+                        collapsed[index] |= child.priority < below_priority;
+                    }
+                }
+                BreakpointAction::HideChildren { below_priority } => {
+                    visible.retain(|index| children[*index].priority >= below_priority);
+                }
+            }
+        }
+
+        // At least the child with the highest priority is always shown on its own
+        let top_priority = (0..children.len())
+            .max_by_key(|index| children[*index].priority)
+            .unwrap();
+        if visible.len() == 0 {
+            visible.push(top_priority);
+        }
+        if visible.iter().all(|index| collapsed[*index]) {
+            collapsed[top_priority] = false;
+            if !visible.contains(&top_priority) {
+                collapsed[visible[0]] = false;
+            }
+        }
+
+        let (merged, visible): (Vec<usize>, Vec<usize>) =
+            visible.into_iter().partition(|index| collapsed[*index]);
+        BreakpointView {
+            orientation: view_orientation,
+            visible,
+            merged,
         }
     }
 }
 
 pub mod active_divider;
+#[cfg(test)]
+mod test;
 
 pub enum LayoutElement {
     Horizontal {
         children: Vec<DividedElement>,
         active_divider: Option<ActiveDivider>,
+        responsive: Responsive,
     },
     Vertical {
         children: Vec<DividedElement>,
         active_divider: Option<ActiveDivider>,
+        responsive: Responsive,
     },
 }
 
@@ -80,6 +234,9 @@ struct ChildrenInfo {
 }
 
 const DIVISION_SIZE: f32 = 2.0;
+/// Space left to a collapsed element, enough to show its tab bar
+const COLLAPSED_SIZE: f32 = tabs_container::TAB_SIZE;
+const DIVIDER_NUDGE: f32 = 10.0;
 
 impl LayoutElement {
     pub fn validate_children(
@@ -148,6 +305,7 @@ impl LayoutElement {
         let horizontal_layout = LayoutElement::Horizontal {
             children: Vec::<DividedElement>::new(),
             active_divider: None,
+            responsive: Responsive::default(),
         };
         Self::validate_and_create(horizontal_layout, new_children, layout_elements)
     }
@@ -159,64 +317,271 @@ impl LayoutElement {
         let vertical_layout = LayoutElement::Vertical {
             children: Vec::<DividedElement>::new(),
             active_divider: None,
+            responsive: Responsive::default(),
         };
         Self::validate_and_create(vertical_layout, new_children, layout_elements)
     }
 
+    pub fn children(&self) -> &Vec<DividedElement> {
+        match self {
+            LayoutElement::Horizontal { children, .. } => children,
+            LayoutElement::Vertical { children, .. } => children,
+        }
+    }
+
+    /// Any active divider is dropped, since its index might not be valid after the change
+    pub fn children_mut(&mut self) -> &mut Vec<DividedElement> {
+        match self {
+            LayoutElement::Horizontal {
+                children,
+                active_divider,
+                ..
+            } => {
+                *active_divider = None;
+                children
+            }
+            LayoutElement::Vertical {
+                children,
+                active_divider,
+                ..
+            } => {
+                *active_divider = None;
+                children
+            }
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        match self {
+            LayoutElement::Horizontal { .. } => Orientation::Horizontal,
+            LayoutElement::Vertical { .. } => Orientation::Vertical,
+        }
+    }
+
+    pub fn find_child(&self, key: LayoutOrTabKey) -> Option<usize> {
+        self.children()
+            .iter()
+            .position(|child| child.layout_or_tab_key == key)
+    }
+
+    /// Same orientation and breakpoints with other children, the children are not validated.
+    /// The copy gets its own merged tab group the first time it needs one.
+    pub fn duplicate_with_children(&self, children: Vec<DividedElement>) -> LayoutElement {
+        let responsive = Responsive {
+            breakpoints: self.children_and_responsive().1.breakpoints.clone(),
+            ..Responsive::default()
+        };
+        match self {
+            LayoutElement::Horizontal { .. } => LayoutElement::Horizontal {
+                children,
+                active_divider: None,
+                responsive,
+            },
+            LayoutElement::Vertical { .. } => LayoutElement::Vertical {
+                children,
+                active_divider: None,
+                responsive,
+            },
+        }
+    }
+
+    pub fn set_breakpoints(&mut self, mut breakpoints: Vec<Breakpoint>) {
+        breakpoints.sort_by(|a, b| {
+            b.min_size
+                .partial_cmp(&a.min_size)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let responsive = match self {
+            LayoutElement::Horizontal { responsive, .. } => responsive,
+            LayoutElement::Vertical { responsive, .. } => responsive,
+        };
+        responsive.breakpoints = breakpoints;
+        responsive.applied_count = 0;
+    }
+
+    /// Keys of the children merged into one tab group while the layout has the given size
+    pub fn merged_children(&self, size: Vec2, scale: &GUIScale) -> Vec<LayoutOrTabKey> {
+        let (children, responsive) = self.children_and_responsive();
+        let applied_count = responsive.count_applied(self.orientation(), size, scale);
+        if applied_count == 0 {
+            return Vec::new();
+        }
+        responsive
+            .view(children, self.orientation(), applied_count)
+            .merged
+            .iter()
+            .map(|index| children[*index].layout_or_tab_key)
+            .collect()
+    }
+
+    pub fn merged_tabs(&self) -> Option<TabsSlotKey> {
+        self.children_and_responsive().1.merged_tabs
+    }
+
+    pub fn set_merged_tabs(&mut self, key: TabsSlotKey) {
+        match self {
+            LayoutElement::Horizontal { responsive, .. }
+            | LayoutElement::Vertical { responsive, .. } => responsive.merged_tabs = Some(key),
+        }
+    }
+
+    fn children_and_responsive(&self) -> (&Vec<DividedElement>, &Responsive) {
+        match self {
+            LayoutElement::Horizontal {
+                children,
+                responsive,
+                ..
+            }
+            | LayoutElement::Vertical {
+                children,
+                responsive,
+                ..
+            } => (children, responsive),
+        }
+    }
+
     pub fn handle_event(
         &mut self,
         event: &mut UIEvent,
         container_info: ContainerInfo,
         control_state: &mut ControlState,
+        public_data: &PublicData,
     ) -> Vec<LayoutOrTabInfo> {
-        let size = container_info.rect.size;
-        let position = container_info.rect.position;
-        match self {
+        let orientation = self.orientation();
+        let (children, active_divider, responsive) = match self {
             LayoutElement::Horizontal {
                 children,
                 active_divider,
-                ..
-            } => handle_event_layout_element(
+                responsive,
+            }
+            | LayoutElement::Vertical {
                 children,
-                control_state,
-                container_info,
-                event,
                 active_divider,
-                Sign::Positive,
-                position.x - size.x * 0.5,
-                size.x,
-                Orientation::Horizontal,
-                &|child_info| Rect {
-                    position: vec2(child_info.position, position.y),
-                    size: vec2(child_info.size, size.y),
-                },
-            ),
-            LayoutElement::Vertical {
+                responsive,
+            } => (children, active_divider, responsive),
+        };
+
+        let applied_count =
+            responsive.count_applied(orientation, container_info.rect.size, &control_state.scale);
+        if applied_count != responsive.applied_count {
+            // Divider indices are not the same with a different set of children
+            responsive.applied_count = applied_count;
+            responsive.merged_element = None;
+            *active_divider = None;
+        }
+        if applied_count == 0 {
+            return layout_children(
                 children,
                 active_divider,
-                ..
-            } => handle_event_layout_element(
-                children,
-                control_state,
-                container_info,
+                orientation,
                 event,
-                active_divider,
-                Sign::Negative,
-                position.y + size.y * 0.5,
-                size.y,
-                Orientation::Vertical,
-                &|child_info| Rect {
-                    position: vec2(position.x, child_info.position),
-                    size: vec2(size.x, child_info.size),
-                },
-            ),
+                container_info,
+                control_state,
+                public_data,
+            );
+        }
+
+        let view = responsive.view(children, orientation, applied_count);
+        let mut view_indices: Vec<Option<usize>> =
+            view.visible.iter().map(|index| Some(*index)).collect();
+        let merged_tabs = responsive.merged_tabs.filter(|_| view.merged.len() > 0);
+        if let Some(merged_tabs) = merged_tabs {
+            // The group takes the place of the first merged child
+            let position = view_indices
+                .iter()
+                .position(|index| index.map_or(false, |index| index > view.merged[0]))
+                .unwrap_or(view_indices.len());
+            view_indices.insert(position, None);
+            if responsive.merged_element.is_none() {
+                let mut element = children[view.merged[0]].clone();
+                element.layout_or_tab_key = merged_tabs.into();
+                element.collapsed = false;
+                responsive.merged_element = Some(element);
+            }
         }
+
+        let mut view_children: Vec<DividedElement> = view_indices
+            .iter()
+            .map(|index| match index {
+                Some(index) => children[*index].clone(),
+                None => responsive.merged_element.clone().unwrap(),
+            })
+            .collect();
+        let layout_info = layout_children(
+            &mut view_children,
+            active_divider,
+            view.orientation,
+            event,
+            container_info,
+            control_state,
+            public_data,
+        );
+
+        // Keeps the changes made with the dividers
+        for (view_child, index) in view_children.into_iter().zip(view_indices.iter()) {
+            match index {
+                Some(index) => {
+                    children[*index].size = view_child.size;
+                    children[*index].collapsed = view_child.collapsed;
+                }
+                None => responsive.merged_element = Some(view_child),
+            }
+        }
+        layout_info
+    }
+}
+
+fn layout_children(
+    children: &mut Vec<DividedElement>,
+    active_divider: &mut Option<ActiveDivider>,
+    orientation: Orientation,
+    event: &mut UIEvent,
+    container_info: ContainerInfo,
+    control_state: &mut ControlState,
+    public_data: &PublicData,
+) -> Vec<LayoutOrTabInfo> {
+    let size = container_info.rect.size;
+    let position = container_info.rect.position;
+    match orientation {
+        Orientation::Horizontal => handle_event_layout_element(
+            children,
+            control_state,
+            public_data,
+            container_info,
+            event,
+            active_divider,
+            Sign::Positive,
+            position.x - size.x * 0.5,
+            size.x,
+            Orientation::Horizontal,
+            &|child_info| Rect {
+                position: vec2(child_info.position, position.y),
+                size: vec2(child_info.size, size.y),
+            },
+        ),
+        Orientation::Vertical => handle_event_layout_element(
+            children,
+            control_state,
+            public_data,
+            container_info,
+            event,
+            active_divider,
+            Sign::Negative,
+            position.y + size.y * 0.5,
+            size.y,
+            Orientation::Vertical,
+            &|child_info| Rect {
+                position: vec2(position.x, child_info.position),
+                size: vec2(size.x, child_info.size),
+            },
+        ),
     }
 }
 
 fn handle_event_layout_element(
     children: &mut Vec<DividedElement>,
     control_state: &mut ControlState,
+    public_data: &PublicData,
     container_info: ContainerInfo,
     event: &mut UIEvent,
     active_divider: &mut Option<ActiveDivider>,
@@ -226,14 +591,22 @@ fn handle_event_layout_element(
     orientation: Orientation,
     create_child_rect: &dyn Fn(&ChildrenInfo) -> Rect,
 ) -> Vec<LayoutOrTabInfo> {
-    let (children_sizes, division_positions) =
-        compute_children_sizes(children, start_position, inner_size, DIVISION_SIZE, sign);
+    let scale = control_state.scale;
+    let (children_sizes, division_positions) = compute_children_sizes(
+        children,
+        start_position,
+        inner_size,
+        scale.px(DIVISION_SIZE),
+        sign,
+        &scale,
+    );
 
     handle_event_layout_dividers(
         children,
         &children_sizes,
         division_positions,
         control_state,
+        public_data,
         container_info,
         event,
         orientation,
@@ -246,7 +619,7 @@ fn handle_event_layout_element(
             key: info.key.clone(),
             container_info: ContainerInfo {
                 rect: create_child_rect(info),
-                depth_range: container_info.depth_range,
+                layers: container_info.layers,
             },
         })
         .collect()
@@ -256,16 +629,18 @@ fn create_div_rects(
     div_position: f32,
     orientation: Orientation,
     container_info: ContainerInfo,
+    scale: &GUIScale,
 ) -> Rect {
+    let div_thickness = scale.px(DIVISION_SIZE * 4.0);
     let (div_position, div_size) = if let Orientation::Horizontal = orientation {
         (
             vec2(div_position, container_info.rect.position.y),
-            vec2(DIVISION_SIZE * 4.0, container_info.rect.size.y),
+            vec2(div_thickness, container_info.rect.size.y),
         )
     } else {
         (
             vec2(container_info.rect.position.x, div_position),
-            vec2(container_info.rect.size.x, DIVISION_SIZE * 4.0),
+            vec2(container_info.rect.size.x, div_thickness),
         )
     };
 
@@ -281,15 +656,21 @@ fn handle_event_layout_dividers(
     children_sizes: &Vec<ChildrenInfo>,
     division_positions: Vec<f32>,
     control_state: &mut ControlState,
+    public_data: &PublicData,
     container_info: ContainerInfo,
     event: &mut UIEvent,
     orientation: Orientation,
     active_divider: &mut Option<ActiveDivider>,
 ) {
+    let scale = control_state.scale;
     for (div_index, div_pos) in division_positions.iter().enumerate() {
-        let draw_rect = create_div_rects(*div_pos, orientation, container_info);
+        let draw_rect = create_div_rects(*div_pos, orientation, container_info, &scale);
 
-        control_state.set_depth_and_save(container_info.depth_range.0 + depth_offset::DIVIDER);
+        control_state.set_depth_and_save(
+            container_info
+                .layers
+                .depth(Layer::Overlay, depth_offset::DIVIDER),
+        );
         let control_id = control_state.get_id();
         control_state.restore_depth();
 
@@ -323,29 +704,36 @@ fn handle_event_layout_dividers(
                 }
             }
             UIEvent::MouseButton(mouse_input) => {
-                if mouse_input.is_left_pressed() && active_divider.is_none() {
-                    if let Some(active_id) = control_state.set_active(control_id) {
-                        let cursor_pos = control_state.last_cursor_position.unwrap();
-                        let start_cursor_position = if let Orientation::Horizontal = orientation {
-                            cursor_pos.x
-                        } else {
-                            cursor_pos.y
-                        };
-                        *active_divider = Some(ActiveDivider::new(
-                            active_id,
-                            div_index,
-                            [
-                                DivData {
-                                    div_size: children_elements[div_index].size,
-                                    div_px_size: children_sizes[div_index].size,
-                                },
-                                DivData {
-                                    div_size: children_elements[div_index + 1].size,
-                                    div_px_size: children_sizes[div_index + 1].size,
-                                },
-                            ],
-                            start_cursor_position,
-                        ));
+                if mouse_input.is_left_pressed()
+                    && active_divider.is_none()
+                    && control_state.is_hovered(control_id)
+                {
+                    control_state.set_focus(control_id);
+                    if control_state.register_click(control_id) {
+                        toggle_collapse(children_elements, children_sizes, div_index);
+                    } else if !is_next_to_collapsed(children_elements, div_index) {
+                        if let Some(active_id) = control_state.set_active(control_id) {
+                            let cursor_pos = control_state.last_cursor_position.unwrap();
+                            let start_cursor_position = if let Orientation::Horizontal = orientation
+                            {
+                                cursor_pos.x
+                            } else {
+                                cursor_pos.y
+                            };
+                            let (div_data, weight_per_px) = drag_divider_data(
+                                children_elements,
+                                children_sizes,
+                                div_index,
+                                &scale,
+                            );
+                            *active_divider = Some(ActiveDivider::new(
+                                active_id,
+                                div_index,
+                                div_data,
+                                weight_per_px,
+                                start_cursor_position,
+                            ));
+                        }
                     }
                 }
 
@@ -353,26 +741,72 @@ fn handle_event_layout_dividers(
                     *active_divider = None;
                 }
             }
+            UIEvent::KeyboardInput(..) => {
+                let nudge = match (keyboard::get_pressed_key(event), orientation) {
+                    (Some(VirtualKeyCode::Left), Orientation::Horizontal) => -DIVIDER_NUDGE,
+                    (Some(VirtualKeyCode::Right), Orientation::Horizontal) => DIVIDER_NUDGE,
+                    (Some(VirtualKeyCode::Up), Orientation::Vertical) => DIVIDER_NUDGE,
+                    (Some(VirtualKeyCode::Down), Orientation::Vertical) => -DIVIDER_NUDGE,
+                    _ => 0.0,
+                };
+                let nudge = scale.px(nudge);
+                if nudge != 0.0
+                    && control_state.is_focused(control_id)
+                    && !is_next_to_collapsed(children_elements, div_index)
+                {
+                    let (div_data, weight_per_px) =
+                        drag_divider_data(children_elements, children_sizes, div_index, &scale);
+                    let mut drag_divider = LayoutDragDivider::new(div_data, weight_per_px, 0.0);
+                    drag_divider.update_cursor_position(nudge);
+
+                    let new_div_sizes = drag_divider.compute_new_division_sizes(orientation);
+                    children_elements[div_index].size = new_div_sizes.0;
+                    children_elements[div_index + 1].size = new_div_sizes.1;
+                }
+            }
             UIEvent::Render {
                 extra_render_steps, ..
             } => {
                 let state =
                     get_current_state(&control_state, &active_divider, div_index, control_id);
+                let focused = control_state.is_focused(control_id);
+                let resize_icon = match orientation {
+                    Orientation::Horizontal => CursorIcon::ColResize,
+                    Orientation::Vertical => CursorIcon::RowResize,
+                };
+                // Only a double click can expand a collapsed element
+                let drag_blocked = is_next_to_collapsed(children_elements, div_index);
+                match state {
+                    control::State::Hovered if drag_blocked => {
+                        control_state.request_cursor(CursorIcon::NotAllowed, CursorPriority::Hover)
+                    }
+                    control::State::Hovered => {
+                        control_state.request_cursor(resize_icon, CursorPriority::Hover)
+                    }
+                    control::State::Active => {
+                        control_state.request_cursor(resize_icon, CursorPriority::Active)
+                    }
+                    control::State::Inactive => {}
+                }
+                let colors = &get_theme(public_data).colors;
                 let divider_color = match state {
-                    control::State::Hovered => RGBA::new(0.0, 0.25, 0.75, 1.0),
-                    control::State::Active => RGBA::RED,
+                    control::State::Hovered => colors.divider_hover,
+                    control::State::Active => colors.divider_active,
+                    _ if focused => colors.focus,
                     _ => RGBA::TRANSPARENT,
                 };
                 match state {
-                    control::State::Hovered | control::State::Active => extra_render_steps.push(
+                    control::State::Inactive if !focused => {}
+                    _ => extra_render_steps.push(
                         Box::new(move |gui_rects| {
                             ElementBuilder::new_with_rect(draw_rect)
                                 .set_color(divider_color.into())
                                 .build(gui_rects);
                         }),
-                        container_info.depth_range.0 + depth_offset::RESIZE_CONTROL,
+                        container_info
+                            .layers
+                            .depth(Layer::Overlay, depth_offset::DIVIDER),
                     ),
-                    _ => {}
                 }
             }
             _ => { /* No op */ }
@@ -380,6 +814,63 @@ fn handle_event_layout_dividers(
     }
 }
 
+fn is_next_to_collapsed(children_elements: &Vec<DividedElement>, div_index: usize) -> bool {
+    children_elements[div_index].collapsed || children_elements[div_index + 1].collapsed
+}
+
+/// Collapses the smaller of the two elements next to the divider, or expands them if one of them
+/// was collapsed
+fn toggle_collapse(
+    children_elements: &mut Vec<DividedElement>,
+    children_sizes: &Vec<ChildrenInfo>,
+    div_index: usize,
+) {
+    if is_next_to_collapsed(children_elements, div_index) {
+        children_elements[div_index].collapsed = false;
+        children_elements[div_index + 1].collapsed = false;
+    } else if children_sizes[div_index].size <= children_sizes[div_index + 1].size {
+        children_elements[div_index].collapsed = true;
+    } else {
+        children_elements[div_index + 1].collapsed = true;
+    }
+}
+
+fn drag_divider_data(
+    children_elements: &Vec<DividedElement>,
+    children_sizes: &Vec<ChildrenInfo>,
+    div_index: usize,
+    scale: &GUIScale,
+) -> ([DivData; 2], f32) {
+    let (flexible_weight, flexible_px_size) = children_elements
+        .iter()
+        .zip(children_sizes.iter())
+        .filter(|(element, _)| !element.fixed && !element.collapsed)
+        .fold((0.0_f32, 0.0_f32), |(weight, px_size), (element, info)| {
+            (weight + element.size, px_size + info.size)
+        });
+    let weight_per_px = if flexible_px_size > 0.0 {
+        flexible_weight / flexible_px_size
+    } else {
+        1.0
+    };
+
+    (
+        [
+            DivData::new(
+                &children_elements[div_index],
+                children_sizes[div_index].size,
+                scale,
+            ),
+            DivData::new(
+                &children_elements[div_index + 1],
+                children_sizes[div_index + 1].size,
+                scale,
+            ),
+        ],
+        weight_per_px,
+    )
+}
+
 fn get_current_state(
     control_state: &ControlState,
     divider: &Option<ActiveDivider>,
@@ -402,27 +893,101 @@ fn get_current_state(
     )
 }
 
+/// Size in pixels of every child. Collapsed and fixed children are sized first, the space left
+/// is shared by the rest according to their weights, respecting their size limits.
+fn compute_children_px_sizes(
+    children: &Vec<DividedElement>,
+    available_size: f32,
+    scale: &GUIScale,
+) -> Vec<f32> {
+    let mut px_sizes = vec![0.0; children.len()];
+    let mut resolved = vec![false; children.len()];
+
+    let mut flexible_space = available_size;
+    for (index, child) in children.iter().enumerate() {
+        if child.collapsed {
+            px_sizes[index] = scale.px(COLLAPSED_SIZE);
+        } else if child.fixed {
+            px_sizes[index] = scale.px(child.size.max(child.min_size).min(child.max_size));
+        } else {
+            continue;
+        }
+        resolved[index] = true;
+        flexible_space -= px_sizes[index];
+    }
+
+    // Children that would break their limits are clamped and taken out of the distribution,
+    // until every child left fits
+    loop {
+        let total_weight = children
+            .iter()
+            .zip(resolved.iter())
+            .filter(|(_, resolved)| !**resolved)
+            .fold(0.0, |acum: f32, (child, _)| acum + child.size);
+        if total_weight <= 0.0 {
+            break;
+        }
+
+        let mut clamped_any = false;
+        for (index, child) in children.iter().enumerate() {
+            if resolved[index] {
+                continue;
+            }
+            let px_size = flexible_space.max(0.0) * child.size / total_weight;
+            let clamped_size = px_size
+                .max(scale.px(child.min_size))
+                .min(scale.px(child.max_size));
+            px_sizes[index] = px_size;
+            if clamped_size != px_size {
+                px_sizes[index] = clamped_size;
+                resolved[index] = true;
+                flexible_space -= clamped_size;
+                clamped_any = true;
+                break;
+            }
+        }
+        if !clamped_any {
+            break;
+        }
+    }
+
+    // The minimum sizes can add up to more than there is, the children that are not collapsed
+    // are shrunk by the same ratio so they stay inside of the parent
+    let collapsed_space = children
+        .iter()
+        .zip(px_sizes.iter())
+        .filter(|(child, _)| child.collapsed)
+        .fold(0.0, |acum: f32, (_, px_size)| acum + px_size);
+    let expanded_space = px_sizes.iter().sum::<f32>() - collapsed_space;
+    let space_left = (available_size - collapsed_space).max(0.0);
+    if expanded_space > space_left {
+        let ratio = space_left / expanded_space;
+        for (px_size, child) in px_sizes.iter_mut().zip(children.iter()) {
+            if !child.collapsed {
+                *px_size *= ratio;
+            }
+        }
+    }
+    px_sizes
+}
+
 fn compute_children_sizes(
     children: &Vec<DividedElement>,
     start_pos: f32,
     inner_size: f32,
     margin: f32,
     sign: Sign,
+    scale: &GUIScale,
 ) -> (Vec<ChildrenInfo>, Vec<f32>) {
     let mut children_sizes = Vec::<ChildrenInfo>::with_capacity(children.len());
     let mut division_positions = Vec::<f32>::with_capacity(children.len());
 
-    let total_size = children
-        .iter()
-        .fold(0.0, |acum: f32, div: &DividedElement| acum + div.size);
-
     let mut start_pos = start_pos;
     let gap_count = (children.len() as i32 - 1) as f32;
     let children_available_size = inner_size - (margin * gap_count);
+    let px_sizes = compute_children_px_sizes(children, children_available_size, scale);
 
-    for (index, child) in children.iter().enumerate() {
-        let element_proportion = child.size / total_size;
-        let child_size = children_available_size * element_proportion;
+    for (index, (child, child_size)) in children.iter().zip(px_sizes).enumerate() {
         let child_position = start_pos + child_size * 0.5 * sign.as_f32();
 
         children_sizes.push(ChildrenInfo {
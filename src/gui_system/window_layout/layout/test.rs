@@ -0,0 +1,136 @@
+use rwge::slotmap::prelude::*;
+
+use crate::gui_system::{control::scale::GUIScale, window_layout::TabsSlotKey};
+
+use super::{
+    compute_children_px_sizes, Breakpoint, BreakpointAction, DividedElement, Orientation,
+    Responsive, COLLAPSED_SIZE,
+};
+
+fn children(count: usize) -> (Slotmap<()>, Vec<TabsSlotKey>) {
+    let mut slotmap = Slotmap::<()>::with_capacity(count);
+    let keys = (0..count)
+        .map(|_| TabsSlotKey(slotmap.push(()).unwrap()))
+        .collect();
+    (slotmap, keys)
+}
+
+#[test]
+fn sizes_follow_the_weights() {
+    let (_slotmap, keys) = children(2);
+    let elements = vec![
+        DividedElement::new_tab(keys[0], 1.0),
+        DividedElement::new_tab(keys[1], 3.0),
+    ];
+
+    let sizes = compute_children_px_sizes(&elements, 400.0, &GUIScale::new());
+    assert_eq!(sizes, vec![100.0, 300.0]);
+}
+
+#[test]
+fn minimum_sizes_are_kept_when_they_fit() {
+    let (_slotmap, keys) = children(2);
+    let elements = vec![
+        DividedElement::new_tab(keys[0], 1.0).set_size_limits(150.0, f32::INFINITY),
+        DividedElement::new_tab(keys[1], 9.0),
+    ];
+
+    let sizes = compute_children_px_sizes(&elements, 400.0, &GUIScale::new());
+    assert_eq!(sizes, vec![150.0, 250.0]);
+}
+
+#[test]
+fn overflowing_minimum_sizes_are_shrunk_to_fit() {
+    let (_slotmap, keys) = children(3);
+    let elements = vec![
+        DividedElement::new_tab(keys[0], 1.0).set_size_limits(200.0, f32::INFINITY),
+        DividedElement::new_tab(keys[1], 1.0).set_size_limits(200.0, f32::INFINITY),
+        DividedElement::new_tab(keys[2], 1.0).set_fixed_size(100.0),
+    ];
+
+    let sizes = compute_children_px_sizes(&elements, 250.0, &GUIScale::new());
+    assert!((sizes.iter().sum::<f32>() - 250.0).abs() < 0.001);
+    assert!((sizes[0] - 100.0).abs() < 0.001);
+    assert!((sizes[1] - 100.0).abs() < 0.001);
+    assert!((sizes[2] - 50.0).abs() < 0.001);
+}
+
+#[test]
+fn collapsed_children_keep_their_strip() {
+    let (_slotmap, keys) = children(2);
+    let mut collapsed = DividedElement::new_tab(keys[0], 1.0);
+    collapsed.collapsed = true;
+    let elements = vec![
+        collapsed,
+        DividedElement::new_tab(keys[1], 1.0).set_size_limits(200.0, f32::INFINITY),
+    ];
+
+    let sizes = compute_children_px_sizes(&elements, 100.0, &GUIScale::new());
+    assert_eq!(sizes[0], COLLAPSED_SIZE);
+    assert!((sizes[1] - (100.0 - COLLAPSED_SIZE)).abs() < 0.001);
+}
+
+fn responsive(actions: Vec<BreakpointAction>) -> Responsive {
+    Responsive {
+        breakpoints: actions
+            .into_iter()
+            .map(|action| Breakpoint {
+                min_size: 500.0,
+                action,
+            })
+            .collect(),
+        ..Responsive::default()
+    }
+}
+
+#[test]
+fn low_priority_children_are_merged() {
+    let (_slotmap, keys) = children(4);
+    let elements = vec![
+        DividedElement::new_tab(keys[0], 1.0),
+        DividedElement::new_tab(keys[1], 1.0).set_priority(2),
+        DividedElement::new_tab(keys[2], 1.0),
+        DividedElement::new_tab(keys[3], 1.0).set_priority(1),
+    ];
+    let responsive = responsive(vec![BreakpointAction::CollapseChildren {
+        below_priority: 1,
+    }]);
+
+    let view = responsive.view(&elements, Orientation::Horizontal, 1);
+    assert_eq!(view.merged, vec![0, 2]);
+    assert_eq!(view.visible, vec![1, 3]);
+}
+
+#[test]
+fn the_top_priority_child_is_never_merged() {
+    let (_slotmap, keys) = children(2);
+    let elements = vec![
+        DividedElement::new_tab(keys[0], 1.0),
+        DividedElement::new_tab(keys[1], 1.0).set_priority(1),
+    ];
+    let responsive = responsive(vec![BreakpointAction::CollapseChildren {
+        below_priority: 5,
+    }]);
+
+    let view = responsive.view(&elements, Orientation::Horizontal, 1);
+    assert_eq!(view.merged, vec![0]);
+    assert_eq!(view.visible, vec![1]);
+}
+
+#[test]
+fn hidden_children_are_not_merged() {
+    let (_slotmap, keys) = children(3);
+    let elements = vec![
+        DividedElement::new_tab(keys[0], 1.0),
+        DividedElement::new_tab(keys[1], 1.0).set_priority(1),
+        DividedElement::new_tab(keys[2], 1.0).set_priority(2),
+    ];
+    let responsive = responsive(vec![
+        BreakpointAction::CollapseChildren { below_priority: 2 },
+        BreakpointAction::HideChildren { below_priority: 1 },
+    ]);
+
+    let view = responsive.view(&elements, Orientation::Horizontal, 2);
+    assert_eq!(view.merged, vec![1]);
+    assert_eq!(view.visible, vec![2]);
+}
@@ -1,28 +1,52 @@
 use std::ops::Deref;
+mod context_menu;
+use context_menu::ContextAction;
 mod layout;
-use layout::LayoutElement;
+use layout::{LayoutElement, Orientation};
 mod tabs_container;
 use tabs_container::TabsContainer;
 mod window;
 use window::UIWindow;
+mod workspace;
+use workspace::Workspace;
 
 //For now the style of the tabs is going to be fixed
 use rwge::{
     glam::Vec2,
     gui::rect_ui::{
         event::{ExtraRenderSteps, UIEvent},
-        GUIRects,
+        GUIRects, Rect,
     },
     slotmap::prelude::*,
+    winit::event::VirtualKeyCode,
 };
 
-use crate::runtime_data::{PublicData, RuntimeData};
-
-pub use tabs_container::{GUI_ACTIVE_COLOR, GUI_HOVER_COLOR, GUI_INACTIVE_COLOR};
+use crate::runtime_data::{
+    utils::{get_engine_data, get_time},
+    PublicData, RuntimeData,
+};
 
-pub use self::layout::DividedElement;
+pub use self::layout::{Breakpoint, BreakpointAction, DividedElement};
 
-use super::{control::ControlState, gui_container::GUIContainer, ContainerInfo};
+use super::{
+    access_tree::{
+        adapter::{self, AccessKitTreeUpdate},
+        AccessNode, AccessNodeId, AccessRole, AccessTree,
+    },
+    accessibility::get_accessibility,
+    control::{
+        keyboard,
+        scale::GUIScale,
+        snapping::{SnapSettings, SnapTargets},
+        tooltip, ControlState,
+    },
+    gui_container::GUIContainer,
+    layers::Layer,
+    modal,
+    popup::{MenuItem, PopupSystem},
+    theme::get_theme,
+    ContainerInfo,
+};
 
 create_custom_key!(
     GUIContainerSlotkey;
@@ -58,6 +82,16 @@ pub enum LayoutOrTabKey {
     TabKey(TabsSlotKey),
     LayoutKey(LayoutSlotKey),
 }
+
+impl PartialEq for LayoutOrTabKey {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (LayoutOrTabKey::TabKey(a), LayoutOrTabKey::TabKey(b)) => a.0 == b.0,
+            (LayoutOrTabKey::LayoutKey(a), LayoutOrTabKey::LayoutKey(b)) => a.0 == b.0,
+            (_, _) => false,
+        }
+    }
+}
 pub struct LayoutOrTabInfo {
     key: LayoutOrTabKey,
     container_info: ContainerInfo,
@@ -65,6 +99,8 @@ pub struct LayoutOrTabInfo {
 pub struct GUIContainerInfo {
     key: GUIContainerSlotkey,
     container_info: ContainerInfo,
+    /// Tab panel the nodes of the container are pushed into
+    access_id: AccessNodeId,
 }
 
 pub struct WindowSystem {
@@ -73,17 +109,22 @@ pub struct WindowSystem {
     layout_slotmap: Slotmap<LayoutElement>,
     window_collection: Slotmap<UIWindow>,
     window_order: Vec<WindowSlotKey>,
+    workspaces: Vec<Workspace>,
+    active_workspace: usize,
     pub control_state: ControlState,
+    popups: PopupSystem<ContextAction>,
+    pub snap_settings: SnapSettings,
 }
 
-pub const DEPTH_SLICE_SIZE: u32 = 15;
-
+/// Offsets inside of the layer each element is drawn on
 pub mod depth_offset {
-    pub const TAB_SHADOW: u32 = 5;
-    pub const RESIZE_CONTROL: u32 = 10;
-    pub const DIVIDER: u32 = 2;
-    pub const SELECT_COUNT: u32 = 6;
-    pub const FONT_ANIM_OFFSET: u32 = 8;
+    // Layer::ContainerContent
+    pub const TAB_SHADOW: u32 = 0;
+    pub const SELECT_COUNT: u32 = 1;
+    pub const FONT_ANIM_OFFSET: u32 = 2;
+    // Layer::Overlay
+    pub const DIVIDER: u32 = 0;
+    pub const RESIZE_CONTROL: u32 = 1;
 }
 
 impl WindowSystem {
@@ -97,8 +138,12 @@ impl WindowSystem {
             //windowing
             window_collection: Slotmap::<UIWindow>::with_capacity(5),
             window_order: Vec::<WindowSlotKey>::with_capacity(5),
+            workspaces: vec![Workspace::new("Default")],
+            active_workspace: 0,
             //control
             control_state: ControlState::new(),
+            popups: PopupSystem::new(),
+            snap_settings: SnapSettings::default(),
         }
     }
 
@@ -120,6 +165,19 @@ impl WindowSystem {
         window_key
     }
 
+    /// Window that floats over the main window and can be moved inside of it
+    pub fn create_floating_window(
+        &mut self,
+        layout_key: LayoutSlotKey,
+        size: Vec2,
+        position: Vec2,
+    ) -> WindowSlotKey {
+        let window_layout = UIWindow::new_floating(layout_key, size, position);
+        let window_key = WindowSlotKey(self.window_collection.push(window_layout).unwrap());
+        self.window_order.push(window_key);
+        window_key
+    }
+
     pub fn push_vertical(&mut self, children: Vec<DividedElement>) -> Option<LayoutSlotKey> {
         LayoutElement::create_vertical(children, &mut self.layout_slotmap)
     }
@@ -128,6 +186,12 @@ impl WindowSystem {
         LayoutElement::create_horizontal(children, &mut self.layout_slotmap)
     }
 
+    pub fn set_layout_breakpoints(&mut self, key: LayoutSlotKey, breakpoints: Vec<Breakpoint>) {
+        if let Some(layout) = self.layout_slotmap.get_value_mut(&key) {
+            layout.set_breakpoints(breakpoints);
+        }
+    }
+
     pub fn push_gui_container(
         &mut self,
         container: Box<dyn GUIContainer>,
@@ -139,11 +203,307 @@ impl WindowSystem {
         }
     }
 
+    /// Rect of the first window, modal dialogs are centered over it
+    pub fn main_window_rect(&self, public_data: &PublicData) -> Rect {
+        match self
+            .window_order
+            .first()
+            .and_then(|window_key| self.window_collection.get_value(&window_key.0))
+        {
+            Some(window) => Rect {
+                position: window.position,
+                size: window.size,
+            },
+            None => {
+                let screen_size = get_engine_data(public_data).screen_size.as_vec2();
+                Rect {
+                    position: screen_size * 0.5,
+                    size: screen_size,
+                }
+            }
+        }
+    }
+
+    /// Returns the layout that has `key` as a direct child, and the index of the child
+    pub fn find_parent_layout(&self, key: LayoutOrTabKey) -> Option<(LayoutSlotKey, usize)> {
+        let mut layout_stack: Vec<LayoutSlotKey> = self
+            .window_order
+            .iter()
+            .filter_map(|window_key| self.window_collection.get_value(&window_key.0))
+            .map(|window| window.layout_key)
+            .collect();
+
+        while let Some(layout_key) = layout_stack.pop() {
+            let layout = self.layout_slotmap.get_value(&layout_key).unwrap();
+            if let Some(index) = layout.find_child(key) {
+                return Some((layout_key, index));
+            }
+            for child in layout.children() {
+                if let LayoutOrTabKey::LayoutKey(child_key) = child.layout_or_tab_key {
+                    layout_stack.push(child_key);
+                }
+            }
+        }
+        None
+    }
+
+    /// Removes the tab from its group. Closing the last tab of a group removes the group from
+    /// its layout, unless it is the only child left. The container is dropped once no tab group
+    /// of any workspace shows it. Returns true if the tab was closed.
+    pub fn close_tab(&mut self, tabs_key: TabsSlotKey, index: usize) -> bool {
+        let container_key = match self.detach_tab(tabs_key, index) {
+            Some(container_key) => container_key,
+            None => return false,
+        };
+        if !self.is_container_shown(container_key) {
+            self.gui_container_slotmap.remove(container_key.0);
+        }
+        true
+    }
+
+    /// Returns true if a tab group of any workspace has the container as one of its tabs
+    fn is_container_shown(&self, key: GUIContainerSlotkey) -> bool {
+        let mut layout_stack: Vec<LayoutSlotKey> = self
+            .window_order
+            .iter()
+            .chain(self.workspaces.iter().flat_map(|workspace| workspace.window_order.iter()))
+            .filter_map(|window_key| self.window_collection.get_value(&window_key.0))
+            .map(|window| window.layout_key)
+            .collect();
+
+        while let Some(layout_key) = layout_stack.pop() {
+            let layout = self.layout_slotmap.get_value(&layout_key).unwrap();
+            for child in layout.children() {
+                match child.layout_or_tab_key {
+                    LayoutOrTabKey::LayoutKey(child_key) => layout_stack.push(child_key),
+                    LayoutOrTabKey::TabKey(tabs_key) => {
+                        let shown = self
+                            .tabs_slotmap
+                            .get_value(&tabs_key)
+                            .map_or(false, |tabs| tabs.tabs.iter().any(|tab| tab.0 == key.0));
+                        if shown {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Takes the tab out of the layout and returns its container
+    fn detach_tab(&mut self, tabs_key: TabsSlotKey, index: usize) -> Option<GUIContainerSlotkey> {
+        let tab_container = self.tabs_slotmap.get_value_mut(&tabs_key)?;
+        if tab_container.tabs.len() > 1 {
+            return tab_container.remove_tab(index);
+        }
+        let container_key = tab_container.tabs[0];
+
+        let (parent_key, child_index) = self.find_parent_layout(tabs_key.into())?;
+        let parent = self.layout_slotmap.get_value_mut(&parent_key).unwrap();
+        if parent.children().len() <= 1 {
+            return None;
+        }
+        parent.children_mut().remove(child_index);
+        for window_key in self.window_order.iter() {
+            if let Some(window) = self.window_collection.get_value_mut(&window_key.0) {
+                if window.maximized_tabs.map_or(false, |maximized| maximized.0 == tabs_key.0) {
+                    window.maximized_tabs = None;
+                }
+            }
+        }
+        self.tabs_slotmap.remove(tabs_key.0);
+        Some(container_key)
+    }
+
+    /// Returns the window whose layout contains `key`
+    pub fn find_window(&self, key: LayoutOrTabKey) -> Option<WindowSlotKey> {
+        let mut root = key;
+        while let Some((parent_key, _)) = self.find_parent_layout(root) {
+            root = parent_key.into();
+        }
+        let root_key = match root {
+            LayoutOrTabKey::LayoutKey(root_key) => root_key,
+            LayoutOrTabKey::TabKey(_) => return None,
+        };
+        self.window_order
+            .iter()
+            .find(|window_key| match self.window_collection.get_value(&window_key.0) {
+                Some(window) => window.layout_key.0 == root_key.0,
+                None => false,
+            })
+            .copied()
+    }
+
+    /// Draws the tab group over its whole window, or restores the layout if it was already
+    /// maximized
+    pub fn toggle_maximize(&mut self, tabs_key: TabsSlotKey) {
+        let window_key = match self.find_window(tabs_key.into()) {
+            Some(window_key) => window_key,
+            None => return,
+        };
+        let window = self.window_collection.get_value_mut(&window_key.0).unwrap();
+        window.maximized_tabs = match window.maximized_tabs {
+            Some(maximized) if maximized.0 == tabs_key.0 => None,
+            _ => Some(tabs_key),
+        };
+    }
+
+    /// Moves the tab to a new group placed to the right of its current group
+    pub fn split_tab_right(&mut self, tabs_key: TabsSlotKey, index: usize) -> Option<TabsSlotKey> {
+        let (parent_key, child_index) = self.find_parent_layout(tabs_key.into())?;
+        let container_key = self
+            .tabs_slotmap
+            .get_value_mut(&tabs_key)?
+            .remove_tab(index)?;
+        let new_tabs_key = self.create_tab(vec![container_key]);
+
+        let orientation = self
+            .layout_slotmap
+            .get_value(&parent_key)
+            .unwrap()
+            .orientation();
+        match orientation {
+            Orientation::Horizontal => {
+                let children = self
+                    .layout_slotmap
+                    .get_value_mut(&parent_key)
+                    .unwrap()
+                    .children_mut();
+                children[child_index].size *= 0.5;
+                let size = children[child_index].size;
+                children.insert(child_index + 1, DividedElement::new_tab(new_tabs_key, size));
+            }
+            Orientation::Vertical => {
+                let split_key = self
+                    .push_horizontal(vec![
+                        DividedElement::new_tab(tabs_key, 1.0),
+                        DividedElement::new_tab(new_tabs_key, 1.0),
+                    ])
+                    .unwrap();
+                let children = self
+                    .layout_slotmap
+                    .get_value_mut(&parent_key)
+                    .unwrap()
+                    .children_mut();
+                children[child_index].layout_or_tab_key = split_key.into();
+            }
+        }
+        Some(new_tabs_key)
+    }
+
+    /// A tab of a merged tab group is handled by the tab group it was merged from
+    fn resolve_merged_tab(&self, tabs_key: TabsSlotKey, index: usize) -> (TabsSlotKey, usize) {
+        let mut layout_stack: Vec<LayoutSlotKey> = self
+            .window_order
+            .iter()
+            .filter_map(|window_key| self.window_collection.get_value(&window_key.0))
+            .map(|window| window.layout_key)
+            .collect();
+
+        while let Some(layout_key) = layout_stack.pop() {
+            let layout = self.layout_slotmap.get_value(&layout_key).unwrap();
+            if layout.merged_tabs().map_or(false, |merged| merged.0 == tabs_key.0) {
+                let container_key = match self
+                    .tabs_slotmap
+                    .get_value(&tabs_key)
+                    .and_then(|tabs| tabs.tabs.get(index))
+                {
+                    Some(container_key) => *container_key,
+                    None => break,
+                };
+                let mut key_stack: Vec<LayoutOrTabKey> = layout
+                    .children()
+                    .iter()
+                    .map(|child| child.layout_or_tab_key)
+                    .collect();
+                while let Some(key) = key_stack.pop() {
+                    match key {
+                        LayoutOrTabKey::TabKey(child_key) => {
+                            let position = self.tabs_slotmap.get_value(&child_key).and_then(|tabs| {
+                                tabs.tabs.iter().position(|tab| tab.0 == container_key.0)
+                            });
+                            if let Some(position) = position {
+                                return (child_key, position);
+                            }
+                        }
+                        LayoutOrTabKey::LayoutKey(child_key) => key_stack.extend(
+                            self.layout_slotmap
+                                .get_value(&child_key)
+                                .unwrap()
+                                .children()
+                                .iter()
+                                .map(|child| child.layout_or_tab_key),
+                        ),
+                    }
+                }
+                break;
+            }
+            for child in layout.children() {
+                if let LayoutOrTabKey::LayoutKey(child_key) = child.layout_or_tab_key {
+                    layout_stack.push(child_key);
+                }
+            }
+        }
+        (tabs_key, index)
+    }
+
+    /// Fills the merged tab group of the layout with the tabs of the children collapsed into it,
+    /// the group is created the first time it is needed
+    fn sync_merged_tabs(
+        layout_key: LayoutSlotKey,
+        size: Vec2,
+        scale: &GUIScale,
+        layout_slotmap: &mut Slotmap<LayoutElement>,
+        tabs_slotmap: &mut Slotmap<TabsContainer>,
+    ) {
+        let layout = layout_slotmap.get_value(&layout_key).unwrap();
+        let mut key_stack = layout.merged_children(size, scale);
+        // Popped from the end, reversed so the tabs keep the order of the children
+        key_stack.reverse();
+        let mut containers = Vec::new();
+        while let Some(key) = key_stack.pop() {
+            match key {
+                LayoutOrTabKey::TabKey(tabs_key) => {
+                    if let Some(tabs) = tabs_slotmap.get_value(&tabs_key) {
+                        containers.extend(tabs.tabs.iter().copied());
+                    }
+                }
+                LayoutOrTabKey::LayoutKey(child_key) => key_stack.extend(
+                    layout_slotmap
+                        .get_value(&child_key)
+                        .unwrap()
+                        .children()
+                        .iter()
+                        .rev()
+                        .map(|child| child.layout_or_tab_key),
+                ),
+            }
+        }
+        if containers.len() == 0 {
+            return;
+        }
+
+        match layout.merged_tabs().and_then(|key| tabs_slotmap.get_value_mut(&key)) {
+            Some(merged_tabs) => merged_tabs.set_tabs(containers),
+            None => {
+                let merged_tabs = TabsContainer::new(containers);
+                let merged_key = TabsSlotKey(tabs_slotmap.push(merged_tabs).unwrap());
+                layout_slotmap
+                    .get_value_mut(&layout_key)
+                    .unwrap()
+                    .set_merged_tabs(merged_key);
+            }
+        }
+    }
+
     pub fn layouts_handle_event(
         control_state: &mut ControlState,
         layout_slotmap: &mut Slotmap<LayoutElement>,
+        tabs_slotmap: &mut Slotmap<TabsContainer>,
         root_layout: LayoutOrTabInfo,
         event: &mut UIEvent,
+        public_data: &PublicData,
     ) -> Vec<TabLayoutInfo> {
         let mut tab_handle_stack = Vec::<TabLayoutInfo>::new();
         let mut layout_handle_stack = Vec::<LayoutOrTabInfo>::new();
@@ -160,10 +520,22 @@ impl WindowSystem {
                         });
                     }
                     LayoutOrTabKey::LayoutKey(layout_key) => {
+                        WindowSystem::sync_merged_tabs(
+                            layout_key,
+                            layout_handle.container_info.rect.size,
+                            &control_state.scale,
+                            layout_slotmap,
+                            tabs_slotmap,
+                        );
                         let children = layout_slotmap
                             .get_value_mut(&layout_key)
                             .unwrap()
-                            .handle_event(event, layout_handle.container_info, control_state);
+                            .handle_event(
+                                event,
+                                layout_handle.container_info,
+                                control_state,
+                                public_data,
+                            );
                         layout_handle_stack.extend(children);
                     }
                 },
@@ -187,6 +559,9 @@ impl WindowSystem {
         mut tab_handle_stack: Vec<TabLayoutInfo>,
         event: &mut UIEvent,
         public_data: &PublicData,
+        context_menu: &mut Option<Vec<MenuItem<ContextAction>>>,
+        tab_actions: &mut Vec<ContextAction>,
+        maximized_tabs: Option<TabsSlotKey>,
     ) -> Vec<GUIContainerInfo> {
         let mut gui_handle_stack = Vec::with_capacity(tab_handle_stack.len());
         for tab in tab_handle_stack.drain(..) {
@@ -199,6 +574,11 @@ impl WindowSystem {
                     .expect("Should never return none, removing a gui container should do the proper cleanup")
                     .get_name())
                 .collect();
+            let tab_descriptions: Vec<Option<&str>> = tab_container
+                .tabs
+                .iter()
+                .map(|gui_key| gui_container_slotmap.get_value(gui_key).unwrap().get_description())
+                .collect();
 
             let gui_container_info = tab_container.handle_event(
                 event,
@@ -206,33 +586,105 @@ impl WindowSystem {
                 tab.container_info,
                 control_state,
                 &tab_names,
+                &tab_descriptions,
             );
             gui_handle_stack.push(gui_container_info);
+
+            let maximize_key_pressed = keyboard::key_pressed(event, VirtualKeyCode::F11)
+                && match control_state.last_cursor_position {
+                    Some(cursor_pos) => tab.container_info.rect.inside_rect(cursor_pos),
+                    None => false,
+                };
+            if std::mem::take(&mut tab_container.toggle_maximize) || maximize_key_pressed {
+                tab_actions.push(ContextAction::ToggleMaximize { tabs_key: tab.key });
+            }
+
+            if let Some(index) = tab_container.close_tab.take() {
+                tab_actions.push(ContextAction::CloseTab {
+                    tabs_key: tab.key,
+                    index,
+                });
+            }
+
+            if let Some(index) = tab_container.context_menu_tab.take() {
+                let container_key = tab_container.tabs[index];
+                *context_menu = Some(context_menu::tab_context_menu(
+                    tab.key,
+                    index,
+                    tab_container.tabs.len(),
+                    maximized_tabs.map_or(false, |maximized| maximized.0 == tab.key.0),
+                    container_key,
+                    gui_container_slotmap.get_value(&container_key).unwrap().as_ref(),
+                ));
+            }
         }
         gui_handle_stack
     }
 
-    pub fn windows_handle_event(&mut self, event: &mut UIEvent, public_data: &PublicData) {
+    /// Returns the items of the context menu requested during the event, if any
+    pub fn windows_handle_event(
+        &mut self,
+        event: &mut UIEvent,
+        public_data: &PublicData,
+    ) -> Option<Vec<MenuItem<ContextAction>>> {
+        let mut context_menu = None;
+        let mut tab_actions = Vec::new();
+        let snap_bounds = self.main_window_rect(public_data);
+        let floating_rects: Vec<(usize, Rect)> = self
+            .window_order
+            .iter()
+            .enumerate()
+            .filter_map(|(index, window_key)| {
+                let window = self.window_collection.get_value(&window_key.0)?;
+                window.floating.then(|| (index, window.rect()))
+            })
+            .collect();
         for (index, window_key) in self.window_order.iter().enumerate() {
             match self.window_collection.get_value_mut(&window_key.0) {
                 Some(window_mut) => {
-                    let depth_range = (
-                        index as u32 * DEPTH_SLICE_SIZE,
-                        ((index as u32 + 1) * DEPTH_SLICE_SIZE) - 1,
+                    let window_layers = self.control_state.layers.window_layers(index);
+                    let maximized_tabs = window_mut.maximized_tabs;
+                    self.control_state
+                        .set_depth(window_layers.depth(Layer::Base, 0));
+
+                    let others: Vec<Rect> = floating_rects
+                        .iter()
+                        .filter(|(other_index, _)| *other_index != index)
+                        .map(|(_, rect)| *rect)
+                        .collect();
+                    let snap_targets = SnapTargets {
+                        bounds: snap_bounds,
+                        others: &others,
+                        settings: SnapSettings {
+                            threshold: self.control_state.scale.px(self.snap_settings.threshold),
+                            ..self.snap_settings
+                        },
+                    };
+                    let window_label = if window_mut.floating {
+                        "Floating window"
+                    } else {
+                        "Main window"
+                    };
+                    let window_access_id = self.control_state.push_access_node(
+                        AccessNode::new(AccessRole::Window, window_mut.rect(), window_label),
                     );
+                    self.control_state.access.push_parent(window_access_id);
 
                     let root_layout = window_mut.handle_event(
                         event,
                         public_data,
                         &mut self.control_state,
-                        depth_range,
+                        window_layers,
+                        &snap_targets,
                     );
 
                     let tab_handle_stack = WindowSystem::layouts_handle_event(
                         &mut self.control_state,
                         &mut self.layout_slotmap,
+                        &mut self.tabs_slotmap,
                         root_layout,
                         event,
+                        public_data,
                     );
 
                     let gui_handle_stack = WindowSystem::tabs_handle_event(
@@ -242,6 +694,9 @@ impl WindowSystem {
                         tab_handle_stack,
                         event,
                         public_data,
+                        &mut context_menu,
+                        &mut tab_actions,
+                        maximized_tabs,
                     );
 
                     for gui_handle in gui_handle_stack {
@@ -249,13 +704,39 @@ impl WindowSystem {
                             .gui_container_slotmap
                             .get_value_mut(&gui_handle.key)
                             .unwrap();
+                        // Catches the cursor over the empty parts of the container, the
+                        // controls of the container take it over
+                        let container_id = self.control_state.get_id();
+                        if let UIEvent::Update = event {
+                            self.control_state
+                                .set_hot_with_rect(container_id, &gui_handle.container_info.rect);
+                        }
+                        self.control_state.access.push_parent(gui_handle.access_id);
                         gui_container.handle_event(
                             event,
                             public_data,
                             gui_handle.container_info,
                             &mut self.control_state,
                         );
+                        self.control_state.access.pop_parent();
+
+                        if let UIEvent::MouseButton(mouse_input) = event {
+                            let last_id = self.control_state.get_last_id();
+                            let hovered = self
+                                .control_state
+                                .is_hovered_between(container_id, last_id);
+                            if keyboard::is_right_pressed(mouse_input) && hovered {
+                                let items = context_menu::container_context_menu(
+                                    gui_handle.key,
+                                    gui_container.as_ref(),
+                                );
+                                if items.len() > 0 {
+                                    context_menu = Some(items);
+                                }
+                            }
+                        }
                     }
+                    self.control_state.access.pop_parent();
                 }
                 None => { /* No op */ }
             }
@@ -268,15 +749,84 @@ impl WindowSystem {
                 extra_render_steps.execute_render_steps(gui_rects);
             }
         }
+
+        if let UIEvent::Render { gui_rects, .. } = event {
+            tooltip::push_tooltip_render_step(&mut self.control_state, public_data);
+            self.control_state.layers.execute_top_render_steps(gui_rects);
+        }
+
+        // Applied once every window handled the event, so no layout changes while it is in use
+        for action in tab_actions {
+            self.apply_context_action(action, public_data);
+        }
+        context_menu
     }
 
     pub fn handle_event(&mut self, event: &mut UIEvent, public_data: &PublicData) {
         self.control_state.on_gui_start();
+        self.control_state.layers.begin_frame(self.window_order.len());
         if let UIEvent::MouseMove { corrected, .. } = event {
             self.control_state.last_cursor_position = Some(*corrected);
         }
+        if let UIEvent::Update = event {
+            self.control_state.update_time(get_time(public_data).time.0);
+            self.control_state.access.begin_frame();
+        }
+        if let UIEvent::MouseButton(mouse_input) = event {
+            if mouse_input.is_left_pressed() {
+                self.control_state.clear_focus();
+            }
+        }
+
+        // Modals and popups go first so they can block the input of everything under them
+        let modal_open = modal::handle_modal_event(
+            event,
+            public_data,
+            &mut self.control_state,
+            self.main_window_rect(public_data),
+        );
+        if modal_open {
+            self.popups.close();
+        }
+
+        let popup_was_open = self.popups.is_open();
+        let popup_response = self
+            .popups
+            .handle_event(event, public_data, &mut self.control_state);
+        if let Some(action) = popup_response.action {
+            self.apply_context_action(action, public_data);
+        }
+        if popup_response.consumed {
+            // The key is also queued for the focused text field, which reads it on the update
+            self.control_state.text_input.take_events();
+        }
+
+        // Keyboard focus stays inside of the modal
+        let keyboard_trapped = modal_open && matches!(event, UIEvent::KeyboardInput(..));
+        if !keyboard_trapped && !popup_response.consumed {
+            let context_menu = self.windows_handle_event(event, public_data);
+            if let (Some(items), Some(cursor_pos)) =
+                (context_menu, self.control_state.last_cursor_position)
+            {
+                if !popup_was_open && !modal_open {
+                    let mut items = items;
+                    items.push(workspace::workspace_menu(
+                        &self.workspaces,
+                        self.active_workspace,
+                    ));
+                    items.push(context_menu::theme_menu(&get_theme(public_data).name));
+                    items.push(context_menu::accessibility_menu(get_accessibility(
+                        public_data,
+                    )));
+                    self.popups.open(items, cursor_pos);
+                }
+            }
 
-        self.windows_handle_event(event, public_data);
+            // After the windows so the frame is not switched half way through
+            if keyboard::key_pressed(event, VirtualKeyCode::F6) {
+                self.switch_to_next_workspace();
+            }
+        }
 
         self.control_state.on_gui_end();
 
@@ -285,10 +835,22 @@ impl WindowSystem {
         }
 
         if let UIEvent::Update = event {
+            self.control_state.access.end_frame();
             self.control_state.on_after_update();
         }
     }
 
+    /// Semantic tree of the windows and controls, built on every update
+    pub fn access_tree(&self) -> &AccessTree {
+        self.control_state.access.tree()
+    }
+
+    /// Last tree in the shape `accesskit` expects
+    pub fn access_tree_update(&self, public_data: &PublicData) -> AccessKitTreeUpdate {
+        let screen_height = get_engine_data(public_data).screen_size.as_vec2().y;
+        adapter::tree_update(self.access_tree(), screen_height, "rwge_game")
+    }
+
     pub fn render_event(&mut self, public_data: &PublicData, gui_rects: &mut GUIRects) {
         let extra_render_steps = ExtraRenderSteps::new(25);
         let mut event = UIEvent::Render {
@@ -296,7 +858,18 @@ impl WindowSystem {
             extra_render_steps,
         };
         self.control_state.on_gui_start();
+        self.control_state.layers.begin_frame(self.window_order.len());
+        modal::handle_modal_event(
+            &mut event,
+            public_data,
+            &mut self.control_state,
+            self.main_window_rect(public_data),
+        );
+        self.popups
+            .handle_event(&mut event, public_data, &mut self.control_state);
         self.windows_handle_event(&mut event, public_data);
         self.control_state.on_gui_end();
+        self.control_state.cursor.apply(public_data);
+        self.control_state.text_input.apply_ime_position(public_data);
     }
 }
@@ -1,23 +1,28 @@
 use rwge::{
-    color::*,
-    font::font_layout::create_single_line,
+    font::{font_layout::create_single_line, font_load_gpu::FontCollection},
     glam::*,
     gui::rect_ui::{
         element::{builder::ElementBuilder, LinearGradient},
         event::UIEvent,
         BorderRadius, GUIRects, Rect,
     },
+    uuid::Uuid,
 };
 
 use crate::{
     gui_system::{
-        control::{ControlState, State},
+        access_tree::{AccessNode, AccessRole},
+        control::{
+            cursor::{CursorIcon, CursorPriority},
+            keyboard,
+            scale::GUIScale,
+            tooltip, ControlState, State, Uiid,
+        },
+        layers::Layer,
+        theme::{get_theme, Theme},
         ContainerInfo,
     },
-    runtime_data::{
-        utils::{get_font_collections, get_time},
-        PublicData,
-    },
+    runtime_data::{utils::get_font_collections, PublicData},
 };
 
 use super::{depth_offset, GUIContainerInfo, GUIContainerSlotkey};
@@ -25,15 +30,60 @@ use super::{depth_offset, GUIContainerInfo, GUIContainerSlotkey};
 pub struct TabsContainer {
     pub tabs: Vec<GUIContainerSlotkey>,
     active_tab: usize,
+    /// Tab that was right clicked during the last event
+    pub context_menu_tab: Option<usize>,
+    /// Tab whose close button was pressed during the last event
+    pub close_tab: Option<usize>,
+    /// Set when a tab was double clicked during the last event
+    pub toggle_maximize: bool,
+    /// Horizontal scroll of the tab bar when the tabs do not fit
+    scroll_offset: f32,
+    tab_drag: Option<TabDrag>,
+    /// One per tab, in the same order
+    label_cache: Vec<CachedTabLabel>,
+}
+
+/// A tab being dragged to be reordered inside of its group
+struct TabDrag {
+    active_id: Uuid,
+    index: usize,
+    /// Distance from the center of the tab to the cursor when the drag started, the tab is drawn
+    /// at the same distance from the cursor while it is dragged
+    grab_offset: f32,
+}
+
+pub enum TabButtonInput {
+    None,
+    Select(Option<Uuid>),
+    ContextMenu,
+}
+
+#[derive(Clone)]
+struct TabLabel {
+    /// Name truncated to fit inside of the maximum width
+    text: String,
+    width: f32,
+}
+
+/// Fitting a label measures it once per removed character, so it is only done again when the
+/// name or the sizes it was fitted with change
+struct CachedTabLabel {
+    name: String,
+    font_collection: usize,
+    font_size: f32,
+    max_width: f32,
+    label: TabLabel,
 }
 
 pub const TAB_SIZE: f32 = 30.0;
-pub const TAB_WIDTH: f32 = 100.0;
-pub const TAB_GAP: f32 = 5.0;
-pub const TAB_BG_COLOR: RGBA = RGBA::rrr1(0.25);
-pub const GUI_ACTIVE_COLOR: RGBA = RGBA::rrr1(0.2);
-pub const GUI_HOVER_COLOR: RGBA = RGBA::rgb(0.4, 0.9, 0.0);
-pub const GUI_INACTIVE_COLOR: RGBA = RGBA::rrr1(0.35);
+pub const TAB_MIN_WIDTH: f32 = 70.0;
+pub const TAB_MAX_WIDTH: f32 = 180.0;
+pub const TAB_LABEL_MARGIN: f32 = 10.0;
+pub const TAB_CLOSE_SIZE: f32 = 16.0;
+pub const TAB_SCROLL_BUTTON_WIDTH: f32 = 20.0;
+pub const TAB_SCROLL_STEP: f32 = 80.0;
+
+const ELLIPSIS: &str = "...";
 
 impl TabsContainer {
     pub fn new(mut containers: Vec<GUIContainerSlotkey>) -> Self {
@@ -47,24 +97,86 @@ impl TabsContainer {
         Self {
             tabs,
             active_tab: 0,
+            context_menu_tab: None,
+            close_tab: None,
+            toggle_maximize: false,
+            scroll_offset: 0.0,
+            tab_drag: None,
+            label_cache: Vec::new(),
+        }
+    }
+
+    /// New tab container with the same tabs, used to copy layouts
+    pub fn duplicate(&self) -> Self {
+        let mut tabs_container = Self::new(self.tabs.clone());
+        tabs_container.active_tab = self.active_tab;
+        tabs_container
+    }
+
+    pub fn active_tab(&self) -> usize {
+        self.active_tab
+    }
+
+    /// Replaces the tabs, unless it would only change their order. The active container stays
+    /// active if it is still one of the tabs.
+    pub fn set_tabs(&mut self, tabs: Vec<GUIContainerSlotkey>) {
+        let same_tabs = tabs.len() == self.tabs.len()
+            && tabs.iter().all(|tab| self.tabs.iter().any(|own| own.0 == tab.0));
+        if same_tabs || tabs.len() == 0 {
+            return;
+        }
+        let active_key = self.tabs[self.active_tab];
+        self.active_tab = tabs
+            .iter()
+            .position(|tab| tab.0 == active_key.0)
+            .unwrap_or(0);
+        self.tabs = tabs;
+        self.tab_drag = None;
+    }
+
+    /// The last tab cannot be removed, the whole tab container should be removed instead
+    pub fn remove_tab(&mut self, index: usize) -> Option<GUIContainerSlotkey> {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
+            return None;
+        }
+        let removed = self.tabs.remove(index);
+        if index < self.active_tab {
+            self.active_tab -= 1;
+        }
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        self.tab_drag = None;
+        Some(removed)
+    }
+
+    /// Swaps two tabs keeping the same tab active
+    fn swap_tabs(&mut self, a: usize, b: usize) {
+        self.tabs.swap(a, b);
+        if self.active_tab == a {
+            self.active_tab = b;
+        } else if self.active_tab == b {
+            self.active_tab = a;
         }
     }
 
     pub fn tab_button(
         control_state: &mut ControlState,
         event: &mut UIEvent,
+        control_id: Uiid,
         mut rect: Rect,
         rect_mask: &Rect,
         is_active_tab: bool,
         public_data: &PublicData,
         tab_name: &str,
-    ) -> bool {
-        let control_id = control_state.get_id();
-
+        tab_label: &str,
+        tab_description: Option<&str>,
+    ) -> TabButtonInput {
         if let UIEvent::MouseButton(mouse_input) = event {
-            if mouse_input.is_left_pressed() {
-                if control_state.is_hovered(control_id) {
-                    return true;
+            if control_state.is_hovered(control_id) {
+                if mouse_input.is_left_pressed() {
+                    return TabButtonInput::Select(control_state.set_active(control_id));
+                }
+                if keyboard::is_right_pressed(mouse_input) {
+                    return TabButtonInput::ContextMenu;
                 }
             }
         }
@@ -77,35 +189,40 @@ impl TabsContainer {
         }
 
         if let UIEvent::Render { gui_rects, .. } = event {
+            let scale = control_state.scale;
+            let theme = get_theme(public_data);
+            let colors = &theme.colors;
+            let tab_gap = theme.metrics.tab_gap;
+            match tab_description {
+                Some(description) => tooltip::tooltip(
+                    control_state,
+                    control_id,
+                    format!("{tab_name}\n{description}").as_str(),
+                ),
+                None => tooltip::tooltip(control_state, control_id, tab_name),
+            }
+
             let (round_rect, color) = if is_active_tab {
                 rect = rect
-                    .offset_position(-vec2(0.0, TAB_GAP * 0.5))
-                    .offset_size(vec2(0.0, TAB_GAP + 2.0));
+                    .offset_position(-vec2(0.0, scale.px(tab_gap * 0.5)))
+                    .offset_size(vec2(0.0, scale.px(tab_gap + 2.0)));
                 (
                     BorderRadius::ForTopBottom {
-                        top: rect.size.y * 0.5 - TAB_GAP,
+                        top: rect.size.y * 0.5 - scale.px(tab_gap),
                         bottom: 0.0,
                     },
-                    GUI_ACTIVE_COLOR,
+                    colors.background,
                 )
             } else {
                 (
-                    BorderRadius::ForAll(rect.size.y * 0.5 - 2.0),
-                    GUI_INACTIVE_COLOR,
+                    BorderRadius::ForAll(rect.size.y * 0.5 - scale.px(2.0)),
+                    colors.tab_inactive,
                 )
             };
 
             let state = control_state.get_control_state(control_id.into());
             let btn_color = if let State::Hovered = state {
-                let color_interp = get_time(public_data).sin_time(0.5) * 0.5 + 0.5;
-                let color: RGBA = HSLA {
-                    h: color_interp * 360.0,
-                    s: 0.5,
-                    l: 0.5,
-                    a: 1.0,
-                }
-                .into();
-                color
+                colors.tab_hover
             } else {
                 color
             };
@@ -113,14 +230,14 @@ impl TabsContainer {
             let elem_build = ElementBuilder::new_with_rect(rect).set_round_rect(round_rect.into());
             if is_active_tab {
                 let lin_gradient = LinearGradient {
-                    colors: [RGBA::rrr1(0.10), color],
+                    colors: [colors.shadow, color],
                     start_position: vec2(0.0, rect.size.y * 0.5),
-                    end_position: vec2(0.0, rect.size.y * 0.5 - 6.0),
+                    end_position: vec2(0.0, rect.size.y * 0.5 - scale.px(6.0)),
                 };
                 elem_build.set_linear_gradient(lin_gradient.into())
             } else {
                 let lin_gradient = LinearGradient {
-                    colors: [GUI_INACTIVE_COLOR * 1.5, btn_color],
+                    colors: [colors.tab_inactive * 1.5, btn_color],
                     start_position: vec2(rect.size.x * 0.5, 0.0),
                     end_position: vec2(-rect.size.x * 0.5, 0.0),
                 };
@@ -129,24 +246,206 @@ impl TabsContainer {
             .set_rect_mask((*rect_mask).into())
             .build(gui_rects);
 
-            let font_collection = &get_font_collections(public_data)[0];
-            let (font_elements, mut text_rect) =
-                create_single_line(tab_name, 16.0, font_collection, 0, 0.0);
+            // The label is centered in the space left by the close button
+            let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+            let (font_elements, text_rect) = create_single_line(
+                tab_label,
+                scale.font(theme.fonts.size),
+                font_collection,
+                0,
+                0.0,
+            );
+            let label_center = rect.position - vec2(scale.px(TAB_CLOSE_SIZE * 0.5), 0.0);
 
             for font_elem in font_elements {
                 ElementBuilder::new_with_rect(
                     font_elem
                         .rect
-                        .offset_position(rect.position - text_rect.size * 0.5),
+                        .offset_position((label_center - text_rect.size * 0.5).round()),
                 )
                 .set_rect_mask((*rect_mask).into())
                 .set_sdffont(font_elem.tx_slice.into())
+                .set_color(colors.text.into())
+                .build(gui_rects);
+            }
+        }
+        return TabButtonInput::None;
+    }
+
+    /// Small button drawn on the right side of the tab
+    fn close_button(
+        control_state: &mut ControlState,
+        event: &mut UIEvent,
+        control_id: Uiid,
+        tab_rect: Rect,
+        rect_mask: &Rect,
+        public_data: &PublicData,
+    ) -> bool {
+        let scale = control_state.scale;
+        let rect = Rect {
+            position: vec2(
+                tab_rect.position.x + tab_rect.width() * 0.5
+                    - scale.px(TAB_LABEL_MARGIN * 0.5 + TAB_CLOSE_SIZE * 0.5),
+                tab_rect.position.y,
+            ),
+            size: scale.vec(vec2(TAB_CLOSE_SIZE, TAB_CLOSE_SIZE)),
+        };
+
+        match event {
+            UIEvent::Update => {
+                if let Some(mouse_test_rect) = rect.combine_rects(rect_mask) {
+                    control_state.set_hot_with_rect(control_id, &mouse_test_rect);
+                }
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Button, rect, "Close tab").set_control(control_id),
+                );
+            }
+            UIEvent::MouseButton(mouse_input) => {
+                if mouse_input.is_left_pressed() && control_state.is_hovered(control_id) {
+                    return true;
+                }
+            }
+            UIEvent::Render { gui_rects, .. } => {
+                let theme = get_theme(public_data);
+                let hovered = control_state.is_hovered(control_id);
+                if hovered {
+                    ElementBuilder::new_with_rect(rect)
+                        .set_circle()
+                        .set_color(theme.colors.control_hover.into())
+                        .set_rect_mask((*rect_mask).into())
+                        .build(gui_rects);
+                }
+
+                let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+                let (font_elements, text_rect) = create_single_line(
+                    "x",
+                    scale.font(theme.fonts.size - 2.0),
+                    font_collection,
+                    0,
+                    0.0,
+                );
+                for font_elem in font_elements {
+                    ElementBuilder::new_with_rect(
+                        font_elem
+                            .rect
+                            .offset_position((rect.position - text_rect.size * 0.5).round()),
+                    )
+                    .set_rect_mask((*rect_mask).into())
+                    .set_sdffont(font_elem.tx_slice.into())
+                    .set_color(theme.colors.text.into())
+                    .build(gui_rects);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Arrow shown at the right of the tab bar when the tabs do not fit
+    fn scroll_button(
+        control_state: &mut ControlState,
+        event: &mut UIEvent,
+        rect: Rect,
+        label: &str,
+        access_label: &str,
+        public_data: &PublicData,
+    ) -> bool {
+        let control_id = control_state.get_id();
+        let scale = control_state.scale;
+
+        match event {
+            UIEvent::Update => {
+                control_state.set_hot_with_rect(control_id, &rect);
+                control_state.push_access_node(
+                    AccessNode::new(AccessRole::Button, rect, access_label).set_control(control_id),
+                );
+            }
+            UIEvent::MouseButton(mouse_input) => {
+                if mouse_input.is_left_pressed() && control_state.is_hovered(control_id) {
+                    return true;
+                }
+            }
+            UIEvent::Render { gui_rects, .. } => {
+                let theme = get_theme(public_data);
+                let color = if control_state.is_hovered(control_id) {
+                    theme.colors.tab_inactive * 1.5
+                } else {
+                    theme.colors.tab_inactive
+                };
+                ElementBuilder::new_with_rect(
+                    rect.offset_size(-scale.vec(vec2(2.0, theme.metrics.tab_gap * 2.0))),
+                )
+                .set_color(color.into())
+                .set_round_rect(BorderRadius::ForAll(scale.px(theme.metrics.radius_small)).into())
                 .build(gui_rects);
+
+                let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+                let (font_elements, text_rect) = create_single_line(
+                    label,
+                    scale.font(theme.fonts.size),
+                    font_collection,
+                    0,
+                    0.0,
+                );
+                for font_elem in font_elements {
+                    ElementBuilder::new_with_rect(
+                        font_elem
+                            .rect
+                            .offset_position((rect.position - text_rect.size * 0.5).round()),
+                    )
+                    .set_sdffont(font_elem.tx_slice.into())
+                    .set_color(theme.colors.text.into())
+                    .build(gui_rects);
+                }
             }
+            _ => {}
         }
-        return false;
+        false
     }
 
+    /// Labels of the tabs, taken from the cache when the tab was fitted with the same sizes
+    fn fit_tab_labels(
+        &mut self,
+        tab_names: &Vec<&str>,
+        scale: &GUIScale,
+        theme: &Theme,
+        public_data: &PublicData,
+    ) -> Vec<TabLabel> {
+        let font_collection = &get_font_collections(public_data)[theme.fonts.collection];
+        let font_size = scale.font(theme.fonts.size);
+        let max_width = scale.px(TAB_MAX_WIDTH);
+
+        self.label_cache.truncate(tab_names.len());
+        for (index, name) in tab_names.iter().enumerate() {
+            let cached = self.label_cache.get(index).map_or(false, |cached| {
+                cached.name == *name
+                    && cached.font_collection == theme.fonts.collection
+                    && cached.font_size == font_size
+                    && cached.max_width == max_width
+            });
+            if cached {
+                continue;
+            }
+            let entry = CachedTabLabel {
+                name: String::from(*name),
+                font_collection: theme.fonts.collection,
+                font_size,
+                max_width,
+                label: fit_tab_label(name, font_collection, scale, theme),
+            };
+            if index < self.label_cache.len() {
+                self.label_cache[index] = entry;
+            } else {
+                self.label_cache.push(entry);
+            }
+        }
+        self.label_cache
+            .iter()
+            .map(|cached| cached.label.clone())
+            .collect()
+    }
+
+    /// Returns the rect of the active tab
     pub fn create_tab_buttons(
         &mut self,
         control_state: &mut ControlState,
@@ -155,32 +454,195 @@ impl TabsContainer {
         public_data: &PublicData,
         tab_rect: Rect,
         tab_names: &Vec<&str>,
-    ) {
-        let mut current_pos = tab_rect.left_position();
-
-        for index in 0..self.tabs.len() {
-            let tab_btn_pos = current_pos + vec2(TAB_GAP + TAB_WIDTH * 0.5, 0.0);
-            let tab_btn_size = vec2(TAB_WIDTH, tab_rect.size.y - TAB_GAP * 2.0);
+        tab_descriptions: &Vec<Option<&str>>,
+    ) -> Rect {
+        let scale = control_state.scale;
+        let theme = get_theme(public_data);
+        let tab_labels = self.fit_tab_labels(tab_names, &scale, theme, public_data);
+
+        let tab_gap = scale.px(theme.metrics.tab_gap);
+        let scroll_button_width = scale.px(TAB_SCROLL_BUTTON_WIDTH);
+        let tabs_width = tab_labels
+            .iter()
+            .fold(tab_gap, |width, label| width + label.width + tab_gap);
+        let overflow = tabs_width > tab_rect.width();
+
+        // The scroll arrows take the right side of the bar
+        let tabs_view_rect = if overflow {
+            Rect {
+                position: tab_rect.position - vec2(scroll_button_width, 0.0),
+                size: tab_rect.size - vec2(scroll_button_width * 2.0, 0.0),
+            }
+        } else {
+            tab_rect
+        };
 
-            let rect = Rect {
-                position: tab_btn_pos,
-                size: tab_btn_size,
+        if overflow {
+            let right_edge = tab_rect.position.x + tab_rect.width() * 0.5;
+            let arrow_rect = |offset: f32| Rect {
+                position: vec2(
+                    right_edge - scroll_button_width * (offset + 0.5),
+                    tab_rect.position.y,
+                ),
+                size: vec2(scroll_button_width, tab_rect.height()),
             };
+            let left_rect = arrow_rect(1.0);
+            if Self::scroll_button(
+                control_state,
+                event,
+                left_rect,
+                "<",
+                "Scroll tabs left",
+                public_data,
+            ) {
+                self.scroll_offset -= scale.px(TAB_SCROLL_STEP);
+            }
+            let right_rect = arrow_rect(0.0);
+            if Self::scroll_button(
+                control_state,
+                event,
+                right_rect,
+                ">",
+                "Scroll tabs right",
+                public_data,
+            ) {
+                self.scroll_offset += scale.px(TAB_SCROLL_STEP);
+            }
+        }
+        let max_scroll = (tabs_width - tabs_view_rect.width()).max(0.0);
+        self.scroll_offset = self.scroll_offset.max(0.0).min(max_scroll);
+
+        let view_left = tabs_view_rect.left_position().x;
+        let tab_height = tab_rect.size.y - tab_gap * 2.0;
+        let mut current_left = view_left + tab_gap - self.scroll_offset;
+        let tab_rects: Vec<Rect> = tab_labels
+            .iter()
+            .map(|label| {
+                let rect = Rect {
+                    position: vec2(current_left + label.width * 0.5, tab_rect.position.y),
+                    size: vec2(label.width, tab_height),
+                };
+                current_left += label.width + tab_gap;
+                rect
+            })
+            .collect();
+
+        // The dragged tab follows the cursor, but it is kept inside of the bar
+        let mut drawn_rects = tab_rects.clone();
+        if let (Some(tab_drag), Some(cursor_pos)) =
+            (&self.tab_drag, control_state.last_cursor_position)
+        {
+            let rect = &mut drawn_rects[tab_drag.index];
+            let min_x = view_left + rect.width() * 0.5;
+            let max_x = (view_left + tabs_view_rect.width() - rect.width() * 0.5).max(min_x);
+            rect.position.x = (cursor_pos.x - tab_drag.grab_offset).max(min_x).min(max_x);
+        }
 
-            current_pos += vec2(TAB_GAP + TAB_WIDTH, 0.0);
-
-            if Self::tab_button(
+        let control_ids: Vec<(Uiid, Uiid)> = (0..self.tabs.len())
+            .map(|_| (control_state.get_id(), control_state.get_id()))
+            .collect();
+
+        // The dragged tab is handled last so it is drawn over the others
+        let dragged_index = self.tab_drag.as_ref().map(|tab_drag| tab_drag.index);
+        let mut tab_order: Vec<usize> = (0..self.tabs.len())
+            .filter(|index| Some(*index) != dragged_index)
+            .collect();
+        tab_order.extend(dragged_index);
+
+        for index in tab_order {
+            let (tab_id, close_id) = control_ids[index];
+            let tab_access_id = control_state.push_access_node(
+                AccessNode::new(AccessRole::Tab, drawn_rects[index], tab_names[index])
+                    .set_control(tab_id)
+                    .set_selected(index == self.active_tab),
+            );
+            match Self::tab_button(
                 control_state,
                 event,
-                rect,
-                &tab_rect,
+                tab_id,
+                drawn_rects[index],
+                &tabs_view_rect,
                 index == self.active_tab,
                 public_data,
                 &tab_names[index],
+                &tab_labels[index].text,
+                tab_descriptions[index],
             ) {
-                self.active_tab = index;
+                TabButtonInput::Select(active_id) => {
+                    self.active_tab = index;
+                    if control_state.register_click(tab_id) {
+                        self.toggle_maximize = true;
+                    }
+
+                    if let (Some(active_id), Some(cursor_pos)) =
+                        (active_id, control_state.last_cursor_position)
+                    {
+                        self.tab_drag = Some(TabDrag {
+                            active_id,
+                            index,
+                            grab_offset: cursor_pos.x - tab_rects[index].position.x,
+                        });
+                    }
+                }
+                TabButtonInput::ContextMenu => self.context_menu_tab = Some(index),
+                TabButtonInput::None => {}
             }
+
+            control_state.access.push_parent(tab_access_id);
+            if Self::close_button(
+                control_state,
+                event,
+                close_id,
+                drawn_rects[index],
+                &tabs_view_rect,
+                public_data,
+            ) {
+                self.close_tab = Some(index);
+            }
+            control_state.access.pop_parent();
         }
+
+        match event {
+            UIEvent::Update => {
+                if let Some(tab_drag) = &self.tab_drag {
+                    control_state.hold_active_state(tab_drag.active_id);
+                }
+            }
+            UIEvent::Render { .. } => {
+                if self.tab_drag.is_some() {
+                    control_state.request_cursor(CursorIcon::Grabbing, CursorPriority::Active);
+                }
+            }
+            UIEvent::MouseButton(mouse_input) => {
+                if mouse_input.is_left_released() {
+                    if let Some(tab_drag) = self.tab_drag.take() {
+                        let _ = control_state.remove_active(tab_drag.active_id);
+                    }
+                }
+            }
+            UIEvent::MouseMove { .. } => {
+                // Swaps with a neighbour once the dragged tab passes its center
+                if let Some(index) = dragged_index {
+                    let dragged_x = drawn_rects[index].position.x;
+                    let swap_with = if index > 0 && dragged_x < tab_rects[index - 1].position.x {
+                        Some(index - 1)
+                    } else if index + 1 < tab_rects.len()
+                        && dragged_x > tab_rects[index + 1].position.x
+                    {
+                        Some(index + 1)
+                    } else {
+                        None
+                    };
+                    if let Some(other) = swap_with {
+                        self.swap_tabs(index, other);
+                        self.tab_drag.as_mut().unwrap().index = other;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        drawn_rects[self.active_tab]
     }
 
     pub fn handle_event(
@@ -190,22 +652,26 @@ impl TabsContainer {
         container_info: ContainerInfo,
         control_state: &mut ControlState,
         tab_names: &Vec<&str>,
+        tab_descriptions: &Vec<Option<&str>>,
     ) -> GUIContainerInfo {
         let active_tab_key = self.tabs[self.active_tab];
+        let active_tab_name = tab_names[self.active_tab];
+        let scale = control_state.scale;
+        let tab_size = scale.px(TAB_SIZE);
 
         let container_position = vec2(
             container_info.rect.position.x,
-            container_info.rect.position.y - (TAB_SIZE * 0.5),
+            container_info.rect.position.y - (tab_size * 0.5),
         );
         let container_size = vec2(
             container_info.rect.size.x,
-            container_info.rect.size.y - TAB_SIZE,
+            container_info.rect.size.y - tab_size,
         );
 
-        let tab_menu_size = vec2(container_info.rect.size.x, TAB_SIZE);
+        let tab_menu_size = vec2(container_info.rect.size.x, tab_size);
         let tab_menu_position = vec2(
             container_info.rect.position.x,
-            container_info.rect.position.y + (container_info.rect.size.y - TAB_SIZE) * 0.5,
+            container_info.rect.position.y + (container_info.rect.size.y - tab_size) * 0.5,
         );
 
         let tab_rect = Rect {
@@ -213,72 +679,142 @@ impl TabsContainer {
             size: tab_menu_size,
         };
 
-        if let UIEvent::Render {
-            gui_rects,
-            extra_render_steps,
-        } = event
-        {
+        if let UIEvent::Render { gui_rects, .. } = event {
             ElementBuilder::new(tab_menu_position, tab_menu_size)
-                .set_color(TAB_BG_COLOR.into())
+                .set_color(get_theme(public_data).colors.tab_bar.into())
                 .build(gui_rects);
-
-            extra_render_steps.push(
-                render_shadow_under_tab(self.active_tab, container_info, tab_rect),
-                container_info.depth_range.0 + depth_offset::TAB_SHADOW,
-            );
         }
 
-        self.create_tab_buttons(
+        let tab_list_access_id =
+            control_state.push_access_node(AccessNode::new(AccessRole::TabList, tab_rect, ""));
+        control_state.access.push_parent(tab_list_access_id);
+        let active_tab_rect = self.create_tab_buttons(
             control_state,
             &container_info,
             event,
             public_data,
             tab_rect,
             tab_names,
+            tab_descriptions,
         );
+        control_state.access.pop_parent();
+
+        if let UIEvent::Render {
+            extra_render_steps, ..
+        } = event
+        {
+            extra_render_steps.push(
+                render_shadow_under_tab(
+                    active_tab_rect,
+                    container_info,
+                    tab_rect,
+                    &scale,
+                    get_theme(public_data),
+                ),
+                container_info
+                    .layers
+                    .depth(Layer::ContainerContent, depth_offset::TAB_SHADOW),
+            );
+        }
+
+        let container_rect = Rect {
+            position: container_position,
+            size: vec2(container_size.x, container_size.y.round()),
+        };
+        let access_id = control_state.push_access_node(AccessNode::new(
+            AccessRole::TabPanel,
+            container_rect,
+            active_tab_name,
+        ));
 
         GUIContainerInfo {
             key: active_tab_key,
             container_info: ContainerInfo {
-                rect: Rect {
-                    position: container_position,
-                    size: vec2(container_size.x, container_size.y.round()),
-                },
-                depth_range: container_info.depth_range,
+                rect: container_rect,
+                layers: container_info.layers,
             },
+            access_id,
+        }
+    }
+}
+
+/// Measures the name and truncates it with an ellipsis if the tab would be wider than the maximum
+fn fit_tab_label(
+    name: &str,
+    font_collection: &FontCollection,
+    scale: &GUIScale,
+    theme: &Theme,
+) -> TabLabel {
+    let fixed_width = scale.px(TAB_LABEL_MARGIN * 2.0 + TAB_CLOSE_SIZE);
+    let max_text_width = scale.px(TAB_MAX_WIDTH) - fixed_width;
+    let font_size = scale.font(theme.fonts.size);
+    let text_width = |text: &str| {
+        create_single_line(text, font_size, font_collection, 0, 0.0)
+            .1
+            .width()
+    };
+
+    let mut text = String::from(name);
+    let mut width = text_width(&text);
+    if width > max_text_width {
+        let mut chars: Vec<char> = name.chars().collect();
+        loop {
+            chars.pop();
+            text = chars.iter().collect::<String>().trim_end().to_string() + ELLIPSIS;
+            width = text_width(&text);
+            if width <= max_text_width || chars.len() == 0 {
+                break;
+            }
         }
     }
+
+    TabLabel {
+        text,
+        width: (width + fixed_width)
+            .max(scale.px(TAB_MIN_WIDTH))
+            .min(scale.px(TAB_MAX_WIDTH))
+            .round(),
+    }
 }
 
 fn render_shadow_under_tab(
-    active_tab: usize,
+    active_tab_rect: Rect,
     container_info: ContainerInfo,
     tab_rect: Rect,
+    scale: &GUIScale,
+    theme: &Theme,
 ) -> Box<dyn FnOnce(&mut GUIRects) -> ()> {
-    let left_shadow_size = (active_tab as f32) * (TAB_GAP + TAB_WIDTH) + TAB_GAP;
-    let left_shadow_size = left_shadow_size.min(container_info.rect.size.x);
-    let left_shadow_position = container_info.top_left_position().x + left_shadow_size * 0.5;
+    let container_left = container_info.top_left_position().x;
+    let container_right = container_left + container_info.rect.size.x;
+    let active_left = (active_tab_rect.position.x - active_tab_rect.width() * 0.5)
+        .max(container_left)
+        .min(container_right);
+    let active_right = (active_tab_rect.position.x + active_tab_rect.width() * 0.5)
+        .max(container_left)
+        .min(container_right);
+
+    let left_shadow_size = active_left - container_left;
+    let left_shadow_position = container_left + left_shadow_size * 0.5;
 
-    let right_shadow_start_pos = (active_tab as f32 + 1.0) * (TAB_GAP + TAB_WIDTH);
-    let right_shadow_size = container_info.rect.size.x - right_shadow_start_pos;
-    let right_shadow_pos =
-        right_shadow_start_pos + container_info.top_left_position().x + right_shadow_size * 0.5;
+    let right_shadow_size = container_right - active_right;
+    let right_shadow_pos = active_right + right_shadow_size * 0.5;
 
-    let show_right_shadow = right_shadow_start_pos < container_info.rect.size.x;
+    let show_right_shadow = right_shadow_size > 0.0;
 
-    let bottom_position = tab_rect.position.y - (tab_rect.size.y * 0.5 + 3.0);
+    let shadow_size = scale.px(6.0);
+    let bottom_position = tab_rect.position.y - (tab_rect.size.y + shadow_size) * 0.5;
     let right_shadow_rect = Rect {
         position: vec2(right_shadow_pos, bottom_position),
-        size: vec2(right_shadow_size, 6.0),
+        size: vec2(right_shadow_size, shadow_size),
     };
     let left_shadow_rect = Rect {
         position: vec2(left_shadow_position, bottom_position),
-        size: vec2(left_shadow_size, 6.0),
+        size: vec2(left_shadow_size, shadow_size),
     };
     let lin_gradient = LinearGradient {
-        colors: [RGBA::rrr1(0.10), RGBA::rrr1(0.10).set_alpha(0.0)],
-        start_position: vec2(0.0, 3.0),
-        end_position: vec2(0.0, -3.0),
+        colors: [theme.colors.shadow, theme.colors.shadow.set_alpha(0.0)],
+        start_position: vec2(0.0, shadow_size * 0.5),
+        end_position: vec2(0.0, -shadow_size * 0.5),
     };
 
     let mut result_elems = Vec::with_capacity(2);
@@ -293,7 +829,7 @@ fn render_shadow_under_tab(
     );
 
     Box::new(move |gui_rects| {
-        for elem in result_elems.drain(..){
+        for elem in result_elems.drain(..) {
             elem.build(gui_rects);
         }
     })
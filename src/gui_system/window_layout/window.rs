@@ -2,9 +2,9 @@ use rwge::{
     color::{HSLA, RGBA},
     glam::{dvec2, ivec2, vec2, DVec2, Vec2},
     gui::rect_ui::{
-        element::builder::ElementBuilder,
+        element::{builder::ElementBuilder, Border},
         event::{MouseInput, UIEvent},
-        Rect,
+        BorderRadius, Rect,
     },
     math_utils::lerp_f32,
     uuid::Uuid,
@@ -17,7 +17,15 @@ use rwge::{
 
 use crate::{
     gui_system::{
-        control::{drag_element::DragElement, main_window_top_bar, ControlId, ControlState, State},
+        control::{
+            cursor::{CursorIcon, CursorPriority},
+            drag_element::DragElement,
+            get_current_control_id, main_window_top_bar,
+            snapping::{Snap, SnapTargets},
+            ControlState, State,
+        },
+        layers::{Layer, WindowLayers},
+        theme::get_theme,
         ContainerInfo,
     },
     runtime_data::{
@@ -26,7 +34,7 @@ use crate::{
     },
 };
 
-use super::{LayoutOrTabInfo, LayoutOrTabKey, LayoutSlotKey, depth_offset};
+use super::{LayoutOrTabInfo, LayoutOrTabKey, LayoutSlotKey, TabsSlotKey, depth_offset};
 
 pub struct ResizeDrag {
     active_id: Uuid,
@@ -71,12 +79,127 @@ impl ResizeDrag {
 
 pub struct UIWindow {
     pub layout_key: LayoutSlotKey,
+    /// Tab group drawn over the whole window instead of the layout
+    pub maximized_tabs: Option<TabsSlotKey>,
     pub size: Vec2,
     pub position: Vec2,
+    /// Floating windows are moved inside of the main window instead of moving the OS window
+    pub floating: bool,
     /////
     drag_window: DragElement,
     top_bar_active_id: Option<Uuid>,
     resize_drag_active_id: Option<ResizeDrag>,
+    /// Rect the window takes once the drag ends
+    snap_zone: Option<Rect>,
+}
+
+const TOP_BAR_HEIGHT: f32 = 20.0;
+
+/// Top bar of the windows that float inside of the main window, dragging it snaps the window
+pub fn floating_top_bar(
+    window: &mut UIWindow,
+    event: &mut UIEvent,
+    public_data: &PublicData,
+    control_state: &mut ControlState,
+    snap_targets: &SnapTargets,
+) {
+    let control_id = control_state.get_id();
+    let bar_height = control_state.scale.px(TOP_BAR_HEIGHT);
+    let bar_rect = Rect {
+        position: window.position + vec2(0.0, (window.size.y - bar_height) * 0.5),
+        size: vec2(window.size.x, bar_height),
+    };
+
+    match event {
+        UIEvent::Render { gui_rects, .. } => {
+            let theme = get_theme(public_data);
+            let colors = theme.colors;
+            let state = control_state.get_control_state(get_current_control_id(
+                control_id,
+                &window.top_bar_active_id,
+            ));
+            let color: RGBA = match state {
+                State::Active => {
+                    control_state.request_cursor(CursorIcon::Grabbing, CursorPriority::Active);
+                    colors.top_bar_active
+                }
+                State::Hovered => {
+                    control_state.request_cursor(CursorIcon::Grab, CursorPriority::Hover);
+                    colors.top_bar_hover
+                }
+                State::Inactive => colors.top_bar,
+            };
+
+            ElementBuilder::new(window.position, window.size)
+                .set_color(colors.window_background.into())
+                .build(gui_rects);
+            ElementBuilder::new(bar_rect.position, bar_rect.size)
+                .set_color(color.into())
+                .build(gui_rects);
+
+            if let Some(zone) = window.snap_zone {
+                let scale = control_state.scale;
+                let radius = scale.px(theme.metrics.radius);
+                let border_size = scale.border(theme.metrics.border_width * 2);
+                control_state.layers.push_top_render_step(
+                    Layer::DragPreview,
+                    0,
+                    Box::new(move |gui_rects| {
+                        ElementBuilder::new(zone.position, zone.size)
+                            .set_color(colors.snap_preview.set_alpha(0.25).into())
+                            .set_round_rect(BorderRadius::ForAll(radius).into())
+                            .set_border(Some(Border {
+                                size: border_size,
+                                color: colors.snap_preview.set_alpha(0.8).into(),
+                            }))
+                            .build(gui_rects);
+                    }),
+                );
+            }
+        }
+        UIEvent::Update => {
+            control_state.set_hot_with_rect(control_id, &bar_rect);
+            if let Some(active_id) = window.top_bar_active_id {
+                control_state.hold_active_state(active_id);
+            }
+        }
+        UIEvent::MouseMove { corrected, .. } => {
+            window.drag_window.update_position(*corrected);
+            if window.top_bar_active_id.is_some() {
+                let unsnapped = Rect {
+                    position: window.drag_window.compute_element_position(),
+                    size: window.size,
+                };
+                window.snap_zone = None;
+                window.position = match snap_targets.snap(unsnapped, *corrected) {
+                    Some(Snap::Position(position)) => position,
+                    Some(Snap::Zone(zone)) => {
+                        window.snap_zone = Some(zone);
+                        unsnapped.position
+                    }
+                    None => unsnapped.position,
+                };
+            }
+        }
+        UIEvent::MouseButton(mouse_input) => {
+            if mouse_input.is_left_pressed() {
+                window.top_bar_active_id = control_state.set_active(control_id);
+                if window.top_bar_active_id.is_some() {
+                    window.drag_window.start_dragging(window.position);
+                }
+            } else if mouse_input.is_left_released() {
+                if let Some(active_id) = window.top_bar_active_id.take() {
+                    let _ = control_state.remove_active(active_id);
+                    window.drag_window.stop_dragging();
+                    if let Some(zone) = window.snap_zone.take() {
+                        window.position = zone.position;
+                        window.size = zone.size;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
 }
 
 pub fn resize_controls(
@@ -94,13 +217,16 @@ pub fn resize_controls(
         vec2(1.0, 1.0),
         vec2(-1.0, 1.0),
     ];
-    control_state.set_depth_and_save(container_info.depth_range.0 + depth_offset::RESIZE_CONTROL);
+    let resize_depth = container_info
+        .layers
+        .depth(Layer::Overlay, depth_offset::RESIZE_CONTROL);
+    control_state.set_depth_and_save(resize_depth);
     let rect_mask = Rect { position, size };
     /*for mult in offset_multipliers*/
     {
         let mult = offset_multipliers[1];
         let c_position = position + (size * 0.5) * mult;
-        let c_size = vec2(60.0, 60.0);
+        let c_size = control_state.scale.vec(vec2(60.0, 60.0));
 
         let control_id = control_state.get_id();
 
@@ -152,7 +278,7 @@ pub fn resize_controls(
                     }
                 } else {
                     if let Some(cursor_pos) = control_state.last_cursor_position {
-                        if (cursor_pos - c_position).length() <= 30.0 {
+                        if (cursor_pos - c_position).length() <= c_size.x * 0.5 {
                             control_state.set_hot(control_id);
                         }
                     }
@@ -162,27 +288,38 @@ pub fn resize_controls(
                 extra_render_steps,
                 ..
             } => {
-                let state = control_state.get_control_state(ControlId::Control(control_id));
+                let colors = get_theme(public_data).colors;
+                let drag_id = active_id.as_ref().map(|drag| drag.active_id);
+                let state =
+                    control_state.get_control_state(get_current_control_id(control_id, &drag_id));
+                // The handle is on the bottom right corner
+                match state {
+                    State::Hovered => control_state
+                        .request_cursor(CursorIcon::NwseResize, CursorPriority::Hover),
+                    State::Active => control_state
+                        .request_cursor(CursorIcon::NwseResize, CursorPriority::Active),
+                    State::Inactive => {}
+                }
                 match state {
                     State::Hovered => extra_render_steps.push(
                         Box::new(move |gui_rects| {
                             ElementBuilder::new(c_position, c_size)
                                 .set_circle()
                                 .set_rect_mask(rect_mask.into())
-                                .set_color(RGBA::RED.into())
+                                .set_color(colors.handle_hover.into())
                                 .build(gui_rects);
                         }),
-                        container_info.depth_range.0 + depth_offset::RESIZE_CONTROL,
+                        resize_depth,
                     ),
                     State::Active => extra_render_steps.push(
                         Box::new(move |gui_rects| {
                             ElementBuilder::new(c_position, c_size)
                                 .set_circle()
                                 .set_rect_mask(rect_mask.into())
-                                .set_color(RGBA::GREEN.into())
+                                .set_color(colors.handle_active.into())
                                 .build(gui_rects);
                         }),
-                        container_info.depth_range.0 + depth_offset::RESIZE_CONTROL,
+                        resize_depth,
                     ),
                     State::Inactive => {}
                 }
@@ -197,12 +334,29 @@ impl UIWindow {
     pub fn new_with_contianer(layout_key: LayoutSlotKey, size: Vec2, position: Vec2) -> Self {
         Self {
             layout_key: layout_key,
+            maximized_tabs: None,
             size,
             position,
+            floating: false,
             ////
             drag_window: DragElement::new(),
             top_bar_active_id: None,
             resize_drag_active_id: None,
+            snap_zone: None,
+        }
+    }
+
+    pub fn new_floating(layout_key: LayoutSlotKey, size: Vec2, position: Vec2) -> Self {
+        Self {
+            floating: true,
+            ..Self::new_with_contianer(layout_key, size, position)
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect {
+            position: self.position,
+            size: self.size,
         }
     }
 
@@ -211,35 +365,50 @@ impl UIWindow {
         event: &mut UIEvent,
         public_data: &PublicData,
         control_state: &mut ControlState,
-        depth_range: (u32, u32),
+        window_layers: WindowLayers,
+        snap_targets: &SnapTargets,
     ) -> LayoutOrTabInfo {
-        let inner_size = self.size - vec2(10.0, 30.0);
-        let inner_position = self.position - vec2(0.0, 10.0);
+        if self.floating {
+            floating_top_bar(self, event, public_data, control_state, snap_targets);
+        } else {
+            let bar_height = control_state.scale.px(TOP_BAR_HEIGHT);
+            let menu_bar_pos = self.position + vec2(0.0, (self.size.y - bar_height) * 0.5);
+            let menu_bar_size = vec2(self.size.x, bar_height);
 
-        let menu_bar_pos = self.position + vec2(0.0, self.size.y * 0.5 - 10.0);
-        let menu_bar_size = vec2(self.size.x, 20.0);
+            main_window_top_bar::main_window_top_bar(
+                menu_bar_pos,
+                menu_bar_size,
+                event,
+                public_data,
+                control_state,
+                &mut self.top_bar_active_id,
+                &mut self.drag_window,
+            );
 
-        main_window_top_bar::main_window_top_bar(
-            menu_bar_pos,
-            menu_bar_size,
-            event,
-            public_data,
-            control_state,
-            &mut self.top_bar_active_id,
-            &mut self.drag_window,
-        );
+            resize_controls(
+                self.position,
+                self.size,
+                event,
+                public_data,
+                control_state,
+                &mut self.resize_drag_active_id,
+                &ContainerInfo {
+                    rect: Rect::default(),
+                    layers: window_layers,
+                },
+            );
+        }
 
-        resize_controls(
-            self.position,
-            self.size,
-            event,
-            public_data,
-            control_state,
-            &mut self.resize_drag_active_id,
-            &ContainerInfo { rect: Rect::default(), depth_range}
-        );
+        let inner_size = self.size - control_state.scale.vec(vec2(10.0, 30.0));
+        let inner_position = self.position - control_state.scale.vec(vec2(0.0, 10.0));
 
         match event {
+            UIEvent::Resize(screen_size) if self.floating => {
+                // Kept inside of the main window
+                let screen_size = screen_size.as_vec2();
+                let half_size = (self.size * 0.5).min(screen_size * 0.5);
+                self.position = self.position.clamp(half_size, screen_size - half_size);
+            }
             UIEvent::Resize(screen_size) => {
                 self.size = screen_size.as_vec2();
                 self.position = self.size * 0.5;
@@ -247,14 +416,20 @@ impl UIWindow {
             UIEvent::Update => {}
             _ => {}
         }
+        // The layout is left untouched while a tab group is maximized, so the sizes of its
+        // dividers are the same once it is restored
+        let root_key = match self.maximized_tabs {
+            Some(tabs_key) => LayoutOrTabKey::TabKey(tabs_key),
+            None => LayoutOrTabKey::LayoutKey(self.layout_key),
+        };
         LayoutOrTabInfo {
-            key: LayoutOrTabKey::LayoutKey(self.layout_key),
+            key: root_key,
             container_info: ContainerInfo {
                 rect: Rect {
                     position: inner_position,
                     size: inner_size,
                 },
-                depth_range: depth_range
+                layers: window_layers,
             },
         }
     }
@@ -0,0 +1,155 @@
+use crate::gui_system::popup::MenuItem;
+
+use super::{
+    context_menu::ContextAction, window::UIWindow, DividedElement, LayoutOrTabKey, LayoutSlotKey,
+    TabsSlotKey, WindowSlotKey, WindowSystem,
+};
+
+/// A named arrangement of windows. Every workspace has its own windows, layouts and tab groups,
+/// but the GUI containers are shared, so they keep their state when switching.
+pub struct Workspace {
+    pub name: String,
+    /// Only up to date for the workspaces that are not active, the active one uses
+    /// `WindowSystem::window_order`
+    pub(super) window_order: Vec<WindowSlotKey>,
+}
+
+impl Workspace {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            window_order: Vec::new(),
+        }
+    }
+}
+
+impl WindowSystem {
+    /// Creates an empty workspace, windows can be added to it after switching to it
+    pub fn create_workspace(&mut self, name: &str) -> usize {
+        self.workspaces.push(Workspace::new(name));
+        self.workspaces.len() - 1
+    }
+
+    pub fn active_workspace(&self) -> usize {
+        self.active_workspace
+    }
+
+    pub fn switch_workspace(&mut self, index: usize) {
+        if index >= self.workspaces.len() || index == self.active_workspace {
+            return;
+        }
+
+        // Windows of the other workspaces did not get the resize events
+        let main_window_rect = self
+            .window_order
+            .first()
+            .and_then(|window_key| self.window_collection.get_value(&window_key.0))
+            .map(|window| (window.size, window.position));
+
+        let current_order = std::mem::take(&mut self.window_order);
+        self.workspaces[self.active_workspace].window_order = current_order;
+        self.window_order = std::mem::take(&mut self.workspaces[index].window_order);
+        self.active_workspace = index;
+        self.popups.close();
+
+        if let (Some((size, position)), Some(window_key)) =
+            (main_window_rect, self.window_order.first())
+        {
+            if let Some(window) = self.window_collection.get_value_mut(&window_key.0) {
+                window.size = size;
+                window.position = position;
+            }
+        }
+    }
+
+    pub fn switch_to_next_workspace(&mut self) {
+        self.switch_workspace((self.active_workspace + 1) % self.workspaces.len());
+    }
+
+    /// Copies the windows of the active workspace into a new workspace and switches to it, so
+    /// the arrangement that was saved is not changed by any later edit
+    pub fn save_workspace_as(&mut self, name: &str) -> usize {
+        let mut window_order = Vec::with_capacity(self.window_order.len());
+        for window_key in self.window_order.clone() {
+            let (layout_key, size, position, floating) =
+                match self.window_collection.get_value(&window_key.0) {
+                    Some(window) => (
+                        window.layout_key,
+                        window.size,
+                        window.position,
+                        window.floating,
+                    ),
+                    None => continue,
+                };
+            let new_layout_key = self.duplicate_layout(layout_key);
+            let new_window = if floating {
+                UIWindow::new_floating(new_layout_key, size, position)
+            } else {
+                UIWindow::new_with_contianer(new_layout_key, size, position)
+            };
+            window_order.push(WindowSlotKey(
+                self.window_collection.push(new_window).unwrap(),
+            ));
+        }
+
+        let index = self.create_workspace(name);
+        self.workspaces[index].window_order = window_order;
+        self.switch_workspace(index);
+        index
+    }
+
+    /// Deep copy of the layout tree, the tab groups are copied too
+    fn duplicate_layout(&mut self, layout_key: LayoutSlotKey) -> LayoutSlotKey {
+        let children: Vec<DividedElement> = self
+            .layout_slotmap
+            .get_value(&layout_key)
+            .unwrap()
+            .children()
+            .clone();
+
+        let new_children = children
+            .into_iter()
+            .map(|mut child| {
+                child.layout_or_tab_key = match child.layout_or_tab_key {
+                    LayoutOrTabKey::TabKey(tabs_key) => {
+                        let tabs_container =
+                            self.tabs_slotmap.get_value(&tabs_key).unwrap().duplicate();
+                        LayoutOrTabKey::TabKey(TabsSlotKey(
+                            self.tabs_slotmap.push(tabs_container).unwrap(),
+                        ))
+                    }
+                    LayoutOrTabKey::LayoutKey(child_key) => {
+                        LayoutOrTabKey::LayoutKey(self.duplicate_layout(child_key))
+                    }
+                };
+                child
+            })
+            .collect();
+
+        let new_layout = self
+            .layout_slotmap
+            .get_value(&layout_key)
+            .unwrap()
+            .duplicate_with_children(new_children);
+        LayoutSlotKey(self.layout_slotmap.push(new_layout).unwrap())
+    }
+}
+
+pub fn workspace_menu(workspaces: &Vec<Workspace>, active: usize) -> MenuItem<ContextAction> {
+    let mut items: Vec<MenuItem<ContextAction>> = workspaces
+        .iter()
+        .enumerate()
+        .map(|(index, workspace)| {
+            if index == active {
+                MenuItem::new_disabled(&workspace.name)
+            } else {
+                MenuItem::new(&workspace.name, ContextAction::SwitchWorkspace { index })
+            }
+        })
+        .collect();
+    items.push(MenuItem::new(
+        "Save as new workspace",
+        ContextAction::SaveWorkspace,
+    ));
+    MenuItem::new_submenu("Workspace", items)
+}
@@ -7,7 +7,10 @@ use gui_font::load_default_font_data;
 use runtime_data::{utils::get_render_texture, EngineData, RuntimeData};
 pub use rwge::gui::rect_ui::GUIRects;
 mod gui_system;
-use gui_system::{gui_container::text_animation::TextAnimationData, GUISystem};
+use gui_system::{
+    accessibility::AccessibilitySettings, gui_container::text_animation::TextAnimationData,
+    theme::Theme, GUISystem,
+};
 
 use rwge::{
     color::*,
@@ -86,6 +89,8 @@ impl Game {
             create_gui_copy_texture_to_surface(&mut runtime_data.public_data, &gui_rects, engine);
 
         runtime_data.insert_pub(TextAnimationData::new());
+        runtime_data.insert_pub(Theme::dark());
+        runtime_data.insert_pub(AccessibilitySettings::new());
 
         Self {
             gui_rects,
@@ -114,6 +119,8 @@ impl rwge::Runtime for Game {
             let close_event_handled = rwge::default_close_event_handler(event, exit_event_loop);
 
             if !close_event_handled {
+                self.gui_system.handle_text_input(event);
+                self.gui_system.handle_wheel_input(event);
                 let size_event = Graphics::resize_event_transformation(event);
                 if let Some(new_size) = size_event {
                     //Resize event
@@ -137,7 +144,9 @@ impl rwge::Runtime for Game {
                     let mut resize_ui_event = UIEvent::Resize(new_size);
                     self.gui_system
                         .handle_event(&mut resize_ui_event, &mut self.runtime_data.public_data);
-                } else {
+                } else if !self.gui_system.handle_zoom_input(event) {
+                    // The GUI is laid out in physical pixels scaled by `GUIScale`, so the cursor
+                    // is flipped with the physical size of the window
                     let gui_event = rwge::gui::rect_ui::event::default_event_transformation(
                         event,
                         engine.graphics.render_window.size,